@@ -0,0 +1,80 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use std::path::PathBuf;
+use supertonic_tts::{
+    chunk_text, load_text_to_speech, load_voice_style, preprocess_text, to_pcm16, Benchmark,
+    SynthesisOptions,
+};
+
+const PARAGRAPH: &str = "Your order of $42.99 will arrive on 3/14/2025 at 5 PM. \
+    Please visit www.example.com or email support@example.com for help. \
+    \"Are you coming?\" she asked, glancing at the clock. After a long pause, \
+    the speaker continued with renewed energy and conviction, and NASA and the \
+    ESA announced a joint mission yesterday.";
+
+fn text_benches(c: &mut Criterion) {
+    let chapter = PARAGRAPH.repeat(50);
+
+    c.bench_function("preprocess_text/paragraph", |b| {
+        b.iter(|| preprocess_text(black_box(PARAGRAPH)))
+    });
+    c.bench_function("chunk_text/chapter", |b| {
+        b.iter(|| chunk_text(black_box(&chapter), None))
+    });
+}
+
+fn audio_benches(c: &mut Criterion) {
+    // A minute of audio at 44.1 kHz
+    let samples: Vec<f32> = (0..44_100 * 60)
+        .map(|i| (i as f32 * 0.01).sin() * 1.2)
+        .collect();
+
+    c.bench_function("to_pcm16/minute", |b| {
+        b.iter(|| to_pcm16(black_box(&samples)))
+    });
+}
+
+/// End-to-end synthesis with the downloaded models; skipped when they are absent
+fn synthesis_benches(c: &mut Criterion) {
+    let onnx_dir = "assets/onnx";
+    let style_path = "assets/voice_styles/M1.json";
+    if !PathBuf::from(onnx_dir).exists() || !PathBuf::from(style_path).exists() {
+        eprintln!("Assets not found, skipping synthesis benchmarks.");
+        return;
+    }
+
+    let mut tts = load_text_to_speech(onnx_dir, false).unwrap();
+    let style = load_voice_style(&[style_path.to_string()], false).unwrap();
+    let options = SynthesisOptions::default();
+
+    let mut group = c.benchmark_group("synthesize");
+    group.sample_size(10);
+    group.bench_function("sentence", |b| {
+        b.iter(|| tts.synthesize(black_box("Hello from the benchmark."), &style, &options))
+    });
+    group.bench_function("paragraph", |b| {
+        b.iter(|| tts.synthesize(black_box(PARAGRAPH), &style, &options))
+    });
+    group.finish();
+
+    let corpus = vec![
+        "Hello from the benchmark.".to_string(),
+        PARAGRAPH.to_string(),
+    ];
+    let report = Benchmark::default().run(&mut tts, &style, &corpus).unwrap();
+    let [dp, text_enc, vector_est, vocoder] = report.stage_shares();
+    eprintln!(
+        "RTF {:.3} (p50 {:.3}, p95 {:.3}); stages: duration {:.0}%, text encoder {:.0}%, \
+         vector estimator {:.0}%, vocoder {:.0}%",
+        report.rtf,
+        report.rtf_p50,
+        report.rtf_p95,
+        dp * 100.0,
+        text_enc * 100.0,
+        vector_est * 100.0,
+        vocoder * 100.0
+    );
+}
+
+criterion_group!(benches, text_benches, audio_benches, synthesis_benches);
+criterion_main!(benches);
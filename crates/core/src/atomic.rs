@@ -0,0 +1,64 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::SupertonicError;
+
+// ============================================================================
+// Atomic File Writes
+// ============================================================================
+//
+// Outputs are written to a temporary file next to the destination and renamed
+// into place once complete, so an interrupted run never leaves a truncated
+// file under the final name. Renames within a directory are atomic on POSIX
+// and replace the destination on Windows.
+
+/// Process id and a per-process counter, so temporary files of concurrent
+/// writes never share a name
+pub(crate) fn unique_suffix() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+pub(crate) fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", unique_suffix()));
+    path.with_file_name(name)
+}
+
+/// Write `path` atomically, streaming the contents through `write`
+pub fn write_atomic<P, F>(path: P, write: F) -> Result<(), SupertonicError>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), SupertonicError>,
+{
+    let path = path.as_ref();
+    let tmp = temp_path(path);
+
+    let result = (|| {
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        write(&mut writer)?;
+        writer.flush()?;
+        writer
+            .into_inner()
+            .map_err(|e| SupertonicError::Io(e.into_error()))?
+            .sync_all()?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Write `contents` to `path` atomically
+pub fn write_file_atomic<P: AsRef<Path>>(path: P, contents: &[u8]) -> Result<(), SupertonicError> {
+    write_atomic(path, |w| Ok(w.write_all(contents)?))
+}
@@ -1,36 +1,1490 @@
+use crate::atomic::{temp_path, unique_suffix, write_atomic};
 use crate::error::SupertonicError;
-use hound::{SampleFormat, WavSpec, WavWriter};
-use std::path::Path;
+use crate::options::{apply_gain, SynthesisOptions};
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 // ============================================================================
 // WAV File I/O
 // ============================================================================
 
+/// Sample encodings for WAV output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WavFormat {
+    /// 16-bit PCM, clamped to full scale
+    #[default]
+    Pcm16,
+    /// 24-bit PCM, clamped to full scale
+    Pcm24,
+    /// 32-bit IEEE float (no clipping or quantization)
+    Float32,
+}
+
+impl WavFormat {
+    fn spec(&self, channels: u16, sample_rate: i32) -> WavSpec {
+        let (bits_per_sample, sample_format) = match self {
+            WavFormat::Pcm16 => (16, SampleFormat::Int),
+            WavFormat::Pcm24 => (24, SampleFormat::Int),
+            WavFormat::Float32 => (32, SampleFormat::Float),
+        };
+        WavSpec {
+            channels,
+            sample_rate: sample_rate as u32,
+            bits_per_sample,
+            sample_format,
+        }
+    }
+}
+
+impl std::str::FromStr for WavFormat {
+    type Err = SupertonicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "16" | "pcm16" | "int16" => Ok(WavFormat::Pcm16),
+            "24" | "pcm24" | "int24" => Ok(WavFormat::Pcm24),
+            "f32" | "float" | "float32" => Ok(WavFormat::Float32),
+            other => Err(SupertonicError::Validation(format!(
+                "unknown WAV format '{}' (expected pcm16, pcm24 or float32)",
+                other
+            ))),
+        }
+    }
+}
+
+fn pcm16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * 32767.0) as i16
+}
+
+/// Samples converted per iteration of `pcm16_into`; fixed-size blocks let the
+/// compiler turn the clamp, scale and conversion into vector instructions
+const PCM16_LANES: usize = 16;
+
+/// Convert `samples` to 16-bit PCM in `out`, which must be as long
+pub fn pcm16_into(samples: &[f32], out: &mut [i16]) {
+    assert_eq!(
+        samples.len(),
+        out.len(),
+        "output length must match the samples"
+    );
+    let mut blocks = samples.chunks_exact(PCM16_LANES);
+    let mut out_blocks = out.chunks_exact_mut(PCM16_LANES);
+    for (block, out_block) in (&mut blocks).zip(&mut out_blocks) {
+        let block: &[f32; PCM16_LANES] = block.try_into().unwrap();
+        let out_block: &mut [i16; PCM16_LANES] = out_block.try_into().unwrap();
+        for (out, &sample) in out_block.iter_mut().zip(block) {
+            *out = pcm16(sample);
+        }
+    }
+    for (out, &sample) in out_blocks
+        .into_remainder()
+        .iter_mut()
+        .zip(blocks.remainder())
+    {
+        *out = pcm16(sample);
+    }
+}
+
+/// Convert samples to 16-bit PCM, clamping to full scale as `write_wav_file` does
+pub fn to_pcm16(samples: &[f32]) -> Vec<i16> {
+    let mut out = vec![0; samples.len()];
+    pcm16_into(samples, &mut out);
+    out
+}
+
+/// Samples `write_wav_samples` converts and writes at a time
+const PCM16_WRITE_BLOCK: usize = 4096;
+
+/// Append `samples` to `writer` in `format`
+fn write_wav_samples<W: std::io::Write + std::io::Seek>(
+    writer: &mut WavWriter<W>,
+    samples: &[f32],
+    format: WavFormat,
+) -> Result<(), SupertonicError> {
+    let result = match format {
+        WavFormat::Pcm16 => {
+            let mut pcm = [0i16; PCM16_WRITE_BLOCK];
+            samples.chunks(PCM16_WRITE_BLOCK).try_for_each(|block| {
+                let pcm = &mut pcm[..block.len()];
+                pcm16_into(block, pcm);
+                let mut block_writer = writer.get_i16_writer(block.len() as u32);
+                for &sample in pcm.iter() {
+                    block_writer.write_sample(sample);
+                }
+                block_writer.flush()
+            })
+        }
+        WavFormat::Pcm24 => samples
+            .iter()
+            .try_for_each(|&s| writer.write_sample((s.clamp(-1.0, 1.0) * 8388607.0) as i32)),
+        WavFormat::Float32 => samples.iter().try_for_each(|&s| writer.write_sample(s)),
+    };
+    result.map_err(|e| SupertonicError::Io(std::io::Error::other(e)))
+}
+
+/// Write `audio_data` as a 16-bit PCM WAV
 pub fn write_wav_file<P: AsRef<Path>>(
     filename: P,
     audio_data: &[f32],
     sample_rate: i32,
 ) -> Result<(), SupertonicError> {
-    let spec = WavSpec {
-        channels: 1,
-        sample_rate: sample_rate as u32,
-        bits_per_sample: 16,
-        sample_format: SampleFormat::Int,
-    };
+    write_wav_file_with_format(filename, audio_data, sample_rate, WavFormat::Pcm16)
+}
 
-    let mut writer = WavWriter::create(filename, spec)
-        .map_err(|e| SupertonicError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
+/// Write `audio_data` as a WAV with samples encoded as `format`
+pub fn write_wav_file_with_format<P: AsRef<Path>>(
+    filename: P,
+    audio_data: &[f32],
+    sample_rate: i32,
+    format: WavFormat,
+) -> Result<(), SupertonicError> {
+    write_wav_file_interleaved(filename, audio_data, 1, sample_rate, format)
+}
 
-    for &sample in audio_data {
-        let clamped = sample.max(-1.0).min(1.0);
-        let val = (clamped * 32767.0) as i16;
-        writer
-            .write_sample(val)
-            .map_err(|e| SupertonicError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
+/// Write interleaved `channels`-channel samples (e.g. from `pan_stereo`) as a
+/// WAV encoded as `format`
+pub fn write_wav_file_interleaved<P: AsRef<Path>>(
+    filename: P,
+    audio_data: &[f32],
+    channels: u16,
+    sample_rate: i32,
+    format: WavFormat,
+) -> Result<(), SupertonicError> {
+    write_wav_file_tagged(
+        filename,
+        audio_data,
+        channels,
+        sample_rate,
+        format,
+        &AudioMetadata::default(),
+    )
+}
+
+/// `write_wav_file_interleaved`, embedding `metadata` in a `LIST`/`INFO` chunk
+/// after the samples
+pub fn write_wav_file_tagged<P: AsRef<Path>>(
+    filename: P,
+    audio_data: &[f32],
+    channels: u16,
+    sample_rate: i32,
+    format: WavFormat,
+    metadata: &AudioMetadata,
+) -> Result<(), SupertonicError> {
+    if channels == 0 || !audio_data.len().is_multiple_of(channels as usize) {
+        return Err(SupertonicError::Validation(format!(
+            "{} samples do not divide into {} channels",
+            audio_data.len(),
+            channels
+        )));
     }
+    let spec = format.spec(channels, sample_rate);
+
+    // Written to a temporary file and renamed, so readers never see a partial WAV
+    write_atomic(filename, |file| {
+        let mut writer = WavWriter::new(&mut *file, spec)
+            .map_err(|e| SupertonicError::Io(std::io::Error::other(e)))?;
+        write_wav_samples(&mut writer, audio_data, format)?;
+        writer
+            .finalize()
+            .map_err(|e| SupertonicError::Io(std::io::Error::other(e)))?;
+        append_info_chunk(file, metadata)
+    })
+}
+
+/// Encode samples as an in-memory 16-bit WAV file, e.g. for IPC or HTTP responses
+pub fn wav_bytes(audio_data: &[f32], sample_rate: i32) -> Result<Vec<u8>, SupertonicError> {
+    wav_bytes_with_format(audio_data, sample_rate, WavFormat::Pcm16)
+}
 
+/// Encode samples as an in-memory WAV file with samples encoded as `format`
+pub fn wav_bytes_with_format(
+    audio_data: &[f32],
+    sample_rate: i32,
+    format: WavFormat,
+) -> Result<Vec<u8>, SupertonicError> {
+    let mut bytes = Cursor::new(Vec::new());
+    let mut writer = WavWriter::new(&mut bytes, format.spec(1, sample_rate))
+        .map_err(|e| SupertonicError::Io(std::io::Error::other(e)))?;
+    write_wav_samples(&mut writer, audio_data, format)?;
     writer
         .finalize()
-        .map_err(|e| SupertonicError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
+        .map_err(|e| SupertonicError::Io(std::io::Error::other(e)))?;
+    Ok(bytes.into_inner())
+}
+
+/// Read a WAV file as mono f32 samples, returning the samples and sample rate.
+///
+/// Multi-channel files are downmixed by averaging the channels.
+pub fn read_wav_file<P: AsRef<Path>>(filename: P) -> Result<(Vec<f32>, i32), SupertonicError> {
+    let reader =
+        WavReader::open(filename).map_err(|e| SupertonicError::Io(std::io::Error::other(e)))?;
+    decode_wav(reader)
+}
+
+/// Decode an in-memory WAV file (e.g. one received over IPC) like `read_wav_file`
+pub fn read_wav_bytes(bytes: &[u8]) -> Result<(Vec<f32>, i32), SupertonicError> {
+    let reader = WavReader::new(Cursor::new(bytes))
+        .map_err(|e| SupertonicError::Io(std::io::Error::other(e)))?;
+    decode_wav(reader)
+}
+
+fn decode_wav<R: Read>(reader: WavReader<R>) -> Result<(Vec<f32>, i32), SupertonicError> {
+    let spec = reader.spec();
+
+    let samples: Vec<f32> = match spec.sample_format {
+        SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| SupertonicError::Io(std::io::Error::other(e)))?,
+        SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| SupertonicError::Io(std::io::Error::other(e)))?
+        }
+    };
+
+    let channels = spec.channels.max(1) as usize;
+    let mono = if channels == 1 {
+        samples
+    } else {
+        samples
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect()
+    };
+
+    Ok((mono, spec.sample_rate as i32))
+}
+
+/// Write `audio_data` as a 32-bit float WAV (no clipping or quantization)
+pub fn write_wav_file_f32<P: AsRef<Path>>(
+    filename: P,
+    audio_data: &[f32],
+    sample_rate: i32,
+) -> Result<(), SupertonicError> {
+    write_wav_file_with_format(filename, audio_data, sample_rate, WavFormat::Float32)
+}
+
+// ============================================================================
+// WAV Metadata
+// ============================================================================
+//
+// Tags are stored in a RIFF `LIST` chunk of type `INFO` appended after the
+// `data` chunk, where players and tag editors look for them and decoders that
+// only want the samples (including `read_wav_file`) skip over them.
+
+/// Longest title `AudioMetadata::for_synthesis` takes from the text
+const METADATA_TITLE_CHARS: usize = 80;
+
+/// Descriptive tags embedded in output files: an `INFO` chunk in WAVs and
+/// Vorbis comments in Opus files (the tag names are listed per field)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AudioMetadata {
+    /// `INAM` / `TITLE`: e.g. the start of the text or a chapter title
+    pub title: Option<String>,
+    /// `IART` / `ARTIST`: the voice
+    pub artist: Option<String>,
+    /// `IPRD` / `ALBUM`: e.g. the book a chapter belongs to
+    pub album: Option<String>,
+    /// `ITRK` / `TRACKNUMBER`: e.g. the chapter number
+    pub track: Option<u32>,
+    /// `ICMT` / `COMMENT`: e.g. the source text and synthesis options
+    pub comment: Option<String>,
+    /// `ISFT` / `ENCODER`: the program that rendered the file
+    pub software: Option<String>,
+}
+
+impl AudioMetadata {
+    /// Tags describing a synthesized file: the first line of `text` as the
+    /// title, `voice` as the artist, and the full text followed by the
+    /// options that were set (as JSON) as the comment
+    pub fn for_synthesis(text: &str, voice: Option<&str>, options: &SynthesisOptions) -> Self {
+        let first_line = text.trim().lines().next().unwrap_or_default();
+        let mut title: String = first_line.chars().take(METADATA_TITLE_CHARS).collect();
+        if title.len() < first_line.len() {
+            title.push_str("...");
+        }
+
+        let mut comment = text.trim().to_string();
+        if let Ok(serde_json::Value::Object(mut fields)) = serde_json::to_value(options) {
+            fields.retain(|_, value| !value.is_null());
+            if !fields.is_empty() {
+                comment.push_str("\n\nOptions: ");
+                comment.push_str(&serde_json::Value::Object(fields).to_string());
+            }
+        }
+
+        AudioMetadata {
+            title: (!title.is_empty()).then_some(title),
+            artist: voice.map(str::to_string),
+            album: None,
+            track: None,
+            comment: (!comment.is_empty()).then_some(comment),
+            software: Some(format!("supertonic-tts {}", env!("CARGO_PKG_VERSION"))),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields().is_empty()
+    }
+
+    /// The tags that are set, as (`INFO` id, Vorbis comment name, value)
+    fn fields(&self) -> Vec<(&'static [u8; 4], &'static str, String)> {
+        [
+            (b"INAM", "TITLE", self.title.clone()),
+            (b"IART", "ARTIST", self.artist.clone()),
+            (b"IPRD", "ALBUM", self.album.clone()),
+            (b"ITRK", "TRACKNUMBER", self.track.map(|t| t.to_string())),
+            (b"ICMT", "COMMENT", self.comment.clone()),
+            (b"ISFT", "ENCODER", self.software.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(id, name, value)| value.map(|value| (id, name, value)))
+        .collect()
+    }
+
+    fn set_info_field(&mut self, id: &[u8], value: String) {
+        match id {
+            b"INAM" => self.title = Some(value),
+            b"IART" => self.artist = Some(value),
+            b"IPRD" => self.album = Some(value),
+            b"ITRK" => self.track = value.trim().parse().ok(),
+            b"ICMT" => self.comment = Some(value),
+            b"ISFT" => self.software = Some(value),
+            _ => {}
+        }
+    }
+
+    /// `opusenc` arguments setting the tags as Vorbis comments
+    fn opusenc_args(&self) -> Vec<String> {
+        self.fields()
+            .into_iter()
+            // opusenc names itself as the encoder
+            .filter(|(_, name, _)| *name != "ENCODER")
+            .flat_map(|(_, name, value)| ["--comment".to_string(), format!("{}={}", name, value)])
+            .collect()
+    }
+
+    /// The `LIST` chunk holding the tags, including its header
+    fn info_chunk(&self) -> Vec<u8> {
+        let mut body = b"INFO".to_vec();
+        for (id, _, value) in self.fields() {
+            // NUL-terminated, padded to an even length
+            let mut text = value.replace('\0', "").into_bytes();
+            text.push(0);
+            body.extend_from_slice(id);
+            body.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            body.extend_from_slice(&text);
+            if text.len() % 2 == 1 {
+                body.push(0);
+            }
+        }
+        let mut chunk = b"LIST".to_vec();
+        chunk.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(&body);
+        chunk
+    }
+}
+
+/// Append `metadata` to a finalized WAV and fix up the RIFF size
+fn append_info_chunk<W: Write + Seek>(
+    wav: &mut W,
+    metadata: &AudioMetadata,
+) -> Result<(), SupertonicError> {
+    if metadata.is_empty() {
+        return Ok(());
+    }
+    let end = wav.seek(SeekFrom::End(0))?;
+    let mut chunk = Vec::new();
+    // Chunks start on even offsets
+    if end % 2 == 1 {
+        chunk.push(0);
+    }
+    chunk.extend_from_slice(&metadata.info_chunk());
+    wav.write_all(&chunk)?;
+    let riff_size = end + chunk.len() as u64 - 8;
+    let riff_size = u32::try_from(riff_size).map_err(|_| {
+        SupertonicError::Validation("WAV is too large for a metadata chunk".to_string())
+    })?;
+    wav.seek(SeekFrom::Start(4))?;
+    wav.write_all(&riff_size.to_le_bytes())?;
+    wav.seek(SeekFrom::End(0))?;
     Ok(())
 }
+
+/// Read the `INFO` tags of a WAV file; files without any give empty metadata
+pub fn read_wav_metadata<P: AsRef<Path>>(filename: P) -> Result<AudioMetadata, SupertonicError> {
+    let mut file = std::io::BufReader::new(File::open(filename)?);
+    let mut header = [0u8; 12];
+    file.read_exact(&mut header)?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err(SupertonicError::Validation(
+            "not a RIFF WAVE file".to_string(),
+        ));
+    }
+
+    let mut metadata = AudioMetadata::default();
+    let mut chunk_header = [0u8; 8];
+    while file.read_exact(&mut chunk_header).is_ok() {
+        let size = u32::from_le_bytes(chunk_header[4..8].try_into().unwrap()) as u64;
+        let padded = size + size % 2;
+        if &chunk_header[0..4] != b"LIST" || size < 4 {
+            file.seek(SeekFrom::Current(padded as i64))?;
+            continue;
+        }
+        let mut body = vec![0u8; size as usize];
+        file.read_exact(&mut body)?;
+        if size % 2 == 1 {
+            file.seek(SeekFrom::Current(1))?;
+        }
+        if &body[0..4] == b"INFO" {
+            parse_info(&body[4..], &mut metadata);
+        }
+    }
+    Ok(metadata)
+}
+
+fn parse_info(mut body: &[u8], metadata: &mut AudioMetadata) {
+    while body.len() >= 8 {
+        let id = &body[0..4];
+        let size = u32::from_le_bytes(body[4..8].try_into().unwrap()) as usize;
+        let Some(value) = body.get(8..8 + size) else {
+            break;
+        };
+        let text = value.split(|&b| b == 0).next().unwrap_or_default();
+        metadata.set_info_field(id, String::from_utf8_lossy(text).into_owned());
+        body = body.get(8 + size + size % 2..).unwrap_or_default();
+    }
+}
+
+// ============================================================================
+// Streaming WAV Output
+// ============================================================================
+
+/// WAV written incrementally, for renders too long to hold in memory.
+///
+/// Samples go to a temporary file next to `path`; `finish` fixes up the
+/// header and renames it into place. Dropping the writer without calling
+/// `finish` deletes the temporary file.
+pub struct WavStreamWriter {
+    writer: Option<WavWriter<BufWriter<File>>>,
+    /// Second handle on the temporary file, for `sync_all` after finalizing
+    file: File,
+    tmp: PathBuf,
+    path: PathBuf,
+    format: WavFormat,
+    sample_rate: i32,
+    samples: u64,
+    metadata: AudioMetadata,
+}
+
+impl WavStreamWriter {
+    /// Start a 16-bit PCM WAV at `path`
+    pub fn create<P: AsRef<Path>>(path: P, sample_rate: i32) -> Result<Self, SupertonicError> {
+        Self::create_with_format(path, sample_rate, WavFormat::Pcm16)
+    }
+
+    /// Start a WAV at `path` with samples encoded as `format`
+    pub fn create_with_format<P: AsRef<Path>>(
+        path: P,
+        sample_rate: i32,
+        format: WavFormat,
+    ) -> Result<Self, SupertonicError> {
+        let path = path.as_ref().to_path_buf();
+        let tmp = temp_path(&path);
+        let file = File::create(&tmp)?;
+        let spec = format.spec(1, sample_rate);
+        let writer = file
+            .try_clone()
+            .map_err(SupertonicError::Io)
+            .and_then(|handle| {
+                WavWriter::new(BufWriter::new(handle), spec)
+                    .map_err(|e| SupertonicError::Io(std::io::Error::other(e)))
+            });
+        let writer = match writer {
+            Ok(writer) => writer,
+            Err(e) => {
+                let _ = std::fs::remove_file(&tmp);
+                return Err(e);
+            }
+        };
+
+        Ok(WavStreamWriter {
+            writer: Some(writer),
+            file,
+            tmp,
+            path,
+            format,
+            sample_rate,
+            samples: 0,
+            metadata: AudioMetadata::default(),
+        })
+    }
+
+    /// Append mono samples; integer formats clamp them to [-1, 1]
+    pub fn write_samples(&mut self, samples: &[f32]) -> Result<(), SupertonicError> {
+        let writer = self.writer.as_mut().ok_or_else(finished_error)?;
+        write_wav_samples(writer, samples, self.format)?;
+        self.samples += samples.len() as u64;
+        Ok(())
+    }
+
+    /// Seconds of audio written so far
+    pub fn duration(&self) -> f32 {
+        self.samples as f32 / self.sample_rate as f32
+    }
+
+    /// Tags to embed when the file is finished
+    pub fn set_metadata(&mut self, metadata: AudioMetadata) {
+        self.metadata = metadata;
+    }
+
+    /// Finalize the header and move the file to its destination
+    pub fn finish(mut self) -> Result<(), SupertonicError> {
+        self.finish_in_place()
+    }
+
+    /// `finish` for callers that only hold a reference, e.g. `AudioSink::finalize`
+    pub(crate) fn finish_in_place(&mut self) -> Result<(), SupertonicError> {
+        let writer = self.writer.take().ok_or_else(finished_error)?;
+        let result = (|| {
+            writer
+                .finalize()
+                .map_err(|e| SupertonicError::Io(std::io::Error::other(e)))?;
+            append_info_chunk(&mut self.file, &self.metadata)?;
+            self.file.sync_all()?;
+            std::fs::rename(&self.tmp, &self.path)?;
+            Ok(())
+        })();
+        if result.is_err() {
+            let _ = std::fs::remove_file(&self.tmp);
+        }
+        result
+    }
+}
+
+fn finished_error() -> SupertonicError {
+    SupertonicError::Validation("WAV stream is already finished".to_string())
+}
+
+impl Drop for WavStreamWriter {
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            drop(writer);
+            let _ = std::fs::remove_file(&self.tmp);
+        }
+    }
+}
+
+// ============================================================================
+// Output Formats
+// ============================================================================
+
+/// File formats `write_audio_file` can produce
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AudioFormat {
+    /// 16-bit PCM WAV
+    #[default]
+    Wav,
+    /// 24-bit PCM WAV
+    Wav24,
+    /// 32-bit float WAV
+    WavFloat,
+    /// Ogg Opus, encoded by the `opusenc` command-line tool (opus-tools)
+    Opus,
+}
+
+impl AudioFormat {
+    /// Usual file extension for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav | AudioFormat::Wav24 | AudioFormat::WavFloat => "wav",
+            AudioFormat::Opus => "opus",
+        }
+    }
+
+    /// Sample encoding for the WAV formats
+    pub fn wav_format(&self) -> Option<WavFormat> {
+        match self {
+            AudioFormat::Wav => Some(WavFormat::Pcm16),
+            AudioFormat::Wav24 => Some(WavFormat::Pcm24),
+            AudioFormat::WavFloat => Some(WavFormat::Float32),
+            AudioFormat::Opus => None,
+        }
+    }
+}
+
+impl std::str::FromStr for AudioFormat {
+    type Err = SupertonicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wav" => Ok(AudioFormat::Wav),
+            "wav-24" | "wav24" => Ok(AudioFormat::Wav24),
+            "wav-f32" | "f32" => Ok(AudioFormat::WavFloat),
+            "opus" | "ogg" => Ok(AudioFormat::Opus),
+            other => Err(SupertonicError::Validation(format!(
+                "unknown audio format '{}' (expected wav, wav-24, wav-f32 or opus)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Write mono samples in `format`; Opus uses the default `OpusOptions`
+pub fn write_audio_file<P: AsRef<Path>>(
+    filename: P,
+    audio_data: &[f32],
+    sample_rate: i32,
+    format: AudioFormat,
+) -> Result<(), SupertonicError> {
+    write_audio_file_tagged(
+        filename,
+        audio_data,
+        sample_rate,
+        format,
+        &AudioMetadata::default(),
+    )
+}
+
+/// `write_audio_file`, tagging the file with `metadata` in the format's own way
+pub fn write_audio_file_tagged<P: AsRef<Path>>(
+    filename: P,
+    audio_data: &[f32],
+    sample_rate: i32,
+    format: AudioFormat,
+    metadata: &AudioMetadata,
+) -> Result<(), SupertonicError> {
+    match format.wav_format() {
+        Some(wav_format) => {
+            write_wav_file_tagged(filename, audio_data, 1, sample_rate, wav_format, metadata)
+        }
+        None => write_opus_file_tagged(
+            filename,
+            audio_data,
+            sample_rate,
+            &OpusOptions::default(),
+            metadata,
+        ),
+    }
+}
+
+/// Sample rate Opus encodes at; other rates are resampled first
+pub const OPUS_SAMPLE_RATE: i32 = 48000;
+
+/// Frame durations (ms) Opus supports
+const OPUS_FRAME_SIZES: [f32; 6] = [2.5, 5.0, 10.0, 20.0, 40.0, 60.0];
+
+/// Encoder settings for `write_opus_file`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpusOptions {
+    /// Target bitrate in kbit/s (6-256); `None` uses the encoder's default
+    pub bitrate_kbps: Option<u32>,
+    /// Frame duration in milliseconds: 2.5, 5, 10, 20, 40 or 60.
+    ///
+    /// Short frames lower latency (WebRTC, voice assistants); long frames
+    /// compress speech slightly better.
+    pub frame_ms: f32,
+}
+
+impl Default for OpusOptions {
+    fn default() -> Self {
+        OpusOptions {
+            bitrate_kbps: None,
+            frame_ms: 20.0,
+        }
+    }
+}
+
+impl OpusOptions {
+    pub fn validate(&self) -> Result<(), SupertonicError> {
+        if let Some(bitrate) = self.bitrate_kbps {
+            if !(6..=256).contains(&bitrate) {
+                return Err(SupertonicError::Validation(format!(
+                    "Opus bitrate must be between 6 and 256 kbit/s, got {}",
+                    bitrate
+                )));
+            }
+        }
+        if !OPUS_FRAME_SIZES.contains(&self.frame_ms) {
+            return Err(SupertonicError::Validation(format!(
+                "Opus frame size must be 2.5, 5, 10, 20, 40 or 60 ms, got {}",
+                self.frame_ms
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Write mono samples as Ogg Opus, encoded by the `opusenc` command-line tool (opus-tools).
+///
+/// Audio is resampled to 48 kHz with `resample` before encoding.
+pub fn write_opus_file<P: AsRef<Path>>(
+    filename: P,
+    audio_data: &[f32],
+    sample_rate: i32,
+    options: &OpusOptions,
+) -> Result<(), SupertonicError> {
+    write_opus_file_tagged(
+        filename,
+        audio_data,
+        sample_rate,
+        options,
+        &AudioMetadata::default(),
+    )
+}
+
+/// `write_opus_file`, storing `metadata` as Vorbis comments
+pub fn write_opus_file_tagged<P: AsRef<Path>>(
+    filename: P,
+    audio_data: &[f32],
+    sample_rate: i32,
+    options: &OpusOptions,
+    metadata: &AudioMetadata,
+) -> Result<(), SupertonicError> {
+    options.validate()?;
+    let filename = filename.as_ref();
+    let input = std::env::temp_dir().join(format!(
+        "supertonic-opus-{}-{}.wav",
+        unique_suffix(),
+        filename
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    ));
+    if sample_rate == OPUS_SAMPLE_RATE {
+        write_wav_file_f32(&input, audio_data, sample_rate)?;
+    } else {
+        let resampled = resample(audio_data, sample_rate, OPUS_SAMPLE_RATE);
+        write_wav_file_f32(&input, &resampled, OPUS_SAMPLE_RATE)?;
+    }
+
+    let mut command = Command::new("opusenc");
+    command
+        .arg("--quiet")
+        .arg("--framesize")
+        .arg(options.frame_ms.to_string());
+    if let Some(bitrate) = options.bitrate_kbps {
+        command.arg("--bitrate").arg(bitrate.to_string());
+    }
+    command.args(metadata.opusenc_args());
+    // Encoded next to `filename` and renamed into place, like the other writers
+    let tmp = temp_path(filename);
+    let output = command.arg(&input).arg(&tmp).output();
+    let _ = std::fs::remove_file(&input);
+    let result = match output {
+        Err(e) => Err(SupertonicError::Io(std::io::Error::new(
+            e.kind(),
+            format!("failed to run opusenc (is opus-tools installed?): {}", e),
+        ))),
+        Ok(output) if !output.status.success() => Err(SupertonicError::Unknown(format!(
+            "opusenc failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))),
+        Ok(_) => std::fs::rename(&tmp, filename).map_err(SupertonicError::Io),
+    };
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+// ============================================================================
+// Resampling
+// ============================================================================
+
+/// Zero crossings of the sinc kernel on each side; more is sharper and slower
+const RESAMPLE_ZERO_CROSSINGS: f64 = 16.0;
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+/// Blackman window over `u` in [-1, 1]
+fn blackman(u: f64) -> f64 {
+    let pu = std::f64::consts::PI * u;
+    0.42 + 0.5 * pu.cos() + 0.08 * (2.0 * pu).cos()
+}
+
+/// Resample mono audio from `from` Hz to `to` Hz with a windowed-sinc filter.
+///
+/// When downsampling, the filter cuts off at the new Nyquist frequency so
+/// content above it does not alias.
+pub fn resample(samples: &[f32], from: i32, to: i32) -> Vec<f32> {
+    if from == to || from <= 0 || to <= 0 || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = to as f64 / from as f64;
+    let cutoff = ratio.min(1.0);
+    let half_width = RESAMPLE_ZERO_CROSSINGS / cutoff;
+    let out_len = (samples.len() as f64 * ratio).round() as usize;
+    let last = samples.len() as f64 - 1.0;
+
+    (0..out_len)
+        .map(|i| {
+            let t = i as f64 / ratio;
+            let start = (t - half_width).ceil().max(0.0) as usize;
+            let end = (t + half_width).floor().min(last) as usize;
+            (start..=end)
+                .map(|j| {
+                    let x = j as f64 - t;
+                    samples[j] as f64 * cutoff * sinc(cutoff * x) * blackman(x / half_width)
+                })
+                .sum::<f64>() as f32
+        })
+        .collect()
+}
+
+// ============================================================================
+// Time-Stretching
+// ============================================================================
+//
+// WSOLA (waveform-similarity overlap-add): Hann-windowed frames are read from
+// the input at `factor` times the output hop and overlap-added, each shifted
+// by up to `WSOLA_TOLERANCE_SECS` to where it best continues the previous
+// frame. Matching the waveform keeps the pitch and avoids the phasiness of
+// a plain overlap-add.
+
+/// Frame length; a few pitch periods of speech
+const WSOLA_FRAME_SECS: f32 = 0.03;
+/// How far a frame may move from its nominal position to line up
+const WSOLA_TOLERANCE_SECS: f32 = 0.005;
+
+/// Change the duration of `samples` by `1 / factor` without changing the pitch.
+///
+/// `factor` above 1 speeds speech up, below 1 slows it down, like the
+/// `speed` synthesis option.
+pub fn time_stretch(samples: &[f32], sample_rate: i32, factor: f32) -> Vec<f32> {
+    let frame = ((WSOLA_FRAME_SECS * sample_rate as f32) as usize) & !1;
+    if factor <= 0.0 || factor == 1.0 || frame < 4 || samples.len() < frame {
+        return samples.to_vec();
+    }
+    let hop = frame / 2;
+    let tolerance = (WSOLA_TOLERANCE_SECS * sample_rate as f32) as usize;
+    let window: Vec<f32> = (0..frame)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / frame as f32).cos())
+        .collect();
+
+    let out_len = (samples.len() as f64 / factor as f64).round() as usize;
+    let last_start = samples.len() - frame;
+    let mut out = vec![0.0f32; out_len + frame];
+    let mut norm = vec![0.0f32; out_len + frame];
+    let mut previous: Option<usize> = None;
+
+    for k in 0..=out_len / hop {
+        let nominal = ((k * hop) as f64 * factor as f64).round() as usize;
+        let start = match previous {
+            // Search around the nominal position for the frame most like the
+            // natural continuation of the previous one
+            Some(p) if p + hop + hop <= samples.len() => {
+                let target = &samples[p + hop..p + hop + hop];
+                let lo = nominal.saturating_sub(tolerance).min(last_start);
+                let hi = (nominal + tolerance).min(last_start);
+                (lo..=hi)
+                    .map(|c| {
+                        let score: f32 = samples[c..c + hop]
+                            .iter()
+                            .zip(target)
+                            .map(|(a, b)| a * b)
+                            .sum();
+                        (c, score)
+                    })
+                    .fold(
+                        (lo, f32::MIN),
+                        |best, cand| {
+                            if cand.1 > best.1 {
+                                cand
+                            } else {
+                                best
+                            }
+                        },
+                    )
+                    .0
+            }
+            _ => nominal.min(last_start),
+        };
+        let offset = k * hop;
+        for i in 0..frame {
+            out[offset + i] += samples[start + i] * window[i];
+            norm[offset + i] += window[i];
+        }
+        previous = Some(start);
+    }
+
+    for (sample, weight) in out.iter_mut().zip(&norm) {
+        if *weight > 1e-3 {
+            *sample /= weight;
+        }
+    }
+    out.truncate(out_len);
+    out
+}
+
+/// Shift the pitch of `samples` by `semitones` (negative is deeper) without
+/// changing their duration: a `time_stretch` by the pitch ratio, then a
+/// `resample` back to the original length
+#[cfg(feature = "dsp")]
+pub fn pitch_shift(samples: &[f32], sample_rate: i32, semitones: f32) -> Vec<f32> {
+    if semitones == 0.0 || sample_rate <= 0 || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = 2f32.powf(semitones / 12.0);
+    let stretched = time_stretch(samples, sample_rate, 1.0 / ratio);
+    let mut shifted = resample(
+        &stretched,
+        (sample_rate as f32 * ratio).round() as i32,
+        sample_rate,
+    );
+    shifted.resize(samples.len(), 0.0);
+    shifted
+}
+
+// ============================================================================
+// Filters
+// ============================================================================
+
+/// Corner frequency of the DC blocker, well below any voice content
+const DC_BLOCK_HZ: f64 = 5.0;
+
+/// Second-order IIR section (direct form I, `a0` normalized to 1)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Biquad {
+    pub(crate) b: [f64; 3],
+    pub(crate) a: [f64; 2],
+}
+
+impl Biquad {
+    /// Butterworth high pass at `cutoff_hz` (RBJ cookbook)
+    fn high_pass(cutoff_hz: f64, sample_rate: f64) -> Self {
+        let w0 = 2.0 * std::f64::consts::PI * cutoff_hz / sample_rate;
+        let alpha = w0.sin() / std::f64::consts::SQRT_2;
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha;
+        Biquad {
+            b: [
+                (1.0 + cos) / 2.0 / a0,
+                -(1.0 + cos) / a0,
+                (1.0 + cos) / 2.0 / a0,
+            ],
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+        }
+    }
+
+    pub(crate) fn run(&self, input: &[f64]) -> Vec<f64> {
+        let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+        input
+            .iter()
+            .map(|&x| {
+                let y = self.b[0] * x + self.b[1] * x1 + self.b[2] * x2
+                    - self.a[0] * y1
+                    - self.a[1] * y2;
+                x2 = x1;
+                x1 = x;
+                y2 = y1;
+                y1 = y;
+                y
+            })
+            .collect()
+    }
+}
+
+/// Remove any DC offset with a one-pole blocker at `DC_BLOCK_HZ`
+pub fn remove_dc(samples: &mut [f32], sample_rate: i32) {
+    if sample_rate <= 0 {
+        return;
+    }
+    let r = (-2.0 * std::f64::consts::PI * DC_BLOCK_HZ / sample_rate as f64).exp();
+    let (mut x1, mut y1) = (0.0f64, 0.0f64);
+    for sample in samples.iter_mut() {
+        let x = *sample as f64;
+        let y = x - x1 + r * y1;
+        x1 = x;
+        y1 = y;
+        *sample = y as f32;
+    }
+}
+
+/// Attenuate rumble below `cutoff_hz` (12 dB/octave Butterworth); 40-60 Hz
+/// leaves speech untouched
+pub fn high_pass(samples: &mut [f32], sample_rate: i32, cutoff_hz: f32) {
+    if sample_rate <= 0 || cutoff_hz <= 0.0 || cutoff_hz as f64 >= sample_rate as f64 / 2.0 {
+        return;
+    }
+    let input: Vec<f64> = samples.iter().map(|&s| s as f64).collect();
+    let output = Biquad::high_pass(cutoff_hz as f64, sample_rate as f64).run(&input);
+    for (sample, y) in samples.iter_mut().zip(output) {
+        *sample = y as f32;
+    }
+}
+
+// ============================================================================
+// Fades
+// ============================================================================
+
+/// Ramp the first `fade_in` samples up from silence and the last `fade_out`
+/// samples down to it, linearly
+pub fn fade(samples: &mut [f32], fade_in: usize, fade_out: usize) {
+    let len = samples.len();
+    let fade_in = fade_in.min(len);
+    for (i, sample) in samples[..fade_in].iter_mut().enumerate() {
+        *sample *= i as f32 / fade_in as f32;
+    }
+    let fade_out = fade_out.min(len);
+    for (i, sample) in samples[len - fade_out..].iter_mut().enumerate() {
+        *sample *= 1.0 - (i + 1) as f32 / fade_out as f32;
+    }
+}
+
+/// `fade` with lengths in milliseconds at `sample_rate`
+pub fn fade_ms(samples: &mut [f32], sample_rate: i32, fade_in_ms: f32, fade_out_ms: f32) {
+    let to_samples = |ms: f32| (ms.max(0.0) / 1000.0 * sample_rate as f32) as usize;
+    fade(samples, to_samples(fade_in_ms), to_samples(fade_out_ms));
+}
+
+// ============================================================================
+// Silence Compression
+// ============================================================================
+//
+// A frame-energy voice activity detector: 10 ms frames below
+// `SILENCE_THRESHOLD_DB` are silent. Internal runs of silence longer than the
+// maximum lose their middle, keeping half the maximum on each side so speech
+// still trails off and starts naturally. Leading and trailing silence is left
+// alone.
+
+/// Level below which a frame counts as silence, in dBFS
+pub const SILENCE_THRESHOLD_DB: f32 = -45.0;
+
+/// Frame length of the silence detector
+const SILENCE_FRAME_SECS: f32 = 0.01;
+
+/// Shorten every internal silence longer than `max_secs` to `max_secs`, e.g.
+/// overlong pauses from model hiccups or pause markup
+pub fn compress_silences(samples: &[f32], sample_rate: i32, max_secs: f32) -> Vec<f32> {
+    remove_ranges(samples, &silence_cuts(samples, sample_rate, max_secs))
+}
+
+/// Sample ranges `compress_silences` removes, in order
+pub(crate) fn silence_cuts(samples: &[f32], sample_rate: i32, max_secs: f32) -> Vec<Range<usize>> {
+    let frame = ((SILENCE_FRAME_SECS * sample_rate as f32) as usize).max(1);
+    let keep = (max_secs.max(0.0) * sample_rate as f32) as usize;
+    let threshold = 10f32.powf(SILENCE_THRESHOLD_DB / 20.0);
+    let silent = |chunk: &[f32]| {
+        let power = chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32;
+        power.sqrt() < threshold
+    };
+
+    let mut cuts = Vec::new();
+    let mut run_start = None;
+    for (i, chunk) in samples.chunks(frame).enumerate() {
+        let start = i * frame;
+        match (silent(chunk), run_start) {
+            (true, None) => run_start = Some(start),
+            (false, Some(run)) => {
+                run_start = None;
+                if run > 0 && start - run > keep {
+                    cuts.push(run + keep / 2..start - (keep - keep / 2));
+                }
+            }
+            _ => {}
+        }
+    }
+    cuts
+}
+
+/// `samples` without `cuts` (sorted, non-overlapping)
+pub(crate) fn remove_ranges(samples: &[f32], cuts: &[Range<usize>]) -> Vec<f32> {
+    let removed: usize = cuts.iter().map(|cut| cut.len()).sum();
+    let mut output = Vec::with_capacity(samples.len() - removed);
+    let mut position = 0;
+    for cut in cuts {
+        output.extend_from_slice(&samples[position..cut.start]);
+        position = cut.end;
+    }
+    output.extend_from_slice(&samples[position..]);
+    output
+}
+
+/// Where sample `position` lands once `cuts` are removed
+pub(crate) fn position_after_cuts(position: usize, cuts: &[Range<usize>]) -> usize {
+    let removed: usize = cuts
+        .iter()
+        .map(|cut| position.min(cut.end) - position.min(cut.start))
+        .sum();
+    position - removed
+}
+
+// ============================================================================
+// Stereo
+// ============================================================================
+
+/// Spread mono `samples` across two interleaved channels at `position`
+/// (-1 hard left, 0 center, 1 hard right).
+///
+/// Uses a constant-power pan law, so a voice keeps its perceived loudness as
+/// it moves; centered audio sits 3 dB below full scale in each channel.
+pub fn pan_stereo(samples: &[f32], position: f32) -> Vec<f32> {
+    let angle = (position.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+    let (left, right) = (angle.cos(), angle.sin());
+    samples
+        .iter()
+        .flat_map(|&s| [s * left, s * right])
+        .collect()
+}
+
+// ============================================================================
+// Mixing
+// ============================================================================
+
+/// Mono clips placed on a timeline, summed into one track by `render`.
+///
+/// All clips share the mixer's sample rate; `resample` others first. Overlaps
+/// add up, so follow with `soft_limit` if the mix may exceed full scale.
+#[derive(Debug, Clone)]
+pub struct Mixer {
+    sample_rate: i32,
+    clips: Vec<MixerClip>,
+}
+
+#[derive(Debug, Clone)]
+struct MixerClip {
+    samples: Vec<f32>,
+    offset: usize,
+    gain: f32,
+}
+
+impl Mixer {
+    pub fn new(sample_rate: i32) -> Self {
+        Mixer {
+            sample_rate,
+            clips: Vec::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
+    /// Place `samples` starting `offset_secs` into the mix, scaled by `gain_db`
+    pub fn add(&mut self, samples: Vec<f32>, offset_secs: f32, gain_db: f32) -> &mut Self {
+        let offset = (offset_secs.max(0.0) * self.sample_rate as f32).round() as usize;
+        self.clips.push(MixerClip {
+            samples,
+            offset,
+            gain: db_to_amplitude(gain_db),
+        });
+        self
+    }
+
+    /// Place `samples` `gap_secs` after the end of the mix so far, e.g. to lay
+    /// out the outputs of `synthesize_batch` as turns of a conversation
+    pub fn append(&mut self, samples: Vec<f32>, gap_secs: f32, gain_db: f32) -> &mut Self {
+        let offset_secs = self.duration() + gap_secs;
+        self.add(samples, offset_secs, gain_db)
+    }
+
+    /// Length of the mix in samples
+    pub fn len(&self) -> usize {
+        self.clips
+            .iter()
+            .map(|clip| clip.offset + clip.samples.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Length of the mix in seconds
+    pub fn duration(&self) -> f32 {
+        self.len() as f32 / self.sample_rate as f32
+    }
+
+    /// Sum every clip into one buffer, silent wherever no clip plays
+    pub fn render(&self) -> Vec<f32> {
+        let mut mix = vec![0.0f32; self.len()];
+        for clip in &self.clips {
+            for (out, &sample) in mix[clip.offset..].iter_mut().zip(&clip.samples) {
+                *out += sample * clip.gain;
+            }
+        }
+        mix
+    }
+}
+
+/// How a background bed sits under speech in `mix_background`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackgroundOptions {
+    /// Level of the bed relative to its source, in dB
+    pub gain_db: f32,
+    /// Further reduction while speech is present, in dB (negative ducks)
+    pub duck_db: f32,
+    /// Speech level above which the bed ducks, in dBFS
+    pub threshold_db: f32,
+    /// Time for the bed to duck once speech starts, in milliseconds
+    pub attack_ms: f32,
+    /// Time for the bed to recover once speech stops, in milliseconds
+    pub release_ms: f32,
+}
+
+impl Default for BackgroundOptions {
+    fn default() -> Self {
+        BackgroundOptions {
+            gain_db: -18.0,
+            duck_db: -12.0,
+            threshold_db: -45.0,
+            attack_ms: 40.0,
+            release_ms: 400.0,
+        }
+    }
+}
+
+/// How long the speech detector holds on after a peak; bridges the gaps
+/// between words so the bed does not pump up and down within a sentence
+const DUCK_DETECTOR_SECS: f32 = 0.05;
+
+/// Mix a mono background `bed` under `speech`, ducking it while speech plays.
+///
+/// `speech` holds `channels` interleaved channels and the bed is added to each
+/// of them. The bed loops if it is shorter than the speech and is cut at its
+/// end, so the output has the length of `speech`. Both must be at
+/// `sample_rate`.
+pub fn mix_background(
+    speech: &[f32],
+    channels: u16,
+    bed: &[f32],
+    sample_rate: i32,
+    options: &BackgroundOptions,
+) -> Vec<f32> {
+    let channels = channels.max(1) as usize;
+    if bed.is_empty() {
+        return speech.to_vec();
+    }
+    let coefficient = |secs: f32| 1.0 - (-1.0 / (secs.max(1e-4) * sample_rate as f32)).exp();
+    let detector_decay = 1.0 - coefficient(DUCK_DETECTOR_SECS);
+    let attack = coefficient(options.attack_ms / 1000.0);
+    let release = coefficient(options.release_ms / 1000.0);
+    let threshold = db_to_amplitude(options.threshold_db);
+    let level = db_to_amplitude(options.gain_db);
+    let ducked = db_to_amplitude(options.duck_db);
+
+    let mut envelope = 0.0f32;
+    let mut gain = 1.0f32;
+    let mut output = Vec::with_capacity(speech.len());
+    for (i, frame) in speech.chunks(channels).enumerate() {
+        let peak = frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        envelope = peak.max(envelope * detector_decay);
+        let target = if envelope > threshold { ducked } else { 1.0 };
+        gain += (target - gain) * if target < gain { attack } else { release };
+
+        let background = bed[i % bed.len()] * level * gain;
+        output.extend(frame.iter().map(|&s| s + background));
+    }
+    output
+}
+
+// ============================================================================
+// Peak Control
+// ============================================================================
+
+/// How far below the ceiling `soft_limit` starts to bend peaks, in dB
+const LIMITER_KNEE_DB: f32 = 3.0;
+
+fn db_to_amplitude(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Scale `samples` so their peak is `ceiling_db` dBFS.
+///
+/// Returns the applied gain in dB, or `None` (leaving the samples untouched)
+/// for silence.
+pub fn normalize_peak(samples: &mut [f32], ceiling_db: f32) -> Option<f32> {
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    if peak <= 0.0 {
+        return None;
+    }
+    let gain_db = ceiling_db - 20.0 * peak.log10();
+    apply_gain(samples, db_to_amplitude(gain_db));
+    Some(gain_db)
+}
+
+/// Bend peaks smoothly so no sample exceeds `ceiling_db` dBFS.
+///
+/// Samples below the knee (`LIMITER_KNEE_DB` under the ceiling) pass
+/// unchanged; above it they approach the ceiling along a tanh curve instead
+/// of being cut off flat, which avoids the harsh distortion of a hard clamp.
+pub fn soft_limit(samples: &mut [f32], ceiling_db: f32) {
+    let ceiling = db_to_amplitude(ceiling_db);
+    let knee = ceiling * db_to_amplitude(-LIMITER_KNEE_DB);
+    let range = ceiling - knee;
+    for sample in samples.iter_mut() {
+        let magnitude = sample.abs();
+        if magnitude > knee {
+            *sample = sample.signum() * (knee + range * ((magnitude - knee) / range).tanh());
+        }
+    }
+}
+
+// ============================================================================
+// Visualization
+// ============================================================================
+//
+// Data for drawing audio in a UI: a waveform overview with a peak and RMS
+// level per bucket, and a mel spectrogram in decibels.
+
+/// Level of one stretch of audio in a waveform `preview`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct WaveformBucket {
+    /// Largest absolute sample
+    pub peak: f32,
+    pub rms: f32,
+}
+
+/// Summarize `samples` as `buckets` consecutive, evenly sized stretches, e.g.
+/// one per pixel column of a waveform display.
+///
+/// Audio shorter than `buckets` samples gives one bucket per sample.
+pub fn preview(samples: &[f32], buckets: usize) -> Vec<WaveformBucket> {
+    let buckets = buckets.min(samples.len());
+    (0..buckets)
+        .map(|b| {
+            let stretch = &samples[b * samples.len() / buckets..(b + 1) * samples.len() / buckets];
+            let peak = stretch.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+            let power = stretch.iter().map(|s| s * s).sum::<f32>() / stretch.len() as f32;
+            WaveformBucket {
+                peak,
+                rms: power.sqrt(),
+            }
+        })
+        .collect()
+}
+
+/// Lowest level `mel_spectrogram` reports, in dB
+const MEL_FLOOR_DB: f32 = -100.0;
+
+/// Analysis settings for `mel_spectrogram`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MelOptions {
+    /// FFT length in samples
+    pub fft_size: usize,
+    /// Samples between the starts of successive frames
+    pub hop: usize,
+    /// Number of mel bands
+    pub bands: usize,
+    /// Lower edge of the lowest band in Hz
+    pub min_hz: f32,
+    /// Upper edge of the highest band in Hz; `None` is the Nyquist frequency
+    pub max_hz: Option<f32>,
+}
+
+impl Default for MelOptions {
+    fn default() -> Self {
+        MelOptions {
+            fft_size: 1024,
+            hop: 256,
+            bands: 80,
+            min_hz: 0.0,
+            max_hz: None,
+        }
+    }
+}
+
+/// Mel-band power over time, as returned by `mel_spectrogram`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MelSpectrogram {
+    /// One row of `bands` levels in dB per frame, lowest band first
+    pub frames: Vec<Vec<f32>>,
+    /// Seconds between frames
+    pub hop_secs: f32,
+    pub min_hz: f32,
+    pub max_hz: f32,
+}
+
+fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+/// Mel spectrogram of mono `samples`: Hann-windowed FFT frames pooled into
+/// triangular mel bands, in dB relative to a full-scale sine
+pub fn mel_spectrogram(samples: &[f32], sample_rate: i32, options: &MelOptions) -> MelSpectrogram {
+    let fft_size = options.fft_size.max(2);
+    let hop = options.hop.max(1);
+    let bands = options.bands.max(1);
+    let nyquist = sample_rate.max(1) as f32 / 2.0;
+    let max_hz = options.max_hz.unwrap_or(nyquist).min(nyquist);
+    let min_hz = options.min_hz.clamp(0.0, max_hz);
+
+    // Band edges evenly spaced on the mel scale, as FFT bin positions
+    let bin_hz = sample_rate.max(1) as f32 / fft_size as f32;
+    let (min_mel, max_mel) = (hz_to_mel(min_hz), hz_to_mel(max_hz));
+    let edges: Vec<f32> = (0..bands + 2)
+        .map(|i| mel_to_hz(min_mel + (max_mel - min_mel) * i as f32 / (bands + 1) as f32) / bin_hz)
+        .collect();
+    let bins = fft_size / 2 + 1;
+    let weight = |band: usize, bin: usize| {
+        let (low, center, high) = (edges[band], edges[band + 1], edges[band + 2]);
+        let bin = bin as f32;
+        if bin <= low || bin >= high {
+            0.0
+        } else if bin <= center {
+            (bin - low) / (center - low).max(f32::EPSILON)
+        } else {
+            (high - bin) / (high - center).max(f32::EPSILON)
+        }
+    };
+    let filters: Vec<Vec<f32>> = (0..bands)
+        .map(|band| (0..bins).map(|bin| weight(band, bin)).collect())
+        .collect();
+
+    let window: Vec<f32> = (0..fft_size)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / fft_size as f32).cos())
+        .collect();
+    // A full-scale sine peaks at this magnitude with the Hann window
+    let reference = (fft_size as f32 / 4.0).powi(2);
+    let fft = rustfft::FftPlanner::<f32>::new().plan_fft_forward(fft_size);
+    let mut buffer = vec![rustfft::num_complex::Complex::new(0.0, 0.0); fft_size];
+
+    let frame_count = samples.len().div_ceil(hop);
+    let frames = (0..frame_count)
+        .map(|f| {
+            let start = f * hop;
+            for (i, value) in buffer.iter_mut().enumerate() {
+                let sample = samples.get(start + i).copied().unwrap_or(0.0);
+                *value = rustfft::num_complex::Complex::new(sample * window[i], 0.0);
+            }
+            fft.process(&mut buffer);
+            let power: Vec<f32> = buffer[..bins].iter().map(|c| c.norm_sqr()).collect();
+            filters
+                .iter()
+                .map(|filter| {
+                    let band: f32 = filter.iter().zip(&power).map(|(w, p)| w * p).sum();
+                    (10.0 * (band / reference).log10()).max(MEL_FLOOR_DB)
+                })
+                .collect()
+        })
+        .collect();
+
+    MelSpectrogram {
+        frames,
+        hop_secs: hop as f32 / sample_rate.max(1) as f32,
+        min_hz,
+        max_hz,
+    }
+}
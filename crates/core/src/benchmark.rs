@@ -0,0 +1,124 @@
+use serde::Serialize;
+use std::time::Instant;
+
+use crate::error::SupertonicError;
+use crate::model::{StageTimings, Style, TextToSpeech};
+use crate::options::SynthesisOptions;
+
+// ============================================================================
+// Benchmarking
+// ============================================================================
+//
+// Repeatable throughput measurement over a fixed corpus, so regressions show
+// up as numbers: real-time factor overall and per text, and where the time
+// goes between the four sessions.
+
+/// Settings for `Benchmark::run`
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    pub options: SynthesisOptions,
+    /// Untimed passes over the corpus before measuring, to warm up the sessions
+    pub warmup: usize,
+    /// Timed passes over the corpus
+    pub iterations: usize,
+}
+
+impl Default for Benchmark {
+    fn default() -> Self {
+        Benchmark {
+            options: SynthesisOptions::default(),
+            warmup: 1,
+            iterations: 3,
+        }
+    }
+}
+
+/// Result of `Benchmark::run`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BenchmarkReport {
+    /// Texts synthesized, over all timed passes
+    pub renders: usize,
+    pub audio_secs: f64,
+    pub synthesis_secs: f64,
+    /// Synthesis time over audio duration for the whole run (below 1.0 is
+    /// faster than real time)
+    pub rtf: f64,
+    /// Median and 95th percentile real-time factor of single texts
+    pub rtf_p50: f64,
+    pub rtf_p95: f64,
+    /// Median and slowest wall time of a single text
+    pub latency_p50_secs: f64,
+    pub latency_max_secs: f64,
+    /// Time spent in each session, summed over the timed passes
+    pub stages: StageTimings,
+}
+
+impl BenchmarkReport {
+    /// Share of the session time spent in each stage, in pipeline order
+    pub fn stage_shares(&self) -> [f64; 4] {
+        let total = self.stages.total().as_secs_f64().max(f64::EPSILON);
+        [
+            self.stages.duration_predictor,
+            self.stages.text_encoder,
+            self.stages.vector_estimator,
+            self.stages.vocoder,
+        ]
+        .map(|stage| stage.as_secs_f64() / total)
+    }
+}
+
+/// Value at fraction `q` of sorted `values`
+fn percentile(values: &[f64], q: f64) -> f64 {
+    match values.len() {
+        0 => 0.0,
+        n => values[((n - 1) as f64 * q).round() as usize],
+    }
+}
+
+impl Benchmark {
+    /// Synthesize every text of `corpus` with `style`, `warmup` times untimed
+    /// and then `iterations` times timed; the first error aborts the run
+    pub fn run(
+        &self,
+        tts: &mut TextToSpeech,
+        style: &Style,
+        corpus: &[String],
+    ) -> Result<BenchmarkReport, SupertonicError> {
+        for _ in 0..self.warmup {
+            for text in corpus {
+                tts.synthesize(text, style, &self.options)?;
+            }
+        }
+
+        let mut report = BenchmarkReport::default();
+        let mut rtfs = Vec::new();
+        let mut latencies = Vec::new();
+        for _ in 0..self.iterations {
+            for text in corpus {
+                let start = Instant::now();
+                let (_, duration) = tts.synthesize(text, style, &self.options)?;
+                let latency = start.elapsed().as_secs_f64();
+
+                report.renders += 1;
+                report.audio_secs += duration as f64;
+                report.synthesis_secs += latency;
+                report.stages += tts.last_stage_timings();
+                latencies.push(latency);
+                if duration > 0.0 {
+                    rtfs.push(latency / duration as f64);
+                }
+            }
+        }
+
+        rtfs.sort_by(f64::total_cmp);
+        latencies.sort_by(f64::total_cmp);
+        if report.audio_secs > 0.0 {
+            report.rtf = report.synthesis_secs / report.audio_secs;
+        }
+        report.rtf_p50 = percentile(&rtfs, 0.5);
+        report.rtf_p95 = percentile(&rtfs, 0.95);
+        report.latency_p50_secs = percentile(&latencies, 0.5);
+        report.latency_max_secs = latencies.last().copied().unwrap_or(0.0);
+        Ok(report)
+    }
+}
@@ -1,45 +1,79 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::fs;
 use std::path::PathBuf;
-use tracing::{info, Level};
+use tracing::{error, info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
 use supertonic_tts::{
-    load_text_to_speech, load_voice_style, sanitize_filename, timer, write_wav_file,
+    command_hook, compress_silences, default_onnx_dir, default_profile_path,
+    load_text_to_speech_with_config, load_text_to_speech_with_snapshot, load_voice_style,
+    mix_background, normalize_loudness, normalize_peak, pan_stereo, read_wav_file,
+    read_wav_metadata, resample, run_soak, sanitize_filename, version, write_chunk_log,
+    write_file_atomic, write_opus_file_tagged, write_wav_file_tagged, AssetManifest, AudioFormat,
+    AudioMetadata, AudioStats, BackgroundOptions, EmojiPolicy, NarrationPreset, OpusOptions,
+    PerformanceProfile, SessionConfig, SoakConfig, SpeedMode, StyleFormat, SuggestTarget,
+    SynthesisOptions, TimingReport, UnknownCharPolicy, Watermark, WavFormat,
 };
 
 #[derive(Parser, Debug)]
 #[command(name = "Supertonic TTS")]
-#[command(version = "0.1.0")]
+#[command(version, disable_version_flag = true)]
 #[command(about = "High-performance, on-device Text-to-Speech synthesis using ONNX Runtime.", long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Use GPU for inference (default: CPU)
     #[arg(long, default_value = "false")]
     use_gpu: bool,
 
-    /// Path to ONNX model directory
-    #[arg(
-        long,
-        default_value = "assets/onnx",
-        help = "Directory containing the ONNX models"
-    )]
-    onnx_dir: String,
+    /// Directory containing the ONNX models [default: assets/onnx, or the models `tts download` installed]
+    #[arg(long)]
+    onnx_dir: Option<String>,
+
+    /// Number of denoising steps (Higher = better quality, slower) [default: voice default or 5]
+    #[arg(long)]
+    total_step: Option<usize>,
+
+    /// Speech speed factor (higher = faster) [default: voice default or 1.05]
+    #[arg(long)]
+    speed: Option<f32>,
+
+    /// How --speed is applied: model (paced by the duration model) or post-process (time-stretched)
+    #[arg(long)]
+    speed_mode: Option<SpeedMode>,
+
+    /// Pause between long-form chunks in seconds [default: voice default or 0.3]
+    #[arg(long)]
+    silence_duration: Option<f32>,
+
+    /// Pause after a paragraph in seconds [default: voice default or --silence-duration]
+    #[arg(long)]
+    paragraph_silence: Option<f32>,
+
+    /// Pause where a long sentence is split in seconds [default: voice default or --silence-duration]
+    #[arg(long)]
+    clause_silence: Option<f32>,
+
+    /// Output gain in dB [default: voice default or 0]
+    #[arg(long, allow_hyphen_values = true)]
+    gain_db: Option<f32>,
 
-    /// Number of denoising steps (Higher = better quality, slower)
-    #[arg(long, default_value = "5")]
-    total_step: usize,
+    /// Locale used to read numbers, currencies, times and dates, e.g. en-US or de-DE [default: en-US]
+    #[arg(long)]
+    locale: Option<String>,
 
-    /// Speech speed factor (higher = faster)
-    #[arg(long, default_value = "1.05")]
-    speed: f32,
+    /// IANA time zone for relative dates, e.g. Europe/Berlin [default: UTC]
+    #[arg(long)]
+    timezone: Option<String>,
 
     /// Number of times to generate each sample
     #[arg(long, default_value = "4")]
     n_test: usize,
 
-    /// Voice style file path(s)
-    #[arg(long, value_delimiter = ',', default_values_t = vec!["assets/voice_styles/M1.json".to_string()])]
+    /// Voice style file path(s) [default: M1.json next to the default models]
+    #[arg(long, value_delimiter = ',')]
     voice_style: Vec<String>,
 
     /// Text(s) to synthesize (separated by | if using batch mode)
@@ -50,36 +84,438 @@ struct Args {
     #[arg(long, default_value = "results")]
     save_dir: String,
 
+    /// Shift the pitch by this many semitones, e.g. -2 for a deeper variant (requires the `dsp` feature)
+    #[cfg(feature = "dsp")]
+    #[arg(long, allow_hyphen_values = true)]
+    pitch: Option<f32>,
+
+    /// Remove DC offset and high-pass the output at this frequency in Hz, e.g. 50
+    #[arg(long)]
+    high_pass_hz: Option<f32>,
+
+    /// Fade the start of each output in over this many milliseconds
+    #[arg(long)]
+    fade_in_ms: Option<f32>,
+
+    /// Fade the end of each output out over this many milliseconds
+    #[arg(long)]
+    fade_out_ms: Option<f32>,
+
+    /// Also fade every chunk in and out where it meets a pause
+    #[arg(long, default_value = "false")]
+    fade_chunks: bool,
+
+    /// Soft-limit peaks to this level in dBFS, e.g. -1
+    #[arg(long, allow_hyphen_values = true)]
+    limiter_db: Option<f32>,
+
+    /// Shorten silences inside each output, pauses included, to at most this many seconds
+    #[arg(long)]
+    max_silence: Option<f32>,
+
+    /// Render the first sentence in extra-small chunks with fewer steps, for faster first audio
+    #[arg(long, default_value = "false")]
+    low_latency: bool,
+
+    /// Resample the output to this rate in Hz [default: the model's native rate]
+    #[arg(long)]
+    sample_rate: Option<i32>,
+
+    /// Save two-channel WAVs, placing each narration role at its `pan`
+    #[arg(long, default_value = "false")]
+    stereo: bool,
+
+    /// Stereo position of the voice, -1 (left) to 1 (right); implies --stereo
+    #[arg(long, allow_hyphen_values = true)]
+    pan: Option<f32>,
+
+    /// Background music or ambience WAV to mix under the speech, looped as needed
+    #[arg(long)]
+    background: Option<PathBuf>,
+
+    /// Level of the background in dB [default: -18]
+    #[arg(long, allow_hyphen_values = true)]
+    background_db: Option<f32>,
+
+    /// How far the background ducks while speech plays, in dB [default: -12]
+    #[arg(long, allow_hyphen_values = true)]
+    duck_db: Option<f32>,
+
+    /// Embed an inaudible watermark with this key, marking the audio as synthetic
+    #[arg(long)]
+    watermark_key: Option<u64>,
+
+    /// Play each output on the default audio device after saving it
+    #[cfg(feature = "playback")]
+    #[arg(long, default_value = "false")]
+    play: bool,
+
+    /// Sample encoding of the saved WAVs: pcm16, pcm24 or float32
+    #[arg(long, default_value = "pcm16")]
+    wav_format: WavFormat,
+
+    /// Leave out the tags (title, voice, source text and options) normally embedded in saved WAVs
+    #[arg(long, default_value = "false")]
+    no_metadata: bool,
+
     /// Enable batch mode (multiple text-style pairs)
     #[arg(long, default_value = "false")]
     batch: bool,
+
+    /// In batch mode, render up to this many sub-batches at once on clones of the engine
+    #[arg(long)]
+    max_in_flight: Option<usize>,
+
+    /// Seed for the latent noise RNG (default: random per run)
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Threads used within each ONNX operator (default: ONNX Runtime's choice)
+    #[arg(long)]
+    intra_threads: Option<usize>,
+
+    /// Threads used to run independent ONNX operators in parallel
+    #[arg(long)]
+    inter_threads: Option<usize>,
+
+    /// Write a per-chunk log (JSON lines) next to each long-form output
+    #[arg(long, default_value = "false")]
+    chunk_log: bool,
+
+    /// Enable ONNX Runtime profiling, writing JSON traces with this path prefix
+    #[arg(long)]
+    profile: Option<PathBuf>,
+
+    /// Narration preset JSON (voices per role); overrides --voice-style in single mode
+    #[arg(long)]
+    preset: Option<PathBuf>,
+
+    /// Size chunks by model tokens (after normalization) instead of characters
+    #[arg(long)]
+    max_tokens: Option<usize>,
+
+    /// Vocode long chunks in windows of this many latent frames, capping peak memory
+    #[arg(long)]
+    vocoder_window: Option<usize>,
+
+    /// Performance profile written by `tts calibrate` [default: user cache directory]
+    #[arg(long)]
+    perf_profile: Option<PathBuf>,
+
+    /// Pick --total-step automatically to reach this real-time factor (0.5 = twice real time)
+    #[arg(long, conflicts_with = "total_step")]
+    target_rtf: Option<f64>,
+
+    /// Level the preset's voices against each other by measured loudness
+    #[arg(long, requires = "preset")]
+    match_loudness: bool,
+
+    /// Shell command run on each chunk; `{input}`/`{output}` expand to WAV paths
+    #[arg(long)]
+    post_cmd: Option<String>,
+
+    /// Rhai script with extra normalization rules (requires the `scripting` feature)
+    #[cfg(feature = "scripting")]
+    #[arg(long)]
+    rules: Option<PathBuf>,
+
+    /// Run text through espeak-ng with this voice before tokenization (requires the `espeak` feature)
+    #[cfg(feature = "espeak")]
+    #[arg(long)]
+    g2p_voice: Option<String>,
+
+    /// Read emoji as short descriptions ("thumbs up") instead of dropping them
+    #[arg(long, default_value = "false")]
+    describe_emoji: bool,
+
+    /// Characters the model has no token for: skip, space or error
+    #[arg(long, default_value = "skip")]
+    unknown_chars: UnknownCharPolicy,
+
+    /// Log each text as the model will read it, with the token id of every character
+    #[arg(long, default_value = "false")]
+    debug_text: bool,
+
+    /// Reject unsupported characters, markup and over-long sentences instead of adjusting the text
+    #[arg(long, default_value = "false")]
+    strict: bool,
+
+    /// Directory for the optimized-engine snapshot (created on first run, reused afterwards)
+    #[arg(long)]
+    snapshot_dir: Option<String>,
+
+    /// Print version
+    #[arg(short = 'V', long)]
+    version: bool,
+
+    /// With --version, also print the ONNX Runtime version, enabled features and supported model configs
+    #[arg(long, requires = "version")]
+    verbose: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Synthesize varied texts continuously and report memory growth, latency drift and errors
+    Soak {
+        /// How long to run, in hours
+        #[arg(long, default_value = "1")]
+        hours: f64,
+
+        /// Minutes between progress reports
+        #[arg(long, default_value = "5")]
+        report_minutes: f64,
+
+        /// Write the final report as JSON to this path
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
+    /// Measure this machine's synthesis costs and save them as a performance profile
+    Calibrate {
+        /// Where to write the profile [default: --perf-profile or the user cache directory]
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Fetch the models and voice styles from the Hugging Face Hub (requires the `download` feature)
+    #[cfg(feature = "download")]
+    Download {
+        /// Hub repository to download from
+        #[arg(long, default_value = supertonic_tts::DEFAULT_HF_REPO)]
+        repo: String,
+
+        /// Branch, tag or commit
+        #[arg(long, default_value = "main")]
+        revision: String,
+
+        /// Directory to download into [default: the user cache directory]
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Access token for private or gated repositories [default: $HF_TOKEN]
+        #[arg(long)]
+        token: Option<String>,
+    },
+    /// Load the models and print each one's inputs and outputs with their dtypes and dims
+    Inspect {
+        /// Print the description as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write checksums.json with the SHA-256 of every file in a model or voice style directory
+    Checksums {
+        /// Directory to describe
+        dir: PathBuf,
+    },
+    /// Process existing audio files without loading the models
+    Audio {
+        #[command(subcommand)]
+        command: AudioCommand,
+    },
+    /// Manage voice style files without loading the models
+    Voices {
+        #[command(subcommand)]
+        command: VoicesCommand,
+    },
+    /// Render each chapter of an EPUB to its own WAV file in --save-dir (requires the `epub` feature)
+    #[cfg(feature = "epub")]
+    Audiobook {
+        /// EPUB file to read
+        epub: PathBuf,
+
+        /// Only render these chapters (1-based, comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        chapters: Vec<usize>,
+
+        /// Chapter file format: wav, wav-24, wav-f32 or opus (needs `opusenc` on PATH) [default: per --wav-format]
+        #[arg(long)]
+        format: Option<AudioFormat>,
+
+        /// Album tag of every chapter [default: the book's title]
+        #[arg(long)]
+        album: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum VoicesCommand {
+    /// Rewrite voice styles in another format, e.g. JSON packs as binary
+    Convert {
+        /// Voice style files to convert
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+
+        /// Output format: json, bin or f16 (binary with half-precision values)
+        #[arg(long, default_value = "bin")]
+        format: StyleFormat,
+
+        /// Directory for the converted files, named after the inputs [default: next to each input]
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum AudioCommand {
+    /// Resample, loudness-normalize and re-encode a WAV file
+    Convert(ConvertArgs),
+    /// Check a WAV file for the mark added by --watermark-key
+    DetectWatermark {
+        /// WAV file to read
+        input: PathBuf,
+
+        /// Key the mark was embedded with
+        #[arg(long)]
+        key: u64,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct ConvertArgs {
+    /// WAV file to read
+    input: PathBuf,
+
+    /// Output path [default: the input path with the format's extension]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Output format: wav (16-bit), wav-24, wav-f32 or opus (needs `opusenc` on PATH)
+    #[arg(long, default_value = "wav")]
+    format: AudioFormat,
+
+    /// Output sample rate in Hz [default: input rate]
+    #[arg(long)]
+    rate: Option<i32>,
+
+    /// Normalize to this integrated loudness (LUFS), e.g. -16
+    #[arg(long, allow_hyphen_values = true)]
+    lufs: Option<f64>,
+
+    /// Scale the audio so its peak is this level in dBFS, e.g. -1
+    #[arg(long, allow_hyphen_values = true, conflicts_with = "lufs")]
+    peak: Option<f32>,
+
+    /// Opus bitrate in kbit/s (6-256) [default: opusenc's choice]
+    #[arg(long)]
+    bitrate: Option<u32>,
+
+    /// Opus frame size in ms: 2.5, 5, 10, 20, 40 or 60
+    #[arg(long, default_value = "20")]
+    frame_size: f32,
+
+    /// Shorten silences inside the audio to at most this many seconds
+    #[arg(long)]
+    max_silence: Option<f32>,
+
+    /// Embed an inaudible watermark with this key after the level changes
+    #[arg(long)]
+    watermark_key: Option<u64>,
 }
 
 fn main() -> Result<()> {
+    // --- 1. Parse arguments --- //
+    let args = Args::parse();
+    if args.version {
+        if args.verbose {
+            println!("{}", version());
+        } else {
+            println!("supertonic-tts {}", env!("CARGO_PKG_VERSION"));
+        }
+        return Ok(());
+    }
+
     // Initialize logging
     let subscriber = FmtSubscriber::builder()
         .with_max_level(Level::INFO)
         .finish();
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 
+    if let Some(Command::Audio { command }) = &args.command {
+        return audio_command(command);
+    }
+    if let Some(Command::Voices { command }) = &args.command {
+        return voices_command(command);
+    }
+    if let Some(Command::Checksums { dir }) = &args.command {
+        let manifest = AssetManifest::generate(dir)?;
+        manifest.save(dir)?;
+        info!(
+            "Wrote checksums of {} files to {}",
+            manifest.files.len(),
+            dir.join(supertonic_tts::CHECKSUM_MANIFEST).display()
+        );
+        return Ok(());
+    }
+    #[cfg(feature = "download")]
+    if let Some(Command::Download {
+        repo,
+        revision,
+        dir,
+        token,
+    }) = &args.command
+    {
+        let downloader = supertonic_tts::Downloader {
+            revision: revision.clone(),
+            token: token.clone().or_else(|| std::env::var("HF_TOKEN").ok()),
+            cache_dir: dir.clone(),
+            ..supertonic_tts::Downloader::new(repo)
+        };
+        return download(&downloader);
+    }
+
     info!("=== Supertonic TTS Inference ===");
 
-    // --- 1. Parse arguments --- //
-    let args = Args::parse();
-    let total_step = args.total_step;
-    let speed = args.speed;
+    let mut options = SynthesisOptions {
+        total_step: args.total_step,
+        speed: args.speed,
+        speed_mode: args.speed_mode,
+        silence_duration: args.silence_duration,
+        paragraph_silence: args.paragraph_silence,
+        clause_silence: args.clause_silence,
+        gain_db: args.gain_db,
+        locale: args.locale.clone(),
+        timezone: args.timezone.clone(),
+        target_sample_rate: args.sample_rate,
+        #[cfg(feature = "dsp")]
+        pitch_semitones: args.pitch,
+        high_pass_hz: args.high_pass_hz,
+        limiter_db: args.limiter_db,
+        fade_in_ms: args.fade_in_ms,
+        fade_out_ms: args.fade_out_ms,
+        fade_chunks: args.fade_chunks.then_some(true),
+        pan: args.pan,
+        watermark_key: args.watermark_key,
+        max_silence: args.max_silence,
+        low_latency: args.low_latency.then_some(true),
+    };
+    let stereo = args.stereo || args.pan.is_some();
     let n_test = args.n_test;
-    let voice_style_paths = &args.voice_style;
     let text_list = &args.text;
     let save_dir = &args.save_dir;
     let batch = args.batch;
 
     // Validate existence of ONNX directory
-    let onnx_path = PathBuf::from(&args.onnx_dir);
+    let onnx_dir = match &args.onnx_dir {
+        Some(dir) => dir.clone(),
+        None => default_onnx_dir().to_string_lossy().into_owned(),
+    };
+    let onnx_path = PathBuf::from(&onnx_dir);
     if !onnx_path.exists() || !onnx_path.is_dir() {
-        anyhow::bail!("ONNX directory not found: {}", args.onnx_dir);
+        anyhow::bail!(
+            "ONNX directory not found: {} (run `tts download` or pass --onnx-dir)",
+            onnx_dir
+        );
     }
 
+    // The voices that ship next to the models, unless others are given
+    let default_voice;
+    let voice_style_paths = if args.voice_style.is_empty() {
+        let assets = onnx_path.parent().unwrap_or(&onnx_path);
+        let path = assets.join("voice_styles").join("M1.json");
+        default_voice = vec![path.to_string_lossy().into_owned()];
+        &default_voice
+    } else {
+        &args.voice_style
+    };
+
     // Validate existence of voice style files
     for path in voice_style_paths {
         if !PathBuf::from(path).exists() {
@@ -87,45 +523,352 @@ fn main() -> Result<()> {
         }
     }
 
-    if batch {
-        if voice_style_paths.len() != text_list.len() {
-            anyhow::bail!(
-                "Number of voice styles ({}) must match number of texts ({})",
-                voice_style_paths.len(),
-                text_list.len()
-            );
-        }
+    if batch && voice_style_paths.len() != 1 && voice_style_paths.len() != text_list.len() {
+        anyhow::bail!(
+            "Number of voice styles ({}) must be 1 or match number of texts ({})",
+            voice_style_paths.len(),
+            text_list.len()
+        );
     }
 
     // --- 2. Load TTS components --- //
-    let mut text_to_speech = load_text_to_speech(&args.onnx_dir, args.use_gpu)?;
+    let session_config = SessionConfig {
+        use_gpu: args.use_gpu,
+        intra_threads: args.intra_threads,
+        inter_threads: args.inter_threads,
+        profile_prefix: args.profile.clone(),
+        ..Default::default()
+    };
+    let mut text_to_speech = match &args.snapshot_dir {
+        Some(snapshot_dir) => {
+            load_text_to_speech_with_snapshot(&onnx_dir, snapshot_dir, &session_config)?
+        }
+        None => load_text_to_speech_with_config(&onnx_dir, &session_config)?,
+    };
+
+    if let Some(Command::Inspect { json }) = &args.command {
+        let info = text_to_speech.model_info();
+        if *json {
+            println!("{}", serde_json::to_string_pretty(&info)?);
+        } else {
+            for session in &info {
+                println!("{}", session);
+            }
+        }
+        return Ok(());
+    }
+
+    let mut preset_name = None;
+    let narration = match &args.preset {
+        Some(path) if !batch => {
+            let mut preset = NarrationPreset::load(path)?;
+            info!("Using narration preset '{}'", preset.name);
+            preset_name = Some(preset.name.clone());
+            preset.match_loudness |= args.match_loudness;
+            Some(preset.load_voices()?)
+        }
+        Some(_) => anyhow::bail!("--preset is not supported in batch mode"),
+        None => None,
+    };
+
+    if let Some(cmd) = &args.post_cmd {
+        if batch {
+            anyhow::bail!("--post-cmd is not supported in batch mode");
+        }
+        text_to_speech.set_post_chunk_hook(Some(command_hook(cmd.clone())));
+    }
+
+    #[cfg(feature = "scripting")]
+    if let Some(path) = &args.rules {
+        let rules = supertonic_tts::ScriptRules::load(path)?;
+        text_to_speech.normalizer_mut().script = Some(std::sync::Arc::new(rules));
+        info!("Loaded normalization rules from {}", path.display());
+    }
+
+    #[cfg(feature = "espeak")]
+    if let Some(voice) = &args.g2p_voice {
+        text_to_speech.set_g2p(Some(std::sync::Arc::new(supertonic_tts::EspeakG2p::new(
+            voice.clone(),
+        ))));
+        info!("Using espeak-ng G2P ({})", voice);
+    }
+
+    text_to_speech.set_max_chunk_tokens(args.max_tokens);
+    text_to_speech.set_vocoder_window(args.vocoder_window);
+
+    if args.describe_emoji {
+        text_to_speech.normalizer_mut().emoji = EmojiPolicy::Describe;
+    }
+    text_to_speech.set_unknown_chars(args.unknown_chars);
+    text_to_speech.set_strict(args.strict);
+
+    if let Some(seed) = args.seed {
+        text_to_speech.set_seed(seed);
+    }
 
     // --- 3. Load voice styles --- //
     let style = load_voice_style(voice_style_paths, true)?;
+    if style.defaults != SynthesisOptions::default() {
+        info!("Voice defaults: {:?}", style.defaults);
+    }
+
+    let profile_path = args.perf_profile.clone().or_else(default_profile_path);
+    if let Some(Command::Calibrate { output }) = &args.command {
+        let profile = text_to_speech.calibrate(&style)?;
+        info!(
+            "Per-step cost {:.4} s/s, vocoder {:.1}x real time, fixed cost {:.4} s/s",
+            profile.step_cost,
+            profile.vocoder_throughput(),
+            profile.fixed_cost
+        );
+        info!(
+            "Real-time factor at {} steps: {:.3} ({:.1} real-time streams)",
+            options.total_step(),
+            profile.rtf(options.total_step()),
+            profile.capacity(options.total_step())
+        );
+        let output = output
+            .clone()
+            .or(profile_path)
+            .ok_or_else(|| anyhow::anyhow!("No cache directory found; pass --output"))?;
+        profile.save(&output)?;
+        info!("Saved profile: {}", output.display());
+        return Ok(());
+    }
+    if let Some(profile) = profile_path
+        .as_ref()
+        .and_then(PerformanceProfile::load_for_current_device)
+    {
+        text_to_speech.set_profile(Some(profile));
+        info!(
+            "Loaded performance profile from {}",
+            profile_path.unwrap().display()
+        );
+    }
+
+    if let Some(rtf) = args.target_rtf {
+        let suggested = text_to_speech.suggest_options(
+            &text_list[0],
+            &style,
+            SuggestTarget::RealTimeFactor(rtf),
+        )?;
+        info!(
+            "Suggested total_step {} for a real-time factor of {}",
+            suggested.total_step(),
+            rtf
+        );
+        options.total_step = suggested.total_step;
+    }
+
+    if let Some(Command::Soak {
+        hours,
+        report_minutes,
+        report,
+    }) = &args.command
+    {
+        return soak(
+            &mut text_to_speech,
+            &style,
+            &options,
+            *hours,
+            *report_minutes,
+            report.as_ref(),
+        );
+    }
+
+    let background = match &args.background {
+        Some(path) => {
+            let (bed, rate) = read_wav_file(path)?;
+            let defaults = BackgroundOptions::default();
+            let bed_options = BackgroundOptions {
+                gain_db: args.background_db.unwrap_or(defaults.gain_db),
+                duck_db: args.duck_db.unwrap_or(defaults.duck_db),
+                ..defaults
+            };
+            let bed = resample(&bed, rate, text_to_speech.output_sample_rate(&options)?);
+            Some((bed, bed_options))
+        }
+        None => None,
+    };
+
+    #[cfg(feature = "epub")]
+    if let Some(Command::Audiobook {
+        epub,
+        chapters,
+        format,
+        album,
+    }) = &args.command
+    {
+        let format = format.unwrap_or(match args.wav_format {
+            WavFormat::Pcm16 => AudioFormat::Wav,
+            WavFormat::Pcm24 => AudioFormat::Wav24,
+            WavFormat::Float32 => AudioFormat::WavFloat,
+        });
+        if format == AudioFormat::Opus && stereo {
+            anyhow::bail!("Opus chapters are mono; drop --stereo and --pan");
+        }
+        return audiobook(
+            &mut text_to_speech,
+            &style,
+            narration.as_ref(),
+            &options,
+            epub,
+            chapters,
+            save_dir,
+            format,
+            album.as_deref(),
+            stereo,
+            background.as_ref(),
+            (!args.no_metadata).then(|| {
+                preset_name
+                    .clone()
+                    .unwrap_or_else(|| voice_name(&voice_style_paths[0]))
+            }),
+            args.chunk_log,
+        );
+    }
 
     // --- 4. Synthesize speech --- //
     fs::create_dir_all(save_dir)?;
 
+    if args.strict {
+        let mut count = 0;
+        for (i, text) in text_list.iter().enumerate() {
+            for issue in text_to_speech.strict_issues(text, &options)? {
+                error!("Text {}, {}", i + 1, issue);
+                count += 1;
+            }
+        }
+        if count > 0 {
+            anyhow::bail!(
+                "Strict mode found {} issue(s); nothing was synthesized",
+                count
+            );
+        }
+    }
+
+    if args.debug_text {
+        for (i, text) in text_list.iter().enumerate() {
+            let (prepared, tokens) = text_to_speech.inspect_text(text, &options)?;
+            info!("Text {} is read as: {:?}", i + 1, prepared);
+            let ids: Vec<String> = tokens
+                .iter()
+                .map(|(ch, id)| format!("{:?}={}", ch, id))
+                .collect();
+            info!("Text {} token ids: {}", i + 1, ids.join(" "));
+            let missing: String = tokens
+                .iter()
+                .filter(|(_, id)| *id < 0)
+                .map(|(ch, _)| *ch)
+                .collect();
+            if !missing.is_empty() {
+                warn!("Text {} has no token for: {:?}", i + 1, missing);
+            }
+        }
+    }
+
+    if !batch && narration.is_none() && text_to_speech.profile().is_some() {
+        let estimate = text_to_speech.estimate_latency(&text_list[0], &style, &options)?;
+        info!(
+            "Rendering ~{:.1} s of audio, estimated synthesis time ~{:.1} s",
+            estimate.audio.as_secs_f64(),
+            estimate.synthesis.as_secs_f64()
+        );
+    }
+
+    #[cfg(feature = "playback")]
+    let player = if args.play {
+        Some(supertonic_tts::Player::open_default()?)
+    } else {
+        None
+    };
+
     for n in 0..n_test {
         info!("Starting synthesis batch [{}/{}]", n + 1, n_test);
 
         let (wav_outputs, _duration) = if batch {
-            timer("Generating speech from text (Batch)", || {
-                Ok(text_to_speech.batch(text_list, &style, total_step, speed)?)
-            })?
+            let (wavs, durations) = match args.max_in_flight {
+                Some(n) => text_to_speech.batch_concurrent(text_list, &style, &options, n)?,
+                None => text_to_speech.synthesize_batch(text_list, &style, &options)?,
+            };
+            let pan = text_to_speech.resolve_options(&style, &options).pan();
+            let wavs = if stereo {
+                wavs.iter().map(|w| pan_stereo(w, pan)).collect()
+            } else {
+                wavs
+            };
+            (wavs, durations)
         } else {
-            let (w, d) = timer("Generating speech from text (Single)", || {
-                Ok(text_to_speech.call(&text_list[0], &style, total_step, speed, 0.3)?)
-            })?;
+            let (w, d) = render(
+                &mut text_to_speech,
+                &text_list[0],
+                &style,
+                narration.as_ref(),
+                &options,
+                stereo,
+            )?;
             (vec![w], vec![d])
         };
 
+        log_timings(
+            if batch { "Batch" } else { "Synthesis" },
+            &text_to_speech.last_timing_report(),
+        );
+        for warning in text_to_speech.last_warnings() {
+            warn!("{}", warning);
+        }
+        for (i, stats) in text_to_speech.last_audio_stats().iter().enumerate() {
+            log_stats(&format!("Output {}", i + 1), stats);
+        }
+
         // Save outputs
-        for (i, wav_data) in wav_outputs.iter().enumerate() {
+        let sample_rate = text_to_speech.output_sample_rate(&options)?;
+        for (i, wav_data) in wav_outputs.into_iter().enumerate() {
             let fname = format!("{}_{}.wav", sanitize_filename(&text_list[i], 20), n + 1);
             let output_path = PathBuf::from(save_dir).join(&fname);
-            write_wav_file(&output_path, wav_data, text_to_speech.sample_rate)?;
+            let wav_data = add_background(wav_data, stereo, background.as_ref(), sample_rate);
+            let metadata = if args.no_metadata {
+                AudioMetadata::default()
+            } else {
+                let path = &voice_style_paths[i.min(voice_style_paths.len() - 1)];
+                let voice = preset_name.clone().unwrap_or_else(|| voice_name(path));
+                let resolved = match narration {
+                    Some(_) => options.clone(),
+                    None => text_to_speech.resolve_options(&style, &options),
+                };
+                AudioMetadata::for_synthesis(&text_list[i], Some(&voice), &resolved)
+            };
+            write_wav_file_tagged(
+                &output_path,
+                &wav_data,
+                channels(stereo),
+                sample_rate,
+                args.wav_format,
+                &metadata,
+            )?;
             info!("Saved: {}", output_path.display());
+
+            if args.chunk_log && !batch {
+                let log_path = output_path.with_extension("chunks.jsonl");
+                write_chunk_log(&log_path, text_to_speech.last_chunk_log())?;
+                info!("Saved chunk log: {}", log_path.display());
+            }
+
+            #[cfg(feature = "playback")]
+            if let Some(player) = &player {
+                let mono: Vec<f32> = wav_data
+                    .chunks(channels(stereo) as usize)
+                    .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+                    .collect();
+                player.play(&mono, sample_rate);
+                player.wait();
+            }
+        }
+    }
+
+    if args.profile.is_some() {
+        for path in text_to_speech.end_profiling()? {
+            info!("Profile written: {}", path.display());
         }
     }
 
@@ -133,3 +876,449 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+fn voices_command(command: &VoicesCommand) -> Result<()> {
+    match command {
+        VoicesCommand::Convert {
+            inputs,
+            format,
+            out_dir,
+        } => {
+            if let Some(dir) = out_dir {
+                fs::create_dir_all(dir)?;
+            }
+            for input in inputs {
+                let output = input.with_extension(format.extension());
+                let output = match (out_dir, output.file_name()) {
+                    (Some(dir), Some(name)) => dir.join(name),
+                    _ => output,
+                };
+                if output == *input {
+                    anyhow::bail!("{} is already {}", input.display(), format.extension());
+                }
+                supertonic_tts::style::convert(input, &output, *format)?;
+                info!(
+                    "{} -> {} ({} bytes)",
+                    input.display(),
+                    output.display(),
+                    fs::metadata(&output)?.len()
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+fn audio_command(command: &AudioCommand) -> Result<()> {
+    match command {
+        AudioCommand::Convert(args) => convert_audio(args),
+        AudioCommand::DetectWatermark { input, key } => {
+            let (samples, _) = read_wav_file(input)?;
+            let detection = Watermark::new(*key).detect(&samples);
+            if detection.is_present() {
+                info!(
+                    "Watermark found (score {:.1}, offset {})",
+                    detection.score, detection.offset
+                );
+            } else {
+                info!("No watermark found (score {:.1})", detection.score);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn convert_audio(args: &ConvertArgs) -> Result<()> {
+    let ConvertArgs {
+        input,
+        output,
+        format,
+        rate,
+        lufs,
+        peak,
+        bitrate,
+        frame_size,
+        max_silence,
+        watermark_key,
+    } = args;
+    let opus = OpusOptions {
+        bitrate_kbps: *bitrate,
+        frame_ms: *frame_size,
+    };
+    if *format == AudioFormat::Opus {
+        opus.validate()?;
+    }
+
+    let (mut samples, input_rate) = read_wav_file(input)?;
+    let metadata = read_wav_metadata(input)?;
+    let rate = rate.unwrap_or(input_rate);
+    if rate <= 0 {
+        anyhow::bail!("--rate must be positive");
+    }
+    if rate != input_rate {
+        samples = resample(&samples, input_rate, rate);
+        info!("Resampled {} Hz -> {} Hz", input_rate, rate);
+    }
+    if let Some(max_secs) = max_silence {
+        let before = samples.len();
+        samples = compress_silences(&samples, rate, *max_secs);
+        info!(
+            "Compressed silences by {:.1} s",
+            (before - samples.len()) as f32 / rate as f32
+        );
+    }
+    if let Some(target) = lufs {
+        match normalize_loudness(&mut samples, rate, *target) {
+            Some(gain_db) => info!("Normalized to {:.1} LUFS ({:+.1} dB)", target, gain_db),
+            None => warn!("Input is too quiet to measure; loudness left unchanged"),
+        }
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        if peak > 1.0 && *format != AudioFormat::WavFloat {
+            warn!(
+                "Peak is {:+.1} dBFS after normalization and will clip",
+                20.0 * peak.log10()
+            );
+        }
+    }
+    if let Some(ceiling_db) = peak {
+        match normalize_peak(&mut samples, *ceiling_db) {
+            Some(gain_db) => info!(
+                "Normalized peak to {:.1} dBFS ({:+.1} dB)",
+                ceiling_db, gain_db
+            ),
+            None => warn!("Input is silent; level left unchanged"),
+        }
+    }
+    if let Some(key) = watermark_key {
+        Watermark::new(*key).embed(&mut samples, rate);
+        info!("Embedded watermark");
+    }
+
+    let output = output.clone().unwrap_or_else(|| {
+        let path = input.with_extension(format.extension());
+        if &path == input {
+            input.with_extension(format!("converted.{}", format.extension()))
+        } else {
+            path
+        }
+    });
+    // Tags of the input carry over, as Vorbis comments for Opus
+    match format.wav_format() {
+        Some(wav_format) => {
+            write_wav_file_tagged(&output, &samples, 1, rate, wav_format, &metadata)?
+        }
+        None => write_opus_file_tagged(&output, &samples, rate, &opus, &metadata)?,
+    }
+    info!("Saved: {}", output.display());
+    Ok(())
+}
+
+/// Synthesize `text`, with the narration preset when there is one, as mono
+/// or as interleaved stereo
+fn render(
+    text_to_speech: &mut supertonic_tts::TextToSpeech,
+    text: &str,
+    style: &supertonic_tts::Style,
+    narration: Option<&supertonic_tts::NarrationVoices>,
+    options: &SynthesisOptions,
+    stereo: bool,
+) -> Result<(Vec<f32>, f32)> {
+    Ok(match (narration, stereo) {
+        (Some(voices), true) => text_to_speech.narrate_stereo(text, voices, options)?,
+        (Some(voices), false) => text_to_speech.narrate(text, voices, options)?,
+        (None, true) => {
+            let pan = text_to_speech.resolve_options(style, options).pan();
+            let (wav, duration) = text_to_speech.synthesize(text, style, options)?;
+            (pan_stereo(&wav, pan), duration)
+        }
+        (None, false) => text_to_speech.synthesize(text, style, options)?,
+    })
+}
+
+/// Log where the time of a render went
+fn log_timings(label: &str, report: &TimingReport) {
+    info!(
+        "{}: {:.2} sec (RTF {:.3}); text {:.1} ms, duration predictor {:.1} ms, text encoder {:.1} ms, vector estimator {:.1} ms, vocoder {:.1} ms",
+        label,
+        report.total_ms / 1000.0,
+        report.rtf,
+        report.text_ms,
+        report.dp_ms,
+        report.enc_ms,
+        report.denoise_ms,
+        report.vocoder_ms,
+    );
+}
+
+/// Log the level and silence figures of a render, warning about silent or clipped output
+fn log_stats(label: &str, stats: &AudioStats) {
+    if stats.is_silent() {
+        warn!("{}: output is silent", label);
+        return;
+    }
+    let loudness = stats
+        .loudness_lufs
+        .map_or("n/a".to_string(), |lufs| format!("{:.1} LUFS", lufs));
+    info!(
+        "{}: peak {:.1} dBFS, RMS {:.1} dBFS, loudness {}, silence {:.2} s lead / {:.2} s tail",
+        label,
+        stats.peak_db(),
+        stats.rms_db(),
+        loudness,
+        stats.leading_silence_secs,
+        stats.trailing_silence_secs
+    );
+}
+
+/// Mix the `--background` bed, if any, under a rendered output
+fn add_background(
+    wav: Vec<f32>,
+    stereo: bool,
+    background: Option<&(Vec<f32>, BackgroundOptions)>,
+    sample_rate: i32,
+) -> Vec<f32> {
+    match background {
+        Some((bed, options)) => mix_background(&wav, channels(stereo), bed, sample_rate, options),
+        None => wav,
+    }
+}
+
+/// Name a voice style file stands for, e.g. `M1` for `assets/voice_styles/M1.json`
+fn voice_name(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn channels(stereo: bool) -> u16 {
+    if stereo {
+        2
+    } else {
+        1
+    }
+}
+
+#[cfg(feature = "download")]
+fn download(downloader: &supertonic_tts::Downloader) -> Result<()> {
+    info!(
+        "Downloading {} ({}) into {}",
+        downloader.repo,
+        downloader.revision,
+        downloader.dir()?.display()
+    );
+    // Log each file when it starts and every 10% after that
+    let mut logged = None;
+    downloader.download(|progress| {
+        let percent = progress
+            .total
+            .filter(|&total| total > 0)
+            .map(|total| progress.downloaded * 100 / total);
+        let step = (progress.file.to_string(), percent.map(|p| p / 10));
+        if logged.as_ref() != Some(&step) {
+            logged = Some(step);
+            match percent {
+                Some(percent) => info!("{}: {}%", progress.file, percent),
+                None => info!("{}: {} bytes", progress.file, progress.downloaded),
+            }
+        }
+    })?;
+    let onnx_dir = downloader.onnx_dir()?;
+    if onnx_dir == default_onnx_dir() {
+        info!(
+            "Done; the models in {} are used by default",
+            onnx_dir.display()
+        );
+    } else {
+        info!(
+            "Done; pass --onnx-dir {} to use the models",
+            onnx_dir.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "epub")]
+#[allow(clippy::too_many_arguments)]
+fn audiobook(
+    text_to_speech: &mut supertonic_tts::TextToSpeech,
+    style: &supertonic_tts::Style,
+    narration: Option<&supertonic_tts::NarrationVoices>,
+    options: &SynthesisOptions,
+    path: &PathBuf,
+    only: &[usize],
+    save_dir: &str,
+    format: AudioFormat,
+    album: Option<&str>,
+    stereo: bool,
+    background: Option<&(Vec<f32>, BackgroundOptions)>,
+    voice: Option<String>,
+    chunk_log: bool,
+) -> Result<()> {
+    fs::create_dir_all(save_dir)?;
+    let mut book = supertonic_tts::EpubBook::open(path)?;
+    let book_title = book.title();
+    info!(
+        "Reading '{}'",
+        book_title
+            .clone()
+            .unwrap_or_else(|| path.display().to_string())
+    );
+    let album = album.map(str::to_string).or(book_title);
+
+    // Read dates and times the way the book's language does unless --locale says otherwise
+    let mut options = options.clone();
+    if options.locale.is_none() {
+        options.locale = book.language();
+    }
+
+    let mut total = 0.0;
+    for chapter in book.chapters() {
+        let chapter = chapter?;
+        let number = chapter.index + 1;
+        if !only.is_empty() && !only.contains(&number) {
+            continue;
+        }
+        let title = chapter
+            .title
+            .clone()
+            .unwrap_or_else(|| format!("Chapter {}", number));
+
+        info!("Chapter {}: {}", number, title);
+        let (wav, duration) = render(
+            text_to_speech,
+            &chapter.text,
+            style,
+            narration,
+            &options,
+            stereo,
+        )?;
+        log_timings(
+            &format!("Chapter {}", number),
+            &text_to_speech.last_timing_report(),
+        );
+        total += duration;
+        for warning in text_to_speech.last_warnings() {
+            warn!("Chapter {}: {}", number, warning);
+        }
+        if let Some(stats) = text_to_speech.last_audio_stats().first() {
+            log_stats(&format!("Chapter {}", number), stats);
+        }
+
+        let short_title: String = title.chars().take(40).collect();
+        let fname = format!(
+            "{:03}_{}.{}",
+            number,
+            sanitize_filename(&short_title, short_title.len()),
+            format.extension()
+        );
+        let output_path = PathBuf::from(save_dir).join(&fname);
+        let sample_rate = text_to_speech.output_sample_rate(&options)?;
+        let wav = add_background(wav, stereo, background, sample_rate);
+        // `voice` is None under --no-metadata
+        let metadata = match &voice {
+            Some(voice) => AudioMetadata {
+                title: Some(title.clone()),
+                album: album.clone(),
+                track: Some(number as u32),
+                ..AudioMetadata::for_synthesis(&chapter.text, Some(voice), &options)
+            },
+            None => AudioMetadata::default(),
+        };
+        match format.wav_format() {
+            Some(wav_format) => write_wav_file_tagged(
+                &output_path,
+                &wav,
+                channels(stereo),
+                sample_rate,
+                wav_format,
+                &metadata,
+            )?,
+            None => write_opus_file_tagged(
+                &output_path,
+                &wav,
+                sample_rate,
+                &OpusOptions::default(),
+                &metadata,
+            )?,
+        }
+        info!("Saved: {} ({:.1} s)", output_path.display(), duration);
+
+        if chunk_log {
+            let log_path = output_path.with_extension("chunks.jsonl");
+            write_chunk_log(&log_path, text_to_speech.last_chunk_log())?;
+        }
+    }
+
+    info!("Audiobook finished: {:.1} min of audio", total / 60.0);
+    Ok(())
+}
+
+fn soak(
+    text_to_speech: &mut supertonic_tts::TextToSpeech,
+    style: &supertonic_tts::Style,
+    options: &SynthesisOptions,
+    hours: f64,
+    report_minutes: f64,
+    report_path: Option<&PathBuf>,
+) -> Result<()> {
+    let config = SoakConfig {
+        duration: std::time::Duration::from_secs_f64(hours * 3600.0),
+        report_interval: std::time::Duration::from_secs_f64(report_minutes * 60.0),
+        ..Default::default()
+    };
+    info!("Starting soak test for {:.2} h", hours);
+
+    let mb = |bytes: Option<u64>| {
+        bytes.map_or("n/a".to_string(), |b| format!("{:.1} MB", b as f64 / 1e6))
+    };
+    let report = run_soak(text_to_speech, style, options, &config, |r| {
+        info!(
+            "[{:.0} s] {} iterations, {} errors, RTF {:.3} -> {:.3}, RSS {}",
+            r.elapsed_secs,
+            r.iterations,
+            r.errors,
+            r.rtf_start,
+            r.rtf_end,
+            mb(r.rss_end)
+        );
+    });
+
+    info!("=== Soak Report ===");
+    info!(
+        "Elapsed: {:.0} s, audio generated: {:.0} s",
+        report.elapsed_secs, report.audio_secs
+    );
+    info!(
+        "Iterations: {}, errors: {} ({:.2}%)",
+        report.iterations,
+        report.errors,
+        report.error_rate() * 100.0
+    );
+    for (kind, count) in &report.error_kinds {
+        info!("  {} x {}", count, kind);
+    }
+    info!(
+        "RTF first window {:.3}, last window {:.3} (drift {:+.1}%), max latency {:.2} s",
+        report.rtf_start,
+        report.rtf_end,
+        report.latency_drift * 100.0,
+        report.max_latency_secs
+    );
+    info!(
+        "RSS start {}, end {}, peak {}",
+        mb(report.rss_start),
+        mb(report.rss_end),
+        mb(report.rss_peak)
+    );
+
+    if let Some(path) = report_path {
+        write_file_atomic(path, &serde_json::to_vec_pretty(&report)?)?;
+        info!("Saved soak report: {}", path.display());
+    }
+
+    if report.errors > 0 {
+        anyhow::bail!("Soak test finished with {} errors", report.errors);
+    }
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek};
+use std::path::Path;
+
+use crate::error::SupertonicError;
+use crate::integrity::{AssetManifest, CHECKSUM_MANIFEST};
+use crate::model::{load_text_to_speech_from_memory_with_config, ModelBytes, TextToSpeech};
+use crate::session::SessionConfig;
+
+// ============================================================================
+// Model Bundles
+// ============================================================================
+//
+// Apps and mobile builds ship one archive instead of six loose files. A bundle
+// is a `.zip` (stored or deflated), a `.tar` or a zstd compressed `.tar.zst`
+// holding the four models, `tts.json` and `unicode_indexer.json` at any
+// depth, optionally with voice styles under a `voice_styles/` directory and a
+// `checksums.json` with the digests of the other files.
+
+/// Magic bytes at the start of a zip archive
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Magic bytes at the start of a zstd frame
+const ZSTD_MAGIC: &[u8] = b"\x28\xb5\x2f\xfd";
+
+/// Files of a bundle, read into memory, by their path in the archive
+pub struct ModelBundle {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl ModelBundle {
+    /// Read the archive at `path`, a zip, tar or zstd compressed tar file
+    /// told apart by its contents rather than its extension
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SupertonicError> {
+        let mut file = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; 4];
+        let magic = file
+            .read_exact(&mut magic)
+            .map(|()| magic)
+            .unwrap_or_default();
+        file.rewind()?;
+        match &magic[..] {
+            ZIP_MAGIC => Self::read_zip(file),
+            ZSTD_MAGIC => Self::read_tar_zst(file),
+            _ => Self::read_tar(file),
+        }
+    }
+
+    /// Read an archive already in memory, e.g. an Android asset
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SupertonicError> {
+        if bytes.starts_with(ZIP_MAGIC) {
+            Self::read_zip(Cursor::new(bytes))
+        } else if bytes.starts_with(ZSTD_MAGIC) {
+            Self::read_tar_zst(bytes)
+        } else {
+            Self::read_tar(bytes)
+        }
+    }
+
+    fn read_zip<R: Read + Seek>(reader: R) -> Result<Self, SupertonicError> {
+        let mut archive = zip::ZipArchive::new(reader).map_err(bundle_error)?;
+        let mut files = BTreeMap::new();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(bundle_error)?;
+            if entry.is_file() {
+                let mut bytes = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut bytes)?;
+                files.insert(entry.name().to_string(), bytes);
+            }
+        }
+        Self::checked(files)
+    }
+
+    fn read_tar<R: Read>(reader: R) -> Result<Self, SupertonicError> {
+        let mut archive = tar::Archive::new(reader);
+        let mut files = BTreeMap::new();
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if entry.header().entry_type().is_file() {
+                let path = entry.path()?.to_string_lossy().into_owned();
+                let mut bytes = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut bytes)?;
+                files.insert(path, bytes);
+            }
+        }
+        Self::checked(files)
+    }
+
+    fn read_tar_zst<R: Read>(reader: R) -> Result<Self, SupertonicError> {
+        let decoder = ruzstd::decoding::StreamingDecoder::new(reader)
+            .map_err(|e| SupertonicError::Io(io::Error::other(format!("model bundle: {}", e))))?;
+        Self::read_tar(decoder)
+    }
+
+    /// Check the files against the bundle's checksums, if it has them
+    fn checked(files: BTreeMap<String, Vec<u8>>) -> Result<Self, SupertonicError> {
+        let bundle = ModelBundle { files };
+        if let Some(manifest) = bundle.file(CHECKSUM_MANIFEST) {
+            let manifest: AssetManifest = serde_json::from_slice(manifest)?;
+            for (path, bytes) in &bundle.files {
+                manifest.verify_bytes(file_name(path), bytes)?;
+            }
+        }
+        Ok(bundle)
+    }
+
+    /// Contents of the first file called `name`, in whatever directory
+    pub fn file(&self, name: &str) -> Option<&[u8]> {
+        self.files
+            .iter()
+            .find(|(path, _)| file_name(path) == name)
+            .map(|(_, bytes)| bytes.as_slice())
+    }
+
+    /// Paths of every file in the bundle
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Voice styles in the bundle's `voice_styles/` directory, by file name
+    /// without `.json` or `.bin`, in name order
+    pub fn voice_styles(&self) -> Vec<(&str, &[u8])> {
+        self.files
+            .iter()
+            .filter_map(|(path, bytes)| {
+                let (dir, name) = path.rsplit_once('/')?;
+                let dir = dir.rsplit('/').next().unwrap_or(dir);
+                let id = name
+                    .strip_suffix(".json")
+                    .or_else(|| name.strip_suffix(".bin"))?;
+                (dir == "voice_styles").then_some((id, bytes.as_slice()))
+            })
+            .collect()
+    }
+
+    /// The models, config and indexer, or a `NotFound` error naming the first
+    /// one the bundle lacks
+    pub fn model_bytes(&self) -> Result<ModelBytes<'_>, SupertonicError> {
+        let get = |name: &str| {
+            self.file(name).ok_or_else(|| {
+                SupertonicError::Io(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} not found in the model bundle", name),
+                ))
+            })
+        };
+        Ok(ModelBytes {
+            config: get("tts.json")?,
+            duration_predictor: get("duration_predictor.onnx")?,
+            text_encoder: get("text_encoder.onnx")?,
+            vector_estimator: get("vector_estimator.onnx")?,
+            vocoder: get("vocoder.onnx")?,
+            unicode_indexer: get("unicode_indexer.json")?,
+        })
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn bundle_error(e: zip::result::ZipError) -> SupertonicError {
+    SupertonicError::Io(io::Error::other(format!("model bundle: {}", e)))
+}
+
+/// Load TTS components from a single-archive model bundle
+pub fn load_text_to_speech_from_bundle<P: AsRef<Path>>(
+    path: P,
+    use_gpu: bool,
+) -> Result<TextToSpeech, SupertonicError> {
+    load_text_to_speech_from_bundle_with_config(path, &SessionConfig::with_gpu(use_gpu))
+}
+
+/// Load TTS components from a model bundle with explicit session options
+pub fn load_text_to_speech_from_bundle_with_config<P: AsRef<Path>>(
+    path: P,
+    session_config: &SessionConfig,
+) -> Result<TextToSpeech, SupertonicError> {
+    let bundle = ModelBundle::open(path)?;
+    load_text_to_speech_from_memory_with_config(bundle.model_bytes()?, session_config)
+}
@@ -0,0 +1,342 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+use ndarray::Array3;
+
+use crate::chunk_log::ChunkLogEntry;
+use crate::model::Style;
+use crate::options::SynthesisOptions;
+use crate::stats::AudioStats;
+use crate::warning::SynthesisWarning;
+
+// ============================================================================
+// Least Recently Used Eviction
+// ============================================================================
+//
+// Both caches keep recent entries up to a budget, the encoder cache counted
+// in batches and the synthesis cache in bytes, and make room by dropping the
+// entries used longest ago. Entries are indexed by when they were last used,
+// so finding that entry takes a lookup rather than a scan.
+
+/// Entries up to a total size, evicting the least recently used first
+pub struct LruCache<K, V> {
+    max_size: usize,
+    size: usize,
+    /// Use counter, for finding the least recently used entry
+    clock: u64,
+    /// Each value with its size and when it was last used
+    entries: HashMap<K, (V, usize, u64)>,
+    /// Keys by when they were last used, least recently used first
+    order: BTreeMap<u64, K>,
+    hits: u64,
+    misses: u64,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> LruCache<K, V> {
+    /// Empty cache holding entries of at most `max_size` in total
+    pub fn new(max_size: usize) -> Self {
+        LruCache {
+            max_size,
+            size: 0,
+            clock: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Total size of the entries
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Lookups that found an entry
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Lookups that found none
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Drop every entry
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.size = 0;
+    }
+
+    /// The value of `key`, marked as most recently used
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        self.clock += 1;
+        match self.entries.get_mut(key) {
+            Some((value, _, last_used)) => {
+                if let Some(key) = self.order.remove(last_used) {
+                    self.order.insert(self.clock, key);
+                }
+                *last_used = self.clock;
+                self.hits += 1;
+                Some(value.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Add `value` of `size`, replacing any under `key` and evicting the least
+    /// recently used entries to make room; values larger than the whole cache
+    /// are not kept
+    pub fn insert(&mut self, key: K, value: V, size: usize) {
+        if size > self.max_size {
+            return;
+        }
+        if let Some((_, old_size, last_used)) = self.entries.remove(&key) {
+            self.order.remove(&last_used);
+            self.size -= old_size;
+        }
+        while self.size + size > self.max_size {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some((_, old_size, _)) = self.entries.remove(&oldest) {
+                self.size -= old_size;
+            }
+        }
+        self.clock += 1;
+        self.size += size;
+        self.order.insert(self.clock, key.clone());
+        self.entries.insert(key, (value, size, self.clock));
+    }
+}
+
+// ============================================================================
+// Encoder Cache
+// ============================================================================
+//
+// The duration predictor and text encoder only see the token ids and the
+// voice, so their outputs can be reused when the same phrase is rendered
+// again with another seed or step count; see `TextToSpeech::set_encoder_cache`.
+
+/// Token ids of a batch and the voice it was encoded with
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct EncoderKey {
+    text_ids: Vec<Vec<i64>>,
+    style: u64,
+}
+
+impl EncoderKey {
+    pub fn new(text_ids: &[Vec<i64>], style: &Style) -> Self {
+        EncoderKey {
+            text_ids: text_ids.to_vec(),
+            style: style_id(style),
+        }
+    }
+}
+
+/// Duration predictor and text encoder outputs of a batch
+#[derive(Clone)]
+pub struct EncoderOutputs {
+    /// Predicted durations in seconds, before the speed factor
+    pub duration: Vec<f32>,
+    pub text_emb: Array3<f32>,
+}
+
+/// Most recently used encoder outputs, up to a fixed number of batches
+pub struct EncoderCache(LruCache<EncoderKey, EncoderOutputs>);
+
+impl EncoderCache {
+    /// Empty cache holding the outputs of at most `capacity` batches
+    pub fn new(capacity: usize) -> Self {
+        EncoderCache(LruCache::new(capacity))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.0.max_size()
+    }
+
+    /// Batches whose encoding was skipped
+    pub fn hits(&self) -> u64 {
+        self.0.hits()
+    }
+
+    /// Batches that had to be encoded
+    pub fn misses(&self) -> u64 {
+        self.0.misses()
+    }
+
+    /// Drop every cached batch, e.g. once the models change
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn get(&mut self, key: &EncoderKey) -> Option<EncoderOutputs> {
+        self.0.get(key)
+    }
+
+    pub fn insert(&mut self, key: EncoderKey, outputs: EncoderOutputs) {
+        self.0.insert(key, outputs, 1);
+    }
+}
+
+// ============================================================================
+// Synthesis Cache
+// ============================================================================
+
+/// Text, voice, resolved options and seed of a `synthesize` call
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SynthesisKey {
+    text: String,
+    style: u64,
+    options: String,
+    /// Seed of a seeded engine, `None` for the thread-local RNG
+    seed: Option<u64>,
+}
+
+impl SynthesisKey {
+    pub(crate) fn new(
+        text: &str,
+        style: &Style,
+        options: &SynthesisOptions,
+        seed: Option<u64>,
+    ) -> Self {
+        SynthesisKey {
+            text: text.to_string(),
+            style: style_id(style),
+            options: serde_json::to_string(options).unwrap_or_default(),
+            seed,
+        }
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.text.len() + self.options.len() + std::mem::size_of::<Self>()
+    }
+}
+
+/// A finished render and the records `synthesize` leaves with it
+#[derive(Clone)]
+pub(crate) struct CachedRender {
+    pub(crate) wav: Vec<f32>,
+    pub(crate) duration: f32,
+    pub(crate) chunk_log: Vec<ChunkLogEntry>,
+    pub(crate) warnings: Vec<SynthesisWarning>,
+    pub(crate) stats: AudioStats,
+}
+
+impl CachedRender {
+    fn size_bytes(&self) -> usize {
+        self.wav.len() * std::mem::size_of::<f32>()
+            + self.chunk_log.len() * std::mem::size_of::<ChunkLogEntry>()
+            + self
+                .chunk_log
+                .iter()
+                .map(|e| e.normalized_text.len())
+                .sum::<usize>()
+    }
+}
+
+/// Finished `synthesize` outputs of recent calls, bounded by memory, for
+/// phrases that are rendered again and again (voice assistant prompts, UI
+/// sounds); see `TextToSpeech::set_synthesis_cache`.
+///
+/// Calls match on the text, the voice, the options after voice and engine
+/// defaults are applied, and the engine's seed, so a repeated phrase sounds
+/// the same each time and renders under different seeds are kept apart.
+/// Engines given their own RNG with `set_rng`, or running a post-chunk hook,
+/// always synthesize. Changing the normalizer, G2P, chunking, vocoder window
+/// or strict mode of an engine clears its cache.
+pub struct SynthesisCache {
+    renders: LruCache<SynthesisKey, CachedRender>,
+}
+
+impl SynthesisCache {
+    /// Empty cache holding at most `max_bytes` of audio and bookkeeping
+    pub fn new(max_bytes: usize) -> Self {
+        SynthesisCache {
+            renders: LruCache::new(max_bytes),
+        }
+    }
+
+    /// Number of cached renders
+    pub fn len(&self) -> usize {
+        self.renders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.renders.is_empty()
+    }
+
+    /// Approximate memory held by the cached renders
+    pub fn size_bytes(&self) -> usize {
+        self.renders.size()
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.renders.max_size()
+    }
+
+    /// Calls answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.renders.hits()
+    }
+
+    /// Calls that had to synthesize
+    pub fn misses(&self) -> u64 {
+        self.renders.misses()
+    }
+
+    /// Drop every cached render
+    pub fn clear(&mut self) {
+        self.renders.clear();
+    }
+
+    pub(crate) fn get(&mut self, key: &SynthesisKey) -> Option<CachedRender> {
+        self.renders.get(key)
+    }
+
+    /// Cache `render`, evicting the least recently used renders to make room;
+    /// renders larger than the whole cache are not kept
+    pub(crate) fn insert(&mut self, key: SynthesisKey, render: CachedRender) {
+        let size = key.size_bytes() + render.size_bytes();
+        self.renders.insert(key, render, size);
+    }
+}
+
+/// Hash of a voice's style vectors, identifying it without keeping a copy
+fn style_id(style: &Style) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    style.ttl.shape().hash(&mut hasher);
+    style.dp.shape().hash(&mut hasher);
+    for value in style.ttl.iter().chain(style.dp.iter()) {
+        value.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
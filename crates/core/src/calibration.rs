@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::atomic::write_file_atomic;
+use crate::error::SupertonicError;
+use crate::model::{Style, TextToSpeech};
+use crate::options::SynthesisOptions;
+use crate::text::ChunkBoundary;
+
+// ============================================================================
+// Calibration and Option Suggestions
+// ============================================================================
+//
+// Synthesis time grows with the length of the audio, and the vector estimator
+// runs once per denoising step. A short calibration run measures both costs on
+// the current machine, which is enough to pick the largest `total_step` that
+// still meets a latency or real-time-factor target. `calibrate` repeats the
+// measurement for a steadier profile that can be saved and reused across runs.
+
+const CALIBRATION_TEXT: &str =
+    "The quick brown fox jumps over the lazy dog, then rests in the shade of an old oak tree.";
+const CALIBRATION_STEPS: usize = 5;
+const CALIBRATION_ROUNDS: usize = 3;
+
+/// Range `suggest_options` picks `total_step` from
+pub const MIN_SUGGESTED_STEPS: usize = 1;
+pub const MAX_SUGGESTED_STEPS: usize = 16;
+
+/// Measured synthesis costs of one machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceProfile {
+    /// Machine the profile was measured on (see `current_device`)
+    pub device: String,
+    /// Preprocessed characters per second of audio at speed 1.0
+    pub chars_per_audio_sec: f64,
+    /// Compute seconds per audio second for the duration predictor and text encoder
+    pub fixed_cost: f64,
+    /// Compute seconds per audio second for each denoising step
+    pub step_cost: f64,
+    /// Compute seconds per audio second for the vocoder
+    pub vocoder_cost: f64,
+}
+
+impl PerformanceProfile {
+    /// Estimated audio length of `chars` preprocessed characters, in seconds
+    pub fn audio_secs(&self, chars: usize, speed: f32) -> f64 {
+        chars as f64 / self.chars_per_audio_sec / speed.max(0.01) as f64
+    }
+
+    /// Estimated real-time factor (compute time / audio time) at `total_step`
+    pub fn rtf(&self, total_step: usize) -> f64 {
+        self.fixed_cost + self.vocoder_cost + self.step_cost * total_step as f64
+    }
+
+    /// Real-time streams this machine can sustain at `total_step` (1 / rtf)
+    pub fn capacity(&self, total_step: usize) -> f64 {
+        let rtf = self.rtf(total_step);
+        if rtf > 0.0 {
+            1.0 / rtf
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    /// Seconds of audio the vocoder produces per second of compute
+    pub fn vocoder_throughput(&self) -> f64 {
+        if self.vocoder_cost > 0.0 {
+            1.0 / self.vocoder_cost
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    /// Load a profile saved with `save`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SupertonicError> {
+        let bytes = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// Load a saved profile if it exists and was measured on this machine
+    pub fn load_for_current_device<P: AsRef<Path>>(path: P) -> Option<Self> {
+        Self::load(path)
+            .ok()
+            .filter(|profile| profile.device == current_device())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SupertonicError> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_file_atomic(path, &serde_json::to_vec_pretty(self)?)
+    }
+}
+
+/// Short description of this machine, used to tell whether a saved profile still applies
+pub fn current_device() -> String {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    format!(
+        "{}-{} {} threads{}",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        threads,
+        if cfg!(feature = "rocm") { " rocm" } else { "" }
+    )
+}
+
+/// Where the CLI keeps its profile: `profile.json` in `paths::cache_dir`
+pub fn default_profile_path() -> Option<PathBuf> {
+    Some(crate::paths::cache_dir()?.join("profile.json"))
+}
+
+/// Result of `TextToSpeech::estimate_latency`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatencyEstimate {
+    /// Length of the audio the text renders to
+    pub audio: Duration,
+    /// Expected wall time to synthesize it
+    pub synthesis: Duration,
+}
+
+impl LatencyEstimate {
+    /// Whether synthesis is expected to take longer than playing the audio back
+    pub fn slower_than_real_time(&self) -> bool {
+        self.synthesis > self.audio
+    }
+}
+
+/// What `suggest_options` optimizes for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SuggestTarget {
+    /// Compute time per second of audio (0.5 = twice as fast as real time)
+    RealTimeFactor(f64),
+    /// Total synthesis time for the text
+    Latency(Duration),
+}
+
+impl TextToSpeech {
+    /// Time a short synthesis with `style` and derive this machine's profile.
+    ///
+    /// Runs one warm-up pass first, so session start-up cost is not counted.
+    /// Overwrites the last stage timings and chunk log.
+    pub fn measure_profile(
+        &mut self,
+        style: &Style,
+    ) -> Result<PerformanceProfile, SupertonicError> {
+        self.measure_rounds(style, 1)
+    }
+
+    /// Measure this machine's profile over several rounds and use it from now on.
+    ///
+    /// Slower than the quick measurement `suggest_options` falls back to, but
+    /// steadier; save the result with `PerformanceProfile::save` and restore
+    /// it with `set_profile` to skip calibration on later runs.
+    pub fn calibrate(&mut self, style: &Style) -> Result<PerformanceProfile, SupertonicError> {
+        let profile = self.measure_rounds(style, CALIBRATION_ROUNDS)?;
+        self.profile = Some(profile.clone());
+        Ok(profile)
+    }
+
+    fn measure_rounds(
+        &mut self,
+        style: &Style,
+        rounds: usize,
+    ) -> Result<PerformanceProfile, SupertonicError> {
+        let options = SynthesisOptions {
+            total_step: Some(CALIBRATION_STEPS),
+            speed: Some(1.0),
+            silence_duration: Some(0.0),
+            ..Default::default()
+        };
+        let style = style.broadcast(1)?;
+        self.synthesize("Warm up.", &style, &options)?;
+
+        let mut audio = 0.0;
+        let (mut fixed, mut steps, mut vocoder) = (0.0, 0.0, 0.0);
+        for _ in 0..rounds {
+            let (_, duration) = self.synthesize(CALIBRATION_TEXT, &style, &options)?;
+            let timings = self.last_stage_timings();
+            audio += duration.max(f32::EPSILON) as f64;
+            fixed += (timings.duration_predictor + timings.text_encoder).as_secs_f64();
+            steps += timings.vector_estimator.as_secs_f64() / CALIBRATION_STEPS as f64;
+            vocoder += timings.vocoder.as_secs_f64();
+        }
+
+        let chars = self
+            .text_processor
+            .preprocess(CALIBRATION_TEXT)
+            .chars()
+            .count();
+        Ok(PerformanceProfile {
+            device: current_device(),
+            chars_per_audio_sec: chars as f64 * rounds as f64 / audio,
+            fixed_cost: fixed / audio,
+            step_cost: steps / audio,
+            vocoder_cost: vocoder / audio,
+        })
+    }
+
+    /// Estimate how much audio `text` renders to and how long synthesizing it takes.
+    ///
+    /// The audio length comes from the duration predictor (cheap compared to
+    /// synthesis) and the synthesis time from the current profile; a quick
+    /// calibration run is made if there is none yet.
+    pub fn estimate_latency(
+        &mut self,
+        text: &str,
+        style: &Style,
+        options: &SynthesisOptions,
+    ) -> Result<LatencyEstimate, SupertonicError> {
+        let profile = self.profile_or_measure(style)?;
+        let options = self.resolve_options(style, options);
+        let context = self
+            .text_processor
+            .set_context(options.normalize_context()?);
+        let (chunks, boundaries): (Vec<String>, Vec<ChunkBoundary>) =
+            self.chunks_with_boundaries(text).into_iter().unzip();
+        let durations = self.predict_durations(&chunks, style, options.speed());
+        self.text_processor.set_context(context);
+
+        let pauses = options.pauses();
+        let silence: f32 = boundaries
+            .iter()
+            .take(boundaries.len().saturating_sub(1))
+            .map(|&boundary| pauses.after(boundary))
+            .sum();
+        let audio = (durations?.iter().sum::<f32>() + silence).max(0.0) as f64;
+        Ok(LatencyEstimate {
+            audio: Duration::from_secs_f64(audio),
+            synthesis: Duration::from_secs_f64(audio * profile.rtf(options.total_step())),
+        })
+    }
+
+    fn profile_or_measure(&mut self, style: &Style) -> Result<PerformanceProfile, SupertonicError> {
+        match &self.profile {
+            Some(profile) => Ok(profile.clone()),
+            None => {
+                let profile = self.measure_profile(style)?;
+                self.profile = Some(profile.clone());
+                Ok(profile)
+            }
+        }
+    }
+
+    /// Recommend options for `text` that meet `target` on this machine.
+    ///
+    /// Picks the largest `total_step` within the budget (at least
+    /// `MIN_SUGGESTED_STEPS`, at most `MAX_SUGGESTED_STEPS`), estimating the
+    /// audio length from the normalized text, so expanded numbers, dates and
+    /// URLs count. A calibration run is made the first time it is needed.
+    pub fn suggest_options(
+        &mut self,
+        text: &str,
+        style: &Style,
+        target: SuggestTarget,
+    ) -> Result<SynthesisOptions, SupertonicError> {
+        let profile = self.profile_or_measure(style)?;
+
+        let speed = self
+            .resolve_options(style, &SynthesisOptions::default())
+            .speed();
+        let chars = self.text_processor.preprocess(text).chars().count();
+        let audio = profile.audio_secs(chars, speed).max(f64::EPSILON);
+        let budget = match target {
+            SuggestTarget::RealTimeFactor(rtf) => rtf,
+            SuggestTarget::Latency(latency) => latency.as_secs_f64() / audio,
+        };
+
+        let steps = if profile.step_cost > 0.0 {
+            ((budget - profile.fixed_cost - profile.vocoder_cost) / profile.step_cost)
+                .floor()
+                .max(0.0) as usize
+        } else {
+            MAX_SUGGESTED_STEPS
+        };
+        Ok(SynthesisOptions {
+            total_step: Some(steps.clamp(MIN_SUGGESTED_STEPS, MAX_SUGGESTED_STEPS)),
+            ..Default::default()
+        })
+    }
+
+    /// Profile used by `suggest_options`, if one was measured or set
+    pub fn profile(&self) -> Option<&PerformanceProfile> {
+        self.profile.as_ref()
+    }
+
+    pub fn set_profile(&mut self, profile: Option<PerformanceProfile>) {
+        self.profile = profile;
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Build Capabilities
+// ============================================================================
+
+/// Optional features compiled into this build, so frontends can adapt their UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Chunk-by-chunk streaming synthesis
+    pub streaming: bool,
+    /// Direct audio playback without saving a file first
+    pub playback: bool,
+    /// MP3 encoded output
+    pub mp3: bool,
+    /// GPU inference (`rocm` feature)
+    pub gpu: bool,
+    /// Word-level timestamps alongside the audio
+    pub word_timestamps: bool,
+}
+
+/// Capabilities of the current build
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        streaming: true,
+        playback: cfg!(feature = "playback"),
+        mp3: false,
+        gpu: cfg!(feature = "rocm"),
+        word_timestamps: false,
+    }
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::SupertonicError;
+
+// ============================================================================
+// Voice Catalogs
+// ============================================================================
+//
+// A `voices.json` next to the voice styles describes them for pickers, instead
+// of apps guessing from file names:
+//
+// {
+//   "voices": [
+//     { "id": "F1", "name": "Sarah", "language": "en", "gender": "female",
+//       "tags": ["warm", "narration"], "preview_text": "Hi, I'm Sarah.",
+//       "file": "F1.json" },
+//     { "id": "K1", "name": "Minjun", "language": "ko",
+//       "file": "https://example.com/voices/K1.bin" }
+//   ]
+// }
+//
+// `file` is relative to the catalog or an http(s) URL, and defaults to
+// `<id>.json`.
+
+/// Name of the catalog the loaders look for next to the voice styles
+pub const VOICE_CATALOG: &str = "voices.json";
+
+/// One voice of a catalog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoiceEntry {
+    pub id: String,
+    /// Display name (defaults to the id)
+    #[serde(default)]
+    pub name: String,
+    /// BCP 47 language tag, e.g. `en` or `ko-KR`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gender: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Sentence to read when previewing the voice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_text: Option<String>,
+    /// Style file, relative to the catalog or a URL (defaults to `<id>.json`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+impl VoiceEntry {
+    /// An entry with only an id, for voices without catalog metadata
+    pub fn new(id: &str) -> Self {
+        VoiceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            language: None,
+            gender: None,
+            tags: Vec::new(),
+            preview_text: None,
+            file: None,
+        }
+    }
+
+    /// `file`, or `<id>.json`
+    pub fn file(&self) -> String {
+        self.file
+            .clone()
+            .unwrap_or_else(|| format!("{}.json", self.id))
+    }
+
+    /// Whether the style has to be fetched from a URL
+    pub fn is_remote(&self) -> bool {
+        let file = self.file();
+        file.starts_with("http://") || file.starts_with("https://")
+    }
+}
+
+/// The voices of one directory
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VoiceCatalog {
+    pub voices: Vec<VoiceEntry>,
+}
+
+impl VoiceCatalog {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SupertonicError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Parse a catalog, rejecting duplicate or empty ids
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SupertonicError> {
+        let mut catalog: VoiceCatalog = serde_json::from_slice(bytes)?;
+        for (i, voice) in catalog.voices.iter_mut().enumerate() {
+            if voice.id.is_empty() {
+                return Err(SupertonicError::Validation(format!(
+                    "voice catalog entry {} has no id",
+                    i
+                )));
+            }
+            if voice.name.is_empty() {
+                voice.name = voice.id.clone();
+            }
+        }
+        for (i, voice) in catalog.voices.iter().enumerate() {
+            if catalog.voices[..i].iter().any(|v| v.id == voice.id) {
+                return Err(SupertonicError::Validation(format!(
+                    "voice catalog lists '{}' twice",
+                    voice.id
+                )));
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// The catalog of `dir`, if it has one
+    pub fn find<P: AsRef<Path>>(dir: P) -> Result<Option<Self>, SupertonicError> {
+        let path = dir.as_ref().join(VOICE_CATALOG);
+        if path.is_file() {
+            Self::load(path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The catalog of `dir`, or for a directory without one, an entry per
+    /// `.json` or `.bin` style file named after it, in name order
+    pub fn discover<P: AsRef<Path>>(dir: P) -> Result<Self, SupertonicError> {
+        let dir = dir.as_ref();
+        if let Some(catalog) = Self::find(dir)? {
+            return Ok(catalog);
+        }
+        let mut voices = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !entry.file_type()?.is_file() || name == crate::integrity::CHECKSUM_MANIFEST {
+                continue;
+            }
+            let Some(id) = name
+                .strip_suffix(".json")
+                .or_else(|| name.strip_suffix(".bin"))
+            else {
+                continue;
+            };
+            voices.push(VoiceEntry {
+                file: Some(name.clone()),
+                ..VoiceEntry::new(id)
+            });
+        }
+        voices.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(VoiceCatalog { voices })
+    }
+
+    pub fn get(&self, id: &str) -> Option<&VoiceEntry> {
+        self.voices.iter().find(|voice| voice.id == id)
+    }
+
+    /// Voices whose language is `language` or a regional variant of it
+    /// (`en` matches `en-US`)
+    pub fn by_language<'a>(&'a self, language: &'a str) -> impl Iterator<Item = &'a VoiceEntry> {
+        self.voices.iter().filter(move |voice| {
+            voice.language.as_deref().is_some_and(|tag| {
+                tag.eq_ignore_ascii_case(language)
+                    || tag
+                        .split_once('-')
+                        .is_some_and(|(base, _)| base.eq_ignore_ascii_case(language))
+            })
+        })
+    }
+
+    /// Local path of `id`'s style for a catalog in `dir`, or a `Validation`
+    /// error for unknown or remote voices
+    pub fn style_path<P: AsRef<Path>>(&self, dir: P, id: &str) -> Result<PathBuf, SupertonicError> {
+        let voice = self.get(id).ok_or_else(|| {
+            SupertonicError::Validation(format!("voice '{}' is not in the catalog", id))
+        })?;
+        if voice.is_remote() {
+            return Err(SupertonicError::Validation(format!(
+                "voice '{}' is remote ({}); download it first",
+                id,
+                voice.file()
+            )));
+        }
+        Ok(dir.as_ref().join(voice.file()))
+    }
+}
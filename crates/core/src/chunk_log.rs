@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+use crate::atomic::write_atomic;
+use crate::error::SupertonicError;
+
+// ============================================================================
+// Per-Render Chunk Log
+// ============================================================================
+
+/// One synthesized chunk of a long-form render
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkLogEntry {
+    pub index: usize,
+    /// Chunk text after preprocessing, i.e. what the model actually saw
+    pub normalized_text: String,
+    pub char_count: usize,
+    /// Seed of the noise RNG, if a seeded RNG was in use
+    pub seed: Option<u64>,
+    pub total_step: usize,
+    /// Predicted duration of the chunk in seconds
+    pub duration: f32,
+    /// Wall-time spent synthesizing the chunk in seconds
+    pub synth_time: f32,
+    /// First sample of the chunk in the concatenated output
+    pub start_sample: usize,
+    /// One past the last sample of the chunk in the concatenated output
+    pub end_sample: usize,
+}
+
+/// Write chunk log entries as JSON lines, one entry per line
+pub fn write_chunk_log<P: AsRef<Path>>(
+    path: P,
+    entries: &[ChunkLogEntry],
+) -> Result<(), SupertonicError> {
+    write_atomic(path, |writer| {
+        for entry in entries {
+            serde_json::to_writer(&mut *writer, entry)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,254 @@
+use ort::session::Session;
+use ort::tensor::TensorElementType;
+use ort::value::ValueType;
+use serde::Serialize;
+use std::fmt;
+
+use crate::error::SupertonicError;
+
+// ============================================================================
+// Model Compatibility
+// ============================================================================
+//
+// A model exported with renamed inputs, another dtype or an extra input loads
+// fine and only fails when ONNX Runtime is first asked to run it, often deep
+// into a long render. Every session is checked against the tensors the
+// engine feeds it and reads back as soon as it is created.
+
+/// An input or output the engine relies on: name, element type and rank
+/// (`None` = any rank)
+type Tensor = (&'static str, TensorElementType, Option<usize>);
+
+const F32: TensorElementType = TensorElementType::Float32;
+const I64: TensorElementType = TensorElementType::Int64;
+
+/// Inputs the engine feeds and outputs it reads, by model
+fn expected(model: &str) -> Option<(&'static [Tensor], &'static [Tensor])> {
+    Some(match model {
+        "duration_predictor" => (
+            &[
+                ("text_ids", I64, Some(2)),
+                ("style_dp", F32, Some(3)),
+                ("text_mask", F32, Some(3)),
+            ],
+            &[("duration", F32, None)],
+        ),
+        "text_encoder" => (
+            &[
+                ("text_ids", I64, Some(2)),
+                ("style_ttl", F32, Some(3)),
+                ("text_mask", F32, Some(3)),
+            ],
+            &[("text_emb", F32, Some(3))],
+        ),
+        "vector_estimator" => (
+            &[
+                ("noisy_latent", F32, Some(3)),
+                ("text_emb", F32, Some(3)),
+                ("style_ttl", F32, Some(3)),
+                ("latent_mask", F32, Some(3)),
+                ("text_mask", F32, Some(3)),
+                ("current_step", F32, Some(1)),
+                ("total_step", F32, Some(1)),
+            ],
+            &[("denoised_latent", F32, Some(3))],
+        ),
+        "vocoder" => (&[("latent", F32, Some(3))], &[("wav_tts", F32, None)]),
+        _ => return None,
+    })
+}
+
+/// Check that `session`, created for `model` (one of `MODEL_NAMES`), takes
+/// exactly the inputs the engine feeds it and has the outputs it reads, with
+/// the expected dtypes and ranks
+pub fn check_session(model: &str, session: &Session) -> Result<(), SupertonicError> {
+    let Some((inputs, outputs)) = expected(model) else {
+        return Ok(());
+    };
+    for &(name, ty, rank) in inputs {
+        match session.inputs.iter().find(|input| input.name == name) {
+            Some(input) => check_type(model, "input", name, &input.input_type, ty, rank)?,
+            None => return Err(incompatible(model, format!("has no '{}' input", name))),
+        }
+    }
+    // ONNX Runtime rejects runs that leave an input unset
+    if let Some(extra) = session
+        .inputs
+        .iter()
+        .find(|input| !inputs.iter().any(|(name, _, _)| input.name == *name))
+    {
+        return Err(incompatible(
+            model,
+            format!(
+                "has an input '{}' ({}) the engine does not feed",
+                extra.name, extra.input_type
+            ),
+        ));
+    }
+    for &(name, ty, rank) in outputs {
+        match session.outputs.iter().find(|output| output.name == name) {
+            Some(output) => check_type(model, "output", name, &output.output_type, ty, rank)?,
+            None => return Err(incompatible(model, format!("has no '{}' output", name))),
+        }
+    }
+    Ok(())
+}
+
+fn check_type(
+    model: &str,
+    kind: &str,
+    name: &str,
+    actual: &ValueType,
+    ty: TensorElementType,
+    rank: Option<usize>,
+) -> Result<(), SupertonicError> {
+    let matches = match actual {
+        ValueType::Tensor {
+            ty: actual_ty,
+            shape,
+            ..
+        } => *actual_ty == ty && rank.is_none_or(|rank| shape.len() == rank),
+        _ => false,
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(incompatible(
+            model,
+            format!(
+                "{} '{}' is {}, expected a {} tensor{}",
+                kind,
+                name,
+                actual,
+                ty,
+                rank.map(|rank| format!(" with {} dims", rank))
+                    .unwrap_or_default()
+            ),
+        ))
+    }
+}
+
+fn incompatible(model: &str, problem: String) -> SupertonicError {
+    SupertonicError::Config(format!(
+        "{}.onnx is not compatible with this engine: it {}",
+        model, problem
+    ))
+}
+
+// ============================================================================
+// Model Inspection
+// ============================================================================
+//
+// What the loaded models actually declare, for comparing an exported or
+// quantized model against the released one; see `TextToSpeech::model_info`
+// and `tts inspect`.
+
+/// One input or output of a model
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TensorInfo {
+    pub name: String,
+    /// Element type, e.g. `f32` or `i64`, or the kind of a non-tensor value
+    pub dtype: String,
+    /// Dims, `-1` where dynamic
+    pub shape: Vec<i64>,
+    /// Symbolic name of each dim, empty for fixed or unnamed dims
+    pub dim_names: Vec<String>,
+}
+
+impl TensorInfo {
+    fn new(name: &str, value_type: &ValueType) -> Self {
+        let (dtype, shape, dim_names) = match value_type {
+            ValueType::Tensor {
+                ty,
+                shape,
+                dimension_symbols,
+            } => (ty.to_string(), shape.to_vec(), dimension_symbols.to_vec()),
+            other => (other.to_string(), Vec::new(), Vec::new()),
+        };
+        TensorInfo {
+            name: name.to_string(),
+            dtype,
+            shape,
+            dim_names,
+        }
+    }
+
+    /// Indices of the dims that vary between runs
+    pub fn dynamic_dims(&self) -> impl Iterator<Item = usize> + '_ {
+        self.shape
+            .iter()
+            .enumerate()
+            .filter(|(_, &dim)| dim < 0)
+            .map(|(i, _)| i)
+    }
+}
+
+impl fmt::Display for TensorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} [", self.name, self.dtype)?;
+        for (i, dim) in self.shape.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            match self.dim_names.get(i).filter(|name| !name.is_empty()) {
+                Some(name) if *dim < 0 => f.write_str(name)?,
+                _ if *dim < 0 => f.write_str("?")?,
+                _ => write!(f, "{}", dim)?,
+            }
+        }
+        f.write_str("]")
+    }
+}
+
+/// Inputs and outputs of one of the engine's models
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionInfo {
+    /// One of `MODEL_NAMES`, e.g. `vocoder`
+    pub model: String,
+    /// Whether the session has been created; sessions a `LoadPlan` deferred
+    /// or skipped have no inputs or outputs listed until then
+    pub loaded: bool,
+    pub inputs: Vec<TensorInfo>,
+    pub outputs: Vec<TensorInfo>,
+}
+
+impl SessionInfo {
+    pub(crate) fn new(model: &str, session: Option<&Session>) -> Self {
+        SessionInfo {
+            model: model.to_string(),
+            loaded: session.is_some(),
+            inputs: session
+                .map(|s| {
+                    s.inputs
+                        .iter()
+                        .map(|i| TensorInfo::new(&i.name, &i.input_type))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            outputs: session
+                .map(|s| {
+                    s.outputs
+                        .iter()
+                        .map(|o| TensorInfo::new(&o.name, &o.output_type))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for SessionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.model)?;
+        if !self.loaded {
+            return f.write_str(" (not loaded)");
+        }
+        for input in &self.inputs {
+            write!(f, "\n  in  {}", input)?;
+        }
+        for output in &self.outputs {
+            write!(f, "\n  out {}", output)?;
+        }
+        Ok(())
+    }
+}
@@ -1,39 +1,80 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Config {
     pub ae: AEConfig,
     pub ttl: TTLConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AEConfig {
     pub sample_rate: i32,
     pub base_chunk_size: i32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TTLConfig {
     pub chunk_compress_factor: i32,
     pub latent_dim: i32,
 }
 
+impl Config {
+    /// Check that the values the engine sizes its tensors from are usable
+    pub fn validate(&self) -> Result<()> {
+        if !(8000..=192_000).contains(&self.ae.sample_rate) {
+            bail!(
+                "tts.json: ae.sample_rate is {}, expected 8000 to 192000 Hz",
+                self.ae.sample_rate
+            );
+        }
+        let positive = [
+            ("ae.base_chunk_size", self.ae.base_chunk_size),
+            ("ttl.chunk_compress_factor", self.ttl.chunk_compress_factor),
+            ("ttl.latent_dim", self.ttl.latent_dim),
+        ];
+        for (field, value) in positive {
+            if value <= 0 {
+                bail!(
+                    "tts.json: {} is {}, expected a positive value",
+                    field,
+                    value
+                );
+            }
+        }
+        // Samples per latent frame and channels of the latent
+        let frame = self
+            .ae
+            .base_chunk_size
+            .checked_mul(self.ttl.chunk_compress_factor);
+        let channels = self
+            .ttl
+            .latent_dim
+            .checked_mul(self.ttl.chunk_compress_factor);
+        if frame.is_none() || channels.is_none() {
+            bail!("tts.json: chunk sizes overflow");
+        }
+        Ok(())
+    }
+}
+
 /// Load configuration from JSON file
 pub fn load_cfgs<P: AsRef<Path>>(onnx_dir: P) -> Result<Config> {
     let cfg_path = onnx_dir.as_ref().join("tts.json");
     let file = File::open(cfg_path)?;
     let reader = BufReader::new(file);
     let cfgs: Config = serde_json::from_reader(reader)?;
+    cfgs.validate()?;
     Ok(cfgs)
 }
 
 /// Load configuration from bytes
 pub fn load_cfgs_from_bytes(bytes: &[u8]) -> Result<Config> {
     let cfgs: Config = serde_json::from_slice(bytes)?;
+    cfgs.validate()?;
     Ok(cfgs)
 }
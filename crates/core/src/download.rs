@@ -0,0 +1,236 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use ureq::tls::{TlsConfig, TlsProvider};
+use ureq::Agent;
+
+use crate::error::SupertonicError;
+use crate::paths::{data_dir, installed_assets_dir};
+
+// ============================================================================
+// Hugging Face Hub Downloads
+// ============================================================================
+//
+// First-run setup without copying files by hand: the models and voice styles
+// are fetched from a Hub repository into a directory laid out like `assets/`,
+// so `Downloader::onnx_dir` can go straight to the loaders.
+
+/// Hub repository the released models are published in
+pub const DEFAULT_HF_REPO: &str = "Supertone/supertonic";
+
+/// Files `Downloader` fetches by default, relative to the repository root
+pub const ASSET_FILES: &[&str] = &[
+    "onnx/duration_predictor.onnx",
+    "onnx/text_encoder.onnx",
+    "onnx/vector_estimator.onnx",
+    "onnx/vocoder.onnx",
+    "onnx/unicode_indexer.json",
+    "onnx/tts.json",
+    "voice_styles/M1.json",
+    "voice_styles/M2.json",
+    "voice_styles/F1.json",
+    "voice_styles/F2.json",
+];
+
+/// Bytes read from the network between progress callbacks
+const PROGRESS_BLOCK: usize = 256 * 1024;
+
+/// Progress of one file, passed to the `Downloader::download` callback
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadProgress<'a> {
+    /// Path of the file in the repository
+    pub file: &'a str,
+    /// Bytes on disk so far, including any kept from an interrupted attempt
+    pub downloaded: u64,
+    /// Size of the file, when the server reports it
+    pub total: Option<u64>,
+}
+
+/// Where to download the models from and to; see `download`
+#[derive(Debug, Clone)]
+pub struct Downloader {
+    /// Hub repository, as `owner/name`
+    pub repo: String,
+    /// Branch, tag or commit
+    pub revision: String,
+    /// Hub address, for mirrors
+    pub endpoint: String,
+    /// Access token, for private or gated repositories
+    pub token: Option<String>,
+    /// Directory the files are written to under their repository paths
+    /// (`None` = `paths::installed_assets_dir` for the released models at
+    /// `main`, so the loaders find them without a path, else
+    /// `hub/<owner>--<name>/<revision>` in `paths::data_dir`)
+    pub cache_dir: Option<PathBuf>,
+    /// Repository paths to fetch
+    pub files: Vec<String>,
+}
+
+impl Default for Downloader {
+    fn default() -> Self {
+        Downloader::new(DEFAULT_HF_REPO)
+    }
+}
+
+impl Downloader {
+    /// Fetch `ASSET_FILES` of `repo` at `main` into the data directory
+    pub fn new(repo: &str) -> Self {
+        Downloader {
+            repo: repo.to_string(),
+            revision: "main".to_string(),
+            endpoint: "https://huggingface.co".to_string(),
+            token: None,
+            cache_dir: None,
+            files: ASSET_FILES.iter().map(|file| file.to_string()).collect(),
+        }
+    }
+
+    /// Directory the files are downloaded into
+    pub fn dir(&self) -> Result<PathBuf, SupertonicError> {
+        if let Some(dir) = &self.cache_dir {
+            return Ok(dir.clone());
+        }
+        let missing = || {
+            SupertonicError::Config(
+                "no user data directory found; set Downloader::cache_dir".to_string(),
+            )
+        };
+        if self.repo == DEFAULT_HF_REPO && self.revision == "main" {
+            return installed_assets_dir().ok_or_else(missing);
+        }
+        Ok(data_dir()
+            .ok_or_else(missing)?
+            .join("hub")
+            .join(self.repo.replace('/', "--"))
+            .join(&self.revision))
+    }
+
+    /// Directory of the downloaded models, for `load_text_to_speech`
+    pub fn onnx_dir(&self) -> Result<PathBuf, SupertonicError> {
+        Ok(self.dir()?.join("onnx"))
+    }
+
+    /// Fetch every file missing from the download directory, calling
+    /// `progress` as each one arrives, and return the directory.
+    ///
+    /// Files are written to `<file>.part` and renamed once complete, so files
+    /// already present are not fetched again and an interrupted download
+    /// resumes where it stopped on the next call (when the server honors
+    /// range requests, as the Hub does).
+    pub fn download(
+        &self,
+        mut progress: impl FnMut(&DownloadProgress<'_>),
+    ) -> Result<PathBuf, SupertonicError> {
+        let dir = self.dir()?;
+        let tls = TlsConfig::builder()
+            .provider(TlsProvider::NativeTls)
+            .build();
+        let agent = Agent::new_with_config(Agent::config_builder().tls_config(tls).build());
+        for file in &self.files {
+            let path = dir.join(file);
+            if path.exists() {
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            self.fetch(&agent, file, &path, &mut progress)?;
+        }
+        Ok(dir)
+    }
+
+    fn url(&self, file: &str) -> String {
+        format!(
+            "{}/{}/resolve/{}/{}",
+            self.endpoint.trim_end_matches('/'),
+            self.repo,
+            self.revision,
+            file
+        )
+    }
+
+    /// Download `file` to `path` through its part file
+    fn fetch(
+        &self,
+        agent: &Agent,
+        file: &str,
+        path: &Path,
+        progress: &mut dyn FnMut(&DownloadProgress<'_>),
+    ) -> Result<(), SupertonicError> {
+        let url = self.url(file);
+        let mut part = path.as_os_str().to_owned();
+        part.push(".part");
+        let part = PathBuf::from(part);
+        let resumed = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+
+        let mut request = agent.get(&url);
+        if resumed > 0 {
+            request = request.header("Range", format!("bytes={}-", resumed));
+        }
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        let mut response = match request.call() {
+            Ok(response) => response,
+            // Nothing past the end: the part file already holds all of it
+            Err(ureq::Error::StatusCode(416)) if resumed > 0 => {
+                fs::rename(&part, path)?;
+                return Ok(());
+            }
+            Err(e) => {
+                return Err(SupertonicError::Io(io::Error::other(format!(
+                    "download of {} failed: {}",
+                    url, e
+                ))))
+            }
+        };
+
+        // A server that ignores the range sends the whole file again
+        let append = resumed > 0 && response.status().as_u16() == 206;
+        let mut downloaded = if append { resumed } else { 0 };
+        let total = response.body().content_length().map(|len| len + downloaded);
+        let mut out = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(&part)?;
+
+        let mut reader = response.body_mut().as_reader();
+        let mut block = vec![0u8; PROGRESS_BLOCK];
+        progress(&DownloadProgress {
+            file,
+            downloaded,
+            total,
+        });
+        loop {
+            let n = match reader.read(&mut block) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            out.write_all(&block[..n])?;
+            downloaded += n as u64;
+            progress(&DownloadProgress {
+                file,
+                downloaded,
+                total,
+            });
+        }
+        out.sync_all()?;
+
+        if let Some(total) = total.filter(|&total| downloaded < total) {
+            return Err(SupertonicError::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "download of {} stopped at {} of {} bytes; download again to resume",
+                    url, downloaded, total
+                ),
+            )));
+        }
+        fs::rename(&part, path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use ort::environment::{get_environment, GlobalThreadPoolOptions};
+use ort::memory::{AllocationDevice, AllocatorType, MemoryInfo, MemoryType};
+use ort::AsPointer;
+use serde::{Deserialize, Serialize};
+use std::ptr;
+use std::sync::OnceLock;
+
+use crate::error::SupertonicError;
+
+// ============================================================================
+// ONNX Runtime Environment
+// ============================================================================
+//
+// ONNX Runtime has one environment per process, shared by every session of
+// every engine. By default each session also starts its own intra-op thread
+// pool, so loading several engines (four sessions each) multiplies thread and
+// stack memory, and each session's CPU arena holds on to its own peak. A
+// global thread pool and a CPU arena registered on the environment before the
+// first engine loads are shared by all of them instead.
+
+static COMMITTED: OnceLock<OrtEnvironmentConfig> = OnceLock::new();
+
+/// Process-wide ONNX Runtime settings, applied by `init_environment`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrtEnvironmentConfig {
+    /// Name ONNX Runtime logs the environment under
+    pub name: String,
+    /// Share one thread pool between all sessions instead of one pool per session
+    pub global_thread_pool: bool,
+    /// Intra-op threads of the shared pool (`None` = one per core)
+    pub intra_threads: Option<usize>,
+    /// Inter-op threads of the shared pool
+    pub inter_threads: Option<usize>,
+    /// Let idle pool threads spin for work (lower latency, higher idle CPU use)
+    pub spin_control: Option<bool>,
+    /// Register one CPU arena that sessions allocate from instead of one
+    /// arena each (see `SessionConfig::shared_arena`)
+    pub shared_arena: bool,
+}
+
+impl Default for OrtEnvironmentConfig {
+    fn default() -> Self {
+        OrtEnvironmentConfig {
+            name: "supertonic".to_string(),
+            global_thread_pool: true,
+            intra_threads: None,
+            inter_threads: None,
+            spin_control: None,
+            shared_arena: true,
+        }
+    }
+}
+
+/// Create the ONNX Runtime environment from `config`.
+///
+/// Must run before the first engine is loaded. Returns `Ok(false)` when an
+/// environment already exists — created by an earlier call, by the first
+/// session, or by a host application that initializes `ort` itself — in
+/// which case `config` is ignored and the existing environment is shared.
+pub fn init_environment(config: &OrtEnvironmentConfig) -> Result<bool, SupertonicError> {
+    let mut builder = ort::init().with_name(config.name.clone());
+    if config.global_thread_pool {
+        let mut pool = GlobalThreadPoolOptions::default();
+        if let Some(threads) = config.intra_threads {
+            pool = pool.with_intra_threads(threads)?;
+        }
+        if let Some(threads) = config.inter_threads {
+            pool = pool.with_inter_threads(threads)?;
+        }
+        if let Some(spin) = config.spin_control {
+            pool = pool.with_spin_control(spin)?;
+        }
+        builder = builder.with_global_thread_pool(pool);
+    }
+
+    let created = builder.commit()?;
+    if created {
+        let arena = if config.shared_arena {
+            register_shared_arena()
+        } else {
+            Ok(())
+        };
+        let _ = COMMITTED.set(OrtEnvironmentConfig {
+            shared_arena: config.shared_arena && arena.is_ok(),
+            ..config.clone()
+        });
+        arena?;
+    }
+    Ok(created)
+}
+
+/// Whether `init_environment` registered the shared CPU arena
+pub(crate) fn shared_arena_registered() -> bool {
+    environment_config().is_some_and(|config| config.shared_arena)
+}
+
+/// Register a CPU arena allocator on the environment for sessions that opt
+/// into environment allocators. `ort` has no safe wrapper for this, so it
+/// goes through the C API directly.
+fn register_shared_arena() -> Result<(), SupertonicError> {
+    let env = get_environment()?;
+    let memory_info = MemoryInfo::new(
+        AllocationDevice::CPU,
+        0,
+        AllocatorType::Arena,
+        MemoryType::Default,
+    )?;
+    let api = ort::api();
+    let mut arena_cfg = ptr::null_mut();
+    // SAFETY: no keys are passed, so the arena uses ONNX Runtime's defaults;
+    // the environment and memory info outlive the call, ONNX Runtime copies
+    // what it needs from the config, and the config is released once here.
+    unsafe {
+        ort::error::status_to_result((api.CreateArenaCfgV2)(
+            ptr::null(),
+            ptr::null(),
+            0,
+            &mut arena_cfg,
+        ))?;
+        let status =
+            (api.CreateAndRegisterAllocator)(env.ptr().cast_mut(), memory_info.ptr(), arena_cfg);
+        (api.ReleaseArenaCfg)(arena_cfg);
+        ort::error::status_to_result(status)?;
+    }
+    Ok(())
+}
+
+/// Configuration committed by `init_environment`, if it created the environment
+pub fn environment_config() -> Option<&'static OrtEnvironmentConfig> {
+    COMMITTED.get()
+}
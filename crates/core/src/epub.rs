@@ -0,0 +1,181 @@
+use epub::doc::{EpubDoc, NavPoint};
+use regex::Regex;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::error::SupertonicError;
+
+// ============================================================================
+// EPUB Ingestion
+// ============================================================================
+//
+// Reads an EPUB's spine (the book's reading order) and turns each XHTML
+// document into plain text with one paragraph per line pair, the form the
+// chunker expects. Navigation-only documents (`linear="no"` items such as
+// footnote pages) and documents without text (cover images) are skipped.
+
+/// One chapter of an EPUB, in reading order
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpubChapter {
+    /// Position among the book's text chapters, starting at 0
+    pub index: usize,
+    /// Table-of-contents label of the chapter, if it has one
+    pub title: Option<String>,
+    /// Chapter text, paragraphs separated by blank lines
+    pub text: String,
+}
+
+/// An opened EPUB file
+pub struct EpubBook {
+    doc: EpubDoc<BufReader<File>>,
+}
+
+impl EpubBook {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SupertonicError> {
+        let path = path.as_ref();
+        let doc = EpubDoc::new(path).map_err(|e| {
+            SupertonicError::TextProcessing(format!(
+                "Failed to open EPUB {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(EpubBook { doc })
+    }
+
+    pub fn title(&self) -> Option<String> {
+        self.doc.get_title()
+    }
+
+    /// Language of the book from its metadata (BCP 47, e.g. "en-GB")
+    pub fn language(&self) -> Option<String> {
+        self.doc.mdata("language").map(|item| item.value.clone())
+    }
+
+    /// Text chapters in spine order
+    pub fn chapters(&mut self) -> impl Iterator<Item = Result<EpubChapter, SupertonicError>> + '_ {
+        let mut titles = Vec::new();
+        flatten_toc(&self.doc.toc, &mut titles);
+        let items: Vec<(String, Option<String>)> = self
+            .doc
+            .spine
+            .iter()
+            .filter(|item| item.linear)
+            .map(|item| {
+                let path = self
+                    .doc
+                    .resources
+                    .get(&item.idref)
+                    .map(|resource| resource.path.to_string_lossy().into_owned());
+                let title = path.and_then(|path| {
+                    titles
+                        .iter()
+                        .find(|(content, _)| *content == path)
+                        .map(|(_, label)| label.clone())
+                });
+                (item.idref.clone(), title)
+            })
+            .collect();
+
+        let mut index = 0;
+        items.into_iter().filter_map(move |(id, title)| {
+            let Some((content, _)) = self.doc.get_resource_str(&id) else {
+                return Some(Err(SupertonicError::TextProcessing(format!(
+                    "EPUB spine item '{}' is missing from the archive",
+                    id
+                ))));
+            };
+            let text = xhtml_to_text(&content);
+            if text.is_empty() {
+                return None;
+            }
+            let chapter = EpubChapter { index, title, text };
+            index += 1;
+            Some(Ok(chapter))
+        })
+    }
+}
+
+/// Collect (document path, label) pairs from the table of contents, first entry per document wins
+fn flatten_toc(points: &[NavPoint], out: &mut Vec<(String, String)>) {
+    for point in points {
+        let content = point.content.to_string_lossy();
+        let path = content.split('#').next().unwrap_or_default().to_string();
+        if !out.iter().any(|(p, _)| *p == path) {
+            out.push((path, point.label.trim().to_string()));
+        }
+        flatten_toc(&point.children, out);
+    }
+}
+
+/// Plain text of an XHTML document: block elements become paragraphs, markup
+/// and `<head>`/`<script>`/`<style>` contents are dropped, entities decoded
+pub fn xhtml_to_text(xhtml: &str) -> String {
+    let hidden =
+        Regex::new(r"(?is)<(head|script|style)\b[^>]*>.*?</(head|script|style)\s*>|<!--.*?-->")
+            .unwrap();
+    let block = Regex::new(
+        r"(?i)</?(p|div|h[1-6]|li|blockquote|section|article|tr|dt|dd|figcaption|br|hr)\b[^>]*>",
+    )
+    .unwrap();
+    let tag = Regex::new(r"<[^>]*>").unwrap();
+
+    let text = hidden.replace_all(xhtml, "");
+    // U+2029 PARAGRAPH SEPARATOR, so blank lines inside the source markup don't split paragraphs
+    let text = block.replace_all(&text, "\u{2029}");
+    let text = tag.replace_all(&text, "");
+    decode_entities(&text)
+        .split('\u{2029}')
+        .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let ch = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                "hellip" => Some('…'),
+                "lsquo" => Some('‘'),
+                "rsquo" => Some('’'),
+                "ldquo" => Some('“'),
+                "rdquo" => Some('”'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse::<u32>()))
+                    .and_then(|n| n.ok())
+                    .and_then(char::from_u32),
+            };
+            ch.map(|ch| (ch, end))
+        });
+        match decoded {
+            Some((ch, end)) => {
+                out.push(ch);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -26,6 +26,14 @@ pub enum SupertonicError {
         got: Vec<usize>,
     },
 
+    /// An asset's SHA-256 differs from the one in its directory's `checksums.json`
+    #[error("Integrity error: {file} has SHA-256 {actual}, expected {expected}")]
+    IntegrityError {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
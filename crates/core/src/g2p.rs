@@ -0,0 +1,53 @@
+use crate::error::SupertonicError;
+
+// ============================================================================
+// Grapheme-to-Phoneme Stage
+// ============================================================================
+//
+// An optional stage run on preprocessed text right before it is mapped to
+// token ids. The released models take graphemes, so this is only useful with
+// models trained on the converter's phoneme alphabet, or with converters that
+// respell rare words in plain letters.
+
+/// Converts preprocessed text into the representation the model was trained on
+pub trait G2p: Send + Sync {
+    fn convert(&self, text: &str) -> Result<String, SupertonicError>;
+}
+
+/// G2P through the `espeak-ng` command-line tool (IPA output)
+#[cfg(feature = "espeak")]
+#[derive(Debug, Clone)]
+pub struct EspeakG2p {
+    /// espeak-ng voice, e.g. "en-us" or "de"
+    pub voice: String,
+    /// Path to the `espeak-ng` binary
+    pub binary: std::path::PathBuf,
+}
+
+#[cfg(feature = "espeak")]
+impl EspeakG2p {
+    pub fn new(voice: impl Into<String>) -> Self {
+        EspeakG2p {
+            voice: voice.into(),
+            binary: "espeak-ng".into(),
+        }
+    }
+}
+
+#[cfg(feature = "espeak")]
+impl G2p for EspeakG2p {
+    fn convert(&self, text: &str) -> Result<String, SupertonicError> {
+        let output = std::process::Command::new(&self.binary)
+            .args(["-q", "--ipa", "-v", &self.voice, text])
+            .output()?;
+        if !output.status.success() {
+            return Err(SupertonicError::TextProcessing(format!(
+                "espeak-ng failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        // espeak-ng prints one line per clause; join them back into one string
+        let ipa = String::from_utf8_lossy(&output.stdout);
+        Ok(ipa.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+}
@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::audio::{read_wav_file, write_wav_file};
+use crate::error::SupertonicError;
+
+// ============================================================================
+// Post-Chunk Hooks
+// ============================================================================
+
+/// Information about the chunk passed to a post-chunk hook
+#[derive(Debug, Clone)]
+pub struct ChunkContext<'a> {
+    /// Index of the chunk within the current `call`
+    pub index: usize,
+    /// Chunk text as passed to the model (before normalization)
+    pub text: &'a str,
+    pub sample_rate: i32,
+    /// Seconds of silence inserted before the chunk (0 for the first)
+    pub pause_before: f32,
+}
+
+/// Callback run on each finished chunk's audio before it is concatenated.
+///
+/// The hook may modify the samples in place, including changing their length.
+pub type PostChunkHook =
+    Box<dyn FnMut(&mut Vec<f32>, &ChunkContext<'_>) -> Result<(), SupertonicError> + Send>;
+
+static HOOK_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn hook_temp_path(tag: &str) -> PathBuf {
+    let n = HOOK_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "supertonic-hook-{}-{}-{}.wav",
+        std::process::id(),
+        n,
+        tag
+    ))
+}
+
+/// Build a hook that pipes each chunk through an external shell command.
+///
+/// `{input}` and `{output}` in the command are replaced with paths to WAV
+/// files: the chunk is written to `{input}` and the processed audio is read
+/// back from `{output}` once the command exits successfully, e.g.
+/// `sox {input} {output} compand 0.1,0.2 -60,-60,-30,-10,0,-5`.
+pub fn command_hook(command: impl Into<String>) -> PostChunkHook {
+    let command = command.into();
+    Box::new(move |samples, ctx| {
+        let input = hook_temp_path("in");
+        let output = hook_temp_path("out");
+        write_wav_file(&input, samples, ctx.sample_rate)?;
+
+        let cmdline = command
+            .replace("{input}", &input.to_string_lossy())
+            .replace("{output}", &output.to_string_lossy());
+
+        #[cfg(windows)]
+        let status = Command::new("cmd").args(["/C", &cmdline]).status();
+        #[cfg(not(windows))]
+        let status = Command::new("sh").args(["-c", &cmdline]).status();
+
+        let result = match status {
+            Ok(status) if status.success() => {
+                read_wav_file(&output).and_then(|(processed, rate)| {
+                    if rate != ctx.sample_rate {
+                        return Err(SupertonicError::Validation(format!(
+                            "Post-chunk command changed the sample rate ({} -> {})",
+                            ctx.sample_rate, rate
+                        )));
+                    }
+                    *samples = processed;
+                    Ok(())
+                })
+            }
+            Ok(status) => Err(SupertonicError::Unknown(format!(
+                "Post-chunk command failed on chunk {} ({})",
+                ctx.index, status
+            ))),
+            Err(e) => Err(SupertonicError::Io(e)),
+        };
+
+        let _ = std::fs::remove_file(&input);
+        let _ = std::fs::remove_file(&output);
+        result
+    })
+}
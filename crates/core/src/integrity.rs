@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use crate::atomic::write_file_atomic;
+use crate::error::SupertonicError;
+
+// ============================================================================
+// Asset Integrity
+// ============================================================================
+//
+// A truncated or corrupted model file either crashes ONNX Runtime or loads
+// and renders garbled audio. Directories of models or voice styles can ship a
+// `checksums.json` listing the SHA-256 of each file; the loaders check every
+// listed file against it before using it and name the first one that differs.
+
+/// Name of the manifest the loaders look for next to the assets
+pub const CHECKSUM_MANIFEST: &str = "checksums.json";
+
+/// Bytes hashed per read when hashing a file
+const HASH_BLOCK: usize = 1 << 20;
+
+/// SHA-256 digests of the files of one directory, by file name, as
+/// lowercase hex
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AssetManifest {
+    pub files: BTreeMap<String, String>,
+}
+
+impl AssetManifest {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SupertonicError> {
+        let bytes = fs::read(path)?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// The manifest of `dir`, if it has one
+    pub fn find<P: AsRef<Path>>(dir: P) -> Result<Option<Self>, SupertonicError> {
+        let path = dir.as_ref().join(CHECKSUM_MANIFEST);
+        if path.is_file() {
+            Self::load(path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Digests of every file in `dir` (not its subdirectories or manifest),
+    /// for shipping alongside them
+    pub fn generate<P: AsRef<Path>>(dir: P) -> Result<Self, SupertonicError> {
+        let mut files = BTreeMap::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type()?.is_file() && name != CHECKSUM_MANIFEST {
+                files.insert(name, sha256_file(entry.path())?);
+            }
+        }
+        Ok(AssetManifest { files })
+    }
+
+    /// Write the manifest as `dir/checksums.json`
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<(), SupertonicError> {
+        write_file_atomic(
+            dir.as_ref().join(CHECKSUM_MANIFEST),
+            &serde_json::to_vec_pretty(self)?,
+        )
+    }
+
+    /// Check `bytes`, read from the file `name`, against its digest; files the
+    /// manifest does not list pass
+    pub fn verify_bytes(&self, name: &str, bytes: &[u8]) -> Result<(), SupertonicError> {
+        match self.files.get(name) {
+            Some(expected) => check(name, expected, sha256_hex(bytes)),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the file `name` in `dir` against its digest without loading it
+    /// whole; files the manifest does not list pass
+    pub fn verify_file<P: AsRef<Path>>(&self, dir: P, name: &str) -> Result<(), SupertonicError> {
+        match self.files.get(name) {
+            Some(expected) => check(name, expected, sha256_file(dir.as_ref().join(name))?),
+            None => Ok(()),
+        }
+    }
+}
+
+fn check(name: &str, expected: &str, actual: String) -> Result<(), SupertonicError> {
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(SupertonicError::IntegrityError {
+            file: name.to_string(),
+            expected: expected.to_lowercase(),
+            actual,
+        })
+    }
+}
+
+/// SHA-256 of `bytes` as lowercase hex
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    to_hex(&Sha256::digest(bytes))
+}
+
+/// SHA-256 of the file at `path` as lowercase hex, read in blocks
+pub fn sha256_file<P: AsRef<Path>>(path: P) -> Result<String, SupertonicError> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut block = vec![0u8; HASH_BLOCK];
+    loop {
+        match file.read(&mut block)? {
+            0 => break,
+            n => hasher.update(&block[..n]),
+        }
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
+
+fn to_hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
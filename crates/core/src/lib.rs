@@ -1,15 +1,138 @@
+pub mod atomic;
 pub mod audio;
+pub mod benchmark;
+#[cfg(feature = "bundle")]
+pub mod bundle;
+pub mod cache;
+pub mod calibration;
+pub mod capabilities;
+pub mod catalog;
+pub mod chunk_log;
+pub mod compat;
 pub mod config;
+#[cfg(feature = "download")]
+pub mod download;
+pub mod environment;
+#[cfg(feature = "epub")]
+pub mod epub;
 pub mod error;
+pub mod g2p;
+pub mod hooks;
+pub mod integrity;
+pub mod loudness;
 pub mod model;
+pub mod narration;
+pub mod normalize;
+pub mod options;
+#[cfg(feature = "test-hooks")]
+pub mod parity;
+pub mod paths;
+#[cfg(feature = "playback")]
+pub mod playback;
+pub mod preview;
+pub mod registry;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod session;
+pub mod sink;
+pub mod snapshot;
+pub mod soak;
+pub mod speech_to_speech;
+pub mod stats;
+pub mod strict;
+pub mod style;
 pub mod text;
 pub mod utils;
+pub mod version;
+pub mod voices;
+pub mod warning;
+pub mod watermark;
 
-pub use audio::write_wav_file;
+/// The `ort` version this crate is built against, for hosts that create sessions themselves
+pub use ort;
+
+pub use atomic::{write_atomic, write_file_atomic};
+#[cfg(feature = "dsp")]
+pub use audio::pitch_shift;
+pub use audio::{
+    compress_silences, fade, fade_ms, high_pass, mel_spectrogram, mix_background, normalize_peak,
+    pan_stereo, pcm16_into, preview, read_wav_bytes, read_wav_file, read_wav_metadata, remove_dc,
+    resample, soft_limit, time_stretch, to_pcm16, wav_bytes, wav_bytes_with_format,
+    write_audio_file, write_audio_file_tagged, write_opus_file, write_opus_file_tagged,
+    write_wav_file, write_wav_file_f32, write_wav_file_interleaved, write_wav_file_tagged,
+    write_wav_file_with_format, AudioFormat, AudioMetadata, BackgroundOptions, MelOptions,
+    MelSpectrogram, Mixer, OpusOptions, WavFormat, WavStreamWriter, WaveformBucket,
+    OPUS_SAMPLE_RATE, SILENCE_THRESHOLD_DB,
+};
+pub use benchmark::{Benchmark, BenchmarkReport};
+#[cfg(feature = "bundle")]
+pub use bundle::{
+    load_text_to_speech_from_bundle, load_text_to_speech_from_bundle_with_config, ModelBundle,
+};
+pub use cache::{LruCache, SynthesisCache};
+pub use calibration::{
+    current_device, default_profile_path, LatencyEstimate, PerformanceProfile, SuggestTarget,
+};
+pub use capabilities::{capabilities, Capabilities};
+pub use catalog::{VoiceCatalog, VoiceEntry, VOICE_CATALOG};
+pub use chunk_log::{write_chunk_log, ChunkLogEntry};
+pub use compat::{SessionInfo, TensorInfo};
 pub use config::{load_cfgs, AEConfig, Config, TTLConfig};
+#[cfg(feature = "download")]
+pub use download::{DownloadProgress, Downloader, ASSET_FILES, DEFAULT_HF_REPO};
+pub use environment::{environment_config, init_environment, OrtEnvironmentConfig};
+#[cfg(feature = "epub")]
+pub use epub::{xhtml_to_text, EpubBook, EpubChapter};
+#[cfg(feature = "espeak")]
+pub use g2p::EspeakG2p;
+pub use g2p::G2p;
+pub use hooks::{command_hook, ChunkContext, PostChunkHook};
+pub use integrity::{sha256_file, sha256_hex, AssetManifest, CHECKSUM_MANIFEST};
+pub use loudness::{integrated_loudness, match_gains, normalize_loudness};
 pub use model::{
-    load_text_to_speech, load_text_to_speech_from_memory, load_voice_style,
-    load_voice_style_from_bytes, ModelBytes, Style, TextToSpeech,
+    check_reload_config, crossfade_into, dedup_slots, fan_out, length_buckets, load_text_to_speech,
+    load_text_to_speech_from_memory, load_text_to_speech_from_memory_with_config,
+    load_text_to_speech_from_readers, load_text_to_speech_from_readers_with_config,
+    load_text_to_speech_from_sessions, load_text_to_speech_with_config, load_voice_style,
+    load_voice_style_from_bytes, vocoder_windows, ModelBytes, ModelReaders, ModelSessions,
+    StageTimings, Style, TextToSpeech, TimingReport, VOCODER_WINDOW_OVERLAP,
+};
+pub use narration::{split_dialogue, NarrationPreset, NarrationRole, NarrationVoices};
+pub use normalize::{
+    number_to_words, ordinal_words, AcronymRules, Currency, CurrencyTable, EmojiPolicy, Locale,
+    NormalizeContext, TextNormalizer,
+};
+pub use options::{ChunkPauses, SpeedMode, SynthesisOptions};
+pub use paths::{default_assets_dir, default_onnx_dir, default_voice_styles_dir};
+#[cfg(feature = "rodio")]
+pub use playback::AudioPlayer;
+#[cfg(feature = "playback")]
+pub use playback::{Player, PlayerSink, DEFAULT_READ_AHEAD_SECS};
+pub use preview::{Preview, PreviewRange, DEFAULT_PREVIEW_SECS, PREVIEW_TOTAL_STEP};
+pub use registry::{EngineRegistry, SharedEngine, DEFAULT_MODEL_ID};
+#[cfg(feature = "scripting")]
+pub use scripting::ScriptRules;
+pub use session::{GraphOptLevel, LoadPlan, SessionConfig};
+pub use sink::{AudioSink, CallbackSink, RingBufferReader, RingBufferSink, TeeSink};
+pub use snapshot::load_text_to_speech_with_snapshot;
+pub use soak::{run_soak, SoakConfig, SoakReport};
+pub use speech_to_speech::{
+    Repeat, SegmentTransform, SentenceBridge, SpeechPipeline, SpeechRecognizer, SpeechSegment,
+    SpokenSegment,
+};
+pub use stats::AudioStats;
+pub use strict::{markup_issues, StrictIssue, StrictIssueKind, TextPosition};
+pub use style::{StyleFormat, STYLE_MAGIC};
+pub use text::{
+    chunk_text, chunk_text_by_tokens, chunk_text_with_boundaries, chunk_text_with_locale,
+    preprocess_text, preprocess_text_traced, preprocess_text_with, preprocess_text_with_context,
+    split_sentences, ChunkBoundary, PreprocessReport, PreprocessStage, PreprocessStep,
+    UnicodeProcessor, UnknownCharPolicy, UnsupportedChar,
+};
+pub use utils::sanitize_filename;
+pub use version::{version, VersionInfo};
+pub use voices::VoiceManager;
+pub use warning::SynthesisWarning;
+pub use watermark::{
+    Watermark, WatermarkDetection, DEFAULT_WATERMARK_STRENGTH_DB, WATERMARK_THRESHOLD,
 };
-pub use text::{chunk_text, preprocess_text, UnicodeProcessor};
-pub use utils::{sanitize_filename, timer};
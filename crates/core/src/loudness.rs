@@ -0,0 +1,132 @@
+use crate::audio::Biquad;
+use crate::options::apply_gain;
+
+// ============================================================================
+// Loudness Measurement
+// ============================================================================
+//
+// Integrated loudness per ITU-R BS.1770 (K-weighting, 400 ms blocks with 75%
+// overlap, absolute gate at -70 LUFS and relative gate 10 LU below the
+// ungated level). Used to level voices against each other in multi-voice
+// renders.
+
+const BLOCK_SECS: f64 = 0.4;
+const STEP_SECS: f64 = 0.1;
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+
+/// Largest correction `match_gains` applies to one voice, in dB
+pub const MAX_MATCH_GAIN_DB: f64 = 12.0;
+
+/// The two K-weighting stages (high shelf, then high pass) for `sample_rate`,
+/// designed as in libebur128 so they match the standard's 48 kHz coefficients
+fn k_weighting(sample_rate: f64) -> [Biquad; 2] {
+    // Shelving stage
+    let (f0, gain_db, q) = (
+        1_681.974_450_955_533,
+        3.999_843_853_973_347,
+        0.707_175_236_955_419_6,
+    );
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    };
+
+    // High-pass stage
+    let (f0, q) = (38.135_470_876_139_82, 0.500_327_037_325_395_3);
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    };
+
+    [shelf, high_pass]
+}
+
+fn block_loudness(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.log10()
+}
+
+/// Integrated loudness of mono `samples` in LUFS.
+///
+/// Returns `None` for silence or audio that is gated out entirely. Clips
+/// shorter than one 400 ms block are measured as a single block.
+pub fn integrated_loudness(samples: &[f32], sample_rate: i32) -> Option<f64> {
+    if samples.is_empty() || sample_rate <= 0 {
+        return None;
+    }
+    let fs = sample_rate as f64;
+    let [shelf, high_pass] = k_weighting(fs);
+    let input: Vec<f64> = samples.iter().map(|&s| s as f64).collect();
+    let weighted = high_pass.run(&shelf.run(&input));
+
+    let block = ((BLOCK_SECS * fs) as usize).min(weighted.len());
+    let step = ((STEP_SECS * fs) as usize).max(1);
+    let mut powers = Vec::new();
+    let mut start = 0;
+    while start + block <= weighted.len() {
+        let z = weighted[start..start + block]
+            .iter()
+            .map(|x| x * x)
+            .sum::<f64>()
+            / block as f64;
+        powers.push(z);
+        start += step;
+    }
+
+    let gated: Vec<f64> = powers
+        .into_iter()
+        .filter(|&z| z > 0.0 && block_loudness(z) > ABSOLUTE_GATE)
+        .collect();
+    if gated.is_empty() {
+        return None;
+    }
+    let relative = block_loudness(gated.iter().sum::<f64>() / gated.len() as f64) + RELATIVE_GATE;
+    let kept: Vec<f64> = gated
+        .into_iter()
+        .filter(|&z| block_loudness(z) > relative)
+        .collect();
+    if kept.is_empty() {
+        return None;
+    }
+    Some(block_loudness(kept.iter().sum::<f64>() / kept.len() as f64))
+}
+
+/// Per-voice gains in dB that bring every measured loudness to their mean.
+///
+/// Voices without a measurement (`None`) get 0 dB; corrections are limited
+/// to ±`MAX_MATCH_GAIN_DB`.
+pub fn match_gains(loudness: &[Option<f64>]) -> Vec<f64> {
+    let measured: Vec<f64> = loudness.iter().flatten().copied().collect();
+    if measured.is_empty() {
+        return vec![0.0; loudness.len()];
+    }
+    let target = measured.iter().sum::<f64>() / measured.len() as f64;
+    loudness
+        .iter()
+        .map(|l| {
+            l.map_or(0.0, |l| {
+                (target - l).clamp(-MAX_MATCH_GAIN_DB, MAX_MATCH_GAIN_DB)
+            })
+        })
+        .collect()
+}
+
+/// Scale `samples` so their integrated loudness is `target_lufs`.
+///
+/// Returns the applied gain in dB, or `None` (leaving the samples untouched)
+/// when the audio is too quiet to measure.
+pub fn normalize_loudness(samples: &mut [f32], sample_rate: i32, target_lufs: f64) -> Option<f64> {
+    let gain_db = target_lufs - integrated_loudness(samples, sample_rate)?;
+    apply_gain(samples, 10f64.powf(gain_db / 20.0) as f32);
+    Some(gain_db)
+}
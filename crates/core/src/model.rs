@@ -1,13 +1,45 @@
-use ndarray::{Array, Array3};
-use ort::{session::Session, value::Value};
+use ndarray::{s, Array, Array3, Axis, Ix3};
+use ort::memory::{AllocationDevice, AllocatorType, MemoryInfo, MemoryType};
+use ort::session::Session;
+use ort::value::{DynValue, Tensor, TensorRef, Value};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
 use rand_distr::{Distribution, Normal};
 use serde::{Deserialize, Serialize};
-use serde_json;
+use std::borrow::Cow;
+use std::io::Read;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tracing::info;
 
+use crate::audio::{
+    fade, fade_ms, high_pass, pan_stereo, position_after_cuts, remove_dc, remove_ranges, resample,
+    silence_cuts, soft_limit, time_stretch,
+};
+use crate::cache::{
+    CachedRender, EncoderCache, EncoderKey, EncoderOutputs, SynthesisCache, SynthesisKey,
+};
+use crate::calibration::PerformanceProfile;
+use crate::chunk_log::ChunkLogEntry;
+use crate::compat::{check_session, SessionInfo};
 use crate::config::Config;
 use crate::error::SupertonicError;
-use crate::text::{chunk_text, length_to_mask, UnicodeProcessor};
+use crate::hooks::{ChunkContext, PostChunkHook};
+use crate::integrity::AssetManifest;
+use crate::loudness::{integrated_loudness, match_gains};
+use crate::narration::NarrationVoices;
+use crate::normalize::TextNormalizer;
+use crate::options::{apply_gain, ChunkPauses, ChunkSteps, SynthesisOptions};
+use crate::session::{latent_device, LazySession, ModelSource, SessionConfig, MODEL_NAMES};
+use crate::sink::AudioSink;
+use crate::stats::AudioStats;
+use crate::text::{
+    chunk_text_measured, chunk_text_with_boundaries, ChunkBoundary, PreprocessReport, TokenCounter,
+    UnicodeProcessor, UnknownCharPolicy, UnsupportedChar, MAX_CHUNK_LENGTH,
+};
+use crate::warning::{clipping_warning, SynthesisWarning};
+use crate::watermark::Watermark;
 
 // ============================================================================
 // Voice Style Data Structure
@@ -17,6 +49,9 @@ use crate::text::{chunk_text, length_to_mask, UnicodeProcessor};
 pub struct VoiceStyleData {
     pub style_ttl: StyleComponent,
     pub style_dp: StyleComponent,
+    /// Recommended synthesis options for this voice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<SynthesisOptions>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,239 +62,2270 @@ pub struct StyleComponent {
     pub dtype: String,
 }
 
+#[derive(Clone)]
 pub struct Style {
     pub ttl: Array3<f32>,
     pub dp: Array3<f32>,
+    /// Defaults from the first voice's style file, applied by `synthesize`
+    pub defaults: SynthesisOptions,
+}
+
+impl Style {
+    /// Number of voices in this style batch
+    pub fn batch_size(&self) -> usize {
+        self.ttl.shape()[0]
+    }
+
+    /// This style for a batch of `bsz` texts, copied only when a single voice
+    /// has to be repeated for every text
+    pub(crate) fn for_batch(&self, bsz: usize) -> Result<Cow<'_, Style>, SupertonicError> {
+        if self.batch_size() == bsz {
+            Ok(Cow::Borrowed(self))
+        } else {
+            self.broadcast(bsz).map(Cow::Owned)
+        }
+    }
+
+    /// Repeat a single-voice style `bsz` times so it can be paired with a batch of texts
+    pub fn broadcast(&self, bsz: usize) -> Result<Style, SupertonicError> {
+        let n = self.batch_size();
+        if n == bsz {
+            return Ok(self.clone());
+        }
+        if n != 1 || self.dp.shape()[0] != 1 {
+            return Err(SupertonicError::Validation(format!(
+                "Voice style batch of {} cannot be used with {} texts; pass one style or one per text",
+                n, bsz
+            )));
+        }
+        let broadcast = |a: &Array3<f32>| {
+            let (_, d1, d2) = a.dim();
+            a.broadcast((bsz, d1, d2))
+                .map(|v| v.to_owned())
+                .ok_or_else(|| SupertonicError::ShapeMismatch {
+                    expected: vec![bsz, d1, d2],
+                    got: a.shape().to_vec(),
+                })
+        };
+        Ok(Style {
+            ttl: broadcast(&self.ttl)?,
+            dp: broadcast(&self.dp)?,
+            defaults: self.defaults.clone(),
+        })
+    }
+
+    /// The voices at `indices`, in that order
+    pub(crate) fn select(&self, indices: &[usize]) -> Style {
+        Style {
+            ttl: self.ttl.select(Axis(0), indices),
+            dp: self.dp.select(Axis(0), indices),
+            defaults: self.defaults.clone(),
+        }
+    }
+
+    /// Whether voices `a` and `b` of this batch are identical
+    fn same_voice(&self, a: usize, b: usize) -> bool {
+        self.batch_size() == 1
+            || (self.ttl.index_axis(Axis(0), a) == self.ttl.index_axis(Axis(0), b)
+                && self.dp.index_axis(Axis(0), a) == self.dp.index_axis(Axis(0), b))
+    }
 }
 
 // ============================================================================
 // ONNX Runtime Integration
 // ============================================================================
 
-pub struct TextToSpeech {
-    cfgs: Config,
-    text_processor: UnicodeProcessor,
-    dp_ort: Session,
-    text_enc_ort: Session,
-    vector_est_ort: Session,
-    vocoder_ort: Session,
-    pub sample_rate: i32,
-}
+/// Wall-time spent in text processing and each ONNX session during the last `call`/`batch`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StageTimings {
+    /// Tokenization, before the sessions
+    #[serde(default)]
+    pub text_processing: Duration,
+    pub duration_predictor: Duration,
+    pub text_encoder: Duration,
+    pub vector_estimator: Duration,
+    pub vocoder: Duration,
+}
+
+impl StageTimings {
+    /// Time spent in the four sessions
+    pub fn total(&self) -> Duration {
+        self.duration_predictor + self.text_encoder + self.vector_estimator + self.vocoder
+    }
+
+    /// Each stage's longer time of `self` and `other`, for work that ran at
+    /// the same time
+    pub fn longest(self, other: StageTimings) -> StageTimings {
+        StageTimings {
+            text_processing: self.text_processing.max(other.text_processing),
+            duration_predictor: self.duration_predictor.max(other.duration_predictor),
+            text_encoder: self.text_encoder.max(other.text_encoder),
+            vector_estimator: self.vector_estimator.max(other.vector_estimator),
+            vocoder: self.vocoder.max(other.vocoder),
+        }
+    }
+}
+
+impl std::ops::AddAssign for StageTimings {
+    fn add_assign(&mut self, other: StageTimings) {
+        self.text_processing += other.text_processing;
+        self.duration_predictor += other.duration_predictor;
+        self.text_encoder += other.text_encoder;
+        self.vector_estimator += other.vector_estimator;
+        self.vocoder += other.vocoder;
+    }
+}
+
+/// Per-stage milliseconds, wall time and real-time factor of the last render,
+/// for surfacing performance in an integrator's own telemetry.
+///
+/// The encoder stages of a chunk overlap the vocoder of the one before it, so
+/// the stages can add up to more than `total_ms`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimingReport {
+    pub text_ms: f64,
+    pub dp_ms: f64,
+    pub enc_ms: f64,
+    /// Vector estimator over all denoising steps
+    pub denoise_ms: f64,
+    pub vocoder_ms: f64,
+    /// Wall time of the whole call, including chunking and the output stage
+    pub total_ms: f64,
+    /// `total_ms` over the audio duration (below 1.0 is faster than real
+    /// time); 0 when no audio was rendered
+    pub rtf: f64,
+}
+
+impl TimingReport {
+    pub fn new(timings: StageTimings, total: Duration, audio_secs: f32) -> Self {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        TimingReport {
+            text_ms: ms(timings.text_processing),
+            dp_ms: ms(timings.duration_predictor),
+            enc_ms: ms(timings.text_encoder),
+            denoise_ms: ms(timings.vector_estimator),
+            vocoder_ms: ms(timings.vocoder),
+            total_ms: ms(total),
+            rtf: if audio_secs > 0.0 {
+                total.as_secs_f64() / audio_secs as f64
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// Longest chunk of the first sentence under `SynthesisOptions::low_latency`,
+/// in characters
+const LOW_LATENCY_CHUNK_LENGTH: usize = 40;
+
+/// Length of the micro-fade on each side of a pause inserted between chunks.
+///
+/// Chunks rarely start or end on a zero sample, and the step into or out of
+/// digital silence is audible as a click on headphones.
+const DECLICK_SECS: f32 = 0.002;
+
+/// A narration mix with its duration, and the output sample range and pan of
+/// each segment
+type NarratedSegments = (Vec<f32>, f32, Vec<(Range<usize>, f32)>);
+
+/// Chunk log, warnings and stage timings gathered over several renders that
+/// make up one output
+#[derive(Default)]
+struct RenderTotals {
+    chunk_log: Vec<ChunkLogEntry>,
+    warnings: Vec<SynthesisWarning>,
+    timings: StageTimings,
+}
+
+pub struct TextToSpeech {
+    cfgs: Config,
+    pub(crate) text_processor: UnicodeProcessor,
+    dp_ort: LazySession,
+    text_enc_ort: LazySession,
+    vector_est_ort: LazySession,
+    vocoder_ort: LazySession,
+    /// Device the latent stays on across denoising steps, when the sessions
+    /// run on a GPU provider
+    latent_device: Option<AllocationDevice>,
+    /// Options the sessions were created with, reused by `reload`
+    pub(crate) session_config: SessionConfig,
+    rng: Option<Box<dyn RngCore + Send>>,
+    seed: Option<u64>,
+    #[cfg(feature = "test-hooks")]
+    noise_override: Option<Array3<f32>>,
+    #[cfg(feature = "test-hooks")]
+    captured: Option<crate::parity::StageOutputs>,
+    timings: StageTimings,
+    /// Wall time and audio duration of the last render
+    render_time: (Duration, f32),
+    chunk_log: Vec<ChunkLogEntry>,
+    default_options: SynthesisOptions,
+    post_chunk_hook: Option<PostChunkHook>,
+    pub(crate) profile: Option<PerformanceProfile>,
+    max_chunk_tokens: Option<usize>,
+    pub(crate) strict: bool,
+    warnings: Vec<SynthesisWarning>,
+    stats: Vec<AudioStats>,
+    /// Text `call_chunks` encodes after its last chunk, set by
+    /// `synthesize_to_sink` to the chunk it renders next
+    lookahead: Option<String>,
+    /// The lookahead text, encoded
+    prefetched: Option<Prefetched>,
+    encoder_cache: Option<EncoderCache>,
+    synthesis_cache: Option<SynthesisCache>,
+    latent_buffers: LatentBuffers,
+    /// Latent frames per vocoder run, when long chunks are vocoded in windows
+    vocoder_window: Option<usize>,
+    pub sample_rate: i32,
+}
+
+/// A clone is a second handle on the same loaded models: the four sessions
+/// are shared, not re-read, so cloning is cheap and each thread or component
+/// can own its engine.
+///
+/// Clones take turns on each session, so renders on different clones run
+/// concurrently while they are in different stages (one denoising while
+/// another vocodes) and wait for each other otherwise. Everything else is
+/// copied: settings, default options and the text processor. A seeded
+/// engine's clone restarts the seed's sequence, one using `set_rng` uses the
+/// thread-local RNG, and clones start with empty caches of the same size, no
+/// post-chunk hook and no results of earlier renders.
+impl Clone for TextToSpeech {
+    fn clone(&self) -> Self {
+        let mut tts = Self::with_sessions(
+            self.cfgs.clone(),
+            self.text_processor.clone(),
+            [
+                self.dp_ort.clone(),
+                self.text_enc_ort.clone(),
+                self.vector_est_ort.clone(),
+                self.vocoder_ort.clone(),
+            ],
+            &self.session_config,
+        );
+        if let Some(seed) = self.seed {
+            tts.set_seed(seed);
+        }
+        #[cfg(feature = "test-hooks")]
+        {
+            tts.noise_override = self.noise_override.clone();
+        }
+        tts.default_options = self.default_options.clone();
+        tts.profile = self.profile.clone();
+        tts.max_chunk_tokens = self.max_chunk_tokens;
+        tts.strict = self.strict;
+        tts.encoder_cache = self
+            .encoder_cache
+            .as_ref()
+            .map(|cache| EncoderCache::new(cache.capacity()));
+        tts.synthesis_cache = self
+            .synthesis_cache
+            .as_ref()
+            .map(|cache| SynthesisCache::new(cache.max_bytes()));
+        tts.vocoder_window = self.vocoder_window;
+        tts.sample_rate = self.sample_rate;
+        tts
+    }
+}
+
+impl TextToSpeech {
+    pub fn new(
+        cfgs: Config,
+        text_processor: UnicodeProcessor,
+        dp_ort: Session,
+        text_enc_ort: Session,
+        vector_est_ort: Session,
+        vocoder_ort: Session,
+    ) -> Self {
+        Self::with_sessions(
+            cfgs,
+            text_processor,
+            [
+                LazySession::ready("duration_predictor", dp_ort),
+                LazySession::ready("text_encoder", text_enc_ort),
+                LazySession::ready("vector_estimator", vector_est_ort),
+                LazySession::ready("vocoder", vocoder_ort),
+            ],
+            &SessionConfig::default(),
+        )
+    }
+
+    /// The engine every loader builds, with what follows from the options
+    /// its sessions were created with (such as the latent device)
+    pub(crate) fn with_sessions(
+        cfgs: Config,
+        text_processor: UnicodeProcessor,
+        [dp_ort, text_enc_ort, vector_est_ort, vocoder_ort]: [LazySession; 4],
+        session_config: &SessionConfig,
+    ) -> Self {
+        let sample_rate = cfgs.ae.sample_rate;
+        TextToSpeech {
+            cfgs,
+            text_processor,
+            dp_ort,
+            text_enc_ort,
+            vector_est_ort,
+            vocoder_ort,
+            latent_device: latent_device(session_config),
+            session_config: session_config.clone(),
+            rng: None,
+            seed: None,
+            #[cfg(feature = "test-hooks")]
+            noise_override: None,
+            #[cfg(feature = "test-hooks")]
+            captured: None,
+            timings: StageTimings::default(),
+            render_time: (Duration::ZERO, 0.0),
+            chunk_log: Vec::new(),
+            default_options: SynthesisOptions::default(),
+            post_chunk_hook: None,
+            profile: None,
+            max_chunk_tokens: None,
+            strict: false,
+            warnings: Vec::new(),
+            stats: Vec::new(),
+            lookahead: None,
+            prefetched: None,
+            encoder_cache: None,
+            synthesis_cache: None,
+            latent_buffers: LatentBuffers::default(),
+            vocoder_window: None,
+            sample_rate,
+        }
+    }
+
+    /// Replace the sampled latent noise with a fixed tensor, for bit-exact
+    /// comparisons against reference implementations.
+    ///
+    /// The tensor must have the shape `_infer` would sample, i.e.
+    /// `[batch, latent_dim * chunk_compress_factor, latent_len]`, so this is
+    /// meant for single-chunk `batch` calls. The latent mask is still applied.
+    #[cfg(feature = "test-hooks")]
+    pub fn set_noise_override(&mut self, noise: Option<Array3<f32>>) {
+        self.noise_override = noise;
+    }
+
+    /// Intermediate tensors of the last `_infer` call, for parity checks
+    #[cfg(feature = "test-hooks")]
+    pub fn last_stage_outputs(&self) -> Option<&crate::parity::StageOutputs> {
+        self.captured.as_ref()
+    }
+
+    /// Use a deterministic `StdRng` seeded with `seed` for latent noise
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Some(Box::new(StdRng::seed_from_u64(seed)));
+        self.seed = Some(seed);
+    }
+
+    /// Use a caller-provided RNG for latent noise
+    pub fn set_rng(&mut self, rng: Box<dyn RngCore + Send>) {
+        self.rng = Some(rng);
+        self.seed = None;
+    }
+
+    /// Go back to the default thread-local RNG for latent noise
+    pub fn use_thread_rng(&mut self) {
+        self.rng = None;
+        self.seed = None;
+    }
+
+    /// Seed passed to the last `set_seed` call, if the seeded RNG is active
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Options the sessions were created with
+    pub fn session_config(&self) -> &SessionConfig {
+        &self.session_config
+    }
+
+    /// Device the latent stays on across denoising steps (bound through
+    /// `IoBinding`), or `None` when the sessions run on the CPU
+    pub fn latent_device(&self) -> Option<AllocationDevice> {
+        self.latent_device
+    }
+
+    /// Run `hook` on every chunk produced by `call` (and `synthesize`/`narrate`)
+    /// before it is joined with the others; `None` removes the hook
+    pub fn set_post_chunk_hook(&mut self, hook: Option<PostChunkHook>) {
+        self.post_chunk_hook = hook;
+    }
+
+    /// Size chunks by token count instead of characters; `None` restores the default
+    /// character limit
+    pub fn set_max_chunk_tokens(&mut self, max_tokens: Option<usize>) {
+        self.clear_synthesis_cache();
+        self.max_chunk_tokens = max_tokens;
+    }
+
+    /// Keep the duration predictor and text encoder outputs of the last
+    /// `entries` distinct texts, keyed by their token ids and voice, so
+    /// re-rendering a phrase with another seed or step count skips both
+    /// sessions; `0` disables the cache and drops its contents
+    pub fn set_encoder_cache(&mut self, entries: usize) {
+        self.encoder_cache = (entries > 0).then(|| EncoderCache::new(entries));
+    }
+
+    /// Run the vocoder over windows of at most `frames` latent frames (about
+    /// 70 ms each with the released models) and crossfade them, so its
+    /// tensors stay the same size however long a chunk is; `None` vocodes
+    /// each chunk in one run. Windows share `VOCODER_WINDOW_OVERLAP` frames,
+    /// and shorter settings are raised to twice that.
+    pub fn set_vocoder_window(&mut self, frames: Option<usize>) {
+        self.clear_synthesis_cache();
+        self.vocoder_window = frames.map(|frames| frames.max(2 * VOCODER_WINDOW_OVERLAP));
+    }
+
+    /// Answer repeated `synthesize` calls from `cache`; `None` turns it off.
+    ///
+    /// Renders are keyed by the engine's seed as well, so a seeded engine
+    /// answers a repeated phrase with its first render under that seed (its
+    /// sequence only advances on calls that synthesize). An engine given its
+    /// own RNG with `set_rng`, or running a post-chunk hook, always
+    /// synthesizes. Settings that change what a text renders to (the
+    /// normalizer, G2P, unknown characters, chunking, vocoder window and
+    /// strict mode) clear the cache.
+    pub fn set_synthesis_cache(&mut self, cache: Option<SynthesisCache>) {
+        self.synthesis_cache = cache;
+    }
+
+    /// Whether a `synthesize` call can be answered from (and saved to) the
+    /// synthesis cache: its audio depends on nothing but the key, and no
+    /// hook needs to see its chunks
+    fn renders_are_cacheable(&self) -> bool {
+        #[cfg(feature = "test-hooks")]
+        if self.noise_override.is_some() {
+            return false;
+        }
+        (self.rng.is_none() || self.seed.is_some()) && self.post_chunk_hook.is_none()
+    }
+
+    /// Drop cached renders made under settings that are about to change
+    pub(crate) fn clear_synthesis_cache(&mut self) {
+        if let Some(cache) = &mut self.synthesis_cache {
+            cache.clear();
+        }
+    }
+
+    pub fn synthesis_cache(&self) -> Option<&SynthesisCache> {
+        self.synthesis_cache.as_ref()
+    }
+
+    pub fn synthesis_cache_mut(&mut self) -> Option<&mut SynthesisCache> {
+        self.synthesis_cache.as_mut()
+    }
+
+    /// Engine-wide fallbacks for options neither the request nor the voice sets
+    pub fn set_default_options(&mut self, options: SynthesisOptions) {
+        self.default_options = options;
+    }
+
+    /// Layer `options` over the voice's defaults and the engine's defaults
+    pub fn resolve_options(&self, style: &Style, options: &SynthesisOptions) -> SynthesisOptions {
+        options.or(&style.defaults).or(&self.default_options)
+    }
+
+    /// Sample rate `synthesize`, `synthesize_batch`, `preview` and `narrate`
+    /// return audio at for `options`
+    pub fn output_sample_rate(&self, options: &SynthesisOptions) -> Result<i32, SupertonicError> {
+        options
+            .or(&self.default_options)
+            .output_sample_rate(self.sample_rate)
+    }
+
+    /// Output stage of every render: the pitch shift, conversion from the
+    /// model's rate to `rate`, the high-pass filter, the fades, the watermark,
+    /// then the limiter
+    pub(crate) fn finish_output(
+        &self,
+        wav: Vec<f32>,
+        rate: i32,
+        options: &SynthesisOptions,
+    ) -> Vec<f32> {
+        #[cfg(feature = "dsp")]
+        let wav = match options.pitch_semitones {
+            Some(semitones) => crate::audio::pitch_shift(&wav, self.sample_rate, semitones),
+            None => wav,
+        };
+        let mut wav = if rate == self.sample_rate {
+            wav
+        } else {
+            resample(&wav, self.sample_rate, rate)
+        };
+        if let Some(cutoff_hz) = options.high_pass_hz {
+            remove_dc(&mut wav, rate);
+            high_pass(&mut wav, rate, cutoff_hz);
+        }
+        fade_ms(&mut wav, rate, options.fade_in_ms(), options.fade_out_ms());
+        if let Some(key) = options.watermark_key {
+            Watermark::new(key).embed(&mut wav, rate);
+        }
+        if let Some(ceiling_db) = options.limiter_db {
+            soft_limit(&mut wav, ceiling_db);
+        }
+        wav
+    }
+
+    /// Fade each chunk of the last render in and out, if `fade_chunks` is set
+    pub(crate) fn fade_chunk_edges(&self, wav: &mut [f32], options: &SynthesisOptions) {
+        if !options.fade_chunks() {
+            return;
+        }
+        for entry in &self.chunk_log {
+            fade_ms(
+                &mut wav[entry.start_sample..entry.end_sample],
+                self.sample_rate,
+                options.fade_in_ms(),
+                options.fade_out_ms(),
+            );
+        }
+    }
+
+    /// Move the chunk log's sample positions to `rate` after `finish_output`
+    pub(crate) fn resample_chunk_log(&mut self, rate: i32) {
+        if rate != self.sample_rate {
+            self.scale_chunk_log(rate as f64 / self.sample_rate as f64);
+        }
+    }
+
+    fn scale_chunk_log(&mut self, ratio: f64) {
+        for entry in &mut self.chunk_log {
+            entry.start_sample = (entry.start_sample as f64 * ratio).round() as usize;
+            entry.end_sample = (entry.end_sample as f64 * ratio).round() as usize;
+        }
+    }
+
+    /// Apply `SpeedMode::PostProcess` to a render made at `model_speed`,
+    /// keeping its duration and the chunk log in step
+    pub(crate) fn stretch_output(
+        &mut self,
+        wav: Vec<f32>,
+        duration: f32,
+        options: &SynthesisOptions,
+        chunk_log: bool,
+    ) -> (Vec<f32>, f32) {
+        let Some(factor) = options.stretch_factor() else {
+            return (wav, duration);
+        };
+        let wav = time_stretch(&wav, self.sample_rate, factor);
+        if chunk_log {
+            self.scale_chunk_log(1.0 / factor as f64);
+            for entry in &mut self.chunk_log {
+                entry.duration /= factor;
+            }
+        }
+        (wav, duration / factor)
+    }
+
+    /// Apply `max_silence` to a render at the model's rate, keeping its
+    /// duration and the chunk log in step
+    pub(crate) fn compress_output(
+        &mut self,
+        wav: Vec<f32>,
+        duration: f32,
+        options: &SynthesisOptions,
+        chunk_log: bool,
+    ) -> (Vec<f32>, f32) {
+        let Some(max_secs) = options.max_silence else {
+            return (wav, duration);
+        };
+        let cuts = silence_cuts(&wav, self.sample_rate, max_secs);
+        if cuts.is_empty() {
+            return (wav, duration);
+        }
+        if chunk_log {
+            for entry in &mut self.chunk_log {
+                entry.start_sample = position_after_cuts(entry.start_sample, &cuts);
+                entry.end_sample = position_after_cuts(entry.end_sample, &cuts);
+            }
+        }
+        let removed: usize = cuts.iter().map(|cut| cut.len()).sum();
+        let wav = remove_ranges(&wav, &cuts);
+        (wav, duration - removed as f32 / self.sample_rate as f32)
+    }
+
+    /// Insert a grapheme-to-phoneme stage before tokenization
+    pub fn set_g2p(&mut self, g2p: Option<std::sync::Arc<dyn crate::g2p::G2p>>) {
+        self.clear_synthesis_cache();
+        self.text_processor.set_g2p(g2p);
+    }
+
+    /// Text normalization rules (currency table etc.) applied before synthesis;
+    /// cached renders are dropped, as the rules may change through it
+    pub fn normalizer_mut(&mut self) -> &mut TextNormalizer {
+        self.clear_synthesis_cache();
+        self.text_processor.normalizer_mut()
+    }
+
+    /// What to do with characters the model has no token for
+    pub fn set_unknown_chars(&mut self, policy: UnknownCharPolicy) {
+        self.clear_synthesis_cache();
+        self.text_processor.set_unknown_chars(policy);
+    }
+
+    /// What preprocessing does to `text` with `options`, stage by stage
+    pub fn preprocess_report(
+        &mut self,
+        text: &str,
+        options: &SynthesisOptions,
+    ) -> Result<PreprocessReport, SupertonicError> {
+        let context = self
+            .text_processor
+            .set_context(options.normalize_context()?);
+        let report = self.text_processor.preprocess_traced(text);
+        self.text_processor.set_context(context);
+        Ok(report)
+    }
+
+    /// The text `text` is tokenized as with `options`, and each of its
+    /// characters with its token id (see `UnicodeProcessor::inspect`)
+    pub fn inspect_text(
+        &mut self,
+        text: &str,
+        options: &SynthesisOptions,
+    ) -> Result<(String, Vec<(char, i64)>), SupertonicError> {
+        let context = self
+            .text_processor
+            .set_context(options.normalize_context()?);
+        let prepared = self.text_processor.prepare(text);
+        let tokens = self.text_processor.inspect(text);
+        self.text_processor.set_context(context);
+        Ok((prepared, tokens))
+    }
+
+    /// Characters of `text` the model has no token for, after normalization
+    pub fn unsupported_chars(&self, text: &str) -> Vec<UnsupportedChar> {
+        self.text_processor.unsupported_chars(text)
+    }
+
+    /// Per-stage wall-times of the last `call`/`batch`
+    pub fn last_stage_timings(&self) -> StageTimings {
+        self.timings
+    }
+
+    /// Stage breakdown, wall time and real-time factor of the last render
+    /// (`call`, `batch`, `synthesize`, `synthesize_batch`, `synthesize_to_sink`,
+    /// `narrate`); for a batch, the real-time factor is over the summed durations
+    pub fn last_timing_report(&self) -> TimingReport {
+        let (total, audio_secs) = self.render_time;
+        TimingReport::new(self.timings, total, audio_secs)
+    }
+
+    /// Per-chunk log of the last `call`
+    pub fn last_chunk_log(&self) -> &[ChunkLogEntry] {
+        &self.chunk_log
+    }
+
+    /// Non-fatal problems of the last render (`call`, `synthesize`, `batch`, `preview`)
+    pub fn last_warnings(&self) -> &[SynthesisWarning] {
+        &self.warnings
+    }
+
+    /// Level and silence figures of the last render's output (`synthesize`,
+    /// `synthesize_batch`, `synthesize_to_sink`, `narrate`, `preview`): one
+    /// entry, or one per text for `synthesize_batch`
+    pub fn last_audio_stats(&self) -> &[AudioStats] {
+        &self.stats
+    }
+
+    /// Start a render's warning list, dropping anything left from other calls
+    fn reset_warnings(&mut self) {
+        self.text_processor.take_warnings();
+        self.warnings.clear();
+    }
+
+    pub(crate) fn set_audio_stats(&mut self, stats: AudioStats) {
+        self.stats = vec![stats];
+    }
+
+    pub(crate) fn push_warning(&mut self, warning: SynthesisWarning) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    /// Move the text processor's warnings into the render's list
+    fn collect_warnings(&mut self) {
+        for warning in self.text_processor.take_warnings() {
+            self.push_warning(warning);
+        }
+    }
+
+    /// Stop ORT profiling on every session and return the written JSON trace paths.
+    ///
+    /// Profiling must have been enabled via `SessionConfig::profile_prefix`;
+    /// sessions not created yet under `LoadPlan::Deferred` are skipped.
+    pub fn end_profiling(&mut self) -> Result<Vec<PathBuf>, SupertonicError> {
+        let mut paths = Vec::with_capacity(4);
+        for session in [
+            &self.dp_ort,
+            &self.text_enc_ort,
+            &self.vector_est_ort,
+            &self.vocoder_ort,
+        ] {
+            // Sessions a load plan never created have nothing to write
+            if let Some(mut session) = session.loaded() {
+                paths.push(PathBuf::from(session.end_profiling()?));
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Swap in new model files without replacing the engine, e.g. to upgrade a
+    /// running server or app; sessions are created with the options the
+    /// engine was loaded with.
+    ///
+    /// The new sessions are created (and checked) before anything changes, so
+    /// a failed reload leaves the engine as it was. They then replace the old
+    /// ones for this engine and every clone at once: a render on another
+    /// clone finishes its current stage and continues on the new models.
+    /// The new `tts.json` must match the loaded one, as clones and streams in
+    /// progress were sized from it. Clones keep their own unicode indexer;
+    /// cached encoder outputs and renders of this engine are dropped.
+    pub fn reload(&mut self, models: ModelBytes) -> Result<(), SupertonicError> {
+        let session_config = self.session_config.clone();
+        self.reload_with_config(models, &session_config)
+    }
+
+    /// `reload` with explicit session options, e.g. to move to another
+    /// execution provider; they are kept for later reloads
+    pub fn reload_with_config(
+        &mut self,
+        models: ModelBytes,
+        session_config: &SessionConfig,
+    ) -> Result<(), SupertonicError> {
+        let sources = [
+            models.duration_predictor,
+            models.text_encoder,
+            models.vector_estimator,
+            models.vocoder,
+        ]
+        .map(|bytes| ModelSource::Memory(Cow::Borrowed(bytes)));
+        let (cfgs, text_processor, sessions) = load_engine_parts(
+            models.config,
+            models.unicode_indexer,
+            sources,
+            session_config,
+        )?;
+        check_reload_config(&self.cfgs, &cfgs)?;
+
+        LazySession::replace_all(
+            [
+                &self.dp_ort,
+                &self.text_enc_ort,
+                &self.vector_est_ort,
+                &self.vocoder_ort,
+            ],
+            sessions,
+        );
+        self.text_processor = text_processor;
+        self.latent_device = latent_device(session_config);
+        self.session_config = session_config.clone();
+        // Outputs of the old models
+        self.prefetched = None;
+        if let Some(cache) = &mut self.encoder_cache {
+            cache.clear();
+        }
+        self.clear_synthesis_cache();
+        info!("Reloaded the models");
+        Ok(())
+    }
+
+    /// Inputs and outputs of each model, in `MODEL_NAMES` order; sessions not
+    /// created yet under the load plan are listed as not loaded rather than
+    /// created
+    pub fn model_info(&self) -> Vec<SessionInfo> {
+        let sessions = [
+            &self.dp_ort,
+            &self.text_enc_ort,
+            &self.vector_est_ort,
+            &self.vocoder_ort,
+        ];
+        MODEL_NAMES
+            .into_iter()
+            .zip(sessions)
+            .map(|(name, session)| SessionInfo::new(name, session.loaded().as_deref()))
+            .collect()
+    }
+
+    /// Denoising loop with the latent kept in `device` memory.
+    ///
+    /// The conditioning inputs are copied to the device once and each step's
+    /// output is bound as the next step's input, so only the final latent
+    /// comes back to the host.
+    #[allow(clippy::too_many_arguments)]
+    fn denoise_on_device(
+        session: &mut Session,
+        device: AllocationDevice,
+        xt: &Array3<f32>,
+        text_emb: &Array3<f32>,
+        style_ttl: &Array3<f32>,
+        latent_mask: &Array3<f32>,
+        text_mask: &Array3<f32>,
+        total_step: usize,
+    ) -> Result<Array3<f32>, SupertonicError> {
+        let bsz = xt.shape()[0];
+        let device_memory = MemoryInfo::new(device, 0, AllocatorType::Device, MemoryType::Default)?;
+
+        let mut binding = session.create_binding()?;
+        binding.bind_input("noisy_latent", &*TensorRef::from_array_view(xt)?)?;
+        binding.bind_input("text_emb", &*TensorRef::from_array_view(text_emb)?)?;
+        binding.bind_input("style_ttl", &*TensorRef::from_array_view(style_ttl)?)?;
+        binding.bind_input("latent_mask", &*TensorRef::from_array_view(latent_mask)?)?;
+        binding.bind_input("text_mask", &*TensorRef::from_array_view(text_mask)?)?;
+        binding.bind_input(
+            "total_step",
+            &Value::from_array(Array::from_elem(bsz, total_step as f32))?,
+        )?;
+
+        for step in 0..total_step {
+            binding.bind_input(
+                "current_step",
+                &Value::from_array(Array::from_elem(bsz, step as f32))?,
+            )?;
+            // The last step writes to host memory so the latent can be read back
+            if step + 1 == total_step {
+                binding.bind_output_to_device("denoised_latent", &MemoryInfo::default())?;
+            } else {
+                binding.bind_output_to_device("denoised_latent", &device_memory)?;
+            }
+            let denoised = session
+                .run_binding(&binding)?
+                .remove("denoised_latent")
+                .ok_or_else(|| {
+                    SupertonicError::Unknown("vector estimator returned no latent".to_string())
+                })?;
+            if step + 1 < total_step {
+                binding.bind_input("noisy_latent", &denoised)?;
+                continue;
+            }
+
+            return to_array3(&denoised);
+        }
+        Ok(xt.clone())
+    }
+
+    fn _infer(
+        &mut self,
+        text_list: &[String],
+        style: &Style,
+        total_step: usize,
+        speed: f32,
+    ) -> Result<(Vec<Vec<f32>>, Vec<f32>), SupertonicError> {
+        self._infer_pipelined(text_list, None, None, style, total_step, speed)
+            .map(|(output, _)| output)
+    }
+
+    /// `_infer`, starting from `encoded` when the texts were already encoded
+    /// and encoding `upcoming` on another thread while the vocoder runs, so
+    /// that consecutive chunks overlap their first and last stages
+    fn _infer_pipelined(
+        &mut self,
+        text_list: &[String],
+        encoded: Option<Encoded>,
+        upcoming: Option<&String>,
+        style: &Style,
+        total_step: usize,
+        speed: f32,
+    ) -> Result<InferOutput, SupertonicError> {
+        let encoded = match encoded {
+            Some(encoded) => encoded,
+            None => encode_texts(
+                &self.text_processor,
+                &mut *self.dp_ort.get()?,
+                &mut *self.text_enc_ort.get()?,
+                self.encoder_cache.as_mut(),
+                text_list,
+                style,
+                speed,
+            )?,
+        };
+        self.timings += encoded.timings;
+        let denoised = self.denoise(&encoded, style, total_step)?;
+        #[cfg(feature = "test-hooks")]
+        let captured = (denoised.noise, denoised.latent.clone());
+
+        let window = self.vocoder_window.map(|frames| VocoderWindow {
+            frames,
+            hop: (self.cfgs.ae.base_chunk_size * self.cfgs.ttl.chunk_compress_factor) as usize,
+        });
+        let mut vocoder_ort = self.vocoder_ort.get()?;
+        // The encoders are only held while there is a lookahead to encode,
+        // so clones of the engine can use them in the meantime
+        let mut encoders = match upcoming {
+            Some(_) => Some((self.dp_ort.get()?, self.text_enc_ort.get()?)),
+            None => None,
+        };
+        let (vocoded, next) = std::thread::scope(|scope| {
+            let text_processor = &self.text_processor;
+            let cache = self.encoder_cache.as_mut();
+            let next = upcoming
+                .zip(encoders.as_mut())
+                .map(|(text, (dp, text_enc))| {
+                    let (dp_ort, text_enc_ort): (&mut Session, &mut Session) = (dp, text_enc);
+                    scope.spawn(move || {
+                        encode_texts(
+                            text_processor,
+                            dp_ort,
+                            text_enc_ort,
+                            cache,
+                            std::slice::from_ref(text),
+                            style,
+                            speed,
+                        )
+                    })
+                });
+            let vocoded = match window {
+                Some(window) => vocode_windowed(&mut vocoder_ort, denoised.latent, window),
+                None => vocode(&mut vocoder_ort, denoised.latent),
+            };
+            // A failed lookahead is retried, and reported, when its chunk comes up
+            let next = next.and_then(|handle| match handle.join() {
+                Ok(encoded) => encoded.ok(),
+                Err(panic) => std::panic::resume_unwind(panic),
+            });
+            (vocoded, next)
+        });
+        drop((vocoder_ort, encoders));
+        let (wav, elapsed) = vocoded?;
+        let (_, wav_flat) = wav.try_extract_tensor::<f32>()?;
+        self.timings.vocoder += elapsed;
+        #[cfg(feature = "test-hooks")]
+        self.capture(&encoded, captured, wav_flat);
+        Ok((self.split_output(encoded, wav_flat), next))
+    }
+
+    /// Sample the initial noise for `encoded` and run the denoising loop on it
+    fn denoise(
+        &mut self,
+        encoded: &Encoded,
+        style: &Style,
+        total_step: usize,
+    ) -> Result<Denoised, SupertonicError> {
+        let bsz = encoded.duration.len();
+        let duration = &encoded.duration;
+
+        // Sample noisy latent into the buffers kept from the last batch
+        let rng: &mut dyn RngCore = match self.rng.as_mut() {
+            Some(rng) => rng.as_mut(),
+            None => &mut rand::thread_rng(),
+        };
+        sample_noisy_latent_into(
+            duration,
+            self.sample_rate,
+            self.cfgs.ae.base_chunk_size,
+            self.cfgs.ttl.chunk_compress_factor,
+            self.cfgs.ttl.latent_dim,
+            rng,
+            &mut self.latent_buffers,
+        );
+        let LatentBuffers {
+            noise,
+            mask: latent_mask,
+        } = &mut self.latent_buffers;
+
+        #[cfg(feature = "test-hooks")]
+        if let Some(override_noise) = self.noise_override.as_ref() {
+            if override_noise.shape() != noise.shape() {
+                return Err(SupertonicError::ShapeMismatch {
+                    expected: noise.shape().to_vec(),
+                    got: override_noise.shape().to_vec(),
+                });
+            }
+            *noise = override_noise * &*latent_mask;
+        }
+        #[cfg(feature = "test-hooks")]
+        let captured_noise = noise.clone();
+
+        let style = style.for_batch(bsz)?;
+
+        // Prepare constant arrays
+        let total_step_array = Array::from_elem(bsz, total_step as f32);
+        let text_emb = &encoded.text_emb;
+        let text_mask = &encoded.text_mask;
+
+        // Denoising loop; the sampled noise stays in the buffers for the next batch
+        let xt = if let Some(device) = self.latent_device {
+            let stage_start = Instant::now();
+            let latent = Self::denoise_on_device(
+                &mut *self.vector_est_ort.get()?,
+                device,
+                noise,
+                text_emb,
+                &style.ttl,
+                latent_mask,
+                text_mask,
+                total_step,
+            )?;
+            self.timings.vector_estimator += stage_start.elapsed();
+            latent
+        } else {
+            // The conditioning inputs are lent to the session once for every
+            // step, and each step's output tensor is the next step's input as
+            // it is; only the final latent is copied out
+            let noise_value = TensorRef::from_array_view(&*noise)?.into_dyn();
+            let text_emb_value = TensorRef::from_array_view(text_emb)?;
+            let style_ttl_value = TensorRef::from_array_view(&style.ttl)?;
+            let latent_mask_value = TensorRef::from_array_view(&*latent_mask)?;
+            let text_mask_value2 = TensorRef::from_array_view(text_mask)?;
+            let total_step_value = TensorRef::from_array_view(&total_step_array)?;
+            let mut latent: Option<DynValue> = None;
+            for step in 0..total_step {
+                let current_step_value = Value::from_array(Array::from_elem(bsz, step as f32))?;
+                let xt_value = latent.as_ref().unwrap_or(&*noise_value);
+
+                let stage_start = Instant::now();
+                let denoised = self
+                    .vector_est_ort
+                    .get()?
+                    .run(ort::inputs! {
+                        "noisy_latent" => xt_value,
+                        "text_emb" => &*text_emb_value,
+                        "style_ttl" => &*style_ttl_value,
+                        "latent_mask" => &*latent_mask_value,
+                        "text_mask" => &*text_mask_value2,
+                        "current_step" => &current_step_value,
+                        "total_step" => &*total_step_value
+                    })?
+                    .remove("denoised_latent")
+                    .ok_or_else(|| {
+                        SupertonicError::Unknown("vector estimator returned no latent".to_string())
+                    })?;
+                latent = Some(denoised);
+                self.timings.vector_estimator += stage_start.elapsed();
+            }
+            match latent {
+                Some(latent) => to_array3(&latent)?,
+                None => noise.clone(),
+            }
+        };
+
+        Ok(Denoised {
+            latent: xt,
+            #[cfg(feature = "test-hooks")]
+            noise: captured_noise,
+        })
+    }
+
+    #[cfg(feature = "test-hooks")]
+    fn capture(
+        &mut self,
+        encoded: &Encoded,
+        (noise, latent): (Array3<f32>, Array3<f32>),
+        wav: &[f32],
+    ) {
+        self.captured = Some(crate::parity::StageOutputs {
+            text_ids: encoded.text_ids.clone(),
+            durations: encoded.duration.clone(),
+            text_emb: encoded.text_emb.clone(),
+            noise,
+            latent,
+            wav: wav.to_vec(),
+        });
+    }
+
+    /// Slice the flat vocoder output of a batch into its texts, trimmed to
+    /// their predicted durations
+    fn split_output(&self, encoded: Encoded, wav_flat: &[f32]) -> (Vec<Vec<f32>>, Vec<f32>) {
+        let duration = encoded.duration;
+        let bsz = duration.len();
+        let mut wav_outputs = Vec::with_capacity(bsz);
+        let wav_len_per_sample = wav_flat.len() / bsz;
+
+        for (i, &dur) in duration.iter().enumerate().take(bsz) {
+            let actual_len = (self.sample_rate as f32 * dur) as usize;
+            let wav_start = i * wav_len_per_sample;
+            let wav_end = wav_start + actual_len.min(wav_len_per_sample);
+            wav_outputs.push(wav_flat[wav_start..wav_end].to_vec());
+        }
+
+        (wav_outputs, duration)
+    }
+
+    /// Split `text` into the chunks `call` synthesizes one by one, with the kind of break after each
+    pub(crate) fn chunks_with_boundaries(&self, text: &str) -> Vec<(String, ChunkBoundary)> {
+        let locale = Some(self.text_processor.context().locale.tag.as_str());
+        match self.max_chunk_tokens {
+            Some(max_tokens) => {
+                let counter = TokenCounter::new(&self.text_processor);
+                chunk_text_measured(text, max_tokens, locale, &|s: &str| counter.count(s))
+            }
+            None => chunk_text_with_boundaries(text, None, locale),
+        }
+    }
+
+    /// `chunks_with_boundaries`, with the first sentence split into extra-small
+    /// chunks under `low_latency` and the steps each chunk is rendered with
+    pub(crate) fn plan_chunks(
+        &self,
+        text: &str,
+        options: &SynthesisOptions,
+    ) -> (Vec<(String, ChunkBoundary)>, ChunkSteps) {
+        let mut chunks = self.chunks_with_boundaries(text);
+        let mut steps = ChunkSteps::uniform(options.total_step());
+        if !options.low_latency() || chunks.is_empty() {
+            return (chunks, steps);
+        }
+
+        let (first, boundary) = chunks.remove(0);
+        let locale = Some(self.text_processor.context().locale.tag.as_str());
+        let mut pieces = chunk_text_with_boundaries(&first, Some(LOW_LATENCY_CHUNK_LENGTH), locale);
+        let sentence_end = pieces
+            .iter()
+            .position(|(_, b)| matches!(b, ChunkBoundary::Sentence | ChunkBoundary::Paragraph))
+            .map_or(pieces.len(), |i| i + 1);
+        // The rest of the first chunk stays one chunk, as it was
+        let rest: Vec<String> = pieces
+            .drain(sentence_end..)
+            .map(|(piece, _)| piece)
+            .collect();
+        if rest.is_empty() {
+            if let Some(last) = pieces.last_mut() {
+                last.1 = boundary;
+            }
+        } else {
+            pieces.push((rest.join(" "), boundary));
+        }
+        steps.fast_chunks = sentence_end;
+        pieces.extend(chunks);
+        (pieces, steps)
+    }
+
+    /// Whether `chunk` exceeds the chunk limit, as a single over-long word does
+    pub(crate) fn chunk_too_long(&self, chunk: &str) -> bool {
+        match self.max_chunk_tokens {
+            Some(max_tokens) => self.text_processor.token_count(chunk) > max_tokens,
+            None => chunk.chars().count() > MAX_CHUNK_LENGTH,
+        }
+    }
+
+    /// Predicted audio duration of each text in seconds, running only the duration predictor
+    pub fn predict_durations(
+        &mut self,
+        text_list: &[String],
+        style: &Style,
+        speed: f32,
+    ) -> Result<Vec<f32>, SupertonicError> {
+        let bsz = text_list.len();
+        let style = style.for_batch(bsz)?;
+        let (text_ids, text_mask) = self.text_processor.call(text_list)?;
+        let width = text_ids[0].len();
+        let text_ids_array =
+            Array::from_shape_vec((bsz, width), text_ids.concat()).map_err(|_e| {
+                SupertonicError::ShapeMismatch {
+                    expected: vec![bsz, width],
+                    got: vec![],
+                }
+            })?;
+
+        let text_ids_value = Value::from_array(text_ids_array)?;
+        let text_mask_value = Value::from_array(text_mask)?;
+        let style_dp_value = TensorRef::from_array_view(&style.dp)?;
+        let mut dp_ort = self.dp_ort.get()?;
+        let dp_outputs = dp_ort.run(ort::inputs! {
+            "text_ids" => &text_ids_value,
+            "style_dp" => &*style_dp_value,
+            "text_mask" => &text_mask_value
+        })?;
+
+        let (_, duration_data) = dp_outputs["duration"].try_extract_tensor::<f32>()?;
+        Ok(duration_data.iter().map(|d| d / speed).collect())
+    }
+
+    pub fn call(
+        &mut self,
+        text: &str,
+        style: &Style,
+        total_step: usize,
+        speed: f32,
+        silence_duration: f32,
+    ) -> Result<(Vec<f32>, f32), SupertonicError> {
+        self.enforce_strict(text)?;
+        let chunks = self.chunks_with_boundaries(text);
+        let pauses = ChunkPauses::uniform(silence_duration);
+        let steps = ChunkSteps::uniform(total_step);
+        self.call_chunks(&chunks, style, steps, speed, pauses, None)
+    }
+
+    /// Synthesize `chunks` in order, pausing after each by its boundary and
+    /// stopping early once `max_secs` of audio are rendered
+    pub(crate) fn call_chunks(
+        &mut self,
+        chunks: &[(String, ChunkBoundary)],
+        style: &Style,
+        steps: ChunkSteps,
+        speed: f32,
+        pauses: ChunkPauses,
+        max_secs: Option<f32>,
+    ) -> Result<(Vec<f32>, f32), SupertonicError> {
+        let start = Instant::now();
+        self.timings = StageTimings::default();
+        self.chunk_log.clear();
+        self.reset_warnings();
+
+        let mut wav_cat: Vec<f32> = Vec::new();
+        let mut dur_cat: f32 = 0.0;
+
+        // Each chunk is encoded while the one before it is vocoded; the last
+        // one encodes the caller's lookahead text for its next call
+        let lookahead = self.lookahead.take();
+        let mut pending = self
+            .prefetched
+            .take()
+            .filter(|prefetched| prefetched.speed == speed);
+
+        for (i, (chunk, _)) in chunks.iter().enumerate() {
+            if max_secs.is_some_and(|max| dur_cat >= max) {
+                break;
+            }
+            let chunk_start = Instant::now();
+            let total_step = steps.for_chunk(i);
+            let encoded = pending
+                .take()
+                .filter(|prefetched| prefetched.text == *chunk)
+                .map(|prefetched| prefetched.encoded);
+            let upcoming = chunks
+                .get(i + 1)
+                .map(|(next, _)| next)
+                .or(lookahead.as_ref());
+            let ((wav_batch, duration), next) = self._infer_pipelined(
+                std::slice::from_ref(chunk),
+                encoded,
+                upcoming,
+                style,
+                total_step,
+                speed,
+            )?;
+            pending = upcoming.zip(next).map(|(text, encoded)| Prefetched {
+                text: text.clone(),
+                speed,
+                encoded,
+            });
+            let synth_time = chunk_start.elapsed();
+
+            let mut dur = duration[0];
+            // Wav batch has size 1 here
+            let mut wav_batch = wav_batch;
+            if let Some(hook) = self.post_chunk_hook.as_mut() {
+                let ctx = ChunkContext {
+                    index: i,
+                    text: chunk,
+                    sample_rate: self.sample_rate,
+                    pause_before: match i {
+                        0 => 0.0,
+                        _ => pauses.after(chunks[i - 1].1),
+                    },
+                };
+                hook(&mut wav_batch[0], &ctx)?;
+                dur = wav_batch[0].len() as f32 / self.sample_rate as f32;
+            }
+            let wav_chunk = &wav_batch[0];
+
+            if i == 0 {
+                wav_cat.extend_from_slice(wav_chunk);
+                dur_cat = dur;
+            } else {
+                let silence_duration = pauses.after(chunks[i - 1].1);
+                let silence_len = (silence_duration * self.sample_rate as f32) as usize;
+                let silence = vec![0.0f32; silence_len];
+                let declick = if silence_len > 0 {
+                    (DECLICK_SECS * self.sample_rate as f32) as usize
+                } else {
+                    0
+                };
+
+                let previous_start = self.chunk_log.last().map_or(0, |e| e.start_sample);
+                fade(&mut wav_cat[previous_start..], 0, declick);
+                wav_cat.extend_from_slice(&silence);
+                let start = wav_cat.len();
+                wav_cat.extend_from_slice(wav_chunk);
+                fade(&mut wav_cat[start..], declick, 0);
+                dur_cat += silence_duration + dur;
+            }
+
+            let normalized_text = self.text_processor.prepare(chunk);
+            self.chunk_log.push(ChunkLogEntry {
+                index: i,
+                char_count: normalized_text.chars().count(),
+                normalized_text,
+                seed: self.seed,
+                total_step,
+                duration: dur,
+                synth_time: synth_time.as_secs_f32(),
+                start_sample: wav_cat.len() - wav_chunk.len(),
+                end_sample: wav_cat.len(),
+            });
+
+            self.collect_warnings();
+            let boundary = chunks[i].1;
+            if boundary == ChunkBoundary::Word && (i == 0 || chunks[i - 1].1 != boundary) {
+                self.push_warning(SynthesisWarning::SplitBetweenWords { chunk: i });
+            }
+        }
+
+        self.prefetched = pending.filter(|prefetched| Some(&prefetched.text) == lookahead.as_ref());
+        self.render_time = (start.elapsed(), dur_cat);
+        Ok((wav_cat, dur_cat))
+    }
+
+    /// Synthesize long-form `text`, taking unset options from the voice and engine defaults
+    pub fn synthesize(
+        &mut self,
+        text: &str,
+        style: &Style,
+        options: &SynthesisOptions,
+    ) -> Result<(Vec<f32>, f32), SupertonicError> {
+        let start = Instant::now();
+        let rate = self.output_sample_rate(options)?;
+        let key = self
+            .synthesis_cache
+            .as_ref()
+            .filter(|_| self.renders_are_cacheable())
+            .map(|_| {
+                let options = self.resolve_options(style, options);
+                SynthesisKey::new(text, style, &options, self.seed)
+            });
+        let cached = self
+            .synthesis_cache
+            .as_mut()
+            .zip(key.as_ref())
+            .and_then(|(cache, key)| cache.get(key));
+        if let Some(render) = cached {
+            self.timings = StageTimings::default();
+            self.chunk_log = render.chunk_log;
+            self.warnings = render.warnings;
+            self.stats = vec![render.stats];
+            self.render_time = (start.elapsed(), render.duration);
+            return Ok((render.wav, render.duration));
+        }
+
+        let (wav, duration) = self.synthesize_native(text, style, options)?;
+        let wav = self.finish_output(wav, rate, &self.resolve_options(style, options));
+        self.resample_chunk_log(rate);
+        if let Some(warning) = clipping_warning(&wav) {
+            self.push_warning(warning);
+        }
+        self.stats = vec![AudioStats::measure(&wav, rate)];
+        if let (Some(cache), Some(key)) = (self.synthesis_cache.as_mut(), key) {
+            let render = CachedRender {
+                wav: wav.clone(),
+                duration,
+                chunk_log: self.chunk_log.clone(),
+                warnings: self.warnings.clone(),
+                stats: self.stats[0],
+            };
+            cache.insert(key, render);
+        }
+        self.render_time = (start.elapsed(), duration);
+        Ok((wav, duration))
+    }
+
+    /// `synthesize` at the model's sample rate, without the clipping check
+    fn synthesize_native(
+        &mut self,
+        text: &str,
+        style: &Style,
+        options: &SynthesisOptions,
+    ) -> Result<(Vec<f32>, f32), SupertonicError> {
+        let options = self.resolve_options(style, options);
+        let previous = self
+            .text_processor
+            .set_context(options.normalize_context()?);
+        let result = self.enforce_strict(text).and_then(|()| {
+            let (chunks, steps) = self.plan_chunks(text, &options);
+            self.call_chunks(
+                &chunks,
+                style,
+                steps,
+                options.model_speed(),
+                options.pauses(),
+                None,
+            )
+        });
+        self.text_processor.set_context(previous);
+
+        let (wav, duration) = result?;
+        let (wav, duration) = self.stretch_output(wav, duration, &options, true);
+        let (mut wav, duration) = self.compress_output(wav, duration, &options, true);
+        self.fade_chunk_edges(&mut wav, &options);
+        apply_gain(&mut wav, options.gain());
+        Ok((wav, duration))
+    }
+
+    /// `synthesize`, writing each chunk to `sink` as soon as it is rendered
+    /// instead of returning the whole output; `sink.finalize()` is called at
+    /// the end.
+    ///
+    /// The output stage runs per chunk: the start fade applies to the first
+    /// chunk and the end fade to the last, while the filters restart at each
+    /// chunk, which the pauses between them hide. Returns the duration
+    /// written; the chunk log, warnings and stage timings cover the whole text.
+    ///
+    /// Each chunk after the first is encoded while the vocoder renders the one
+    /// before it.
+    pub fn synthesize_to_sink(
+        &mut self,
+        text: &str,
+        style: &Style,
+        options: &SynthesisOptions,
+        sink: &mut dyn AudioSink,
+    ) -> Result<f32, SupertonicError> {
+        let result = self.render_to_sink(text, style, options, sink);
+        self.lookahead = None;
+        self.prefetched = None;
+        result
+    }
+
+    fn render_to_sink(
+        &mut self,
+        text: &str,
+        style: &Style,
+        options: &SynthesisOptions,
+        sink: &mut dyn AudioSink,
+    ) -> Result<f32, SupertonicError> {
+        let start = Instant::now();
+        let rate = self.output_sample_rate(options)?;
+        let resolved = self.resolve_options(style, options);
+        let previous = self
+            .text_processor
+            .set_context(resolved.normalize_context()?);
+        let planned = self
+            .enforce_strict(text)
+            .map(|()| self.plan_chunks(text, &resolved));
+        self.text_processor.set_context(previous);
+        let (chunks, steps) = planned?;
+
+        let pauses = resolved.pauses();
+        let declick = (DECLICK_SECS * rate as f32) as usize;
+        let mut totals = RenderTotals::default();
+        let mut written = 0;
+        let mut duration = 0.0;
+        let (mut clipped, mut peak) = (0, 0.0f32);
+        let mut stats = AudioStats::default();
+
+        for (i, (chunk, boundary)) in chunks.iter().enumerate() {
+            let pause_before = match i {
+                0 => 0.0,
+                _ => pauses.after(chunks[i - 1].1),
+            };
+            let pause_after = if i + 1 < chunks.len() {
+                pauses.after(*boundary)
+            } else {
+                0.0
+            };
+            let mut chunk_options = resolved.clone();
+            chunk_options.total_step = Some(steps.for_chunk(i));
+            chunk_options.low_latency = Some(false);
+            if !resolved.fade_chunks() {
+                // Set rather than cleared, so voice defaults do not fill them back in
+                if i > 0 {
+                    chunk_options.fade_in_ms = Some(0.0);
+                }
+                if i + 1 < chunks.len() {
+                    chunk_options.fade_out_ms = Some(0.0);
+                }
+            }
+
+            self.lookahead = chunks.get(i + 1).map(|(next, _)| next.clone());
+            let (wav, chunk_duration) = self.synthesize_native(chunk, style, &chunk_options)?;
+            let mut wav = self.finish_output(wav, rate, &chunk_options);
+            self.resample_chunk_log(rate);
+
+            let silence = (pause_before * rate as f32) as usize;
+            if silence > 0 {
+                let pause = vec![0.0; silence];
+                stats.append(&pause, rate);
+                sink.write(&pause)?;
+            }
+            let fade_out = if pause_after > 0.0 { declick } else { 0 };
+            fade(&mut wav, if silence > 0 { declick } else { 0 }, fade_out);
+            written += silence;
+            self.take_render(written, &mut totals);
+            if *boundary == ChunkBoundary::Word && (i == 0 || chunks[i - 1].1 != *boundary) {
+                totals
+                    .warnings
+                    .push(SynthesisWarning::SplitBetweenWords { chunk: i });
+            }
+            if let Some(SynthesisWarning::Clipped { samples, peak: p }) = clipping_warning(&wav) {
+                clipped += samples;
+                peak = peak.max(p);
+            }
+
+            stats.append(&wav, rate);
+            sink.write(&wav)?;
+            written += wav.len();
+            duration += pause_before + chunk_duration;
+        }
+        sink.finalize()?;
+
+        if clipped > 0 {
+            totals.warnings.push(SynthesisWarning::Clipped {
+                samples: clipped,
+                peak,
+            });
+        }
+        self.restore_render(totals);
+        self.stats = vec![stats];
+        self.render_time = (start.elapsed(), duration);
+        Ok(duration)
+    }
+
+    /// Move the chunk log, warnings and stage timings of the last render into
+    /// `totals`, shifting its sample positions by `offset`
+    fn take_render(&mut self, offset: usize, totals: &mut RenderTotals) {
+        for warning in self.warnings.drain(..) {
+            let warning = match warning {
+                SynthesisWarning::SplitBetweenWords { chunk } => {
+                    SynthesisWarning::SplitBetweenWords {
+                        chunk: chunk + totals.chunk_log.len(),
+                    }
+                }
+                other => other,
+            };
+            if !totals.warnings.contains(&warning) {
+                totals.warnings.push(warning);
+            }
+        }
+        for mut entry in self.chunk_log.drain(..) {
+            entry.index = totals.chunk_log.len();
+            entry.start_sample += offset;
+            entry.end_sample += offset;
+            totals.chunk_log.push(entry);
+        }
+        totals.timings += self.timings;
+    }
+
+    /// Make `totals` the record of the last render
+    fn restore_render(&mut self, totals: RenderTotals) {
+        self.chunk_log = totals.chunk_log;
+        self.warnings = totals.warnings;
+        self.timings = totals.timings;
+    }
+
+    /// Batch counterpart of `synthesize`; voice defaults come from the first style
+    pub fn synthesize_batch(
+        &mut self,
+        text_list: &[String],
+        style: &Style,
+        options: &SynthesisOptions,
+    ) -> Result<(Vec<Vec<f32>>, Vec<f32>), SupertonicError> {
+        let start = Instant::now();
+        let rate = self.output_sample_rate(options)?;
+        let options = self.resolve_options(style, options);
+        let previous = self
+            .text_processor
+            .set_context(options.normalize_context()?);
+        let result = self.batch(
+            text_list,
+            style,
+            options.total_step(),
+            options.model_speed(),
+        );
+        self.text_processor.set_context(previous);
+
+        let (wavs, durations) = result?;
+        let mut output = Vec::with_capacity(wavs.len());
+        let mut stretched_durations = Vec::with_capacity(durations.len());
+        let mut stats = Vec::with_capacity(wavs.len());
+        for (wav, duration) in wavs.into_iter().zip(durations) {
+            let (wav, duration) = self.stretch_output(wav, duration, &options, false);
+            let (mut wav, duration) = self.compress_output(wav, duration, &options, false);
+            stretched_durations.push(duration);
+            apply_gain(&mut wav, options.gain());
+            let wav = self.finish_output(wav, rate, &options);
+            if let Some(warning) = clipping_warning(&wav) {
+                self.push_warning(warning);
+            }
+            stats.push(AudioStats::measure(&wav, rate));
+            output.push(wav);
+        }
+        self.stats = stats;
+        self.render_time = (start.elapsed(), stretched_durations.iter().sum());
+        Ok((output, stretched_durations))
+    }
+
+    /// `synthesize_batch` with the texts split, in order, into up to
+    /// `max_in_flight` sub-batches rendered at once: the first on this engine
+    /// and the others on clones of it, each on its own thread.
+    ///
+    /// Results keep the input order and the `last_*` records cover every text.
+    /// Sub-batches run at the same time, so each stage timing is that of the
+    /// longest sub-batch, and the report's `total_ms` and real-time factor are
+    /// wall time.
+    ///
+    /// There is no pool of engines: the clones share this engine's four
+    /// sessions and take turns on each, so sub-batches overlap only while in
+    /// different stages, and beyond four in flight the extra ones only queue.
+    /// On a seeded engine each clone is seeded from the seed and its
+    /// sub-batch, so sub-batches draw different noise and the audio is
+    /// reproducible for a given seed and `max_in_flight`, but differs from
+    /// `synthesize_batch` with the same seed.
+    pub fn batch_concurrent(
+        &mut self,
+        text_list: &[String],
+        style: &Style,
+        options: &SynthesisOptions,
+        max_in_flight: usize,
+    ) -> Result<(Vec<Vec<f32>>, Vec<f32>), SupertonicError> {
+        let start = Instant::now();
+        let len = text_list.len();
+        let size = len.div_ceil(max_in_flight.max(1)).max(1);
+        let per_text = style.batch_size() != 1;
+        if size >= len || (per_text && style.batch_size() != len) {
+            // A mismatched style is reported by `synthesize_batch`
+            return self.synthesize_batch(text_list, style, options);
+        }
+
+        let styles: Vec<Cow<'_, Style>> = (0..len)
+            .step_by(size)
+            .map(|first| {
+                if per_text {
+                    Cow::Owned(style.select(&(first..(first + size).min(len)).collect::<Vec<_>>()))
+                } else {
+                    Cow::Borrowed(style)
+                }
+            })
+            .collect();
+        let mut engines: Vec<TextToSpeech> = (1..styles.len())
+            .map(|index| {
+                let mut engine = self.clone();
+                if let Some(seed) = self.seed {
+                    engine.set_seed(sub_batch_seed(seed, index));
+                }
+                engine
+            })
+            .collect();
+        info!(
+            "Batch of {} texts split into {} sub-batches rendered concurrently",
+            len,
+            styles.len()
+        );
+
+        let results = std::thread::scope(|scope| {
+            let mut batches = text_list.chunks(size).zip(&styles);
+            let (texts, style) = batches.next().expect("at least two sub-batches");
+            let handles: Vec<_> = batches
+                .zip(engines.iter_mut())
+                .map(|((texts, style), engine)| {
+                    scope.spawn(move || engine.synthesize_batch(texts, style, options))
+                })
+                .collect();
+            let mut results = vec![self.synthesize_batch(texts, style, options)];
+            for handle in handles {
+                results.push(
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic)),
+                );
+            }
+            results
+        });
+
+        let mut wavs = Vec::with_capacity(len);
+        let mut durations = Vec::with_capacity(len);
+        for result in results {
+            let (sub_wavs, sub_durations) = result?;
+            wavs.extend(sub_wavs);
+            durations.extend(sub_durations);
+        }
+        for engine in &engines {
+            self.warnings.extend_from_slice(&engine.warnings);
+            self.stats.extend_from_slice(&engine.stats);
+            self.timings = self.timings.longest(engine.timings);
+        }
+        self.render_time = (start.elapsed(), durations.iter().sum());
+        Ok((wavs, durations))
+    }
+
+    /// Synthesize `text` with a narration preset, switching voices per segment.
+    ///
+    /// Role options from the preset sit between `options` and each voice's own
+    /// defaults. Segments are separated by the resolved `silence_duration`, and
+    /// the chunk log and stage timings cover the whole narration. The output
+    /// stage (pitch, resampling, high-pass filter, start and end fades,
+    /// watermark and limiter) runs once on the mix, configured by `options`
+    /// and the engine defaults.
+    pub fn narrate(
+        &mut self,
+        text: &str,
+        voices: &NarrationVoices,
+        options: &SynthesisOptions,
+    ) -> Result<(Vec<f32>, f32), SupertonicError> {
+        let (wav, duration, _) = self.narrate_segments(text, voices, options)?;
+        Ok((wav, duration))
+    }
+
+    /// `narrate` into interleaved stereo, placing each segment at the `pan`
+    /// resolved for its role (centered unless a role or `options` sets one).
+    ///
+    /// Chunk log positions count frames, as in the mono output.
+    pub fn narrate_stereo(
+        &mut self,
+        text: &str,
+        voices: &NarrationVoices,
+        options: &SynthesisOptions,
+    ) -> Result<(Vec<f32>, f32), SupertonicError> {
+        let (wav, duration, segments) = self.narrate_segments(text, voices, options)?;
+        let mut stereo = vec![0.0f32; wav.len() * 2];
+        for (range, pan) in segments {
+            let panned = pan_stereo(&wav[range.clone()], pan);
+            stereo[range.start * 2..range.end * 2].copy_from_slice(&panned);
+        }
+        Ok((stereo, duration))
+    }
+
+    /// `narrate`, also returning the output sample range and pan of each segment
+    fn narrate_segments(
+        &mut self,
+        text: &str,
+        voices: &NarrationVoices,
+        options: &SynthesisOptions,
+    ) -> Result<NarratedSegments, SupertonicError> {
+        let start = Instant::now();
+        let rate = self.output_sample_rate(options)?;
+        let mut wav_cat: Vec<f32> = Vec::new();
+        let mut dur_cat: f32 = 0.0;
+        let mut totals = RenderTotals::default();
+        // Sample range of each segment in `wav_cat`, with the voice that read
+        // it and its stereo position
+        let mut parts: Vec<(usize, Range<usize>, f32)> = Vec::new();
+
+        for (i, segment) in voices.segments(text).iter().enumerate() {
+            let (style, role_options) = voices.voice_for(segment.role);
+            let segment_options = options.or(role_options);
+
+            if i > 0 {
+                let resolved = self.resolve_options(style, &segment_options);
+                let silence_duration = resolved
+                    .silence_duration()
+                    .min(resolved.max_silence.unwrap_or(f32::INFINITY));
+                let silence_len = (silence_duration * self.sample_rate as f32) as usize;
+                wav_cat.extend(std::iter::repeat_n(0.0f32, silence_len));
+                dur_cat += silence_duration;
+            }
 
-impl TextToSpeech {
-    pub fn new(
-        cfgs: Config,
-        text_processor: UnicodeProcessor,
-        dp_ort: Session,
-        text_enc_ort: Session,
-        vector_est_ort: Session,
-        vocoder_ort: Session,
-    ) -> Self {
-        let sample_rate = cfgs.ae.sample_rate;
-        TextToSpeech {
-            cfgs,
-            text_processor,
-            dp_ort,
-            text_enc_ort,
-            vector_est_ort,
-            vocoder_ort,
-            sample_rate,
+            let pan = self.resolve_options(style, &segment_options).pan();
+            let (wav, dur) = self.synthesize_native(&segment.text, style, &segment_options)?;
+
+            let offset = wav_cat.len();
+            parts.push((
+                voices.voice_index(segment.role),
+                offset..offset + wav.len(),
+                pan,
+            ));
+            self.take_render(offset, &mut totals);
+
+            wav_cat.extend_from_slice(&wav);
+            dur_cat += dur;
         }
+
+        if voices.match_loudness() {
+            let loudness: Vec<Option<f64>> = (0..voices.voice_count())
+                .map(|v| {
+                    let samples: Vec<f32> = parts
+                        .iter()
+                        .filter(|(voice, _, _)| *voice == v)
+                        .flat_map(|(_, range, _)| wav_cat[range.clone()].iter().copied())
+                        .collect();
+                    integrated_loudness(&samples, self.sample_rate)
+                })
+                .collect();
+            let gains = match_gains(&loudness);
+            for (voice, range, _) in &parts {
+                apply_gain(
+                    &mut wav_cat[range.clone()],
+                    10f32.powf(gains[*voice] as f32 / 20.0),
+                );
+            }
+        }
+
+        let wav_cat = self.finish_output(wav_cat, rate, &options.or(&self.default_options));
+        totals.warnings.extend(clipping_warning(&wav_cat));
+        self.restore_render(totals);
+        self.resample_chunk_log(rate);
+        self.stats = vec![AudioStats::measure(&wav_cat, rate)];
+        self.render_time = (start.elapsed(), dur_cat);
+
+        let len = wav_cat.len();
+        let scale = |i: usize| (i as u64 * rate as u64 / self.sample_rate as u64) as usize;
+        let segments = parts
+            .into_iter()
+            .map(|(_, range, pan)| (scale(range.start).min(len)..scale(range.end).min(len), pan))
+            .collect();
+        Ok((wav_cat, dur_cat, segments))
     }
 
-    fn _infer(
+    /// Synthesize each text of `text_list` as a single chunk, in one model pass.
+    ///
+    /// Texts that normalize to the same string (and use the same voice) are
+    /// synthesized once and the audio is copied to every position.
+    pub fn batch(
         &mut self,
         text_list: &[String],
         style: &Style,
         total_step: usize,
         speed: f32,
     ) -> Result<(Vec<Vec<f32>>, Vec<f32>), SupertonicError> {
-        let bsz = text_list.len();
+        let start = Instant::now();
+        self.reset_warnings();
+        let result = self.batch_unique(text_list, style, total_step, speed);
+        self.collect_warnings();
+        if let Ok((_, durations)) = &result {
+            self.render_time = (start.elapsed(), durations.iter().sum());
+        }
+        result
+    }
+
+    /// `batch`, synthesizing texts that normalize the same once
+    fn batch_unique(
+        &mut self,
+        text_list: &[String],
+        style: &Style,
+        total_step: usize,
+        speed: f32,
+    ) -> Result<(Vec<Vec<f32>>, Vec<f32>), SupertonicError> {
+        for text in text_list {
+            self.enforce_strict(text)?;
+        }
+        self.timings = StageTimings::default();
+        if style.batch_size() != 1 && style.batch_size() != text_list.len() {
+            // Let `_infer` report the mismatch
+            return self._infer(text_list, style, total_step, speed);
+        }
 
-        // Process text
-        let (text_ids, text_mask) = self.text_processor.call(text_list);
+        let prepared: Vec<String> = text_list
+            .iter()
+            .map(|text| self.text_processor.prepare(text))
+            .collect();
+        let (slots, unique) = dedup_slots(text_list.len(), |i, j| {
+            prepared[i] == prepared[j] && style.same_voice(i, j)
+        });
+        if unique.len() == text_list.len() {
+            return self.infer_bucketed(text_list, style, total_step, speed);
+        }
 
-        let text_ids_array = {
-            let text_ids_shape = (bsz, text_ids[0].len());
-            let mut flat = Vec::new();
-            for row in &text_ids {
-                flat.extend_from_slice(row);
-            }
-            Array::from_shape_vec(text_ids_shape, flat).map_err(|_e| {
-                SupertonicError::ShapeMismatch {
-                    expected: vec![bsz, text_ids[0].len()],
-                    got: vec![],
-                }
-            })?
+        info!(
+            "Batch of {} texts has {} unique, synthesizing each once",
+            text_list.len(),
+            unique.len()
+        );
+        let unique_texts: Vec<String> = unique.iter().map(|&i| text_list[i].clone()).collect();
+        let selected;
+        let unique_style = if style.batch_size() == 1 {
+            style
+        } else {
+            selected = style.select(&unique);
+            &selected
         };
+        let (wavs, durations) =
+            self.infer_bucketed(&unique_texts, unique_style, total_step, speed)?;
+        Ok((fan_out(&wavs, &slots), fan_out(&durations, &slots)))
+    }
 
-        let text_ids_value = Value::from_array(text_ids_array)?;
-        let text_mask_value = Value::from_array(text_mask.clone())?;
-        let style_dp_value = Value::from_array(style.dp.clone())?;
+    /// `_infer` in sub-batches of texts with similar token counts, so short
+    /// texts are not padded to the longest one; results keep the input order
+    fn infer_bucketed(
+        &mut self,
+        text_list: &[String],
+        style: &Style,
+        total_step: usize,
+        speed: f32,
+    ) -> Result<(Vec<Vec<f32>>, Vec<f32>), SupertonicError> {
+        let lengths: Vec<usize> = text_list
+            .iter()
+            .map(|text| self.text_processor.token_count(text))
+            .collect();
+        let buckets = length_buckets(&lengths);
+        if buckets.len() <= 1 {
+            return self._infer(text_list, style, total_step, speed);
+        }
 
-        // Predict duration
-        let dp_outputs = self.dp_ort.run(ort::inputs! {
-            "text_ids" => &text_ids_value,
-            "style_dp" => &style_dp_value,
-            "text_mask" => &text_mask_value
-        })?;
+        info!(
+            "Batch of {} texts split into {} sub-batches by length",
+            text_list.len(),
+            buckets.len()
+        );
+        let mut wavs = vec![Vec::new(); text_list.len()];
+        let mut durations = vec![0.0; text_list.len()];
+        for bucket in buckets {
+            let texts: Vec<String> = bucket.iter().map(|&i| text_list[i].clone()).collect();
+            let selected;
+            let bucket_style = if style.batch_size() == 1 {
+                style
+            } else {
+                selected = style.select(&bucket);
+                &selected
+            };
+            let (bucket_wavs, bucket_durations) =
+                self._infer(&texts, bucket_style, total_step, speed)?;
+            for ((i, wav), duration) in bucket.into_iter().zip(bucket_wavs).zip(bucket_durations) {
+                wavs[i] = wav;
+                durations[i] = duration;
+            }
+        }
+        Ok((wavs, durations))
+    }
+}
 
-        let (_, duration_data) = dp_outputs["duration"].try_extract_tensor::<f32>()?;
-        let mut duration: Vec<f32> = duration_data.to_vec();
+/// A batch after the duration predictor and text encoder, ready to denoise
+struct Encoded {
+    #[cfg(feature = "test-hooks")]
+    text_ids: Vec<Vec<i64>>,
+    text_mask: Array3<f32>,
+    /// Predicted durations in seconds, after the speed factor
+    duration: Vec<f32>,
+    text_emb: Array3<f32>,
+    /// Time spent in the two sessions
+    timings: StageTimings,
+}
+
+/// Waveforms and durations of a batch, with the lookahead text encoded meanwhile
+type InferOutput = ((Vec<Vec<f32>>, Vec<f32>), Option<Encoded>);
+
+/// A chunk encoded ahead of its turn
+struct Prefetched {
+    text: String,
+    speed: f32,
+    encoded: Encoded,
+}
+
+/// Final latent of a batch, with the noise it started from for parity captures
+struct Denoised {
+    latent: Array3<f32>,
+    #[cfg(feature = "test-hooks")]
+    noise: Array3<f32>,
+}
+
+/// Run the duration predictor and text encoder on `text_list`, or take their
+/// outputs from `cache`.
+///
+/// Takes the sessions it uses rather than the engine, so that the next chunk
+/// can be encoded while the vocoder renders the current one.
+fn encode_texts(
+    text_processor: &UnicodeProcessor,
+    dp_ort: &mut Session,
+    text_enc_ort: &mut Session,
+    mut cache: Option<&mut EncoderCache>,
+    text_list: &[String],
+    style: &Style,
+    speed: f32,
+) -> Result<Encoded, SupertonicError> {
+    let bsz = text_list.len();
+    let mut timings = StageTimings::default();
+
+    // A single voice is shared by every text in the batch
+    let style = style.for_batch(bsz)?;
+
+    // Process text
+    let stage_start = Instant::now();
+    let (text_ids, text_mask) = text_processor.call(text_list)?;
+    timings.text_processing += stage_start.elapsed();
+
+    let key = cache.as_ref().map(|_| EncoderKey::new(&text_ids, &style));
+    let cached = cache
+        .as_deref_mut()
+        .zip(key.as_ref())
+        .and_then(|(cache, key)| cache.get(key));
+    if let Some(cached) = cached {
+        return Ok(Encoded {
+            #[cfg(feature = "test-hooks")]
+            text_ids,
+            text_mask,
+            duration: cached.duration.iter().map(|dur| dur / speed).collect(),
+            text_emb: cached.text_emb,
+            timings,
+        });
+    }
 
-        // Apply speed factor to duration
-        for dur in duration.iter_mut() {
-            *dur /= speed;
+    let text_ids_array = {
+        let text_ids_shape = (bsz, text_ids[0].len());
+        let mut flat = Vec::new();
+        for row in &text_ids {
+            flat.extend_from_slice(row);
         }
+        Array::from_shape_vec(text_ids_shape, flat).map_err(|_e| {
+            SupertonicError::ShapeMismatch {
+                expected: vec![bsz, text_ids[0].len()],
+                got: vec![],
+            }
+        })?
+    };
+
+    // The voice and mask are lent to the sessions rather than copied
+    let text_ids_value = Value::from_array(text_ids_array)?;
+    let text_mask_value = TensorRef::from_array_view(&text_mask)?;
+    let style_dp_value = TensorRef::from_array_view(&style.dp)?;
+    let style_ttl_value = TensorRef::from_array_view(&style.ttl)?;
+
+    // Predict duration
+    let stage_start = Instant::now();
+    let dp_outputs = dp_ort.run(ort::inputs! {
+        "text_ids" => &text_ids_value,
+        "style_dp" => &*style_dp_value,
+        "text_mask" => &*text_mask_value
+    })?;
 
-        // Encode text
-        let style_ttl_value = Value::from_array(style.ttl.clone())?;
-        let text_enc_outputs = self.text_enc_ort.run(ort::inputs! {
-            "text_ids" => &text_ids_value,
-            "style_ttl" => &style_ttl_value,
-            "text_mask" => &text_mask_value
-        })?;
+    let (_, duration_data) = dp_outputs["duration"].try_extract_tensor::<f32>()?;
+    let mut duration: Vec<f32> = duration_data.to_vec();
+    timings.duration_predictor += stage_start.elapsed();
 
-        let (text_emb_shape, text_emb_data) =
-            text_enc_outputs["text_emb"].try_extract_tensor::<f32>()?;
-        let text_emb = Array3::from_shape_vec(
-            (
-                text_emb_shape[0] as usize,
-                text_emb_shape[1] as usize,
-                text_emb_shape[2] as usize,
-            ),
-            text_emb_data.to_vec(),
-        )
-        .map_err(|_e| SupertonicError::ShapeMismatch {
-            expected: vec![
-                text_emb_shape[0] as usize,
-                text_emb_shape[1] as usize,
-                text_emb_shape[2] as usize,
-            ],
-            got: vec![],
-        })?;
+    // Encode text
+    let stage_start = Instant::now();
+    let text_enc_outputs = text_enc_ort.run(ort::inputs! {
+        "text_ids" => &text_ids_value,
+        "style_ttl" => &*style_ttl_value,
+        "text_mask" => &*text_mask_value
+    })?;
 
-        // Sample noisy latent
-        let (mut xt, latent_mask) = sample_noisy_latent(
-            &duration,
-            self.sample_rate,
-            self.cfgs.ae.base_chunk_size,
-            self.cfgs.ttl.chunk_compress_factor,
-            self.cfgs.ttl.latent_dim,
+    let (text_emb_shape, text_emb_data) =
+        text_enc_outputs["text_emb"].try_extract_tensor::<f32>()?;
+    let text_emb = Array3::from_shape_vec(
+        (
+            text_emb_shape[0] as usize,
+            text_emb_shape[1] as usize,
+            text_emb_shape[2] as usize,
+        ),
+        text_emb_data.to_vec(),
+    )
+    .map_err(|_e| SupertonicError::ShapeMismatch {
+        expected: vec![
+            text_emb_shape[0] as usize,
+            text_emb_shape[1] as usize,
+            text_emb_shape[2] as usize,
+        ],
+        got: vec![],
+    })?;
+    timings.text_encoder += stage_start.elapsed();
+
+    if let (Some(cache), Some(key)) = (cache, key) {
+        cache.insert(
+            key,
+            EncoderOutputs {
+                duration: duration.clone(),
+                text_emb: text_emb.clone(),
+            },
         );
+    }
 
-        // Prepare constant arrays
-        let total_step_array = Array::from_elem(bsz, total_step as f32);
-
-        // Denoising loop
-        for step in 0..total_step {
-            let current_step_array = Array::from_elem(bsz, step as f32);
-
-            let xt_value = Value::from_array(xt.clone())?;
-            let text_emb_value = Value::from_array(text_emb.clone())?;
-            let latent_mask_value = Value::from_array(latent_mask.clone())?;
-            let text_mask_value2 = Value::from_array(text_mask.clone())?;
-            let current_step_value = Value::from_array(current_step_array)?;
-            let total_step_value = Value::from_array(total_step_array.clone())?;
-
-            let vector_est_outputs = self.vector_est_ort.run(ort::inputs! {
-                "noisy_latent" => &xt_value,
-                "text_emb" => &text_emb_value,
-                "style_ttl" => &style_ttl_value,
-                "latent_mask" => &latent_mask_value,
-                "text_mask" => &text_mask_value2,
-                "current_step" => &current_step_value,
-                "total_step" => &total_step_value
-            })?;
+    // Apply speed factor to duration
+    for dur in duration.iter_mut() {
+        *dur /= speed;
+    }
 
-            let (denoised_shape, denoised_data) =
-                vector_est_outputs["denoised_latent"].try_extract_tensor::<f32>()?;
-            xt = Array3::from_shape_vec(
-                (
-                    denoised_shape[0] as usize,
-                    denoised_shape[1] as usize,
-                    denoised_shape[2] as usize,
-                ),
-                denoised_data.to_vec(),
-            )
-            .map_err(|_e| SupertonicError::ShapeMismatch {
-                expected: vec![
-                    denoised_shape[0] as usize,
-                    denoised_shape[1] as usize,
-                    denoised_shape[2] as usize,
-                ],
-                got: vec![],
-            })?;
-        }
+    Ok(Encoded {
+        #[cfg(feature = "test-hooks")]
+        text_ids,
+        text_mask,
+        duration,
+        text_emb,
+        timings,
+    })
+}
 
-        // Generate waveform
-        let final_latent_value = Value::from_array(xt)?;
-        let vocoder_outputs = self.vocoder_ort.run(ort::inputs! {
+/// Run the vocoder on a final latent, returning the flat waveform of the
+/// batch and the time it took
+fn vocode(
+    vocoder_ort: &mut Session,
+    latent: Array3<f32>,
+) -> Result<(DynValue, Duration), SupertonicError> {
+    let final_latent_value = Value::from_array(latent)?;
+    let stage_start = Instant::now();
+    let wav = vocoder_ort
+        .run(ort::inputs! {
             "latent" => &final_latent_value
-        })?;
+        })?
+        .remove("wav_tts")
+        .ok_or_else(|| SupertonicError::Unknown("vocoder returned no waveform".to_string()))?;
+    Ok((wav, stage_start.elapsed()))
+}
 
-        let (_, wav_data) = vocoder_outputs["wav_tts"].try_extract_tensor::<f32>()?;
-        let wav_flat: Vec<f32> = wav_data.to_vec();
+/// Copy a `[batch, channels, frames]` output tensor out of its session value
+fn to_array3(value: &DynValue) -> Result<Array3<f32>, SupertonicError> {
+    let view = value.try_extract_array::<f32>()?;
+    let shape = view.shape().to_vec();
+    view.into_dimensionality::<Ix3>()
+        .map(|view| view.to_owned())
+        .map_err(|_e| SupertonicError::ShapeMismatch {
+            expected: vec![0; 3],
+            got: shape,
+        })
+}
 
-        // Slice the flat audio array into individual samples
-        let mut wav_outputs = Vec::with_capacity(bsz);
-        let wav_len_per_sample = wav_flat.len() / bsz;
+/// Check that a `tts.json` given to `TextToSpeech::reload` matches the
+/// `loaded` one, or a `Validation` error naming the values that differ
+pub fn check_reload_config(loaded: &Config, new: &Config) -> Result<(), SupertonicError> {
+    let fields = [
+        ("ae.sample_rate", loaded.ae.sample_rate, new.ae.sample_rate),
+        (
+            "ae.base_chunk_size",
+            loaded.ae.base_chunk_size,
+            new.ae.base_chunk_size,
+        ),
+        (
+            "ttl.chunk_compress_factor",
+            loaded.ttl.chunk_compress_factor,
+            new.ttl.chunk_compress_factor,
+        ),
+        ("ttl.latent_dim", loaded.ttl.latent_dim, new.ttl.latent_dim),
+    ];
+    let changed: Vec<String> = fields
+        .iter()
+        .filter(|(_, loaded, new)| loaded != new)
+        .map(|(field, loaded, new)| format!("{} is {}, was {}", field, new, loaded))
+        .collect();
+    if changed.is_empty() {
+        return Ok(());
+    }
+    Err(SupertonicError::Validation(format!(
+        "the new tts.json differs from the loaded one ({}); load a new engine to change it",
+        changed.join(", ")
+    )))
+}
 
-        for i in 0..bsz {
-            let actual_len = (self.sample_rate as f32 * duration[i]) as usize;
-            let wav_start = i * wav_len_per_sample;
-            let wav_end = wav_start + actual_len.min(wav_len_per_sample);
-            wav_outputs.push(wav_flat[wav_start..wav_end].to_vec());
-        }
+/// Latent frames neighbouring vocoder windows share, crossfaded in the output
+pub const VOCODER_WINDOW_OVERLAP: usize = 4;
 
-        Ok((wav_outputs, duration))
+/// Window length of `vocode_windowed`, and output samples per latent frame
+#[derive(Clone, Copy)]
+struct VocoderWindow {
+    frames: usize,
+    hop: usize,
+}
+
+/// `vocode` over windows of `window.frames` latent frames that overlap by
+/// `VOCODER_WINDOW_OVERLAP`, crossfading each window into the one before it
+fn vocode_windowed(
+    vocoder_ort: &mut Session,
+    latent: Array3<f32>,
+    window: VocoderWindow,
+) -> Result<(DynValue, Duration), SupertonicError> {
+    let (bsz, latent_len) = (latent.shape()[0], latent.shape()[2]);
+    if latent_len <= window.frames {
+        return vocode(vocoder_ort, latent);
     }
 
-    pub fn call(
-        &mut self,
-        text: &str,
-        style: &Style,
-        total_step: usize,
-        speed: f32,
-        silence_duration: f32,
-    ) -> Result<(Vec<f32>, f32), SupertonicError> {
-        let chunks = chunk_text(text, None);
+    let len = latent_len * window.hop;
+    let fade_len = VOCODER_WINDOW_OVERLAP * window.hop;
+    let mut wav = vec![0.0f32; bsz * len];
+    let mut elapsed = Duration::ZERO;
+    for frames in vocoder_windows(latent_len, window.frames) {
+        let (part, part_elapsed) = vocode(
+            vocoder_ort,
+            latent.slice(s![.., .., frames.clone()]).to_owned(),
+        )?;
+        let (_, part) = part.try_extract_tensor::<f32>()?;
+        elapsed += part_elapsed;
+
+        let part_len = (part.len() / bsz).max(1);
+        let offset = frames.start * window.hop;
+        let fade = if frames.start > 0 { fade_len } else { 0 };
+        for (row, part_row) in part.chunks_exact(part_len).enumerate() {
+            crossfade_into(
+                &mut wav[row * len + offset..(row + 1) * len],
+                part_row,
+                fade,
+            );
+        }
+    }
+    Ok((Tensor::from_array(([bsz, len], wav))?.into_dyn(), elapsed))
+}
 
-        let mut wav_cat: Vec<f32> = Vec::new();
-        let mut dur_cat: f32 = 0.0;
+/// Latent frame ranges `vocode_windowed` runs the vocoder over for a latent
+/// of `latent_len` frames: windows of `frames` frames (more than
+/// `VOCODER_WINDOW_OVERLAP`), each starting `VOCODER_WINDOW_OVERLAP` frames
+/// before the previous one ends, the last one cut at the end of the latent
+pub fn vocoder_windows(latent_len: usize, frames: usize) -> Vec<Range<usize>> {
+    assert!(
+        frames > VOCODER_WINDOW_OVERLAP,
+        "windows must be longer than their overlap"
+    );
+    let mut windows = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + frames).min(latent_len);
+        windows.push(start..end);
+        if end == latent_len {
+            return windows;
+        }
+        start += frames - VOCODER_WINDOW_OVERLAP;
+    }
+}
 
-        for (i, chunk) in chunks.iter().enumerate() {
-            let (wav_batch, duration) = self._infer(&[chunk.clone()], style, total_step, speed)?;
+/// Write `part` over `out`, fading linearly from `out` into `part` over the
+/// first `fade` samples; `part` is cut to the length of `out`
+pub fn crossfade_into(out: &mut [f32], part: &[f32], fade: usize) {
+    let fade = fade.min(part.len());
+    for (i, (out, &sample)) in out.iter_mut().zip(part).enumerate() {
+        let gain = if i < fade {
+            i as f32 / fade as f32
+        } else {
+            1.0
+        };
+        *out = *out * (1.0 - gain) + sample * gain;
+    }
+}
 
-            let dur = duration[0];
-            // Wav batch has size 1 here
-            let wav_chunk = &wav_batch[0];
+/// Longest text a sub-batch of `batch` admits, relative to its shortest
+const BUCKET_LENGTH_RATIO: f32 = 1.5;
 
-            if i == 0 {
-                wav_cat.extend_from_slice(wav_chunk);
-                dur_cat = dur;
-            } else {
-                let silence_len = (silence_duration * self.sample_rate as f32) as usize;
-                let silence = vec![0.0f32; silence_len];
+/// Length differences below this many tokens never start a new sub-batch,
+/// which would cost more in extra passes than the padding it saves
+const BUCKET_MIN_GAP: usize = 16;
 
-                wav_cat.extend_from_slice(&silence);
-                wav_cat.extend_from_slice(wav_chunk);
-                dur_cat += silence_duration + dur;
-            }
-        }
+/// Seed of sub-batch `index` of a `batch_concurrent` call on an engine
+/// seeded with `seed`; sub-batch 0 continues the engine's own sequence
+fn sub_batch_seed(seed: u64, index: usize) -> u64 {
+    seed ^ (index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
 
-        Ok((wav_cat, dur_cat))
+/// Find the distinct items among `len` items, where `same(i, j)` tells
+/// whether items `i` and `j` are interchangeable.
+///
+/// Returns the position of each item's result among the distinct items, and
+/// the index of the first item of each distinct one, in order.
+pub fn dedup_slots(len: usize, same: impl Fn(usize, usize) -> bool) -> (Vec<usize>, Vec<usize>) {
+    let mut slots = Vec::with_capacity(len);
+    let mut unique: Vec<usize> = Vec::new();
+    for i in 0..len {
+        let slot = unique.iter().position(|&j| same(i, j));
+        slots.push(slot.unwrap_or_else(|| {
+            unique.push(i);
+            unique.len() - 1
+        }));
     }
+    (slots, unique)
+}
 
-    pub fn batch(
-        &mut self,
-        text_list: &[String],
-        style: &Style,
-        total_step: usize,
-        speed: f32,
-    ) -> Result<(Vec<Vec<f32>>, Vec<f32>), SupertonicError> {
-        self._infer(text_list, style, total_step, speed)
+/// The result of each item, given the results of the distinct items and the
+/// `slots` of `dedup_slots`
+pub fn fan_out<T: Clone>(results: &[T], slots: &[usize]) -> Vec<T> {
+    slots.iter().map(|&slot| results[slot].clone()).collect()
+}
+
+/// Group the indices of texts with token counts `lengths` into sub-batches
+/// of similar length, shortest first.
+///
+/// A sub-batch ends once the next text is more than 1.5 times (and more than
+/// 16 tokens) longer than its first, bounding the padding each text carries.
+pub fn length_buckets(lengths: &[usize]) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (0..lengths.len()).collect();
+    order.sort_by_key(|&i| lengths[i]);
+
+    let mut buckets: Vec<Vec<usize>> = Vec::new();
+    for i in order {
+        let start = buckets.last().map(|bucket| lengths[bucket[0]]);
+        match start {
+            Some(start)
+                if lengths[i] as f32 <= start as f32 * BUCKET_LENGTH_RATIO
+                    || lengths[i] < start + BUCKET_MIN_GAP =>
+            {
+                buckets.last_mut().unwrap().push(i)
+            }
+            _ => buckets.push(vec![i]),
+        }
     }
+    buckets
 }
 
 /// Sample noisy latent from normal distribution and apply mask
@@ -270,52 +2336,104 @@ pub fn sample_noisy_latent(
     chunk_compress: i32,
     latent_dim: i32,
 ) -> (Array3<f32>, Array3<f32>) {
+    sample_noisy_latent_with_rng(
+        duration,
+        sample_rate,
+        base_chunk_size,
+        chunk_compress,
+        latent_dim,
+        &mut rand::thread_rng(),
+    )
+}
+
+/// Sample noisy latent using the given RNG and apply mask
+pub fn sample_noisy_latent_with_rng<R: Rng + ?Sized>(
+    duration: &[f32],
+    sample_rate: i32,
+    base_chunk_size: i32,
+    chunk_compress: i32,
+    latent_dim: i32,
+    rng: &mut R,
+) -> (Array3<f32>, Array3<f32>) {
+    let mut buffers = LatentBuffers::default();
+    sample_noisy_latent_into(
+        duration,
+        sample_rate,
+        base_chunk_size,
+        chunk_compress,
+        latent_dim,
+        rng,
+        &mut buffers,
+    );
+    (buffers.noise, buffers.mask)
+}
+
+/// Noise and latent mask of `sample_noisy_latent_into`, kept between calls so
+/// their allocations are reused
+#[derive(Debug, Default)]
+pub struct LatentBuffers {
+    pub noise: Array3<f32>,
+    pub mask: Array3<f32>,
+}
+
+/// `sample_noisy_latent_with_rng`, writing into `buffers` and only
+/// reallocating them when the batch needs more room than they have
+pub fn sample_noisy_latent_into<R: Rng + ?Sized>(
+    duration: &[f32],
+    sample_rate: i32,
+    base_chunk_size: i32,
+    chunk_compress: i32,
+    latent_dim: i32,
+    rng: &mut R,
+    buffers: &mut LatentBuffers,
+) {
     let bsz = duration.len();
     let max_dur = duration.iter().fold(0.0f32, |a, &b| a.max(b));
 
     let wav_len_max = (max_dur * sample_rate as f32) as usize;
-    let wav_lengths: Vec<usize> = duration
-        .iter()
-        .map(|&d| (d * sample_rate as f32) as usize)
-        .collect();
-
     let chunk_size = (base_chunk_size * chunk_compress) as usize;
-    let latent_len = (wav_len_max + chunk_size - 1) / chunk_size;
+    let latent_len = wav_len_max.div_ceil(chunk_size);
     let latent_dim_val = (latent_dim * chunk_compress) as usize;
+    let latent_lengths: Vec<usize> = duration
+        .iter()
+        .map(|&d| ((d * sample_rate as f32) as usize).div_ceil(chunk_size))
+        .collect();
 
-    let mut noisy_latent = Array3::<f32>::zeros((bsz, latent_dim_val, latent_len));
+    reshape(&mut buffers.noise, (bsz, latent_dim_val, latent_len));
+    reshape(&mut buffers.mask, (bsz, 1, latent_len));
 
+    // Standard layout, so samples are drawn in batch, channel, frame order
     let normal = Normal::new(0.0, 1.0).unwrap();
-    let mut rng = rand::thread_rng();
-
-    for b in 0..bsz {
-        for d in 0..latent_dim_val {
-            for t in 0..latent_len {
-                noisy_latent[[b, d, t]] = normal.sample(&mut rng);
-            }
-        }
+    for value in buffers.noise.iter_mut() {
+        *value = normal.sample(rng);
     }
 
-    let latent_lengths: Vec<usize> = wav_lengths
-        .iter()
-        .map(|&len| (len + chunk_size - 1) / chunk_size)
-        .collect();
-
-    let latent_mask = length_to_mask(&latent_lengths, Some(latent_len));
-
-    // Apply mask
-    for b in 0..bsz {
-        for d in 0..latent_dim_val {
-            for t in 0..latent_len {
-                noisy_latent[[b, d, t]] *= latent_mask[[b, 0, t]];
-            }
-        }
+    // Mask out the frames past each text's length
+    buffers.mask.fill(0.0);
+    for ((mut noise, mut mask), &len) in buffers
+        .noise
+        .outer_iter_mut()
+        .zip(buffers.mask.outer_iter_mut())
+        .zip(&latent_lengths)
+    {
+        let len = len.min(latent_len);
+        mask.slice_mut(s![.., ..len]).fill(1.0);
+        noise.slice_mut(s![.., len..]).fill(0.0);
     }
+}
 
-    (noisy_latent, latent_mask)
+/// Give `array` the dimensions `shape`, reusing its allocation
+fn reshape(array: &mut Array3<f32>, shape: (usize, usize, usize)) {
+    if array.dim() == shape {
+        return;
+    }
+    let (mut data, _) = std::mem::take(array).into_raw_vec_and_offset();
+    data.clear();
+    data.resize(shape.0 * shape.1 * shape.2, 0.0);
+    *array = Array3::from_shape_vec(shape, data).unwrap();
 }
 
-/// Load voice style from bytes
+/// Load voice style from bytes, in the JSON or binary format (see `StyleFormat`)
 pub fn load_voice_style_from_bytes(
     bytes_list: &[&[u8]],
     verbose: bool,
@@ -328,8 +2446,7 @@ pub fn load_voice_style_from_bytes(
     }
 
     // Read first file to get dimensions
-    let first_data: VoiceStyleData =
-        serde_json::from_slice(bytes_list[0]).map_err(SupertonicError::Serialization)?;
+    let first_data = crate::style::decode(bytes_list[0])?;
 
     let ttl_dims = &first_data.style_ttl.dims;
     let dp_dims = &first_data.style_dp.dims;
@@ -347,8 +2464,7 @@ pub fn load_voice_style_from_bytes(
 
     // Fill in the data
     for (i, bytes) in bytes_list.iter().enumerate() {
-        let data: VoiceStyleData =
-            serde_json::from_slice(bytes).map_err(SupertonicError::Serialization)?;
+        let data = crate::style::decode(bytes)?;
 
         // Flatten TTL data
         let ttl_offset = i * ttl_dim1 * ttl_dim2;
@@ -395,10 +2511,11 @@ pub fn load_voice_style_from_bytes(
     Ok(Style {
         ttl: ttl_style,
         dp: dp_style,
+        defaults: first_data.defaults.clone().unwrap_or_default(),
     })
 }
 
-/// Load voice style from JSON files
+/// Load voice style from JSON or binary files
 pub fn load_voice_style(
     voice_style_paths: &[String],
     verbose: bool,
@@ -407,6 +2524,11 @@ pub fn load_voice_style(
     let mut file_contents = Vec::new(); // Keep contents alive
     for path in voice_style_paths {
         let content = std::fs::read(path).map_err(SupertonicError::Io)?;
+        let path = Path::new(path);
+        let dir = path.parent().unwrap_or(Path::new(""));
+        if let (Some(manifest), Some(name)) = (AssetManifest::find(dir)?, path.file_name()) {
+            manifest.verify_bytes(&name.to_string_lossy(), &content)?;
+        }
         file_contents.push(content);
     }
 
@@ -431,59 +2553,212 @@ pub fn load_text_to_speech_from_memory(
     models: ModelBytes,
     use_gpu: bool,
 ) -> Result<TextToSpeech, SupertonicError> {
-    if use_gpu {
-        return Err(SupertonicError::Config(
-            "GPU mode is not supported yet".to_string(),
-        ));
-    }
-    info!("Using CPU for inference");
+    load_text_to_speech_from_memory_with_config(models, &SessionConfig::with_gpu(use_gpu))
+}
+
+/// Load TTS components from memory with explicit session options
+pub fn load_text_to_speech_from_memory_with_config(
+    models: ModelBytes,
+    session_config: &SessionConfig,
+) -> Result<TextToSpeech, SupertonicError> {
+    load_text_to_speech_from_sources(
+        models.config,
+        models.unicode_indexer,
+        [
+            models.duration_predictor,
+            models.text_encoder,
+            models.vector_estimator,
+            models.vocoder,
+        ]
+        .map(|bytes| ModelSource::Memory(Cow::Borrowed(bytes))),
+        session_config,
+    )
+}
+
+/// Readers over the six model files, for sources that are neither files nor
+/// slices already in memory: archive entries, network streams or platform
+/// asset managers such as Android's `AssetManager`
+pub struct ModelReaders<'a> {
+    pub config: Box<dyn Read + 'a>,
+    pub duration_predictor: Box<dyn Read + 'a>,
+    pub text_encoder: Box<dyn Read + 'a>,
+    pub vector_estimator: Box<dyn Read + 'a>,
+    pub vocoder: Box<dyn Read + 'a>,
+    pub unicode_indexer: Box<dyn Read + 'a>,
+}
+
+/// Load TTS components from readers
+pub fn load_text_to_speech_from_readers(
+    readers: ModelReaders,
+    use_gpu: bool,
+) -> Result<TextToSpeech, SupertonicError> {
+    load_text_to_speech_from_readers_with_config(readers, &SessionConfig::with_gpu(use_gpu))
+}
+
+/// Load TTS components from readers with explicit session options.
+///
+/// Each reader is read to its end once. ONNX Runtime needs a model whole to
+/// create its session, so the models are held in memory (and handed over to
+/// a deferred session without another copy) but never written to disk.
+pub fn load_text_to_speech_from_readers_with_config(
+    mut readers: ModelReaders,
+    session_config: &SessionConfig,
+) -> Result<TextToSpeech, SupertonicError> {
+    let read = |reader: &mut dyn Read| -> Result<Vec<u8>, SupertonicError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    };
+    let config = read(&mut readers.config)?;
+    let unicode_indexer = read(&mut readers.unicode_indexer)?;
+    let models = [
+        read(&mut readers.duration_predictor)?,
+        read(&mut readers.text_encoder)?,
+        read(&mut readers.vector_estimator)?,
+        read(&mut readers.vocoder)?,
+    ];
+    load_text_to_speech_from_sources(
+        &config,
+        &unicode_indexer,
+        models.map(|bytes| ModelSource::Memory(Cow::Owned(bytes))),
+        session_config,
+    )
+}
+
+/// Build an engine whose sessions are created from `sources` (in
+/// `MODEL_NAMES` order) as `session_config.load_plan` says
+fn load_text_to_speech_from_sources(
+    config: &[u8],
+    unicode_indexer: &[u8],
+    sources: [ModelSource<'_>; 4],
+    session_config: &SessionConfig,
+) -> Result<TextToSpeech, SupertonicError> {
+    let (cfgs, text_processor, sessions) =
+        load_engine_parts(config, unicode_indexer, sources, session_config)?;
+    Ok(TextToSpeech::with_sessions(
+        cfgs,
+        text_processor,
+        sessions,
+        session_config,
+    ))
+}
 
-    let cfgs = crate::config::load_cfgs_from_bytes(models.config)
+/// Config, text processor and sessions of an engine, for loading or `reload`
+fn load_engine_parts(
+    config: &[u8],
+    unicode_indexer: &[u8],
+    sources: [ModelSource<'_>; 4],
+    session_config: &SessionConfig,
+) -> Result<(Config, UnicodeProcessor, [LazySession; 4]), SupertonicError> {
+    info!(
+        "Using {} for inference",
+        if session_config.use_gpu {
+            "ROCm"
+        } else {
+            "CPU"
+        }
+    );
+
+    let cfgs = crate::config::load_cfgs_from_bytes(config)
         .map_err(|e| SupertonicError::Config(e.to_string()))?;
 
-    let dp_ort = Session::builder()?.commit_from_memory(models.duration_predictor)?;
-    let text_enc_ort = Session::builder()?.commit_from_memory(models.text_encoder)?;
-    let vector_est_ort = Session::builder()?.commit_from_memory(models.vector_estimator)?;
-    let vocoder_ort = Session::builder()?.commit_from_memory(models.vocoder)?;
+    let [dp, text_enc, vector_est, vocoder] = sources;
+    let [dp_name, text_enc_name, vector_est_name, vocoder_name] = MODEL_NAMES;
+    let sessions = [
+        LazySession::load(dp_name, dp, session_config)?,
+        LazySession::load(text_enc_name, text_enc, session_config)?,
+        LazySession::load(vector_est_name, vector_est, session_config)?,
+        LazySession::load(vocoder_name, vocoder, session_config)?,
+    ];
+
+    let text_processor = UnicodeProcessor::from_bytes(unicode_indexer)
+        .map_err(|e| SupertonicError::TextProcessing(e.to_string()))?;
+    Ok((cfgs, text_processor, sessions))
+}
+
+/// The four ONNX sessions of an engine, for hosts that create sessions themselves
+pub struct ModelSessions {
+    pub duration_predictor: Session,
+    pub text_encoder: Session,
+    pub vector_estimator: Session,
+    pub vocoder: Session,
+}
+
+impl ModelSessions {
+    /// Check that every session has the inputs the engine feeds it and the
+    /// outputs it reads (see `compat::check_session`)
+    pub fn validate(&self) -> Result<(), SupertonicError> {
+        let sessions = [
+            &self.duration_predictor,
+            &self.text_encoder,
+            &self.vector_estimator,
+            &self.vocoder,
+        ];
+        for (name, session) in MODEL_NAMES.into_iter().zip(sessions) {
+            check_session(name, session)?;
+        }
+        Ok(())
+    }
+}
 
-    let text_processor = UnicodeProcessor::from_bytes(models.unicode_indexer)
+/// Build an engine from sessions created by the host application.
+///
+/// Applications that already embed ONNX Runtime (for speech recognition, say)
+/// can create the sessions inside their own environment and with their own
+/// execution providers; `config` and `unicode_indexer` are the contents of
+/// `tts.json` and `unicode_indexer.json`.
+pub fn load_text_to_speech_from_sessions(
+    config: &[u8],
+    unicode_indexer: &[u8],
+    sessions: ModelSessions,
+) -> Result<TextToSpeech, SupertonicError> {
+    sessions.validate()?;
+    let cfgs = crate::config::load_cfgs_from_bytes(config)
+        .map_err(|e| SupertonicError::Config(e.to_string()))?;
+    let text_processor = UnicodeProcessor::from_bytes(unicode_indexer)
         .map_err(|e| SupertonicError::TextProcessing(e.to_string()))?;
 
     Ok(TextToSpeech::new(
         cfgs,
         text_processor,
-        dp_ort,
-        text_enc_ort,
-        vector_est_ort,
-        vocoder_ort,
+        sessions.duration_predictor,
+        sessions.text_encoder,
+        sessions.vector_estimator,
+        sessions.vocoder,
     ))
 }
 
 /// Load TTS components
 pub fn load_text_to_speech(onnx_dir: &str, use_gpu: bool) -> Result<TextToSpeech, SupertonicError> {
+    load_text_to_speech_with_config(onnx_dir, &SessionConfig::with_gpu(use_gpu))
+}
+
+/// Load TTS components with explicit session options
+pub fn load_text_to_speech_with_config(
+    onnx_dir: &str,
+    session_config: &SessionConfig,
+) -> Result<TextToSpeech, SupertonicError> {
     let cfg_path = format!("{}/tts.json", onnx_dir);
-    let dp_path = format!("{}/duration_predictor.onnx", onnx_dir);
-    let text_enc_path = format!("{}/text_encoder.onnx", onnx_dir);
-    let vector_est_path = format!("{}/vector_estimator.onnx", onnx_dir);
-    let vocoder_path = format!("{}/vocoder.onnx", onnx_dir);
     let unicode_indexer_path = format!("{}/unicode_indexer.json", onnx_dir);
 
     let config = std::fs::read(cfg_path).map_err(SupertonicError::Io)?;
-    let dp = std::fs::read(dp_path).map_err(SupertonicError::Io)?;
-    let text_enc = std::fs::read(text_enc_path).map_err(SupertonicError::Io)?;
-    let vector_est = std::fs::read(vector_est_path).map_err(SupertonicError::Io)?;
-    let vocoder = std::fs::read(vocoder_path).map_err(SupertonicError::Io)?;
     let unicode_indexer = std::fs::read(unicode_indexer_path).map_err(SupertonicError::Io)?;
+    let paths = MODEL_NAMES.map(|name| PathBuf::from(format!("{}/{}.onnx", onnx_dir, name)));
+    // Report a missing model now rather than on first use of a deferred session
+    if let Some(missing) = paths.iter().find(|path| !path.is_file()) {
+        return Err(SupertonicError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("{} not found", missing.display()),
+        )));
+    }
+    if let Some(manifest) = AssetManifest::find(onnx_dir)? {
+        manifest.verify_bytes("tts.json", &config)?;
+        manifest.verify_bytes("unicode_indexer.json", &unicode_indexer)?;
+        for name in MODEL_NAMES {
+            manifest.verify_file(onnx_dir, &format!("{}.onnx", name))?;
+        }
+    }
+    let sources = paths.map(ModelSource::File);
 
-    load_text_to_speech_from_memory(
-        ModelBytes {
-            config: &config,
-            duration_predictor: &dp,
-            text_encoder: &text_enc,
-            vector_estimator: &vector_est,
-            vocoder: &vocoder,
-            unicode_indexer: &unicode_indexer,
-        },
-        use_gpu,
-    )
+    load_text_to_speech_from_sources(&config, &unicode_indexer, sources, session_config)
 }
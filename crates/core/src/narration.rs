@@ -0,0 +1,173 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::error::SupertonicError;
+use crate::model::{load_voice_style, Style};
+use crate::options::SynthesisOptions;
+
+// ============================================================================
+// Narration Presets
+// ============================================================================
+//
+// A preset maps narration roles to voices, e.g. a narrator voice for prose and
+// a second voice for quoted dialogue:
+//
+// {
+//   "name": "audiobook",
+//   "split_quotes": true,
+//   "match_loudness": true,
+//   "voices": [
+//     { "role": "narrator", "voice": "assets/voice_styles/M1.json" },
+//     { "role": "dialogue", "voice": "assets/voice_styles/F1.json", "options": { "speed": 1.1 } }
+//   ]
+// }
+
+/// Part of the text a voice is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NarrationRole {
+    Narrator,
+    Dialogue,
+}
+
+/// One voice of a preset, with its prosody tweaks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NarrationVoice {
+    pub role: NarrationRole,
+    /// Path to the voice style JSON
+    pub voice: String,
+    /// Options applied to this role on top of the voice's own defaults
+    #[serde(default)]
+    pub options: SynthesisOptions,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NarrationPreset {
+    pub name: String,
+    /// Ordered voices; the first one also covers roles without an entry
+    pub voices: Vec<NarrationVoice>,
+    /// Switch to the dialogue voice for quoted spans
+    #[serde(default)]
+    pub split_quotes: bool,
+    /// Level the voices against each other by their measured loudness
+    #[serde(default)]
+    pub match_loudness: bool,
+}
+
+impl NarrationPreset {
+    /// Load a preset from a JSON file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SupertonicError> {
+        let bytes = fs::read(path)?;
+        let preset: NarrationPreset = serde_json::from_slice(&bytes)?;
+        if preset.voices.is_empty() {
+            return Err(SupertonicError::Validation(format!(
+                "Narration preset '{}' has no voices",
+                preset.name
+            )));
+        }
+        Ok(preset)
+    }
+
+    /// Load the style of every voice in the preset
+    pub fn load_voices(&self) -> Result<NarrationVoices, SupertonicError> {
+        let mut voices = Vec::with_capacity(self.voices.len());
+        for v in &self.voices {
+            let style = load_voice_style(std::slice::from_ref(&v.voice), false)?;
+            voices.push((v.role, style, v.options.clone()));
+        }
+        Ok(NarrationVoices {
+            voices,
+            split_quotes: self.split_quotes,
+            match_loudness: self.match_loudness,
+        })
+    }
+}
+
+/// A preset with its voice styles loaded, ready for `TextToSpeech::narrate`
+pub struct NarrationVoices {
+    voices: Vec<(NarrationRole, Style, SynthesisOptions)>,
+    split_quotes: bool,
+    match_loudness: bool,
+}
+
+impl NarrationVoices {
+    /// Style and role options used for `role`
+    pub fn voice_for(&self, role: NarrationRole) -> (&Style, &SynthesisOptions) {
+        let (_, style, options) = &self.voices[self.voice_index(role)];
+        (style, options)
+    }
+
+    /// Index of the voice used for `role`
+    pub fn voice_index(&self, role: NarrationRole) -> usize {
+        self.voices
+            .iter()
+            .position(|(r, _, _)| *r == role)
+            .unwrap_or(0)
+    }
+
+    pub fn voice_count(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Whether `TextToSpeech::narrate` levels the voices by loudness
+    pub fn match_loudness(&self) -> bool {
+        self.match_loudness
+    }
+
+    pub fn set_match_loudness(&mut self, enabled: bool) {
+        self.match_loudness = enabled;
+    }
+
+    /// Split `text` into the segments each role should read
+    pub fn segments(&self, text: &str) -> Vec<NarrationSegment> {
+        if self.split_quotes {
+            split_dialogue(text)
+        } else {
+            vec![NarrationSegment {
+                role: NarrationRole::Narrator,
+                text: text.trim().to_string(),
+            }]
+        }
+    }
+}
+
+// ============================================================================
+// Dialogue Detection
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NarrationSegment {
+    pub role: NarrationRole,
+    pub text: String,
+}
+
+/// Split text into narrator and quoted-dialogue segments.
+///
+/// Straight (`"..."`) and curly (`“...”`) double quotes are recognized; the
+/// quote marks stay with the dialogue segment. Segments without any letters
+/// or digits (e.g. a lone comma between two quotes) are dropped.
+pub fn split_dialogue(text: &str) -> Vec<NarrationSegment> {
+    let quote_re = Regex::new(r#""[^"]+"|“[^”]+”"#).unwrap();
+    let mut segments = Vec::new();
+    let mut push = |role: NarrationRole, s: &str| {
+        let s = s.trim();
+        if s.chars().any(|c| c.is_alphanumeric()) {
+            segments.push(NarrationSegment {
+                role,
+                text: s.to_string(),
+            });
+        }
+    };
+
+    let mut last_end = 0;
+    for m in quote_re.find_iter(text) {
+        push(NarrationRole::Narrator, &text[last_end..m.start()]);
+        push(NarrationRole::Dialogue, m.as_str());
+        last_end = m.end();
+    }
+    push(NarrationRole::Narrator, &text[last_end..]);
+
+    segments
+}
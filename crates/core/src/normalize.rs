@@ -0,0 +1,1036 @@
+use chrono::{Datelike, Duration, NaiveDate, Utc, Weekday};
+use chrono_tz::Tz;
+use regex::{Captures, Regex};
+use std::collections::BTreeSet;
+
+use crate::error::SupertonicError;
+use crate::text::{PreprocessStage, Trace};
+
+// ============================================================================
+// Number Words
+// ============================================================================
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES: [&str; 7] = [
+    "",
+    "thousand",
+    "million",
+    "billion",
+    "trillion",
+    "quadrillion",
+    "quintillion",
+];
+
+fn below_thousand(n: u64) -> String {
+    let mut words = Vec::new();
+    let hundreds = n / 100;
+    let rest = n % 100;
+
+    if hundreds > 0 {
+        words.push(format!("{} hundred", ONES[hundreds as usize]));
+    }
+    if rest > 0 {
+        if rest < 20 {
+            words.push(ONES[rest as usize].to_string());
+        } else if rest.is_multiple_of(10) {
+            words.push(TENS[(rest / 10) as usize].to_string());
+        } else {
+            words.push(format!(
+                "{}-{}",
+                TENS[(rest / 10) as usize],
+                ONES[(rest % 10) as usize]
+            ));
+        }
+    }
+
+    words.join(" ")
+}
+
+/// Spell out a non-negative integer in English words ("one thousand two hundred five")
+pub fn number_to_words(n: u64) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+
+    let mut groups = Vec::new();
+    let mut rest = n;
+    while rest > 0 {
+        groups.push(rest % 1000);
+        rest /= 1000;
+    }
+
+    let mut words = Vec::new();
+    for (scale, &group) in groups.iter().enumerate().rev() {
+        if group == 0 {
+            continue;
+        }
+        let mut part = below_thousand(group);
+        if scale > 0 {
+            part.push(' ');
+            part.push_str(SCALES[scale]);
+        }
+        words.push(part);
+    }
+
+    words.join(" ")
+}
+
+// ============================================================================
+// Currency
+// ============================================================================
+
+/// Spoken names of a currency and its minor unit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Currency {
+    pub symbol: String,
+    pub singular: String,
+    pub plural: String,
+    /// Minor unit names, e.g. ("cent", "cents"); `None` for currencies without one
+    pub minor: Option<(String, String)>,
+}
+
+impl Currency {
+    pub fn new(symbol: &str, singular: &str, plural: &str, minor: Option<(&str, &str)>) -> Self {
+        Currency {
+            symbol: symbol.to_string(),
+            singular: singular.to_string(),
+            plural: plural.to_string(),
+            minor: minor.map(|(s, p)| (s.to_string(), p.to_string())),
+        }
+    }
+}
+
+/// Currency symbols recognized during normalization; extend with `insert`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurrencyTable {
+    currencies: Vec<Currency>,
+}
+
+impl Default for CurrencyTable {
+    fn default() -> Self {
+        CurrencyTable {
+            currencies: vec![
+                Currency::new("$", "dollar", "dollars", Some(("cent", "cents"))),
+                Currency::new("€", "euro", "euros", Some(("cent", "cents"))),
+                Currency::new("£", "pound", "pounds", Some(("penny", "pence"))),
+                Currency::new("¥", "yen", "yen", None),
+                Currency::new("₩", "won", "won", None),
+                Currency::new("₹", "rupee", "rupees", Some(("paisa", "paise"))),
+            ],
+        }
+    }
+}
+
+impl CurrencyTable {
+    /// An empty table (no currency expansion)
+    pub fn empty() -> Self {
+        CurrencyTable {
+            currencies: Vec::new(),
+        }
+    }
+
+    /// Add a currency, replacing any existing entry with the same symbol
+    pub fn insert(&mut self, currency: Currency) {
+        self.currencies.retain(|c| c.symbol != currency.symbol);
+        self.currencies.push(currency);
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&Currency> {
+        self.currencies.iter().find(|c| c.symbol == symbol)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Currency> {
+        self.currencies.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.currencies.is_empty()
+    }
+}
+
+fn spell_amount(currency: &Currency, whole: u64, minor: u64) -> String {
+    let major_words = format!(
+        "{} {}",
+        number_to_words(whole),
+        if whole == 1 {
+            &currency.singular
+        } else {
+            &currency.plural
+        }
+    );
+
+    match (&currency.minor, minor) {
+        (Some((singular, plural)), m) if m > 0 => {
+            let minor_words = format!(
+                "{} {}",
+                number_to_words(m),
+                if m == 1 { singular } else { plural }
+            );
+            if whole == 0 {
+                minor_words
+            } else {
+                format!("{} and {}", major_words, minor_words)
+            }
+        }
+        _ => major_words,
+    }
+}
+
+/// Expand amounts like "$12.50", "£1,000" or "12.50 €" into words
+pub fn expand_currency(text: &str, table: &CurrencyTable) -> String {
+    let symbols: Vec<String> = table.iter().map(|c| symbol_pattern(&c.symbol)).collect();
+    if symbols.is_empty() {
+        return text.to_string();
+    }
+    let symbols = symbols.join("|");
+    let amount = r"(\d{1,3}(?:,\d{3})+|\d+)(?:\.(\d{1,2}))?";
+
+    let spell = |symbol: &str, whole: &str, minor: Option<&str>| -> Option<String> {
+        let currency = table.get(symbol)?;
+        let whole: u64 = whole.replace(',', "").parse().ok()?;
+        let minor = minor.map_or(0, |m| {
+            // "$1.5" means fifty cents, not five
+            let digits = format!("{:0<2}", m);
+            digits.parse().unwrap_or(0)
+        });
+        Some(spell_amount(currency, whole, minor))
+    };
+
+    let leading = Regex::new(&format!(r"({})\s?{}\b", symbols, amount)).unwrap();
+    let text = leading.replace_all(text, |caps: &Captures| {
+        spell(&caps[1], &caps[2], caps.get(3).map(|m| m.as_str()))
+            .unwrap_or_else(|| caps[0].to_string())
+    });
+    // Locales that write the symbol after the amount
+    let trailing = Regex::new(&format!(r"\b{}\s?({})", amount, symbols)).unwrap();
+    trailing
+        .replace_all(&text, |caps: &Captures| {
+            spell(&caps[3], &caps[1], caps.get(2).map(|m| m.as_str()))
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .to_string()
+}
+
+/// Regex for a currency symbol; letter symbols ("kr", "CHF") must stand alone
+fn symbol_pattern(symbol: &str) -> String {
+    let word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    format!(
+        "{}{}{}",
+        if word(symbol.chars().next()) {
+            r"\b"
+        } else {
+            ""
+        },
+        regex::escape(symbol),
+        if word(symbol.chars().last()) {
+            r"\b"
+        } else {
+            ""
+        }
+    )
+}
+
+// ============================================================================
+// URLs and Emails
+// ============================================================================
+
+const URL_TLDS: &str = "com|org|net|edu|gov|io|dev|ai|app|co|uk|de|fr|jp|kr|info|me";
+
+/// Spell a token character by character ("v2" -> "v two")
+fn spell_out(token: &str) -> String {
+    token
+        .chars()
+        .map(|c| match c.to_digit(10) {
+            Some(d) => ONES[d as usize].to_string(),
+            None => c.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn separator_word(c: char) -> Option<&'static str> {
+    match c {
+        '.' => Some("dot"),
+        '/' => Some("slash"),
+        '-' => Some("dash"),
+        '_' => Some("underscore"),
+        '?' => Some("question mark"),
+        '=' => Some("equals"),
+        '&' => Some("and"),
+        '#' => Some("hash"),
+        ':' => Some("colon"),
+        '+' => Some("plus"),
+        '~' => Some("tilde"),
+        '%' => Some("percent"),
+        _ => None,
+    }
+}
+
+/// Read a single token: short or mixed tokens are spelled, numbers become words
+fn speak_token(token: &str, spell_short: bool) -> String {
+    if token.chars().all(|c| c.is_ascii_digit()) {
+        return match token.parse::<u64>() {
+            Ok(n) if token.len() <= 4 && !token.starts_with('0') => number_to_words(n),
+            _ => spell_out(token),
+        };
+    }
+    let has_digit = token.chars().any(|c| c.is_ascii_digit());
+    if (spell_short && token.chars().count() <= 3) || (has_digit && token.chars().count() <= 4) {
+        spell_out(token)
+    } else {
+        token.to_string()
+    }
+}
+
+/// Read `part` token by token, naming separators ("a-b/c" -> "a dash b slash c")
+fn speak_separated(part: &str, spell_short: bool) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut token = String::new();
+    for c in part.chars() {
+        match separator_word(c) {
+            Some(word) => {
+                if !token.is_empty() {
+                    words.push(speak_token(&token, spell_short));
+                    token.clear();
+                }
+                words.push(word.to_string());
+            }
+            None => token.push(c),
+        }
+    }
+    if !token.is_empty() {
+        words.push(speak_token(&token, spell_short));
+    }
+    words
+}
+
+fn speak_url(url: &str) -> String {
+    let re_scheme = Regex::new(r"(?i)^[a-z][a-z0-9+.-]*://").unwrap();
+    let url = re_scheme.replace(url, "");
+    let url = url.trim_end_matches('/');
+
+    let split = url.find(['/', '?', '#']).unwrap_or(url.len());
+    let (host, rest) = url.split_at(split);
+
+    let mut words = Vec::new();
+    for (i, label) in host.split('.').enumerate() {
+        if i > 0 {
+            words.push("dot".to_string());
+        }
+        if label.eq_ignore_ascii_case("www") {
+            words.push("w w w".to_string());
+        } else {
+            words.extend(speak_separated(label, false));
+        }
+    }
+    words.extend(speak_separated(rest, true));
+
+    words.join(" ")
+}
+
+fn speak_email(email: &str) -> String {
+    let (local, domain) = email.split_once('@').unwrap_or((email, ""));
+    let mut words = speak_separated(local, false);
+    words.push("at".to_string());
+    words.extend(speak_separated(domain, false));
+    words.join(" ")
+}
+
+/// Replace URLs and email addresses with a spoken form ("example dot com slash a p i")
+pub fn expand_urls_and_emails(text: &str) -> String {
+    let email_re =
+        Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b")
+            .unwrap();
+    let text = email_re.replace_all(text, |caps: &Captures| speak_email(&caps[0]));
+
+    let url_pattern = format!(
+        r#"(?i)\b(?:[a-z][a-z0-9+.-]*://|www\.)[^\s<>"]*[^\s<>".,!?;:)\]']|\b[a-z0-9-]+(?:\.[a-z0-9-]+)*\.(?:{})\b(?:[/?#][^\s<>"]*[^\s<>".,!?;:)\]'])?"#,
+        URL_TLDS
+    );
+    let url_re = Regex::new(&url_pattern).unwrap();
+    url_re
+        .replace_all(&text, |caps: &Captures| speak_url(&caps[0]))
+        .to_string()
+}
+
+// ============================================================================
+// Acronyms
+// ============================================================================
+
+const DEFAULT_ACRONYM_WORDS: &[&str] = &[
+    "NASA", "NATO", "UNESCO", "UNICEF", "FIFA", "OPEC", "NASDAQ", "SCUBA", "LASER", "RADAR", "GIF",
+    "JPEG", "PIN", "ZIP", "COVID", "AIDS", "IKEA", "LEGO", "SIM", "RAM", "ROM", "CAPTCHA", "WASM",
+];
+
+/// How all-caps tokens (NASA, HTML, USA) are read
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcronymRules {
+    pub enabled: bool,
+    /// Acronyms pronounced as words; everything else is spelled letter by letter
+    pub words: BTreeSet<String>,
+    /// Unknown all-caps tokens longer than this are treated as shouted words
+    /// and lowercased instead of spelled
+    pub max_spell_len: usize,
+}
+
+impl Default for AcronymRules {
+    fn default() -> Self {
+        AcronymRules {
+            enabled: true,
+            words: DEFAULT_ACRONYM_WORDS
+                .iter()
+                .map(|w| w.to_string())
+                .collect(),
+            max_spell_len: 5,
+        }
+    }
+}
+
+impl AcronymRules {
+    /// Pronounce `acronym` as a word rather than spelling it
+    pub fn insert_word(&mut self, acronym: &str) {
+        self.words.insert(acronym.to_uppercase());
+    }
+
+    /// Spell `acronym` letter by letter even if it is in the word list
+    pub fn remove_word(&mut self, acronym: &str) {
+        self.words.remove(&acronym.to_uppercase());
+    }
+}
+
+/// Spell out or re-case all-caps tokens according to `rules`
+pub fn expand_acronyms(text: &str, rules: &AcronymRules) -> String {
+    let re = Regex::new(r"\b([A-Z]{2,})(s?)\b").unwrap();
+    re.replace_all(text, |caps: &Captures| {
+        let token = &caps[1];
+        let plural = &caps[2];
+        if rules.words.contains(token) {
+            // Title case keeps the model from reading it letter by letter
+            let mut chars = token.chars();
+            let first = chars.next().unwrap_or_default();
+            format!("{}{}{}", first, chars.as_str().to_lowercase(), plural)
+        } else if token.len() <= rules.max_spell_len {
+            let letters: Vec<String> = token.chars().map(|c| c.to_string()).collect();
+            format!("{}{}", letters.join(" "), plural)
+        } else {
+            format!("{}{}", token.to_lowercase(), plural)
+        }
+    })
+    .to_string()
+}
+
+// ============================================================================
+// Locales
+// ============================================================================
+
+/// Regions that conventionally use the 12-hour clock
+const TWELVE_HOUR_REGIONS: &[&str] = &["US", "CA", "AU", "NZ", "IN", "PH", "PK", "EG", "SA"];
+
+/// Regions that write numeric dates month first
+const MONTH_FIRST_REGIONS: &[&str] = &["US", "PH"];
+
+/// Languages that write "3,5" for three and a half
+const DECIMAL_COMMA_LANGUAGES: &[&str] = &[
+    "de", "fr", "es", "it", "pt", "nl", "ru", "uk", "pl", "cs", "sk", "hu", "ro", "el", "tr", "id",
+    "sv", "da", "nb", "nn", "no", "fi",
+];
+
+/// Decimal-comma languages that group digits with spaces ("1 000") rather than dots
+const SPACE_GROUP_LANGUAGES: &[&str] = &[
+    "fr", "ru", "uk", "pl", "cs", "sk", "hu", "sv", "nb", "nn", "no", "fi",
+];
+
+/// Number, date and currency conventions of a locale.
+///
+/// `Locale::new` derives them from a BCP 47 tag; the fields can be adjusted
+/// afterwards for conventions the built-in tables get wrong.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locale {
+    /// BCP 47 tag such as "en-US" or "de-DE"
+    pub tag: String,
+    /// Decimal mark in written numbers ("3.5" or "3,5")
+    pub decimal_separator: char,
+    /// Digit group mark ("1,000", "1.000" or "1 000")
+    pub group_separator: char,
+    /// Whether "3/4/2025" is March 4th rather than April 3rd
+    pub month_first: bool,
+    pub twelve_hour_clock: bool,
+    /// Currencies read differently here ("¥" as yuan in zh-CN); they take
+    /// precedence over the normalizer's table
+    pub currencies: Vec<Currency>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::new("en-US")
+    }
+}
+
+impl Locale {
+    pub fn new(tag: &str) -> Self {
+        let mut subtags = tag.split(['-', '_']);
+        let language = subtags.next().unwrap_or_default().to_lowercase();
+        // Skip script subtags ("zh-Hant-TW"); a bare language implies its main region
+        let region = subtags
+            .find(|s| s.len() == 2 || (s.len() == 3 && s.chars().all(|c| c.is_ascii_digit())))
+            .map(|s| s.to_uppercase())
+            .unwrap_or_else(|| match language.as_str() {
+                "en" | "" => "US".to_string(),
+                other => other.to_uppercase(),
+            });
+        let region = region.as_str();
+
+        let (decimal_separator, group_separator) = match (language.as_str(), region) {
+            (_, "CH") => ('.', '\''),
+            ("es", "MX") => ('.', ','),
+            (language, _) if SPACE_GROUP_LANGUAGES.contains(&language) => (',', ' '),
+            (language, _) if DECIMAL_COMMA_LANGUAGES.contains(&language) => (',', '.'),
+            _ => ('.', ','),
+        };
+
+        let mut currencies = Vec::new();
+        match language.as_str() {
+            "zh" => currencies.push(Currency::new("¥", "yuan", "yuan", Some(("fen", "fen")))),
+            "sv" => currencies.push(Currency::new("kr", "krona", "kronor", Some(("ore", "ore")))),
+            "da" | "nb" | "nn" | "no" => {
+                currencies.push(Currency::new("kr", "krone", "kroner", Some(("ore", "ore"))))
+            }
+            "pl" => currencies.push(Currency::new(
+                "zł",
+                "zloty",
+                "zlotys",
+                Some(("grosz", "groszy")),
+            )),
+            _ => {}
+        }
+        match region {
+            "CH" => currencies.push(Currency::new(
+                "CHF",
+                "franc",
+                "francs",
+                Some(("rappen", "rappen")),
+            )),
+            "ZA" => currencies.push(Currency::new("R", "rand", "rand", Some(("cent", "cents")))),
+            "MX" | "PH" => {
+                let symbol = if region == "MX" { "$" } else { "₱" };
+                currencies.push(Currency::new(
+                    symbol,
+                    "peso",
+                    "pesos",
+                    Some(("centavo", "centavos")),
+                ))
+            }
+            _ => {}
+        }
+
+        Locale {
+            tag: tag.to_string(),
+            decimal_separator,
+            group_separator,
+            month_first: MONTH_FIRST_REGIONS.contains(&region),
+            twelve_hour_clock: TWELVE_HOUR_REGIONS.contains(&region),
+            currencies,
+        }
+    }
+}
+
+/// Rewrite numbers written with the locale's separators ("1.234,5" in de-DE)
+/// in the form the later stages and the model read ("1234.5")
+pub fn expand_locale_numbers(text: &str, locale: &Locale) -> String {
+    if locale.decimal_separator == '.' && locale.group_separator == ',' {
+        return text.to_string();
+    }
+    let separators = regex::escape(&format!(
+        "{}{}",
+        locale.decimal_separator, locale.group_separator
+    ));
+    let re = Regex::new(&format!(r"\d+(?:[{}]\d+)*", separators)).unwrap();
+    re.replace_all(text, |caps: &Captures| {
+        canonical_number(&caps[0], locale).unwrap_or_else(|| caps[0].to_string())
+    })
+    .to_string()
+}
+
+/// `number` without group marks and with a "." decimal point, if it is a
+/// well-formed number in `locale` (dates like "3.4.2025" are not)
+fn canonical_number(number: &str, locale: &Locale) -> Option<String> {
+    let (whole, fraction) = match number.split_once(locale.decimal_separator) {
+        Some((_, fraction)) if fraction.contains(locale.decimal_separator) => return None,
+        Some((_, fraction)) if fraction.contains(locale.group_separator) => return None,
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (number, None),
+    };
+    let groups: Vec<&str> = whole.split(locale.group_separator).collect();
+    if groups.len() > 1 && (groups[0].len() > 3 || groups[1..].iter().any(|g| g.len() != 3)) {
+        return None;
+    }
+    let mut out = groups.concat();
+    if let Some(fraction) = fraction {
+        out.push('.');
+        out.push_str(fraction);
+    }
+    Some(out)
+}
+
+// ============================================================================
+// Locale and Time Zone Context
+// ============================================================================
+
+/// Locale and time zone used to read times and dates, e.g. per request
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizeContext {
+    pub locale: Locale,
+    pub timezone: Tz,
+    /// Date treated as "today" for relative dates; `None` uses the current
+    /// date in `timezone`
+    pub today: Option<NaiveDate>,
+}
+
+impl Default for NormalizeContext {
+    fn default() -> Self {
+        NormalizeContext {
+            locale: Locale::default(),
+            timezone: Tz::UTC,
+            today: None,
+        }
+    }
+}
+
+impl NormalizeContext {
+    /// Build a context from optional locale and IANA time zone names
+    pub fn new(locale: Option<&str>, timezone: Option<&str>) -> Result<Self, SupertonicError> {
+        let mut ctx = NormalizeContext::default();
+        if let Some(locale) = locale {
+            ctx.locale = Locale::new(locale);
+        }
+        if let Some(tz) = timezone {
+            ctx.timezone = tz
+                .parse()
+                .map_err(|_| SupertonicError::Validation(format!("Unknown time zone: {}", tz)))?;
+        }
+        Ok(ctx)
+    }
+
+    pub fn uses_12_hour_clock(&self) -> bool {
+        self.locale.twelve_hour_clock
+    }
+
+    pub fn month_first(&self) -> bool {
+        self.locale.month_first
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.today
+            .unwrap_or_else(|| Utc::now().with_timezone(&self.timezone).date_naive())
+    }
+}
+
+// ============================================================================
+// Dates and Times
+// ============================================================================
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Ordinal form of a number in words ("twenty-one" -> "twenty-first")
+pub fn ordinal_words(n: u64) -> String {
+    let words = number_to_words(n);
+    let split = words.rfind([' ', '-']).map_or(0, |i| i + 1);
+    let (head, last) = words.split_at(split);
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        w if w.ends_with('y') => format!("{}ieth", &w[..w.len() - 1]),
+        w => format!("{}th", w),
+    };
+    format!("{}{}", head, last)
+}
+
+/// Read a year the way it is spoken ("nineteen ninety-nine", "two thousand five")
+fn year_words(year: u64) -> String {
+    let (century, rest) = (year / 100, year % 100);
+    if !(1100..10000).contains(&year) || (year >= 2000 && rest < 10) {
+        return number_to_words(year);
+    }
+    match rest {
+        0 => format!("{} hundred", number_to_words(century)),
+        1..=9 => format!("{} oh {}", number_to_words(century), number_to_words(rest)),
+        _ => format!("{} {}", number_to_words(century), number_to_words(rest)),
+    }
+}
+
+fn date_words(date: NaiveDate) -> String {
+    format!(
+        "{} {}",
+        MONTHS[date.month0() as usize],
+        ordinal_words(date.day() as u64)
+    )
+}
+
+fn minute_words(minute: u32) -> String {
+    match minute {
+        0 => String::new(),
+        1..=9 => format!(" oh {}", number_to_words(minute as u64)),
+        _ => format!(" {}", number_to_words(minute as u64)),
+    }
+}
+
+/// Words for a clock time; a meridiem is only read when the text has one, as
+/// bare "H:MM" is just as often a verse reference ("John 3:16") or a ratio
+fn time_words(hour: u32, minute: u32, meridiem: Option<char>, ctx: &NormalizeContext) -> String {
+    match meridiem {
+        Some(m) => format!(
+            "{}{} {} m",
+            number_to_words(hour as u64),
+            minute_words(minute),
+            m.to_ascii_lowercase()
+        ),
+        None if minute == 0 && ctx.uses_12_hour_clock() && (1..=12).contains(&hour) => {
+            format!("{} o'clock", number_to_words(hour as u64))
+        }
+        None if minute == 0 => format!("{} hundred", number_to_words(hour as u64)),
+        None => format!("{}{}", number_to_words(hour as u64), minute_words(minute)),
+    }
+}
+
+/// Expand clock times ("5 PM", "17:30") for the context's locale
+pub fn expand_times(text: &str, ctx: &NormalizeContext) -> String {
+    let clock_re = Regex::new(r"(?i)\b(\d{1,2}):(\d{2})(?:\s*([ap])\.?m\b\.?)?").unwrap();
+    let text = clock_re.replace_all(text, |caps: &Captures| {
+        let hour: u32 = caps[1].parse().unwrap_or(99);
+        let minute: u32 = caps[2].parse().unwrap_or(99);
+        let meridiem = caps.get(3).and_then(|m| m.as_str().chars().next());
+        let max_hour = if meridiem.is_some() { 12 } else { 23 };
+        if hour > max_hour || minute > 59 {
+            return caps[0].to_string();
+        }
+        time_words(hour, minute, meridiem, ctx)
+    });
+
+    let hour_re = Regex::new(r"(?i)\b(\d{1,2})\s*([ap])\.?m\b\.?").unwrap();
+    hour_re
+        .replace_all(&text, |caps: &Captures| {
+            let hour: u32 = caps[1].parse().unwrap_or(99);
+            if hour == 0 || hour > 12 {
+                return caps[0].to_string();
+            }
+            time_words(hour, 0, caps[2].chars().next(), ctx)
+        })
+        .to_string()
+}
+
+/// Expand numeric dates ("3/4/2025") using the context's day/month order
+pub fn expand_dates(text: &str, ctx: &NormalizeContext) -> String {
+    let re = Regex::new(r"\b(\d{1,2})/(\d{1,2})/(\d{4}|\d{2})\b").unwrap();
+    re.replace_all(text, |caps: &Captures| {
+        let a: u32 = caps[1].parse().unwrap_or(0);
+        let b: u32 = caps[2].parse().unwrap_or(0);
+        let (month, day) = if ctx.month_first() { (a, b) } else { (b, a) };
+        let mut year: i32 = caps[3].parse().unwrap_or(0);
+        if caps[3].len() == 2 {
+            year += 2000;
+        }
+        match NaiveDate::from_ymd_opt(year, month, day) {
+            Some(date) => format!("{}, {}", date_words(date), year_words(year as u64)),
+            None => caps[0].to_string(),
+        }
+    })
+    .to_string()
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// Follow "today", "tomorrow" and "yesterday" with the date they refer to in
+/// the context's time zone ("tomorrow, Thursday, October fifteenth")
+pub fn expand_relative_dates(text: &str, ctx: &NormalizeContext) -> String {
+    let re = Regex::new(r"(?i)\b(today|tomorrow|yesterday)\b").unwrap();
+    let today = ctx.today();
+    let text = re.replace_all(text, |caps: &Captures| {
+        let offset = match caps[1].to_lowercase().as_str() {
+            "tomorrow" => 1,
+            "yesterday" => -1,
+            _ => 0,
+        };
+        let date = today + Duration::days(offset);
+        format!(
+            "{}, {}, {},",
+            &caps[1],
+            weekday_name(date.weekday()),
+            date_words(date)
+        )
+    });
+    // Drop the trailing comma when the word ended a clause ("... tomorrow.")
+    Regex::new(r",([.!?;:,])")
+        .unwrap()
+        .replace_all(&text, "$1")
+        .to_string()
+}
+
+// ============================================================================
+// Emoji
+// ============================================================================
+
+/// What `preprocess_text` does with emoji
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmojiPolicy {
+    /// Remove emoji silently
+    #[default]
+    Strip,
+    /// Replace known emoji with a short spoken description; others are removed
+    Describe,
+}
+
+const EMOJI_DESCRIPTIONS: &[(&str, &str)] = &[
+    ("😀", "grinning face"),
+    ("😃", "smiling face"),
+    ("😄", "grinning face with smiling eyes"),
+    ("😁", "beaming face"),
+    ("😂", "tears of joy"),
+    ("🤣", "rolling on the floor laughing"),
+    ("😅", "nervous laugh"),
+    ("😊", "smiling face"),
+    ("😇", "smiling face with halo"),
+    ("🙂", "slight smile"),
+    ("😉", "wink"),
+    ("😍", "heart eyes"),
+    ("😘", "blowing a kiss"),
+    ("😎", "cool face with sunglasses"),
+    ("🤔", "thinking face"),
+    ("😐", "neutral face"),
+    ("🙄", "eye roll"),
+    ("😏", "smirk"),
+    ("😴", "sleeping face"),
+    ("😢", "crying face"),
+    ("😭", "loudly crying face"),
+    ("😡", "angry face"),
+    ("😱", "screaming face"),
+    ("😬", "grimacing face"),
+    ("🥳", "party face"),
+    ("🥺", "pleading face"),
+    ("🤯", "mind blown"),
+    ("🤗", "hug"),
+    ("🤷", "shrug"),
+    ("🤦", "facepalm"),
+    ("🙏", "folded hands"),
+    ("👍", "thumbs up"),
+    ("👎", "thumbs down"),
+    ("👏", "clapping hands"),
+    ("🙌", "raising hands"),
+    ("👋", "waving hand"),
+    ("👌", "OK hand"),
+    ("✌", "victory hand"),
+    ("🤞", "crossed fingers"),
+    ("💪", "flexed biceps"),
+    ("👀", "eyes"),
+    ("❤", "red heart"),
+    ("💔", "broken heart"),
+    ("💕", "two hearts"),
+    ("💯", "hundred points"),
+    ("🔥", "fire"),
+    ("✨", "sparkles"),
+    ("⭐", "star"),
+    ("🎉", "party popper"),
+    ("🎂", "birthday cake"),
+    ("🎁", "gift"),
+    ("✅", "check mark"),
+    ("❌", "cross mark"),
+    ("⚠", "warning"),
+    ("❓", "question mark"),
+    ("❗", "exclamation mark"),
+    ("💡", "light bulb"),
+    ("🚀", "rocket"),
+    ("☀", "sun"),
+    ("🌧", "rain cloud"),
+    ("☕", "coffee"),
+    ("🍕", "pizza"),
+    ("🍺", "beer"),
+    ("🐶", "dog"),
+    ("🐱", "cat"),
+    ("💩", "pile of poo"),
+    ("👻", "ghost"),
+    ("🤖", "robot"),
+];
+
+/// Replace known emoji with their descriptions ("👍" -> "thumbs up")
+pub fn describe_emoji(text: &str) -> String {
+    let mut symbols: Vec<&(&str, &str)> = EMOJI_DESCRIPTIONS.iter().collect();
+    symbols.sort_by_key(|(e, _)| std::cmp::Reverse(e.len()));
+    let alternatives: Vec<String> = symbols.iter().map(|(e, _)| regex::escape(e)).collect();
+    // Variation selectors and skin tone modifiers don't change the description
+    let pattern = format!(
+        r"(?:{})[\x{{FE0F}}\x{{1F3FB}}-\x{{1F3FF}}]*",
+        alternatives.join("|")
+    );
+    let re = Regex::new(&pattern).unwrap();
+
+    re.replace_all(text, |caps: &Captures| {
+        let matched = &caps[0];
+        EMOJI_DESCRIPTIONS
+            .iter()
+            .filter(|(e, _)| matched.starts_with(e))
+            .max_by_key(|(e, _)| e.len())
+            .map_or(String::new(), |(_, desc)| format!(" {} ", desc))
+    })
+    .to_string()
+}
+
+// ============================================================================
+// Text Normalizer
+// ============================================================================
+
+/// Rule-based expansion of written forms into speakable text, applied by
+/// `preprocess_text` before character-level cleanup.
+#[derive(Debug, Clone)]
+pub struct TextNormalizer {
+    pub currencies: CurrencyTable,
+    /// Read URLs and email addresses ("dot", "slash", "at") instead of
+    /// letting the generic symbol rules strip them
+    pub urls: bool,
+    pub acronyms: AcronymRules,
+    /// Read clock times and numeric dates
+    pub dates: bool,
+    /// Append the concrete date to "today", "tomorrow" and "yesterday"
+    pub relative_dates: bool,
+    pub emoji: EmojiPolicy,
+    /// Runtime-loaded rules, run before the built-in ones
+    #[cfg(feature = "scripting")]
+    pub script: Option<std::sync::Arc<crate::scripting::ScriptRules>>,
+}
+
+impl Default for TextNormalizer {
+    fn default() -> Self {
+        TextNormalizer {
+            currencies: CurrencyTable::default(),
+            urls: true,
+            acronyms: AcronymRules::default(),
+            dates: true,
+            relative_dates: false,
+            emoji: EmojiPolicy::default(),
+            #[cfg(feature = "scripting")]
+            script: None,
+        }
+    }
+}
+
+impl TextNormalizer {
+    pub fn normalize(&self, text: &str) -> String {
+        self.normalize_with(text, &NormalizeContext::default())
+    }
+
+    /// Normalize with an explicit locale and time zone
+    pub fn normalize_with(&self, text: &str, ctx: &NormalizeContext) -> String {
+        let mut text = text.to_string();
+        self.normalize_traced(&mut text, ctx, &mut Trace::none());
+        text
+    }
+
+    /// `normalize_with` in place, recording each rule that changes the text
+    pub(crate) fn normalize_traced(
+        &self,
+        text: &mut String,
+        ctx: &NormalizeContext,
+        trace: &mut Trace,
+    ) {
+        #[cfg(feature = "scripting")]
+        if let Some(script) = &self.script {
+            trace.stage(PreprocessStage::RulesScript, text, |t| {
+                match script.apply(t) {
+                    Ok(rewritten) => rewritten,
+                    Err(e) => {
+                        tracing::warn!("Skipping rules script: {}", e);
+                        t.to_string()
+                    }
+                }
+            });
+        }
+        if self.emoji == EmojiPolicy::Describe {
+            trace.stage(PreprocessStage::EmojiDescribed, text, describe_emoji);
+        }
+        if self.urls {
+            trace.stage(PreprocessStage::UrlsAndEmails, text, expand_urls_and_emails);
+        }
+        if self.dates {
+            trace.stage(PreprocessStage::Dates, text, |t| expand_dates(t, ctx));
+            trace.stage(PreprocessStage::Times, text, |t| expand_times(t, ctx));
+        }
+        if self.relative_dates {
+            trace.stage(PreprocessStage::RelativeDates, text, |t| {
+                expand_relative_dates(t, ctx)
+            });
+        }
+        if self.acronyms.enabled {
+            trace.stage(PreprocessStage::Acronyms, text, |t| {
+                expand_acronyms(t, &self.acronyms)
+            });
+        }
+        trace.stage(PreprocessStage::LocaleNumbers, text, |t| {
+            expand_locale_numbers(t, &ctx.locale)
+        });
+        if ctx.locale.currencies.is_empty() || self.currencies.is_empty() {
+            trace.stage(PreprocessStage::Currency, text, |t| {
+                expand_currency(t, &self.currencies)
+            });
+            return;
+        }
+        let mut currencies = self.currencies.clone();
+        for currency in &ctx.locale.currencies {
+            currencies.insert(currency.clone());
+        }
+        trace.stage(PreprocessStage::Currency, text, |t| {
+            expand_currency(t, &currencies)
+        });
+    }
+}
@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::SupertonicError;
+use crate::normalize::NormalizeContext;
+use crate::text::ChunkBoundary;
+
+// ============================================================================
+// Synthesis Options
+// ============================================================================
+
+pub const DEFAULT_TOTAL_STEP: usize = 5;
+pub const DEFAULT_SPEED: f32 = 1.05;
+pub const DEFAULT_SILENCE_DURATION: f32 = 0.3;
+/// Denoising steps of the first sentence under `low_latency`
+pub const LOW_LATENCY_STEPS: usize = 2;
+
+/// Per-request synthesis parameters.
+///
+/// Unset fields fall back, in order, to the selected voice's defaults (the
+/// `defaults` object of its style JSON), the engine's defaults
+/// (`TextToSpeech::set_default_options`) and finally the built-in constants.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SynthesisOptions {
+    /// Number of denoising steps
+    pub total_step: Option<usize>,
+    /// Speech speed factor (higher = faster)
+    pub speed: Option<f32>,
+    /// How `speed` is applied: by the duration model (default) or by
+    /// time-stretching the rendered audio
+    pub speed_mode: Option<SpeedMode>,
+    /// Pause inserted between chunks at sentence ends, in seconds
+    pub silence_duration: Option<f32>,
+    /// Pause after a paragraph, in seconds (defaults to `silence_duration`)
+    pub paragraph_silence: Option<f32>,
+    /// Pause where a long sentence is split, in seconds (defaults to `silence_duration`)
+    pub clause_silence: Option<f32>,
+    /// Output gain in decibels
+    pub gain_db: Option<f32>,
+    /// BCP 47 locale used to read numbers, currencies, times and dates ("en-US", "de-DE")
+    pub locale: Option<String>,
+    /// IANA time zone that relative dates are resolved in ("Europe/Berlin")
+    pub timezone: Option<String>,
+    /// Resample the output to this rate in Hz (e.g. 8000 for telephony, 48000
+    /// for Opus); unset keeps the model's native rate. Taken from the request
+    /// or the engine defaults only, never from voice defaults, so every voice
+    /// of a narration renders at the same rate.
+    pub target_sample_rate: Option<i32>,
+    /// Shift the pitch of the output by this many semitones (negative is deeper)
+    #[cfg(feature = "dsp")]
+    pub pitch_semitones: Option<f32>,
+    /// Remove DC offset and high-pass the output at this frequency in Hz (40-60
+    /// removes rumble without touching the voice)
+    pub high_pass_hz: Option<f32>,
+    /// Soft-limit peaks to this level in dBFS (e.g. -1.0) after gain and resampling
+    pub limiter_db: Option<f32>,
+    /// Fade the start of the output in over this many milliseconds
+    pub fade_in_ms: Option<f32>,
+    /// Fade the end of the output out over this many milliseconds
+    pub fade_out_ms: Option<f32>,
+    /// Also apply the fades to each chunk, where it meets the pause around it
+    pub fade_chunks: Option<bool>,
+    /// Stereo position from -1.0 (left) to 1.0 (right); only used for stereo
+    /// output, e.g. to place narration roles around a centered narrator
+    pub pan: Option<f32>,
+    /// Embed an inaudible watermark with this key, marking the output as
+    /// synthetic for `Watermark::detect`
+    pub watermark_key: Option<u64>,
+    /// Shorten silences inside the output to at most this many seconds,
+    /// including the pauses between chunks (see `compress_silences`)
+    pub max_silence: Option<f32>,
+    /// Split the first sentence into extra-small chunks rendered with
+    /// `LOW_LATENCY_STEPS` denoising steps, so the first audio of a streamed
+    /// render arrives sooner; later chunks use the full step count
+    pub low_latency: Option<bool>,
+}
+
+impl SynthesisOptions {
+    /// Fill every unset field from `fallback`
+    pub fn or(&self, fallback: &SynthesisOptions) -> SynthesisOptions {
+        SynthesisOptions {
+            total_step: self.total_step.or(fallback.total_step),
+            speed: self.speed.or(fallback.speed),
+            speed_mode: self.speed_mode.or(fallback.speed_mode),
+            silence_duration: self.silence_duration.or(fallback.silence_duration),
+            paragraph_silence: self.paragraph_silence.or(fallback.paragraph_silence),
+            clause_silence: self.clause_silence.or(fallback.clause_silence),
+            gain_db: self.gain_db.or(fallback.gain_db),
+            locale: self.locale.clone().or_else(|| fallback.locale.clone()),
+            timezone: self.timezone.clone().or_else(|| fallback.timezone.clone()),
+            target_sample_rate: self.target_sample_rate.or(fallback.target_sample_rate),
+            #[cfg(feature = "dsp")]
+            pitch_semitones: self.pitch_semitones.or(fallback.pitch_semitones),
+            high_pass_hz: self.high_pass_hz.or(fallback.high_pass_hz),
+            limiter_db: self.limiter_db.or(fallback.limiter_db),
+            fade_in_ms: self.fade_in_ms.or(fallback.fade_in_ms),
+            fade_out_ms: self.fade_out_ms.or(fallback.fade_out_ms),
+            fade_chunks: self.fade_chunks.or(fallback.fade_chunks),
+            pan: self.pan.or(fallback.pan),
+            watermark_key: self.watermark_key.or(fallback.watermark_key),
+            max_silence: self.max_silence.or(fallback.max_silence),
+            low_latency: self.low_latency.or(fallback.low_latency),
+        }
+    }
+
+    pub fn total_step(&self) -> usize {
+        self.total_step.unwrap_or(DEFAULT_TOTAL_STEP)
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed.unwrap_or(DEFAULT_SPEED)
+    }
+
+    pub fn speed_mode(&self) -> SpeedMode {
+        self.speed_mode.unwrap_or_default()
+    }
+
+    /// Speed passed to the duration model: `speed`, or 1.0 when the audio is
+    /// time-stretched instead
+    pub fn model_speed(&self) -> f32 {
+        match self.speed_mode() {
+            SpeedMode::Model => self.speed(),
+            SpeedMode::PostProcess => 1.0,
+        }
+    }
+
+    /// Factor the rendered audio is time-stretched by, if any
+    pub fn stretch_factor(&self) -> Option<f32> {
+        match self.speed_mode() {
+            SpeedMode::PostProcess if self.speed() != 1.0 => Some(self.speed()),
+            _ => None,
+        }
+    }
+
+    pub fn silence_duration(&self) -> f32 {
+        self.silence_duration.unwrap_or(DEFAULT_SILENCE_DURATION)
+    }
+
+    pub fn paragraph_silence(&self) -> f32 {
+        self.paragraph_silence
+            .unwrap_or_else(|| self.silence_duration())
+    }
+
+    pub fn clause_silence(&self) -> f32 {
+        self.clause_silence
+            .unwrap_or_else(|| self.silence_duration())
+    }
+
+    /// Pause lengths per chunk boundary, capped at `max_silence`
+    pub fn pauses(&self) -> ChunkPauses {
+        let max = self.max_silence.unwrap_or(f32::INFINITY);
+        ChunkPauses {
+            sentence: self.silence_duration().min(max),
+            clause: self.clause_silence().min(max),
+            paragraph: self.paragraph_silence().min(max),
+        }
+    }
+
+    pub fn low_latency(&self) -> bool {
+        self.low_latency.unwrap_or(false)
+    }
+
+    /// Normalization context for `locale` and `timezone`
+    pub fn normalize_context(&self) -> Result<NormalizeContext, SupertonicError> {
+        NormalizeContext::new(self.locale.as_deref(), self.timezone.as_deref())
+    }
+
+    /// Rate of the rendered audio: `target_sample_rate`, or `native` when unset
+    pub fn output_sample_rate(&self, native: i32) -> Result<i32, SupertonicError> {
+        match self.target_sample_rate {
+            Some(rate) if rate <= 0 => Err(SupertonicError::Validation(format!(
+                "target_sample_rate must be positive, got {}",
+                rate
+            ))),
+            Some(rate) => Ok(rate),
+            None => Ok(native),
+        }
+    }
+
+    pub fn fade_in_ms(&self) -> f32 {
+        self.fade_in_ms.unwrap_or(0.0)
+    }
+
+    pub fn fade_out_ms(&self) -> f32 {
+        self.fade_out_ms.unwrap_or(0.0)
+    }
+
+    pub fn fade_chunks(&self) -> bool {
+        self.fade_chunks.unwrap_or(false)
+    }
+
+    /// `pan` clamped to -1.0..=1.0 (centered when unset)
+    pub fn pan(&self) -> f32 {
+        self.pan.unwrap_or(0.0).clamp(-1.0, 1.0)
+    }
+
+    /// Linear amplitude factor for `gain_db` (1.0 when unset)
+    pub fn gain(&self) -> f32 {
+        self.gain_db.map_or(1.0, |db| 10f32.powf(db / 20.0))
+    }
+}
+
+/// Where `SynthesisOptions::speed` takes effect
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeedMode {
+    /// Scale the predicted durations, so the model paces the speech itself
+    #[default]
+    Model,
+    /// Render at the model's natural pace and time-stretch the audio
+    /// afterwards, keeping the pitch (see `time_stretch`)
+    PostProcess,
+}
+
+impl std::str::FromStr for SpeedMode {
+    type Err = SupertonicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "model" => Ok(SpeedMode::Model),
+            "post-process" | "post_process" | "stretch" => Ok(SpeedMode::PostProcess),
+            other => Err(SupertonicError::Validation(format!(
+                "unknown speed mode '{}' (expected model or post-process)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Seconds of silence inserted after a chunk, by the kind of break that follows it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChunkPauses {
+    pub sentence: f32,
+    /// Also used between words when a clause has to be split
+    pub clause: f32,
+    pub paragraph: f32,
+}
+
+impl ChunkPauses {
+    /// The same pause after every chunk
+    pub fn uniform(secs: f32) -> Self {
+        ChunkPauses {
+            sentence: secs,
+            clause: secs,
+            paragraph: secs,
+        }
+    }
+
+    pub fn after(&self, boundary: ChunkBoundary) -> f32 {
+        match boundary {
+            ChunkBoundary::Paragraph => self.paragraph,
+            ChunkBoundary::Sentence => self.sentence,
+            ChunkBoundary::Clause | ChunkBoundary::Word => self.clause,
+        }
+    }
+}
+
+/// Denoising steps per chunk: `total_step`, except for the first `fast_chunks`
+/// chunks of a `low_latency` render
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ChunkSteps {
+    pub(crate) total_step: usize,
+    pub(crate) fast_chunks: usize,
+}
+
+impl ChunkSteps {
+    pub(crate) fn uniform(total_step: usize) -> Self {
+        ChunkSteps {
+            total_step,
+            fast_chunks: 0,
+        }
+    }
+
+    pub(crate) fn for_chunk(&self, index: usize) -> usize {
+        if index < self.fast_chunks {
+            LOW_LATENCY_STEPS.min(self.total_step)
+        } else {
+            self.total_step
+        }
+    }
+}
+
+/// Scale samples in place by a linear gain factor
+pub fn apply_gain(samples: &mut [f32], gain: f32) {
+    if gain != 1.0 {
+        for s in samples.iter_mut() {
+            *s *= gain;
+        }
+    }
+}
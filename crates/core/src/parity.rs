@@ -0,0 +1,263 @@
+use ndarray::Array3;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::error::SupertonicError;
+
+// ============================================================================
+// Reference-Implementation Parity Harness
+// ============================================================================
+//
+// The reference Python pipeline can dump its intermediate tensors as JSON
+// (`{"dims": [...], "data": [...]}` with row-major flattened data). Loading
+// those next to the outputs captured from `_infer` lets porting bugs in
+// masking or indexing be pinned to the stage that introduced them.
+
+/// Intermediate tensors captured from the last `_infer` call
+#[derive(Debug, Clone, Default)]
+pub struct StageOutputs {
+    /// Token ids fed to the duration predictor and text encoder
+    pub text_ids: Vec<Vec<i64>>,
+    /// Predicted durations in seconds, after the speed factor
+    pub durations: Vec<f32>,
+    /// Text encoder output
+    pub text_emb: Array3<f32>,
+    /// Masked initial noise the denoising loop started from
+    pub noise: Array3<f32>,
+    /// Final denoised latent fed to the vocoder
+    pub latent: Array3<f32>,
+    /// Raw vocoder output, before trimming to the predicted durations
+    pub wav: Vec<f32>,
+}
+
+/// A tensor exported by the reference implementation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefTensor {
+    pub dims: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+impl RefTensor {
+    /// Load a tensor from a JSON file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SupertonicError> {
+        let bytes = fs::read(path)?;
+        let tensor: RefTensor = serde_json::from_slice(&bytes)?;
+        let expected: usize = tensor.dims.iter().product();
+        if expected != tensor.data.len() {
+            return Err(SupertonicError::ShapeMismatch {
+                expected: tensor.dims.clone(),
+                got: vec![tensor.data.len()],
+            });
+        }
+        Ok(tensor)
+    }
+
+    /// Interpret this tensor as a 3-D array (e.g. a reference noise tensor)
+    pub fn to_array3(&self) -> Result<Array3<f32>, SupertonicError> {
+        if self.dims.len() != 3 {
+            return Err(SupertonicError::ShapeMismatch {
+                expected: vec![0, 0, 0],
+                got: self.dims.clone(),
+            });
+        }
+        Array3::from_shape_vec(
+            (self.dims[0], self.dims[1], self.dims[2]),
+            self.data.clone(),
+        )
+        .map_err(|_e| SupertonicError::ShapeMismatch {
+            expected: self.dims.clone(),
+            got: vec![self.data.len()],
+        })
+    }
+}
+
+/// Reference tensors for each stage; missing files are simply not compared
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceTensors {
+    pub text_ids: Option<RefTensor>,
+    pub durations: Option<RefTensor>,
+    pub text_emb: Option<RefTensor>,
+    pub noise: Option<RefTensor>,
+    pub latent: Option<RefTensor>,
+    pub wav: Option<RefTensor>,
+}
+
+impl ReferenceTensors {
+    /// Load `<stage>.json` files from `dir`
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Self, SupertonicError> {
+        let dir = dir.as_ref();
+        let load = |name: &str| -> Result<Option<RefTensor>, SupertonicError> {
+            let path = dir.join(format!("{}.json", name));
+            if path.exists() {
+                RefTensor::load(path).map(Some)
+            } else {
+                Ok(None)
+            }
+        };
+        Ok(ReferenceTensors {
+            text_ids: load("text_ids")?,
+            durations: load("durations")?,
+            text_emb: load("text_emb")?,
+            noise: load("noise")?,
+            latent: load("latent")?,
+            wav: load("wav")?,
+        })
+    }
+}
+
+/// Absolute tolerances per stage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerances {
+    pub durations: f32,
+    pub text_emb: f32,
+    pub latent: f32,
+    pub wav: f32,
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        Tolerances {
+            durations: 1e-4,
+            text_emb: 1e-4,
+            latent: 1e-3,
+            wav: 1e-3,
+        }
+    }
+}
+
+/// Result of comparing one stage against the reference
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageComparison {
+    pub stage: &'static str,
+    pub expected_dims: Vec<usize>,
+    pub got_dims: Vec<usize>,
+    pub max_abs_diff: f32,
+    pub mean_abs_diff: f32,
+    pub tolerance: f32,
+}
+
+impl StageComparison {
+    pub fn passed(&self) -> bool {
+        self.expected_dims == self.got_dims && self.max_abs_diff <= self.tolerance
+    }
+}
+
+/// Per-stage comparison results, in pipeline order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParityReport {
+    pub stages: Vec<StageComparison>,
+}
+
+impl ParityReport {
+    pub fn passed(&self) -> bool {
+        self.stages.iter().all(|s| s.passed())
+    }
+
+    /// First stage that diverged from the reference, if any
+    pub fn first_failure(&self) -> Option<&StageComparison> {
+        self.stages.iter().find(|s| !s.passed())
+    }
+}
+
+fn compare_stage(
+    stage: &'static str,
+    reference: &RefTensor,
+    got_dims: Vec<usize>,
+    got: &[f32],
+    tolerance: f32,
+) -> StageComparison {
+    let (max_abs_diff, mean_abs_diff) = if reference.dims == got_dims && !got.is_empty() {
+        let mut max = 0.0f32;
+        let mut sum = 0.0f64;
+        for (a, b) in reference.data.iter().zip(got) {
+            let diff = (a - b).abs();
+            max = max.max(diff);
+            sum += diff as f64;
+        }
+        (max, (sum / got.len() as f64) as f32)
+    } else {
+        (f32::INFINITY, f32::INFINITY)
+    };
+
+    StageComparison {
+        stage,
+        expected_dims: reference.dims.clone(),
+        got_dims,
+        max_abs_diff,
+        mean_abs_diff,
+        tolerance,
+    }
+}
+
+/// Compare captured stage outputs against reference tensors
+pub fn compare(
+    outputs: &StageOutputs,
+    reference: &ReferenceTensors,
+    tolerances: &Tolerances,
+) -> ParityReport {
+    let mut stages = Vec::new();
+
+    if let Some(r) = &reference.text_ids {
+        let dims = vec![
+            outputs.text_ids.len(),
+            outputs.text_ids.first().map_or(0, |row| row.len()),
+        ];
+        let flat: Vec<f32> = outputs
+            .text_ids
+            .iter()
+            .flatten()
+            .map(|&v| v as f32)
+            .collect();
+        // Token ids must match exactly
+        stages.push(compare_stage("text_ids", r, dims, &flat, 0.0));
+    }
+    if let Some(r) = &reference.durations {
+        let dims = vec![outputs.durations.len()];
+        stages.push(compare_stage(
+            "durations",
+            r,
+            dims,
+            &outputs.durations,
+            tolerances.durations,
+        ));
+    }
+    for (stage, r, array, tolerance) in [
+        (
+            "text_emb",
+            &reference.text_emb,
+            &outputs.text_emb,
+            tolerances.text_emb,
+        ),
+        ("noise", &reference.noise, &outputs.noise, 0.0),
+        (
+            "latent",
+            &reference.latent,
+            &outputs.latent,
+            tolerances.latent,
+        ),
+    ] {
+        if let Some(r) = r {
+            let flat: Vec<f32> = array.iter().copied().collect();
+            stages.push(compare_stage(
+                stage,
+                r,
+                array.shape().to_vec(),
+                &flat,
+                tolerance,
+            ));
+        }
+    }
+    if let Some(r) = &reference.wav {
+        // The reference may export the batch-shaped waveform; compare flat data
+        let dims = if r.dims.len() > 1 {
+            let per_item = outputs.wav.len() / r.dims[0].max(1);
+            vec![r.dims[0], per_item]
+        } else {
+            vec![outputs.wav.len()]
+        };
+        stages.push(compare_stage("wav", r, dims, &outputs.wav, tolerances.wav));
+    }
+
+    ParityReport { stages }
+}
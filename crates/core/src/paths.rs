@@ -0,0 +1,104 @@
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// Standard Directories
+// ============================================================================
+//
+// Where models, voices and caches live when no path is given. A checkout or
+// unpacked release keeps its `assets/` next to the working directory; an
+// installed CLI or app keeps them in the platform's data directory instead:
+//
+// - Linux and other Unix: `$XDG_DATA_HOME/supertonic` (`~/.local/share`) and
+//   `$XDG_CACHE_HOME/supertonic` (`~/.cache`)
+// - macOS: `~/Library/Application Support/supertonic` and
+//   `~/Library/Caches/supertonic`
+// - Windows: `%APPDATA%\supertonic` and `%LOCALAPPDATA%\supertonic`
+//
+// `SUPERTONIC_DATA_DIR` replaces the data directory on every platform, and
+// `XDG_CACHE_HOME` the cache directory.
+
+/// Variable that overrides `data_dir`
+pub const DATA_DIR_ENV: &str = "SUPERTONIC_DATA_DIR";
+
+/// Directory for downloaded models and voices
+pub fn data_dir() -> Option<PathBuf> {
+    data_dir_with(env::var_os(DATA_DIR_ENV))
+}
+
+/// `data_dir` with `override_dir` in place of the `SUPERTONIC_DATA_DIR` value
+pub fn data_dir_with(override_dir: Option<OsString>) -> Option<PathBuf> {
+    if let Some(dir) = override_dir {
+        return Some(PathBuf::from(dir));
+    }
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".local").join("share")))
+    };
+    Some(base?.join("supertonic"))
+}
+
+/// Directory for files that can be rebuilt, such as performance profiles
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            if cfg!(windows) {
+                env::var_os("LOCALAPPDATA").map(PathBuf::from)
+            } else if cfg!(target_os = "macos") {
+                home().map(|home| home.join("Library").join("Caches"))
+            } else {
+                home().map(|home| home.join(".cache"))
+            }
+        })?;
+    Some(base.join("supertonic"))
+}
+
+/// `assets` in the data directory, where `tts download` puts the released
+/// models by default
+pub fn installed_assets_dir() -> Option<PathBuf> {
+    Some(data_dir()?.join("assets"))
+}
+
+/// Directory laid out like `assets/` to load from when none is given:
+/// `assets` in the working directory if it holds the models, else the
+/// installed assets if they do, else `assets` (so errors name the path a
+/// checkout would use)
+pub fn default_assets_dir() -> PathBuf {
+    assets_dir_with(installed_assets_dir())
+}
+
+/// `default_assets_dir` with `installed` as the installed assets
+pub fn assets_dir_with(installed: Option<PathBuf>) -> PathBuf {
+    let local = PathBuf::from("assets");
+    if has_models(&local) {
+        return local;
+    }
+    installed.filter(|dir| has_models(dir)).unwrap_or(local)
+}
+
+/// `onnx` in `default_assets_dir`
+pub fn default_onnx_dir() -> PathBuf {
+    default_assets_dir().join("onnx")
+}
+
+/// `voice_styles` in `default_assets_dir`
+pub fn default_voice_styles_dir() -> PathBuf {
+    default_assets_dir().join("voice_styles")
+}
+
+fn has_models(assets: &Path) -> bool {
+    assets.join("onnx").join("tts.json").is_file()
+}
+
+fn home() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
@@ -0,0 +1,470 @@
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+
+use crate::audio::resample;
+use crate::error::SupertonicError;
+use crate::hooks::PostChunkHook;
+use crate::model::{Style, TextToSpeech};
+use crate::options::SynthesisOptions;
+use crate::sink::AudioSink;
+
+// ============================================================================
+// Playback
+// ============================================================================
+//
+// The cpal stream lives on a thread owned by the `Player` (streams cannot move
+// between threads on every platform), so the player itself can be shared and
+// controlled from anywhere. Queued audio is resampled to the device rate on
+// the way in and played on every output channel.
+
+/// Audio `Player::read_aloud` keeps queued ahead of the listener
+pub const DEFAULT_READ_AHEAD_SECS: f32 = 5.0;
+
+#[derive(Default)]
+struct State {
+    queue: VecDeque<f32>,
+    paused: bool,
+    closed: bool,
+    /// Number of `stop` calls, so sinks notice that their audio was dropped
+    stops: u64,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    /// Signalled when the queue runs dry or the player closes
+    drained: Condvar,
+    /// Signalled when the device takes samples from the queue, the queue is
+    /// dropped or the player closes
+    consumed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Plays audio on the default output device.
+///
+/// Samples are queued with `play` (or chunk by chunk through `hook`, to hear
+/// a render while it is synthesized) and start playing immediately unless
+/// the player is paused.
+pub struct Player {
+    shared: Arc<Shared>,
+    sample_rate: i32,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Player {
+    /// Open the system's default output device
+    pub fn open_default() -> Result<Self, SupertonicError> {
+        let shared = Arc::new(Shared::default());
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let stream_shared = Arc::clone(&shared);
+        let thread = std::thread::spawn(move || {
+            let stream = match open_stream(Arc::clone(&stream_shared)) {
+                Ok((stream, rate)) => {
+                    let _ = ready_tx.send(Ok(rate));
+                    stream
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            let mut state = stream_shared.lock();
+            while !state.closed {
+                state = stream_shared
+                    .drained
+                    .wait(state)
+                    .unwrap_or_else(|e| e.into_inner());
+            }
+            drop(state);
+            drop(stream);
+        });
+
+        let sample_rate = ready_rx
+            .recv()
+            .map_err(|_| playback_error("playback thread exited"))??;
+        Ok(Player {
+            shared,
+            sample_rate,
+            thread: Some(thread),
+        })
+    }
+
+    /// Sample rate of the output device
+    pub fn sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
+    /// Queue mono `samples` recorded at `sample_rate` after anything already queued
+    pub fn play(&self, samples: &[f32], sample_rate: i32) {
+        let samples = resample(samples, sample_rate, self.sample_rate);
+        self.shared.lock().queue.extend(samples);
+    }
+
+    /// Hook that queues each chunk, after its pause, as soon as it is
+    /// synthesized, for `TextToSpeech::set_post_chunk_hook`.
+    ///
+    /// Chunks play as the model renders them, before post-processed speed,
+    /// gain and the output stage are applied to the full render.
+    pub fn hook(&self) -> PostChunkHook {
+        let shared = Arc::clone(&self.shared);
+        let device_rate = self.sample_rate;
+        Box::new(move |samples, ctx| {
+            let pause = (ctx.pause_before * device_rate as f32) as usize;
+            let samples = resample(samples, ctx.sample_rate, device_rate);
+            let mut state = shared.lock();
+            state.queue.extend(std::iter::repeat_n(0.0, pause));
+            state.queue.extend(samples);
+            Ok(())
+        })
+    }
+
+    /// Read `text` aloud as it is synthesized: each chunk plays as soon as it
+    /// is rendered while the next one is synthesized behind it, so long
+    /// documents play without gaps on machines faster than real time.
+    ///
+    /// Synthesis stays at most `DEFAULT_READ_AHEAD_SECS` of audio ahead of the
+    /// listener and waits while playback is paused. Returns once everything
+    /// has played; `stop` from another thread ends the reading early with an
+    /// `Interrupted` I/O error.
+    pub fn read_aloud(
+        &self,
+        tts: &mut TextToSpeech,
+        text: &str,
+        style: &Style,
+        options: &SynthesisOptions,
+    ) -> Result<f32, SupertonicError> {
+        let rate = tts.output_sample_rate(options)?;
+        let mut sink = self.sink(rate).read_ahead(DEFAULT_READ_AHEAD_SECS);
+        tts.synthesize_to_sink(text, style, options, &mut sink)
+    }
+
+    /// Hold playback, keeping the queue
+    pub fn pause(&self) {
+        self.shared.lock().paused = true;
+    }
+
+    /// Continue after `pause`
+    pub fn resume(&self) {
+        self.shared.lock().paused = false;
+    }
+
+    /// Drop everything queued, silencing the output at once; the player
+    /// stays open for the next `play`
+    pub fn stop(&self) {
+        let mut state = self.shared.lock();
+        state.queue.clear();
+        state.stops += 1;
+        self.shared.drained.notify_all();
+        self.shared.consumed.notify_all();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.shared.lock().paused
+    }
+
+    /// Seconds of audio still queued
+    pub fn queued_secs(&self) -> f32 {
+        self.shared.lock().queue.len() as f32 / self.sample_rate as f32
+    }
+
+    /// Block until the queue has played out (or `stop` cleared it)
+    pub fn wait(&self) {
+        let mut state = self.shared.lock();
+        while !state.queue.is_empty() && !state.closed {
+            state = self
+                .shared
+                .drained
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// `AudioSink` that plays blocks recorded at `sample_rate` as they arrive
+    pub fn sink(&self, sample_rate: i32) -> PlayerSink<'_> {
+        PlayerSink {
+            player: self,
+            sample_rate,
+            read_ahead: None,
+            stops: self.shared.lock().stops,
+        }
+    }
+}
+
+/// Plays each block written to it; `finalize` waits for playback to finish.
+///
+/// Once the player is stopped, further writes fail with an `Interrupted` I/O
+/// error, ending the render that feeds the sink.
+pub struct PlayerSink<'a> {
+    player: &'a Player,
+    sample_rate: i32,
+    /// Queued seconds above which `write` waits for the device
+    read_ahead: Option<f32>,
+    /// The player's `stop` count when the sink was created
+    stops: u64,
+}
+
+impl PlayerSink<'_> {
+    /// Make `write` wait while more than `secs` of audio is queued, so the
+    /// render feeding the sink runs at most that far ahead of the listener
+    /// (and waits while playback is paused) instead of queueing everything
+    pub fn read_ahead(mut self, secs: f32) -> Self {
+        self.read_ahead = Some(secs.max(0.0));
+        self
+    }
+}
+
+impl AudioSink for PlayerSink<'_> {
+    fn write(&mut self, samples: &[f32]) -> Result<(), SupertonicError> {
+        let player = self.player;
+        let samples = resample(samples, self.sample_rate, player.sample_rate);
+        let limit = self
+            .read_ahead
+            .map(|secs| (secs * player.sample_rate as f32) as usize);
+
+        let mut state = player.shared.lock();
+        while limit.is_some_and(|limit| state.queue.len() > limit)
+            && state.stops == self.stops
+            && !state.closed
+        {
+            state = player
+                .shared
+                .consumed
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        if state.stops != self.stops || state.closed {
+            return Err(SupertonicError::Io(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "audio output: playback stopped",
+            )));
+        }
+        state.queue.extend(samples);
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<(), SupertonicError> {
+        self.player.wait();
+        Ok(())
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.drained.notify_all();
+        self.shared.consumed.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn playback_error(message: impl std::fmt::Display) -> SupertonicError {
+    SupertonicError::Io(std::io::Error::other(format!("audio output: {}", message)))
+}
+
+fn open_stream(shared: Arc<Shared>) -> Result<(cpal::Stream, i32), SupertonicError> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or_else(|| playback_error("no output device"))?;
+    let supported = device.default_output_config().map_err(playback_error)?;
+    let sample_format = supported.sample_format();
+    let config: cpal::StreamConfig = supported.into();
+    let rate = config.sample_rate.0 as i32;
+
+    let stream = match sample_format {
+        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config, shared),
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config, shared),
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config, shared),
+        other => {
+            return Err(playback_error(format!(
+                "unsupported sample format {}",
+                other
+            )))
+        }
+    }?;
+    stream.play().map_err(playback_error)?;
+    Ok((stream, rate))
+}
+
+fn build_stream<T: SizedSample + FromSample<f32>>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    shared: Arc<Shared>,
+) -> Result<cpal::Stream, SupertonicError> {
+    let channels = config.channels.max(1) as usize;
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                let mut state = shared.lock();
+                let had_audio = !state.queue.is_empty();
+                for frame in data.chunks_mut(channels) {
+                    let sample = if state.paused {
+                        0.0
+                    } else {
+                        state.queue.pop_front().unwrap_or(0.0)
+                    };
+                    frame.fill(T::from_sample(sample));
+                }
+                if had_audio && !state.paused {
+                    shared.consumed.notify_all();
+                }
+                if had_audio && state.queue.is_empty() {
+                    shared.drained.notify_all();
+                }
+            },
+            |e| tracing::warn!("Audio output error: {}", e),
+            None,
+        )
+        .map_err(playback_error)
+}
+
+// ============================================================================
+// Queued Playback (rodio)
+// ============================================================================
+
+/// A queue of clips played one after another through rodio, with volume
+/// control and a callback as each clip finishes.
+///
+/// Suited to applications that enqueue sentences as they are synthesized;
+/// `Player` is the lower-level sample queue.
+#[cfg(feature = "rodio")]
+pub struct AudioPlayer {
+    sink: rodio::Sink,
+    /// Dropping this ends the thread that owns the output stream
+    close: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[cfg(feature = "rodio")]
+impl AudioPlayer {
+    /// Open the system's default output device
+    pub fn open_default() -> Result<Self, SupertonicError> {
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let (close_tx, close_rx) = mpsc::channel::<()>();
+
+        let thread = std::thread::spawn(move || {
+            let opened = rodio::OutputStream::try_default()
+                .map_err(playback_error)
+                .and_then(|(stream, handle)| {
+                    let sink = rodio::Sink::try_new(&handle).map_err(playback_error)?;
+                    Ok((stream, sink))
+                });
+            match opened {
+                Ok((_stream, sink)) => {
+                    let _ = ready_tx.send(Ok(sink));
+                    // Keep the stream alive until the player is dropped
+                    let _ = close_rx.recv();
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                }
+            }
+        });
+
+        let sink = ready_rx
+            .recv()
+            .map_err(|_| playback_error("playback thread exited"))??;
+        Ok(AudioPlayer {
+            sink,
+            close: Some(close_tx),
+            thread: Some(thread),
+        })
+    }
+
+    /// Queue mono `samples` recorded at `sample_rate` after the clips already queued
+    pub fn enqueue(&self, samples: Vec<f32>, sample_rate: i32) {
+        self.sink.append(rodio::buffer::SamplesBuffer::new(
+            1,
+            sample_rate as u32,
+            samples,
+        ));
+    }
+
+    /// `enqueue`, calling `on_finished` once the clip has played out.
+    ///
+    /// The callback runs on the audio thread, so it should return quickly
+    /// (e.g. by sending on a channel); it is skipped if `stop` clears the clip.
+    pub fn enqueue_with_callback<F>(&self, samples: Vec<f32>, sample_rate: i32, on_finished: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.enqueue(samples, sample_rate);
+        let on_finished = Mutex::new(Some(on_finished));
+        self.sink
+            .append(rodio::source::EmptyCallback::<f32>::new(Box::new(
+                move || {
+                    let callback = on_finished.lock().ok().and_then(|mut f| f.take());
+                    if let Some(callback) = callback {
+                        callback();
+                    }
+                },
+            )));
+    }
+
+    /// Playback volume, 1.0 being the clips' own level
+    pub fn volume(&self) -> f32 {
+        self.sink.volume()
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        self.sink.set_volume(volume.max(0.0));
+    }
+
+    /// Hold playback, keeping the queue
+    pub fn pause(&self) {
+        self.sink.pause();
+    }
+
+    /// Continue after `pause`
+    pub fn resume(&self) {
+        self.sink.play();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.sink.is_paused()
+    }
+
+    /// Drop every queued clip, silencing the output at once; the player stays
+    /// open (and keeps its paused state) for the next `enqueue`
+    pub fn stop(&self) {
+        let paused = self.sink.is_paused();
+        self.sink.clear();
+        if !paused {
+            self.sink.play();
+        }
+    }
+
+    /// Whether every queued clip has played
+    pub fn is_idle(&self) -> bool {
+        self.sink.empty()
+    }
+
+    /// Block until every queued clip has played
+    pub fn wait(&self) {
+        self.sink.sleep_until_end();
+    }
+}
+
+#[cfg(feature = "rodio")]
+impl Drop for AudioPlayer {
+    fn drop(&mut self) {
+        self.sink.stop();
+        drop(self.close.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+use crate::audio::fade;
+use crate::error::SupertonicError;
+use crate::model::{Style, TextToSpeech};
+use crate::options::{apply_gain, ChunkSteps, SynthesisOptions};
+use crate::stats::AudioStats;
+use crate::text::ChunkBoundary;
+use crate::warning::clipping_warning;
+
+// ============================================================================
+// Previews
+// ============================================================================
+//
+// Editors want to audition a paragraph while the script is still being
+// written, where sub-second feedback matters more than quality. A preview
+// renders only the start of the document (or one chunk of it) with a low
+// denoising step count.
+
+/// Denoising steps a preview uses unless the options ask for more
+pub const PREVIEW_TOTAL_STEP: usize = 2;
+/// Seconds of audio `PreviewRange::Start` renders by default
+pub const DEFAULT_PREVIEW_SECS: f32 = 8.0;
+
+/// Fade applied where a preview is cut off, so it doesn't end in a click
+const CUT_FADE_SECS: f32 = 0.02;
+
+/// Which part of the text a preview renders
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewRange {
+    /// The first `max_secs` seconds of audio
+    Start { max_secs: f32 },
+    /// One chunk, by its index in `preview_chunks`
+    Chunk(usize),
+}
+
+impl Default for PreviewRange {
+    fn default() -> Self {
+        PreviewRange::Start {
+            max_secs: DEFAULT_PREVIEW_SECS,
+        }
+    }
+}
+
+/// Audio rendered by `TextToSpeech::preview`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Preview {
+    pub samples: Vec<f32>,
+    pub duration: f32,
+    /// Chunks (see `preview_chunks`) covered, `first..end`
+    pub chunks: std::ops::Range<usize>,
+    /// Total number of chunks in the text
+    pub chunk_count: usize,
+    /// Whether the audio was cut off at `max_secs`
+    pub truncated: bool,
+}
+
+impl TextToSpeech {
+    /// The chunks `text` is synthesized in with `options`, as indexed by `PreviewRange::Chunk`
+    pub fn preview_chunks(
+        &mut self,
+        text: &str,
+        options: &SynthesisOptions,
+    ) -> Result<Vec<String>, SupertonicError> {
+        Ok(self
+            .preview_chunks_with_boundaries(text, options)?
+            .into_iter()
+            .map(|(chunk, _)| chunk)
+            .collect())
+    }
+
+    fn preview_chunks_with_boundaries(
+        &mut self,
+        text: &str,
+        options: &SynthesisOptions,
+    ) -> Result<Vec<(String, ChunkBoundary)>, SupertonicError> {
+        let context = self
+            .text_processor
+            .set_context(options.normalize_context()?);
+        let chunks = self.chunks_with_boundaries(text);
+        self.text_processor.set_context(context);
+        Ok(chunks)
+    }
+
+    /// Quickly render part of `text` for auditioning.
+    ///
+    /// Uses `PREVIEW_TOTAL_STEP` denoising steps unless `options.total_step`
+    /// is set; voice defaults for everything else apply as in `synthesize`.
+    pub fn preview(
+        &mut self,
+        text: &str,
+        style: &Style,
+        options: &SynthesisOptions,
+        range: PreviewRange,
+    ) -> Result<Preview, SupertonicError> {
+        let rate = self.output_sample_rate(options)?;
+        let options = SynthesisOptions {
+            total_step: Some(options.total_step.unwrap_or(PREVIEW_TOTAL_STEP)),
+            ..options.clone()
+        };
+        let options = self.resolve_options(style, &options);
+        let chunks = self.preview_chunks_with_boundaries(text, &options)?;
+        let chunk_count = chunks.len();
+
+        let (selected, first, max_secs) = match range {
+            PreviewRange::Start { max_secs } => (chunks, 0, Some(max_secs.max(0.0))),
+            PreviewRange::Chunk(index) => {
+                let chunk = chunks.into_iter().nth(index).ok_or_else(|| {
+                    SupertonicError::Validation(format!(
+                        "Preview chunk {} is out of range; the text has {} chunks",
+                        index, chunk_count
+                    ))
+                })?;
+                (vec![chunk], index, None)
+            }
+        };
+
+        let context = self
+            .text_processor
+            .set_context(options.normalize_context()?);
+        let result = self.enforce_strict(text).and_then(|()| {
+            // Stop rendering once enough audio exists for `max_secs` after stretching
+            let stretch = options.stretch_factor().unwrap_or(1.0);
+            self.call_chunks(
+                &selected,
+                style,
+                ChunkSteps::uniform(options.total_step()),
+                options.model_speed(),
+                options.pauses(),
+                max_secs.map(|secs| secs * stretch),
+            )
+        });
+        self.text_processor.set_context(context);
+        let (samples, duration) = result?;
+        let (samples, duration) = self.stretch_output(samples, duration, &options, true);
+        let (mut samples, mut duration) = self.compress_output(samples, duration, &options, true);
+        self.fade_chunk_edges(&mut samples, &options);
+        let rendered = self.last_chunk_log().len();
+
+        let mut truncated = false;
+        if let Some(max_secs) = max_secs {
+            let max_len = (max_secs * self.sample_rate as f32) as usize;
+            if samples.len() > max_len {
+                samples.truncate(max_len);
+                fade(
+                    &mut samples,
+                    0,
+                    (CUT_FADE_SECS * self.sample_rate as f32) as usize,
+                );
+                duration = max_secs;
+                truncated = true;
+            }
+        }
+        apply_gain(&mut samples, options.gain());
+        let samples = self.finish_output(samples, rate, &options);
+        self.resample_chunk_log(rate);
+        if let Some(warning) = clipping_warning(&samples) {
+            self.push_warning(warning);
+        }
+        self.set_audio_stats(AudioStats::measure(&samples, rate));
+
+        Ok(Preview {
+            samples,
+            duration,
+            chunks: first..first + rendered,
+            chunk_count,
+            truncated,
+        })
+    }
+}
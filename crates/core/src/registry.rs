@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::SupertonicError;
+use crate::model::{Style, TextToSpeech};
+use crate::options::SynthesisOptions;
+
+// ============================================================================
+// Engine Registry
+// ============================================================================
+//
+// An app reading English and Korean, or a server offering a fast and a high
+// quality model, keeps one engine per model set loaded at the same time.
+// `EngineRegistry` holds them by model id and routes each request to the
+// engine it names, or to the default engine when it names none. Requests for
+// different models run at the same time; those for the same model take turns
+// on its engine.
+
+/// Id the plugin and examples load their main model set as
+pub const DEFAULT_MODEL_ID: &str = "default";
+
+/// An engine of a registry, locked by whoever synthesizes with it
+pub type SharedEngine = Arc<Mutex<TextToSpeech>>;
+
+/// Loaded engines by model id
+#[derive(Default)]
+pub struct EngineRegistry {
+    inner: Mutex<Engines>,
+}
+
+#[derive(Default)]
+struct Engines {
+    engines: BTreeMap<String, SharedEngine>,
+    default: Option<String>,
+}
+
+impl EngineRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `engine` as `id`, replacing any engine loaded as `id`; the first
+    /// engine added becomes the default. Requests already holding the
+    /// replaced engine finish on it.
+    pub fn insert(&self, id: &str, engine: TextToSpeech) -> SharedEngine {
+        let engine = Arc::new(Mutex::new(engine));
+        let mut inner = self.lock();
+        inner.engines.insert(id.to_string(), Arc::clone(&engine));
+        inner.default.get_or_insert_with(|| id.to_string());
+        engine
+    }
+
+    /// Drop the engine loaded as `id`, returning it; the default passes to
+    /// the first remaining id when it was the default
+    pub fn remove(&self, id: &str) -> Option<SharedEngine> {
+        let mut inner = self.lock();
+        let engine = inner.engines.remove(id)?;
+        if inner.default.as_deref() == Some(id) {
+            inner.default = inner.engines.keys().next().cloned();
+        }
+        Some(engine)
+    }
+
+    pub fn get(&self, id: &str) -> Option<SharedEngine> {
+        self.lock().engines.get(id).cloned()
+    }
+
+    /// The engine requests without a model id go to
+    pub fn default_engine(&self) -> Option<SharedEngine> {
+        let inner = self.lock();
+        inner
+            .default
+            .as_ref()
+            .and_then(|id| inner.engines.get(id))
+            .cloned()
+    }
+
+    /// The engine of `id`, or the default one for `None`, or a `Validation`
+    /// error naming what is missing
+    pub fn resolve(&self, id: Option<&str>) -> Result<SharedEngine, SupertonicError> {
+        match id {
+            Some(id) => self.get(id).ok_or_else(|| {
+                SupertonicError::Validation(format!("no model is loaded as '{}'", id))
+            }),
+            None => self
+                .default_engine()
+                .ok_or_else(|| SupertonicError::Validation("no model is loaded".to_string())),
+        }
+    }
+
+    pub fn default_id(&self) -> Option<String> {
+        self.lock().default.clone()
+    }
+
+    /// Send requests without a model id to `id`
+    pub fn set_default(&self, id: &str) -> Result<(), SupertonicError> {
+        let mut inner = self.lock();
+        if !inner.engines.contains_key(id) {
+            return Err(SupertonicError::Validation(format!(
+                "no model is loaded as '{}'",
+                id
+            )));
+        }
+        inner.default = Some(id.to_string());
+        Ok(())
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.lock().engines.contains_key(id)
+    }
+
+    /// Ids of the loaded engines, in order
+    pub fn ids(&self) -> Vec<String> {
+        self.lock().engines.keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().engines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().engines.is_empty()
+    }
+
+    /// Run `f` on the engine of `id` (or the default one), holding it for
+    /// the duration
+    pub fn with_engine<T>(
+        &self,
+        id: Option<&str>,
+        f: impl FnOnce(&mut TextToSpeech) -> Result<T, SupertonicError>,
+    ) -> Result<T, SupertonicError> {
+        let engine = self.resolve(id)?;
+        let mut engine = engine.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut engine)
+    }
+
+    /// `TextToSpeech::synthesize` on the engine of `id` (or the default one);
+    /// `style` must be a voice made for that model
+    pub fn synthesize(
+        &self,
+        id: Option<&str>,
+        text: &str,
+        style: &Style,
+        options: &SynthesisOptions,
+    ) -> Result<(Vec<f32>, f32), SupertonicError> {
+        self.with_engine(id, |engine| engine.synthesize(text, style, options))
+    }
+
+    /// The engines, even if a thread panicked while holding them; the
+    /// engines themselves are locked separately, so a long render never
+    /// blocks lookups
+    fn lock(&self) -> MutexGuard<'_, Engines> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
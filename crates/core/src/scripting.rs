@@ -0,0 +1,114 @@
+use regex::Regex;
+use rhai::{Engine, Scope, AST};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::error::SupertonicError;
+use crate::normalize::number_to_words;
+
+// ============================================================================
+// Scripted Normalization Rules
+// ============================================================================
+//
+// A rules script defines `fn normalize(text)` returning the rewritten text. It
+// runs before the built-in rules and may use these helpers on top of Rhai's
+// standard library:
+//
+//   regex_replace(text, pattern, replacement)  // `$1`-style group references
+//   regex_match(text, pattern)                 // bool
+//   number_to_words(n)                         // 42 -> "forty-two"
+//
+// fn normalize(text) {
+//     if text.contains("Dr.") && regex_match(text, "Dr\\. [A-Z]") {
+//         text = regex_replace(text, "Dr\\. ([A-Z])", "Doctor $1");
+//     }
+//     text
+// }
+
+fn regex_error(e: regex::Error) -> Box<rhai::EvalAltResult> {
+    e.to_string().into()
+}
+
+fn rules_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.register_fn(
+        "regex_replace",
+        |text: &str,
+         pattern: &str,
+         replacement: &str|
+         -> Result<String, Box<rhai::EvalAltResult>> {
+            let re = Regex::new(pattern).map_err(regex_error)?;
+            Ok(re.replace_all(text, replacement).to_string())
+        },
+    );
+    engine.register_fn(
+        "regex_match",
+        |text: &str, pattern: &str| -> Result<bool, Box<rhai::EvalAltResult>> {
+            let re = Regex::new(pattern).map_err(regex_error)?;
+            Ok(re.is_match(text))
+        },
+    );
+    engine.register_fn("number_to_words", |n: i64| -> String {
+        if n < 0 {
+            format!("minus {}", number_to_words(n.unsigned_abs()))
+        } else {
+            number_to_words(n as u64)
+        }
+    });
+    engine
+}
+
+/// A compiled `rules.rhai` script
+pub struct ScriptRules {
+    engine: Engine,
+    ast: AST,
+    source: Option<PathBuf>,
+}
+
+impl fmt::Debug for ScriptRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScriptRules")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl ScriptRules {
+    /// Compile a rules script from a file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SupertonicError> {
+        let source = std::fs::read_to_string(path.as_ref())?;
+        let mut rules = Self::from_source(&source)?;
+        rules.source = Some(path.as_ref().to_path_buf());
+        Ok(rules)
+    }
+
+    /// Compile a rules script from source
+    pub fn from_source(source: &str) -> Result<Self, SupertonicError> {
+        let engine = rules_engine();
+        let ast = engine
+            .compile(source)
+            .map_err(|e| SupertonicError::TextProcessing(format!("Rules script: {}", e)))?;
+        if !ast.iter_functions().any(|f| f.name == "normalize") {
+            return Err(SupertonicError::TextProcessing(
+                "Rules script must define `fn normalize(text)`".to_string(),
+            ));
+        }
+        Ok(ScriptRules {
+            engine,
+            ast,
+            source: None,
+        })
+    }
+
+    /// Run the script's `normalize` function on `text`
+    pub fn apply(&self, text: &str) -> Result<String, SupertonicError> {
+        self.engine
+            .call_fn::<String>(
+                &mut Scope::new(),
+                &self.ast,
+                "normalize",
+                (text.to_string(),),
+            )
+            .map_err(|e| SupertonicError::TextProcessing(format!("Rules script: {}", e)))
+    }
+}
@@ -0,0 +1,343 @@
+use ort::execution_providers::{CPUExecutionProvider, ExecutionProviderDispatch};
+use ort::memory::AllocationDevice;
+use ort::session::{
+    builder::{GraphOptimizationLevel, SessionBuilder},
+    Session,
+};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tracing::info;
+
+use crate::compat::check_session;
+use crate::environment::shared_arena_registered;
+use crate::error::SupertonicError;
+
+// ============================================================================
+// Session Configuration
+// ============================================================================
+
+/// Graph optimization level applied by ONNX Runtime when a session is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphOptLevel {
+    Disable,
+    Basic,
+    Extended,
+    All,
+}
+
+impl From<GraphOptLevel> for GraphOptimizationLevel {
+    fn from(level: GraphOptLevel) -> Self {
+        match level {
+            GraphOptLevel::Disable => GraphOptimizationLevel::Disable,
+            GraphOptLevel::Basic => GraphOptimizationLevel::Level1,
+            GraphOptLevel::Extended => GraphOptimizationLevel::Level2,
+            GraphOptLevel::All => GraphOptimizationLevel::Level3,
+        }
+    }
+}
+
+/// Options applied to every ONNX Runtime session the engine creates.
+///
+/// `None` leaves the corresponding ONNX Runtime default in place. When several
+/// engines share a process, set `intra_threads` so their thread pools don't
+/// oversubscribe the available cores, or share one pool between all of them
+/// with `init_environment` (the thread settings here are then ignored).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Use the GPU execution provider (requires the `rocm` feature)
+    pub use_gpu: bool,
+    /// Threads used to parallelize work within a single operator
+    pub intra_threads: Option<usize>,
+    /// Threads used to run independent operators in parallel
+    pub inter_threads: Option<usize>,
+    /// Graph optimization level
+    pub graph_opt_level: Option<GraphOptLevel>,
+    /// Use the CPU arena allocator (faster, but holds on to peak memory)
+    pub memory_arena: bool,
+    /// Allocate from the CPU arena `init_environment` registers for every
+    /// engine, when it registered one, rather than from an arena per session
+    pub shared_arena: bool,
+    /// Enable ORT profiling; each session writes its JSON trace to
+    /// `<prefix>_<model>_<timestamp>.json` (see `TextToSpeech::end_profiling`)
+    pub profile_prefix: Option<PathBuf>,
+    /// Which sessions are created while loading; snapshots always load all four
+    pub load_plan: LoadPlan,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig {
+            use_gpu: false,
+            intra_threads: None,
+            inter_threads: None,
+            graph_opt_level: None,
+            memory_arena: true,
+            shared_arena: true,
+            profile_prefix: None,
+            load_plan: LoadPlan::default(),
+        }
+    }
+}
+
+impl SessionConfig {
+    /// Default configuration with the given device selection
+    pub fn with_gpu(use_gpu: bool) -> Self {
+        SessionConfig {
+            use_gpu,
+            ..Default::default()
+        }
+    }
+}
+
+// ============================================================================
+// Execution Providers
+// ============================================================================
+
+fn execution_providers(
+    config: &SessionConfig,
+) -> Result<Vec<ExecutionProviderDispatch>, SupertonicError> {
+    let mut providers = Vec::new();
+
+    if config.use_gpu {
+        // GPU inference currently goes through the ROCm execution provider, which
+        // requires an ONNX Runtime build with ROCm support (see `ORT_LIB_LOCATION`).
+        #[cfg(feature = "rocm")]
+        providers.push(
+            ort::execution_providers::ROCmExecutionProvider::default()
+                .build()
+                .error_on_failure(),
+        );
+
+        #[cfg(not(feature = "rocm"))]
+        return Err(SupertonicError::Config(
+            "GPU mode requires building with the `rocm` feature".to_string(),
+        ));
+    }
+
+    providers.push(
+        CPUExecutionProvider::default()
+            .with_arena_allocator(config.memory_arena)
+            .build(),
+    );
+
+    Ok(providers)
+}
+
+/// Device that keeps the latent between denoising steps for sessions built
+/// from `config`, or `None` when they run on the CPU
+pub(crate) fn latent_device(config: &SessionConfig) -> Option<AllocationDevice> {
+    if config.use_gpu && cfg!(feature = "rocm") {
+        Some(AllocationDevice::HIP)
+    } else {
+        None
+    }
+}
+
+/// Create a session builder for `model_name` with the execution providers and options from `config`.
+pub(crate) fn session_builder(
+    config: &SessionConfig,
+    model_name: &str,
+) -> Result<SessionBuilder, SupertonicError> {
+    let mut builder = Session::builder()?.with_execution_providers(execution_providers(config)?)?;
+
+    if let Some(threads) = config.intra_threads {
+        builder = builder.with_intra_threads(threads)?;
+    }
+    if let Some(threads) = config.inter_threads {
+        builder = builder
+            .with_parallel_execution(true)?
+            .with_inter_threads(threads)?;
+    }
+    if config.memory_arena && config.shared_arena && shared_arena_registered() {
+        builder = builder.with_env_allocators()?;
+    }
+    if let Some(level) = config.graph_opt_level {
+        builder = builder.with_optimization_level(level.into())?;
+    }
+    if let Some(prefix) = &config.profile_prefix {
+        let mut file_name = prefix.file_name().unwrap_or_default().to_os_string();
+        file_name.push(format!("_{}", model_name));
+        builder = builder.with_profiling(prefix.with_file_name(file_name))?;
+    }
+
+    Ok(builder)
+}
+
+// ============================================================================
+// Load Plans
+// ============================================================================
+
+/// The engine's models, in the order the loaders take them
+pub(crate) const MODEL_NAMES: [&str; 4] = [
+    "duration_predictor",
+    "text_encoder",
+    "vector_estimator",
+    "vocoder",
+];
+
+/// Which of the engine's sessions a loader creates up front
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadPlan {
+    /// Create all four sessions while loading
+    #[default]
+    Eager,
+    /// Create the duration predictor while loading and the other sessions on
+    /// first use, for applications that rarely synthesize
+    Deferred,
+    /// Create only the duration predictor: `predict_durations` and latency
+    /// estimates work, synthesis returns an error
+    DurationOnly,
+}
+
+impl LoadPlan {
+    /// Whether `model` is created while loading (`Some(true)`), on first use
+    /// (`Some(false)`) or never (`None`)
+    fn schedule(self, model: &str) -> Option<bool> {
+        match (self, model) {
+            (LoadPlan::Eager, _) | (_, "duration_predictor") => Some(true),
+            (LoadPlan::Deferred, _) => Some(false),
+            (LoadPlan::DurationOnly, _) => None,
+        }
+    }
+}
+
+/// Where a session's model comes from
+pub(crate) enum ModelSource<'a> {
+    Memory(Cow<'a, [u8]>),
+    File(PathBuf),
+}
+
+impl ModelSource<'_> {
+    fn commit(&self, config: &SessionConfig, name: &str) -> Result<Session, SupertonicError> {
+        let builder = session_builder(config, name)?;
+        let session = match self {
+            ModelSource::Memory(bytes) => builder.commit_from_memory(bytes)?,
+            ModelSource::File(path) => builder.commit_from_file(path)?,
+        };
+        check_session(name, &session)?;
+        Ok(session)
+    }
+
+    fn into_owned(self) -> ModelSource<'static> {
+        match self {
+            ModelSource::Memory(bytes) => ModelSource::Memory(Cow::Owned(bytes.into_owned())),
+            ModelSource::File(path) => ModelSource::File(path),
+        }
+    }
+}
+
+enum LazyState {
+    Ready(Session),
+    Deferred(ModelSource<'static>, Box<SessionConfig>),
+    Skipped,
+}
+
+/// One of the engine's sessions, created while loading or on first use as
+/// the `LoadPlan` says.
+///
+/// Clones share the session, so a deferred session is created once for all
+/// of them, and take turns running it.
+#[derive(Clone)]
+pub(crate) struct LazySession {
+    name: &'static str,
+    state: Arc<Mutex<LazyState>>,
+}
+
+/// A session held for running; other handles wait until it is dropped
+pub(crate) struct SessionGuard<'a>(MutexGuard<'a, LazyState>);
+
+impl Deref for SessionGuard<'_> {
+    type Target = Session;
+
+    fn deref(&self) -> &Session {
+        match &*self.0 {
+            LazyState::Ready(session) => session,
+            _ => unreachable!("guards are only handed out for created sessions"),
+        }
+    }
+}
+
+impl DerefMut for SessionGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Session {
+        match &mut *self.0 {
+            LazyState::Ready(session) => session,
+            _ => unreachable!("guards are only handed out for created sessions"),
+        }
+    }
+}
+
+impl LazySession {
+    pub(crate) fn ready(name: &'static str, session: Session) -> Self {
+        Self::with_state(name, LazyState::Ready(session))
+    }
+
+    fn with_state(name: &'static str, state: LazyState) -> Self {
+        LazySession {
+            name,
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    pub(crate) fn load(
+        name: &'static str,
+        source: ModelSource<'_>,
+        config: &SessionConfig,
+    ) -> Result<Self, SupertonicError> {
+        let state = match config.load_plan.schedule(name) {
+            Some(true) => LazyState::Ready(source.commit(config, name)?),
+            Some(false) => LazyState::Deferred(source.into_owned(), Box::new(config.clone())),
+            None => LazyState::Skipped,
+        };
+        Ok(Self::with_state(name, state))
+    }
+
+    /// Wait for the session, creating it now if it was deferred
+    pub(crate) fn get(&self) -> Result<SessionGuard<'_>, SupertonicError> {
+        let mut state = self.lock();
+        if let LazyState::Deferred(source, config) = &*state {
+            info!("Loading deferred {} session", self.name);
+            *state = LazyState::Ready(source.commit(config, self.name)?);
+        }
+        match &*state {
+            LazyState::Ready(_) => Ok(SessionGuard(state)),
+            _ => Err(SupertonicError::Config(format!(
+                "the {} session is not loaded under this load plan",
+                self.name
+            ))),
+        }
+    }
+
+    /// Give each of `current` (in `MODEL_NAMES` order) the state of the
+    /// matching `replacement`, for every handle sharing it. All four are
+    /// held while swapping, taken in the order renders take them (vocoder,
+    /// then the encoders), so no render sees old and new models in one stage.
+    pub(crate) fn replace_all(current: [&LazySession; 4], replacements: [LazySession; 4]) {
+        let [dp, text_enc, vector_est, vocoder] = current;
+        let mut held = [vocoder, dp, text_enc, vector_est].map(LazySession::lock);
+        let [new_dp, new_text_enc, new_vector_est, new_vocoder] = replacements;
+        for (guard, replacement) in
+            held.iter_mut()
+                .zip([new_vocoder, new_dp, new_text_enc, new_vector_est])
+        {
+            let state = std::mem::replace(&mut *replacement.lock(), LazyState::Skipped);
+            **guard = state;
+        }
+    }
+
+    /// Wait for the session if it has been created
+    pub(crate) fn loaded(&self) -> Option<SessionGuard<'_>> {
+        let state = self.lock();
+        matches!(&*state, LazyState::Ready(_)).then(|| SessionGuard(state))
+    }
+
+    /// A panic during a run leaves the session as usable as before it
+    fn lock(&self) -> MutexGuard<'_, LazyState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,238 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::audio::WavStreamWriter;
+use crate::error::SupertonicError;
+
+// ============================================================================
+// Audio Sinks
+// ============================================================================
+
+/// Destination for mono audio produced incrementally, e.g. by
+/// `TextToSpeech::synthesize_to_sink`.
+///
+/// Sinks do not know the sample rate; create them for the rate the producer
+/// renders at (`TextToSpeech::output_sample_rate`).
+pub trait AudioSink {
+    /// Accept the next block of samples
+    fn write(&mut self, samples: &[f32]) -> Result<(), SupertonicError>;
+
+    /// Called once after the last block, to flush or close the output
+    fn finalize(&mut self) -> Result<(), SupertonicError> {
+        Ok(())
+    }
+}
+
+/// Collects everything in memory
+impl AudioSink for Vec<f32> {
+    fn write(&mut self, samples: &[f32]) -> Result<(), SupertonicError> {
+        self.extend_from_slice(samples);
+        Ok(())
+    }
+}
+
+/// Streams to a WAV file; `finalize` moves it into place
+impl AudioSink for WavStreamWriter {
+    fn write(&mut self, samples: &[f32]) -> Result<(), SupertonicError> {
+        self.write_samples(samples)
+    }
+
+    fn finalize(&mut self) -> Result<(), SupertonicError> {
+        self.finish_in_place()
+    }
+}
+
+impl<S: AudioSink + ?Sized> AudioSink for &mut S {
+    fn write(&mut self, samples: &[f32]) -> Result<(), SupertonicError> {
+        (**self).write(samples)
+    }
+
+    fn finalize(&mut self) -> Result<(), SupertonicError> {
+        (**self).finalize()
+    }
+}
+
+impl<S: AudioSink + ?Sized> AudioSink for Box<S> {
+    fn write(&mut self, samples: &[f32]) -> Result<(), SupertonicError> {
+        (**self).write(samples)
+    }
+
+    fn finalize(&mut self) -> Result<(), SupertonicError> {
+        (**self).finalize()
+    }
+}
+
+/// Hands each block to a closure, e.g. to send it over a socket or channel
+pub struct CallbackSink<F>(pub F);
+
+impl<F> AudioSink for CallbackSink<F>
+where
+    F: FnMut(&[f32]) -> Result<(), SupertonicError>,
+{
+    fn write(&mut self, samples: &[f32]) -> Result<(), SupertonicError> {
+        (self.0)(samples)
+    }
+}
+
+/// Writes every block to two sinks, e.g. a file and a player
+pub struct TeeSink<A, B>(pub A, pub B);
+
+impl<A: AudioSink, B: AudioSink> AudioSink for TeeSink<A, B> {
+    fn write(&mut self, samples: &[f32]) -> Result<(), SupertonicError> {
+        self.0.write(samples)?;
+        self.1.write(samples)
+    }
+
+    fn finalize(&mut self) -> Result<(), SupertonicError> {
+        self.0.finalize()?;
+        self.1.finalize()
+    }
+}
+
+// ============================================================================
+// Ring Buffer
+// ============================================================================
+//
+// Single-producer, single-consumer queue of fixed capacity for real-time
+// consumers: synthesis writes from its own thread while an audio callback
+// reads without locks or allocation. Samples are stored as `f32` bits in
+// atomics; the producer publishes them by advancing `written` (release) and
+// the consumer frees slots by advancing `read`.
+
+struct RingBuffer {
+    slots: Box<[AtomicU32]>,
+    /// Total samples ever written and read; their difference is the fill level
+    written: AtomicUsize,
+    read: AtomicUsize,
+    finished: AtomicBool,
+    underruns: AtomicU64,
+    underrun_samples: AtomicU64,
+}
+
+/// Producer end of a ring buffer, created with `RingBufferSink::new`.
+///
+/// `write` blocks while the buffer is full, so synthesis runs at most
+/// `capacity` samples ahead of the reader.
+pub struct RingBufferSink {
+    ring: Arc<RingBuffer>,
+}
+
+/// Consumer end of a ring buffer, safe to call from an audio callback
+pub struct RingBufferReader {
+    ring: Arc<RingBuffer>,
+}
+
+impl RingBufferSink {
+    /// A buffer holding up to `capacity` samples (at least 1), and its reader
+    pub fn new(capacity: usize) -> (RingBufferSink, RingBufferReader) {
+        let ring = Arc::new(RingBuffer {
+            slots: (0..capacity.max(1)).map(|_| AtomicU32::new(0)).collect(),
+            written: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+            finished: AtomicBool::new(false),
+            underruns: AtomicU64::new(0),
+            underrun_samples: AtomicU64::new(0),
+        });
+        (
+            RingBufferSink {
+                ring: Arc::clone(&ring),
+            },
+            RingBufferReader { ring },
+        )
+    }
+
+    /// Copy as many of `samples` as fit without waiting; returns how many
+    pub fn try_write(&mut self, samples: &[f32]) -> usize {
+        let ring = &self.ring;
+        let capacity = ring.slots.len();
+        let written = ring.written.load(Ordering::Relaxed);
+        let free = capacity - (written - ring.read.load(Ordering::Acquire));
+        let count = free.min(samples.len());
+        for (i, sample) in samples[..count].iter().enumerate() {
+            ring.slots[(written + i) % capacity].store(sample.to_bits(), Ordering::Relaxed);
+        }
+        ring.written.store(written + count, Ordering::Release);
+        count
+    }
+}
+
+impl AudioSink for RingBufferSink {
+    fn write(&mut self, mut samples: &[f32]) -> Result<(), SupertonicError> {
+        while !samples.is_empty() {
+            let count = self.try_write(samples);
+            samples = &samples[count..];
+            if count == 0 {
+                if Arc::strong_count(&self.ring) == 1 {
+                    return Err(SupertonicError::Validation(
+                        "ring buffer reader was dropped".to_string(),
+                    ));
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+        Ok(())
+    }
+
+    /// Mark the end of the stream, so the reader stops reporting under-runs
+    fn finalize(&mut self) -> Result<(), SupertonicError> {
+        self.ring.finished.store(true, Ordering::Release);
+        Ok(())
+    }
+}
+
+impl Drop for RingBufferSink {
+    fn drop(&mut self) {
+        self.ring.finished.store(true, Ordering::Release);
+    }
+}
+
+impl RingBufferReader {
+    /// Fill `out` with the next samples, padding with silence if the buffer
+    /// runs dry; returns how many came from the buffer.
+    ///
+    /// Padding before the producer has finalized counts as an under-run.
+    pub fn read(&mut self, out: &mut [f32]) -> usize {
+        let ring = &self.ring;
+        let capacity = ring.slots.len();
+        let read = ring.read.load(Ordering::Relaxed);
+        // Checked before the fill level, so a stream that finishes in between
+        // is not mistaken for one that was already complete
+        let finished = ring.finished.load(Ordering::Acquire);
+        let available = ring.written.load(Ordering::Acquire) - read;
+        let count = available.min(out.len());
+        for (i, sample) in out[..count].iter_mut().enumerate() {
+            *sample = f32::from_bits(ring.slots[(read + i) % capacity].load(Ordering::Relaxed));
+        }
+        ring.read.store(read + count, Ordering::Release);
+
+        let missing = out.len() - count;
+        out[count..].fill(0.0);
+        if missing > 0 && !finished {
+            ring.underruns.fetch_add(1, Ordering::Relaxed);
+            ring.underrun_samples
+                .fetch_add(missing as u64, Ordering::Relaxed);
+        }
+        count
+    }
+
+    /// Samples waiting to be read
+    pub fn available(&self) -> usize {
+        self.ring.written.load(Ordering::Acquire) - self.ring.read.load(Ordering::Relaxed)
+    }
+
+    /// Whether the producer has finished and every sample has been read
+    pub fn is_finished(&self) -> bool {
+        self.ring.finished.load(Ordering::Acquire) && self.available() == 0
+    }
+
+    /// Number of `read` calls that ran dry before the end of the stream
+    pub fn underruns(&self) -> u64 {
+        self.ring.underruns.load(Ordering::Relaxed)
+    }
+
+    /// Total silence padded in by under-runs, in samples
+    pub fn underrun_samples(&self) -> u64 {
+        self.ring.underrun_samples.load(Ordering::Relaxed)
+    }
+}
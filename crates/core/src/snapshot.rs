@@ -0,0 +1,276 @@
+use ort::session::{builder::GraphOptimizationLevel, Session};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::{info, warn};
+
+use crate::atomic::write_file_atomic;
+use crate::compat::check_session;
+use crate::error::SupertonicError;
+use crate::integrity::AssetManifest;
+use crate::model::{load_text_to_speech_with_config, TextToSpeech};
+use crate::session::{session_builder, GraphOptLevel, LazySession, SessionConfig, MODEL_NAMES};
+use crate::text::UnicodeProcessor;
+
+// ============================================================================
+// Engine Snapshots
+// ============================================================================
+//
+// A snapshot is the set of graphs ONNX Runtime produces after running its full
+// optimization pipeline, serialized next to a small manifest describing the
+// source files they were derived from. Restoring a snapshot skips graph
+// optimization entirely, which is the bulk of the cold-start cost.
+//
+// ONNX Runtime has no API for persisting allocator state, so arenas are still
+// built lazily on the first inference after a restore.
+//
+// A snapshot that cannot be written (a read-only directory, a full disk) is
+// not fatal: the engine is loaded without one and the next start tries again.
+
+const SNAPSHOT_MANIFEST: &str = "snapshot.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Version of this crate that produced the snapshot
+    pub crate_version: String,
+    /// Execution providers the graphs were optimized for, in priority order
+    /// (optimized graphs are not portable between providers)
+    pub execution_providers: Vec<String>,
+    /// Optimization level the graphs were produced with
+    pub graph_opt_level: GraphOptLevel,
+    /// Session options in effect when the snapshot was built
+    pub intra_threads: Option<usize>,
+    pub inter_threads: Option<usize>,
+    pub memory_arena: bool,
+    pub shared_arena: bool,
+    /// Fingerprint of every source model the snapshot was derived from
+    pub sources: BTreeMap<String, SourceFingerprint>,
+}
+
+impl SnapshotManifest {
+    /// Describe the snapshot `session_config` would build from the models in `onnx_dir`
+    pub fn current<P: AsRef<Path>>(
+        onnx_dir: P,
+        session_config: &SessionConfig,
+    ) -> Result<Self, SupertonicError> {
+        let mut sources = BTreeMap::new();
+        for name in MODEL_NAMES {
+            let path = onnx_dir.as_ref().join(format!("{}.onnx", name));
+            sources.insert(name.to_string(), SourceFingerprint::of(path)?);
+        }
+        let mut execution_providers = Vec::new();
+        if session_config.use_gpu {
+            execution_providers.push("rocm".to_string());
+        }
+        execution_providers.push("cpu".to_string());
+        Ok(SnapshotManifest {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            execution_providers,
+            graph_opt_level: snapshot_opt_level(session_config),
+            intra_threads: session_config.intra_threads,
+            inter_threads: session_config.inter_threads,
+            memory_arena: session_config.memory_arena,
+            shared_arena: session_config.shared_arena,
+            sources,
+        })
+    }
+
+    /// Write the manifest into `snapshot_dir`, marking the snapshot complete
+    pub fn save<P: AsRef<Path>>(&self, snapshot_dir: P) -> Result<(), SupertonicError> {
+        write_file_atomic(
+            snapshot_dir.as_ref().join(SNAPSHOT_MANIFEST),
+            &serde_json::to_vec_pretty(self)?,
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceFingerprint {
+    pub len: u64,
+    pub modified_secs: u64,
+}
+
+impl SourceFingerprint {
+    fn of<P: AsRef<Path>>(path: P) -> Result<Self, SupertonicError> {
+        let meta = fs::metadata(path)?;
+        let modified_secs = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Ok(SourceFingerprint {
+            len: meta.len(),
+            modified_secs,
+        })
+    }
+}
+
+/// Snapshots default to full optimization, since that is what they exist to cache
+fn snapshot_opt_level(session_config: &SessionConfig) -> GraphOptLevel {
+    session_config.graph_opt_level.unwrap_or(GraphOptLevel::All)
+}
+
+fn snapshot_model_path(snapshot_dir: &Path, name: &str) -> PathBuf {
+    snapshot_dir.join(format!("{}.optimized.onnx", name))
+}
+
+/// Returns true if `snapshot_dir` holds a complete snapshot described by `expected`
+pub fn snapshot_is_fresh<P: AsRef<Path>>(snapshot_dir: P, expected: &SnapshotManifest) -> bool {
+    let snapshot_dir = snapshot_dir.as_ref();
+    let manifest: SnapshotManifest = match fs::read(snapshot_dir.join(SNAPSHOT_MANIFEST))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+    {
+        Some(m) => m,
+        None => return false,
+    };
+
+    manifest == *expected
+        && MODEL_NAMES
+            .iter()
+            .all(|name| snapshot_model_path(snapshot_dir, name).exists())
+}
+
+fn restore_sessions(
+    snapshot_dir: &Path,
+    session_config: &SessionConfig,
+) -> Result<Vec<Session>, SupertonicError> {
+    let mut sessions = Vec::with_capacity(MODEL_NAMES.len());
+    for name in MODEL_NAMES {
+        let session = session_builder(session_config, name)?
+            .with_optimization_level(GraphOptimizationLevel::Disable)?
+            .commit_from_file(snapshot_model_path(snapshot_dir, name))?;
+        check_session(name, &session)?;
+        sessions.push(session);
+    }
+    Ok(sessions)
+}
+
+fn build_sessions(
+    onnx_dir: &Path,
+    snapshot_dir: &Path,
+    session_config: &SessionConfig,
+) -> Result<Vec<Session>, SupertonicError> {
+    let level = snapshot_opt_level(session_config);
+    let mut sessions = Vec::with_capacity(MODEL_NAMES.len());
+    for name in MODEL_NAMES {
+        let session = session_builder(session_config, name)?
+            .with_optimization_level(level.into())?
+            .with_optimized_model_path(snapshot_model_path(snapshot_dir, name))?
+            .commit_from_file(onnx_dir.join(format!("{}.onnx", name)))?;
+        check_session(name, &session)?;
+        sessions.push(session);
+    }
+    Ok(sessions)
+}
+
+/// Optimize the models into `snapshot_dir` and record the manifest once every
+/// graph has been written
+fn rebuild_snapshot(
+    onnx_dir: &Path,
+    snapshot_dir: &Path,
+    session_config: &SessionConfig,
+    expected: &SnapshotManifest,
+) -> Result<Vec<Session>, SupertonicError> {
+    fs::create_dir_all(snapshot_dir)?;
+    // Invalidate first so an interrupted rebuild never looks fresh
+    let _ = fs::remove_file(snapshot_dir.join(SNAPSHOT_MANIFEST));
+    let sessions = build_sessions(onnx_dir, snapshot_dir, session_config)?;
+    expected.save(snapshot_dir)?;
+    Ok(sessions)
+}
+
+/// Load TTS components, restoring from (or creating) a snapshot in `snapshot_dir`.
+///
+/// If the snapshot is missing, was produced from different model files, by a
+/// different crate version, for different execution providers or with
+/// different session options, or fails to restore, the models are loaded from
+/// `onnx_dir` as usual and a fresh snapshot is written for the next start. If
+/// that snapshot cannot be written, a warning is logged and the engine is
+/// loaded with `load_text_to_speech_with_config` instead.
+pub fn load_text_to_speech_with_snapshot<P: AsRef<Path>, Q: AsRef<Path>>(
+    onnx_dir: P,
+    snapshot_dir: Q,
+    session_config: &SessionConfig,
+) -> Result<TextToSpeech, SupertonicError> {
+    let onnx_dir = onnx_dir.as_ref();
+    let snapshot_dir = snapshot_dir.as_ref();
+
+    let cfg_bytes = fs::read(onnx_dir.join("tts.json"))?;
+    let checksums = AssetManifest::find(onnx_dir)?;
+    if let Some(checksums) = &checksums {
+        checksums.verify_bytes("tts.json", &cfg_bytes)?;
+        checksums.verify_file(onnx_dir, "unicode_indexer.json")?;
+    }
+    let cfgs = crate::config::load_cfgs_from_bytes(&cfg_bytes)
+        .map_err(|e| SupertonicError::Config(e.to_string()))?;
+    let text_processor = UnicodeProcessor::new(onnx_dir.join("unicode_indexer.json"))?;
+
+    let expected = SnapshotManifest::current(onnx_dir, session_config)?;
+
+    let restored = if snapshot_is_fresh(snapshot_dir, &expected) {
+        match restore_sessions(snapshot_dir, session_config) {
+            Ok(sessions) => {
+                info!("Restored engine snapshot from {}", snapshot_dir.display());
+                Some(sessions)
+            }
+            Err(e) => {
+                warn!("Failed to restore engine snapshot, rebuilding: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let sessions = match restored {
+        Some(sessions) => sessions,
+        None => {
+            // Snapshots only restore what was checked when they were built
+            if let Some(checksums) = &checksums {
+                for name in MODEL_NAMES {
+                    checksums.verify_file(onnx_dir, &format!("{}.onnx", name))?;
+                }
+            }
+            match rebuild_snapshot(onnx_dir, snapshot_dir, session_config, &expected) {
+                Ok(sessions) => {
+                    info!("Saved engine snapshot to {}", snapshot_dir.display());
+                    sessions
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to save engine snapshot to {}, loading without one: {}",
+                        snapshot_dir.display(),
+                        e
+                    );
+                    return load_text_to_speech_with_config(
+                        &onnx_dir.to_string_lossy(),
+                        session_config,
+                    );
+                }
+            }
+        }
+    };
+
+    let [dp_ort, text_enc_ort, vector_est_ort, vocoder_ort]: [Session; 4] =
+        sessions.try_into().map_err(|_| {
+            SupertonicError::Unknown("Unexpected number of snapshot sessions".to_string())
+        })?;
+
+    // Built like the other loaders' engines, so GPU snapshots keep the latent
+    // on the device
+    Ok(TextToSpeech::with_sessions(
+        cfgs,
+        text_processor,
+        [
+            LazySession::ready("duration_predictor", dp_ort),
+            LazySession::ready("text_encoder", text_enc_ort),
+            LazySession::ready("vector_estimator", vector_est_ort),
+            LazySession::ready("vocoder", vocoder_ort),
+        ],
+        session_config,
+    ))
+}
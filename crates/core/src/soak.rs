@@ -0,0 +1,201 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::model::{Style, TextToSpeech};
+use crate::options::SynthesisOptions;
+
+// ============================================================================
+// Soak Testing
+// ============================================================================
+//
+// Long-running stability check for embedded and kiosk deployments: synthesize
+// varied texts back to back for hours and watch for memory growth, latency
+// drift and errors.
+
+const SOAK_SENTENCES: &[&str] = &[
+    "This morning, I took a walk in the park.",
+    "The quick brown fox jumps over the lazy dog.",
+    "Your order of $42.99 will arrive on 3/14/2025 at 5 PM.",
+    "Please visit www.example.com or email support@example.com for help.",
+    "NASA and the ESA announced a joint mission yesterday.",
+    "\"Are you coming?\" she asked, glancing at the clock.",
+    "Temperatures will drop sharply overnight, so bring a warm coat.",
+    "After a long pause, the speaker continued with renewed energy and conviction.",
+    "One, two, three, four, five.",
+    "Wait!",
+];
+
+#[derive(Debug, Clone)]
+pub struct SoakConfig {
+    /// Total wall time to run for
+    pub duration: Duration,
+    /// How often `run_soak` reports progress
+    pub report_interval: Duration,
+    /// Seed for picking texts (latent noise follows the engine's own RNG)
+    pub seed: u64,
+    /// Fraction of iterations at the start and end compared for latency drift
+    pub window: f64,
+}
+
+impl Default for SoakConfig {
+    fn default() -> Self {
+        SoakConfig {
+            duration: Duration::from_secs(60 * 60),
+            report_interval: Duration::from_secs(60),
+            seed: 0,
+            window: 0.1,
+        }
+    }
+}
+
+/// Result of a soak run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SoakReport {
+    pub elapsed_secs: f64,
+    pub iterations: usize,
+    pub errors: usize,
+    /// Distinct error messages with their counts
+    pub error_kinds: Vec<(String, usize)>,
+    pub audio_secs: f64,
+    /// Mean real-time factor (synthesis time / audio duration) of the first window
+    pub rtf_start: f64,
+    /// Mean real-time factor of the last window
+    pub rtf_end: f64,
+    /// Relative change from `rtf_start` to `rtf_end` (0.1 = 10% slower)
+    pub latency_drift: f64,
+    pub max_latency_secs: f64,
+    /// Resident set size in bytes, where the platform reports it
+    pub rss_start: Option<u64>,
+    pub rss_end: Option<u64>,
+    pub rss_peak: Option<u64>,
+}
+
+impl SoakReport {
+    pub fn error_rate(&self) -> f64 {
+        if self.iterations == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.iterations as f64
+        }
+    }
+
+    /// Resident memory growth over the run, in bytes
+    pub fn rss_growth(&self) -> Option<i64> {
+        Some(self.rss_end? as i64 - self.rss_start? as i64)
+    }
+}
+
+/// Current resident set size of this process, in bytes (Linux only)
+pub fn resident_memory() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb * 1024)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Build a soak text of one to four random sentences
+fn soak_text<R: Rng>(rng: &mut R) -> String {
+    let n = rng.gen_range(1..=4);
+    (0..n)
+        .map(|_| SOAK_SENTENCES[rng.gen_range(0..SOAK_SENTENCES.len())])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+/// Synthesize varied texts until `config.duration` has elapsed.
+///
+/// `on_progress` is called every `config.report_interval` with the report so
+/// far; errors are counted rather than aborting the run.
+pub fn run_soak<F>(
+    tts: &mut TextToSpeech,
+    style: &Style,
+    options: &SynthesisOptions,
+    config: &SoakConfig,
+    mut on_progress: F,
+) -> SoakReport
+where
+    F: FnMut(&SoakReport),
+{
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let start = Instant::now();
+    let mut last_report = start;
+    let mut rtfs: Vec<f64> = Vec::new();
+    let mut report = SoakReport {
+        rss_start: resident_memory(),
+        ..Default::default()
+    };
+    report.rss_peak = report.rss_start;
+
+    let summarize = |report: &mut SoakReport, rtfs: &[f64]| {
+        let window = ((rtfs.len() as f64 * config.window).ceil() as usize)
+            .max(1)
+            .min(rtfs.len());
+        report.rtf_start = mean(&rtfs[..window]);
+        report.rtf_end = mean(&rtfs[rtfs.len().saturating_sub(window)..]);
+        report.latency_drift = if report.rtf_start > 0.0 {
+            report.rtf_end / report.rtf_start - 1.0
+        } else {
+            0.0
+        };
+        report.rss_end = resident_memory();
+        report.rss_peak = match (report.rss_peak, report.rss_end) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        report.elapsed_secs = start.elapsed().as_secs_f64();
+    };
+
+    while start.elapsed() < config.duration {
+        let text = soak_text(&mut rng);
+        let t0 = Instant::now();
+        let result = tts.synthesize(&text, style, options);
+        let latency = t0.elapsed().as_secs_f64();
+
+        report.iterations += 1;
+        report.max_latency_secs = report.max_latency_secs.max(latency);
+        if let Some(rss) = resident_memory() {
+            report.rss_peak = Some(report.rss_peak.map_or(rss, |peak| peak.max(rss)));
+        }
+        match result {
+            Ok((_, duration)) if duration > 0.0 => {
+                report.audio_secs += duration as f64;
+                rtfs.push(latency / duration as f64);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                report.errors += 1;
+                let kind = e.to_string();
+                match report.error_kinds.iter_mut().find(|(k, _)| *k == kind) {
+                    Some((_, count)) => *count += 1,
+                    None => report.error_kinds.push((kind, 1)),
+                }
+            }
+        }
+
+        if last_report.elapsed() >= config.report_interval {
+            summarize(&mut report, &rtfs);
+            on_progress(&report);
+            last_report = Instant::now();
+        }
+    }
+
+    summarize(&mut report, &rtfs);
+    report
+}
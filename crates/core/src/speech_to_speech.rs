@@ -0,0 +1,301 @@
+use std::time::Duration;
+
+use crate::error::SupertonicError;
+use crate::model::{Style, TextToSpeech};
+use crate::options::SynthesisOptions;
+use crate::text::split_sentences;
+
+// ============================================================================
+// Speech-to-Speech Pipeline
+// ============================================================================
+//
+// Glue for "listen, transform, speak" flows such as translate-and-speak or
+// rephrase-and-speak. Speech recognition is not part of this crate: callers
+// implement `SpeechRecognizer` over their STT engine of choice, and
+// `SpeechPipeline` bridges its output to the engine. Recognizers emit final
+// segments at arbitrary points (pauses, fixed windows), so segments are
+// joined back into whole sentences before they are transformed and spoken.
+
+/// A piece of recognized (or transformed) speech
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpeechSegment {
+    pub text: String,
+    /// BCP 47 language tag ("en", "de-AT"), if the recognizer detected one
+    pub language: Option<String>,
+    /// Position in the input audio, if the recognizer reports timestamps
+    pub start: Option<Duration>,
+    pub end: Option<Duration>,
+    /// Whether the recognizer may still revise this text
+    pub partial: bool,
+}
+
+impl SpeechSegment {
+    pub fn new(text: impl Into<String>, language: Option<&str>) -> Self {
+        SpeechSegment {
+            text: text.into(),
+            language: language.map(str::to_string),
+            ..Default::default()
+        }
+    }
+}
+
+/// Speech-to-text engine implemented outside this crate
+pub trait SpeechRecognizer: Send {
+    /// Feed mono `samples`; returns the segments recognized so far
+    fn push_audio(
+        &mut self,
+        samples: &[f32],
+        sample_rate: i32,
+    ) -> Result<Vec<SpeechSegment>, SupertonicError>;
+
+    /// Recognize whatever audio is still buffered at the end of the input
+    fn finish(&mut self) -> Result<Vec<SpeechSegment>, SupertonicError>;
+}
+
+/// Rewrites a sentence before it is spoken; `None` drops it.
+///
+/// Implemented for closures, e.g. a call into a translation service that
+/// also sets the segment's `language` to the target language.
+pub trait SegmentTransform: Send {
+    fn transform(
+        &mut self,
+        segment: SpeechSegment,
+    ) -> Result<Option<SpeechSegment>, SupertonicError>;
+}
+
+impl<F> SegmentTransform for F
+where
+    F: FnMut(SpeechSegment) -> Result<Option<SpeechSegment>, SupertonicError> + Send,
+{
+    fn transform(
+        &mut self,
+        segment: SpeechSegment,
+    ) -> Result<Option<SpeechSegment>, SupertonicError> {
+        self(segment)
+    }
+}
+
+/// Passes sentences through unchanged ("repeat after me")
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Repeat;
+
+impl SegmentTransform for Repeat {
+    fn transform(
+        &mut self,
+        segment: SpeechSegment,
+    ) -> Result<Option<SpeechSegment>, SupertonicError> {
+        Ok(Some(segment))
+    }
+}
+
+// ============================================================================
+// Sentence Bridging
+// ============================================================================
+
+/// Joins final recognizer segments into whole sentences.
+///
+/// Text after the last sentence end is held back until more text arrives,
+/// the language changes, or `flush` is called. Partial segments are ignored.
+#[derive(Debug, Default)]
+pub struct SentenceBridge {
+    pending: Option<SpeechSegment>,
+}
+
+impl SentenceBridge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a segment; returns the sentences it completes
+    pub fn push(&mut self, segment: SpeechSegment) -> Vec<SpeechSegment> {
+        if segment.partial || segment.text.trim().is_empty() {
+            return Vec::new();
+        }
+
+        let mut ready = Vec::new();
+        let mut pending = match self.pending.take() {
+            Some(pending) if pending.language == segment.language => pending,
+            other => {
+                // A language switch ends the held-back sentence
+                ready.extend(other);
+                SpeechSegment {
+                    text: String::new(),
+                    ..segment.clone()
+                }
+            }
+        };
+        if !pending.text.is_empty() {
+            pending.text.push(' ');
+        }
+        pending.text.push_str(segment.text.trim());
+        pending.end = segment.end.or(pending.end);
+
+        let mut sentences: Vec<String> =
+            split_sentences(&pending.text, pending.language.as_deref())
+                .into_iter()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        let complete = sentences.last().is_some_and(|s| ends_sentence(s));
+        let rest = if complete { None } else { sentences.pop() };
+
+        // Only the last sentence keeps the segment timestamps; the others
+        // cannot be located within the joined text
+        let count = sentences.len();
+        for (i, text) in sentences.into_iter().enumerate() {
+            let last = i + 1 == count && rest.is_none();
+            ready.push(SpeechSegment {
+                text,
+                language: pending.language.clone(),
+                start: if i == 0 { pending.start } else { None },
+                end: if last { pending.end } else { None },
+                partial: false,
+            });
+        }
+        self.pending = rest.map(|text| SpeechSegment {
+            text,
+            start: if count == 0 { pending.start } else { None },
+            ..pending
+        });
+        ready
+    }
+
+    /// Release the held-back text as a sentence of its own
+    pub fn flush(&mut self) -> Option<SpeechSegment> {
+        self.pending.take()
+    }
+}
+
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end()
+        .trim_end_matches(['"', '\'', ')', '»', '”', '」'])
+        .ends_with(['.', '!', '?', '…', '。', '！', '？'])
+}
+
+// ============================================================================
+// Pipeline
+// ============================================================================
+
+/// A sentence of output speech
+#[derive(Debug, Clone)]
+pub struct SpokenSegment {
+    /// The sentence as spoken, after the transform
+    pub segment: SpeechSegment,
+    pub samples: Vec<f32>,
+    pub duration: f32,
+}
+
+/// Recognize, transform and re-synthesize speech, one sentence at a time
+pub struct SpeechPipeline<R, T> {
+    recognizer: R,
+    transform: T,
+    bridge: SentenceBridge,
+    /// Voice per language tag prefix, tried in order
+    voices: Vec<(String, Style)>,
+    default_voice: Style,
+    options: SynthesisOptions,
+}
+
+impl<R: SpeechRecognizer, T: SegmentTransform> SpeechPipeline<R, T> {
+    pub fn new(recognizer: R, transform: T, voice: Style) -> Self {
+        SpeechPipeline {
+            recognizer,
+            transform,
+            bridge: SentenceBridge::new(),
+            voices: Vec::new(),
+            default_voice: voice,
+            options: SynthesisOptions::default(),
+        }
+    }
+
+    /// Speak sentences whose language starts with `language` ("de" matches "de-AT") with `voice`
+    pub fn with_voice(mut self, language: impl Into<String>, voice: Style) -> Self {
+        self.voices.push((language.into(), voice));
+        self
+    }
+
+    /// Options for every sentence; the locale defaults to the sentence's language
+    pub fn with_options(mut self, options: SynthesisOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn recognizer_mut(&mut self) -> &mut R {
+        &mut self.recognizer
+    }
+
+    /// Feed input audio; returns the sentences completed by it, already synthesized
+    pub fn push_audio(
+        &mut self,
+        tts: &mut TextToSpeech,
+        samples: &[f32],
+        sample_rate: i32,
+    ) -> Result<Vec<SpokenSegment>, SupertonicError> {
+        let segments = self.recognizer.push_audio(samples, sample_rate)?;
+        let mut spoken = Vec::new();
+        for segment in segments {
+            for sentence in self.bridge.push(segment) {
+                spoken.extend(self.speak(tts, sentence)?);
+            }
+        }
+        Ok(spoken)
+    }
+
+    /// End of input: recognize the remaining audio and speak everything still pending
+    pub fn finish(
+        &mut self,
+        tts: &mut TextToSpeech,
+    ) -> Result<Vec<SpokenSegment>, SupertonicError> {
+        let mut spoken = Vec::new();
+        for segment in self.recognizer.finish()? {
+            for sentence in self.bridge.push(segment) {
+                spoken.extend(self.speak(tts, sentence)?);
+            }
+        }
+        if let Some(rest) = self.bridge.flush() {
+            spoken.extend(self.speak(tts, rest)?);
+        }
+        Ok(spoken)
+    }
+
+    fn voice_for(&self, language: Option<&str>) -> &Style {
+        language
+            .and_then(|language| {
+                self.voices
+                    .iter()
+                    .find(|(prefix, _)| language_matches(language, prefix))
+            })
+            .map_or(&self.default_voice, |(_, voice)| voice)
+    }
+
+    fn speak(
+        &mut self,
+        tts: &mut TextToSpeech,
+        sentence: SpeechSegment,
+    ) -> Result<Option<SpokenSegment>, SupertonicError> {
+        let Some(segment) = self.transform.transform(sentence)? else {
+            return Ok(None);
+        };
+        if segment.text.trim().is_empty() {
+            return Ok(None);
+        }
+        let options = SynthesisOptions {
+            locale: self.options.locale.clone().or(segment.language.clone()),
+            ..self.options.clone()
+        };
+        let voice = self.voice_for(segment.language.as_deref()).clone();
+        let (samples, duration) = tts.synthesize(&segment.text, &voice, &options)?;
+        Ok(Some(SpokenSegment {
+            segment,
+            samples,
+            duration,
+        }))
+    }
+}
+
+/// Whether `language` equals `prefix` or is a more specific tag of it
+fn language_matches(language: &str, prefix: &str) -> bool {
+    let language = language.to_ascii_lowercase();
+    let prefix = prefix.to_ascii_lowercase();
+    language == prefix || language.starts_with(&format!("{}-", prefix))
+}
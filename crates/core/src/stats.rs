@@ -0,0 +1,94 @@
+use serde::Serialize;
+
+use crate::audio::SILENCE_THRESHOLD_DB;
+use crate::loudness::integrated_loudness;
+
+// ============================================================================
+// Audio Statistics
+// ============================================================================
+
+/// Level and silence figures of a render, for flagging bad output
+/// automatically; see `TextToSpeech::last_audio_stats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct AudioStats {
+    pub duration_secs: f32,
+    /// Largest absolute sample
+    pub peak: f32,
+    pub rms: f32,
+    /// Samples beyond full scale, which clip in integer PCM
+    pub clipped_samples: usize,
+    /// Integrated loudness (BS.1770); `None` for silence and for
+    /// `synthesize_to_sink`, which never holds the whole render
+    pub loudness_lufs: Option<f64>,
+    /// Seconds before the first sample above `SILENCE_THRESHOLD_DB`
+    pub leading_silence_secs: f32,
+    /// Seconds after the last sample above `SILENCE_THRESHOLD_DB`
+    pub trailing_silence_secs: f32,
+}
+
+impl AudioStats {
+    /// Measure mono `samples`
+    pub fn measure(samples: &[f32], sample_rate: i32) -> Self {
+        let mut stats = Self::measure_levels(samples, sample_rate);
+        stats.loudness_lufs = integrated_loudness(samples, sample_rate);
+        stats
+    }
+
+    /// `measure` without the loudness, which needs the whole render
+    fn measure_levels(samples: &[f32], sample_rate: i32) -> Self {
+        let secs = |n: usize| n as f32 / sample_rate.max(1) as f32;
+        let threshold = 10f32.powf(SILENCE_THRESHOLD_DB / 20.0);
+        let first = samples.iter().position(|s| s.abs() > threshold);
+        let last = samples.iter().rposition(|s| s.abs() > threshold);
+        let power = samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32;
+        AudioStats {
+            duration_secs: secs(samples.len()),
+            peak: samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs())),
+            rms: power.sqrt(),
+            clipped_samples: samples.iter().filter(|s| s.abs() > 1.0).count(),
+            loudness_lufs: None,
+            leading_silence_secs: secs(first.unwrap_or(samples.len())),
+            trailing_silence_secs: secs(last.map_or(samples.len(), |i| samples.len() - i - 1)),
+        }
+    }
+
+    /// Peak level in dBFS (`-inf` for silence)
+    pub fn peak_db(&self) -> f32 {
+        20.0 * self.peak.log10()
+    }
+
+    /// RMS level in dBFS (`-inf` for silence)
+    pub fn rms_db(&self) -> f32 {
+        20.0 * self.rms.log10()
+    }
+
+    /// Whether nothing rose above `SILENCE_THRESHOLD_DB`
+    pub fn is_silent(&self) -> bool {
+        self.leading_silence_secs >= self.duration_secs
+    }
+
+    /// Extend figures measured so far with the next block of the same render,
+    /// for output produced piece by piece; the loudness is dropped
+    pub(crate) fn append(&mut self, samples: &[f32], sample_rate: i32) {
+        let next = Self::measure_levels(samples, sample_rate);
+        let duration_secs = self.duration_secs + next.duration_secs;
+        if duration_secs > 0.0 {
+            self.rms = ((self.rms.powi(2) * self.duration_secs
+                + next.rms.powi(2) * next.duration_secs)
+                / duration_secs)
+                .sqrt();
+        }
+        if self.is_silent() {
+            self.leading_silence_secs += next.leading_silence_secs;
+        }
+        self.trailing_silence_secs = if next.is_silent() {
+            self.trailing_silence_secs + next.duration_secs
+        } else {
+            next.trailing_silence_secs
+        };
+        self.duration_secs = duration_secs;
+        self.peak = self.peak.max(next.peak);
+        self.clipped_samples += next.clipped_samples;
+        self.loudness_lufs = None;
+    }
+}
@@ -0,0 +1,247 @@
+use regex::Regex;
+use serde::Serialize;
+use std::fmt;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::SupertonicError;
+use crate::model::TextToSpeech;
+use crate::options::SynthesisOptions;
+use crate::text::ChunkBoundary;
+
+// ============================================================================
+// Strict Mode
+// ============================================================================
+//
+// By default the engine quietly makes any input speakable: characters without
+// a token are dropped, stray markup is read or stripped and sentences too long
+// for one chunk are cut between words. In strict mode each of these is an
+// error instead, reported at its position in the input, so what is rendered
+// is exactly what was reviewed.
+
+/// What strict mode objects to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrictIssueKind {
+    /// A character the model has no token for
+    UnsupportedChar(char),
+    /// A markup tag ("<break/>") or an unterminated one
+    Markup,
+    /// An opening bracket without its closing one, or the reverse
+    UnbalancedBracket(char),
+    /// A sentence or word too long for one chunk
+    OverLength,
+}
+
+/// Location in the input text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TextPosition {
+    /// Index of the character (not byte)
+    pub index: usize,
+    /// 1-based line and column
+    pub line: usize,
+    pub column: usize,
+}
+
+impl TextPosition {
+    /// Position of the character at `index` in `text`
+    pub fn at(text: &str, index: usize) -> Self {
+        let mut line = 1;
+        let mut column = 1;
+        for ch in text.chars().take(index) {
+            if ch == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        TextPosition {
+            index,
+            line,
+            column,
+        }
+    }
+
+    fn at_byte(text: &str, byte: usize) -> Self {
+        Self::at(text, text[..byte].chars().count())
+    }
+}
+
+/// A problem found by strict validation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrictIssue {
+    pub kind: StrictIssueKind,
+    /// Where the problem is; `None` when it only exists after normalization
+    /// (a character produced by a rules script or G2P)
+    pub position: Option<TextPosition>,
+    pub message: String,
+}
+
+impl fmt::Display for StrictIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(p) => write!(f, "line {}, column {}: {}", p.line, p.column, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Markup tags and unbalanced brackets in `text`.
+///
+/// These checks need no model; `TextToSpeech::strict_issues` adds the
+/// character and length checks.
+pub fn markup_issues(text: &str) -> Vec<StrictIssue> {
+    let mut issues = Vec::new();
+
+    let tag = Regex::new(r"<[A-Za-z/!?][^<>]*(>|$)").unwrap();
+    for m in tag.find_iter(text) {
+        let message = if m.as_str().ends_with('>') {
+            format!("markup is not supported: {}", m.as_str())
+        } else {
+            format!("unterminated markup tag: {}", m.as_str().trim_end())
+        };
+        issues.push(StrictIssue {
+            kind: StrictIssueKind::Markup,
+            position: Some(TextPosition::at_byte(text, m.start())),
+            message,
+        });
+    }
+
+    let mut open: Vec<(char, usize)> = Vec::new();
+    for (byte, ch) in text.char_indices() {
+        match ch {
+            '(' | '[' | '{' => open.push((ch, byte)),
+            ')' | ']' | '}' => {
+                let expected = match ch {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                if open.last().is_some_and(|&(c, _)| c == expected) {
+                    open.pop();
+                } else {
+                    issues.push(StrictIssue {
+                        kind: StrictIssueKind::UnbalancedBracket(ch),
+                        position: Some(TextPosition::at_byte(text, byte)),
+                        message: format!("'{}' has no matching '{}'", ch, expected),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    for (ch, byte) in open {
+        issues.push(StrictIssue {
+            kind: StrictIssueKind::UnbalancedBracket(ch),
+            position: Some(TextPosition::at_byte(text, byte)),
+            message: format!("'{}' is never closed", ch),
+        });
+    }
+
+    issues.sort_by_key(|issue| issue.position.map(|p| p.index));
+    issues
+}
+
+/// Index in `text` of the `occurrence`-th appearance of `ch`, counting
+/// characters that decompose into it ("é" for U+0301)
+fn source_index(text: &str, ch: char, occurrence: usize) -> Option<usize> {
+    let mut seen = 0;
+    for (i, c) in text.chars().enumerate() {
+        seen += std::iter::once(c).nfkd().filter(|&d| d == ch).count();
+        if seen > occurrence {
+            return Some(i);
+        }
+    }
+    None
+}
+
+impl TextToSpeech {
+    /// Whether `synthesize`, `batch`, `preview` and `call` reject input with strict-mode issues
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.clear_synthesis_cache();
+        self.strict = strict;
+    }
+
+    /// Everything strict mode would reject in `text` with `options`, in input order
+    pub fn strict_issues(
+        &mut self,
+        text: &str,
+        options: &SynthesisOptions,
+    ) -> Result<Vec<StrictIssue>, SupertonicError> {
+        let context = self
+            .text_processor
+            .set_context(options.normalize_context()?);
+        let issues = self.current_strict_issues(text);
+        self.text_processor.set_context(context);
+        Ok(issues)
+    }
+
+    /// `strict_issues` under the processor's current context
+    fn current_strict_issues(&self, text: &str) -> Vec<StrictIssue> {
+        let mut issues = markup_issues(text);
+
+        let prepared: Vec<char> = self.text_processor.prepare(text).chars().collect();
+        for unsupported in self.text_processor.unsupported_chars(text) {
+            let ch = unsupported.ch;
+            let occurrence = prepared[..unsupported.position]
+                .iter()
+                .filter(|&&c| c == ch)
+                .count();
+            issues.push(StrictIssue {
+                kind: StrictIssueKind::UnsupportedChar(ch),
+                position: source_index(text, ch, occurrence).map(|i| TextPosition::at(text, i)),
+                message: format!("unsupported character {:?} (U+{:04X})", ch, ch as u32),
+            });
+        }
+
+        // Chunks are trimmed slices of the input except for the spacing inside
+        // word-split pieces, so each is found from its first word on
+        let mut cursor = 0;
+        let mut previous = ChunkBoundary::Paragraph;
+        for (chunk, boundary) in self.chunks_with_boundaries(text) {
+            let first_word = chunk.split_whitespace().next().unwrap_or_default();
+            let start = text[cursor..].find(first_word).map(|i| cursor + i);
+            if let Some(start) = start {
+                cursor = start + first_word.len();
+            }
+            let split = boundary == ChunkBoundary::Word && previous != ChunkBoundary::Word;
+            if split || self.chunk_too_long(&chunk) {
+                issues.push(StrictIssue {
+                    kind: StrictIssueKind::OverLength,
+                    position: start.map(|byte| TextPosition::at_byte(text, byte)),
+                    message: if split {
+                        "sentence is too long for one chunk and would be split between words"
+                            .to_string()
+                    } else {
+                        format!("\"{}\" is too long for one chunk", first_word)
+                    },
+                });
+            }
+            previous = boundary;
+        }
+
+        issues.sort_by_key(|issue| issue.position.map_or(usize::MAX, |p| p.index));
+        issues
+    }
+
+    /// Fail with every strict-mode issue in `text`, if strict mode is on
+    pub(crate) fn enforce_strict(&self, text: &str) -> Result<(), SupertonicError> {
+        if !self.strict {
+            return Ok(());
+        }
+        let issues = self.current_strict_issues(text);
+        if issues.is_empty() {
+            return Ok(());
+        }
+        let list: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+        Err(SupertonicError::Validation(format!(
+            "Strict mode rejected the text ({} issue(s)): {}",
+            issues.len(),
+            list.join("; ")
+        )))
+    }
+}
@@ -0,0 +1,210 @@
+use half::f16;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::atomic::write_file_atomic;
+use crate::error::SupertonicError;
+use crate::model::{StyleComponent, VoiceStyleData};
+use crate::options::SynthesisOptions;
+
+// ============================================================================
+// Voice Style Files
+// ============================================================================
+//
+// The released voice styles are JSON with every value written out as text,
+// a few hundred kilobytes per voice that take longer to parse than to use.
+// The binary format holds the same two tensors as little-endian floats,
+// optionally as f16 at half the size. The loaders accept either; `convert`
+// migrates existing files.
+//
+// Layout: the magic `SSTY`, a version byte, a value type byte (0 = f32,
+// 1 = f16) and two zero bytes; the `style_ttl` and `style_dp` dims as three
+// u32 each; the length (u32) and JSON of the voice's default options, empty
+// when it has none; then the `style_ttl` values followed by the `style_dp`
+// values.
+
+/// Magic bytes at the start of a binary voice style
+pub const STYLE_MAGIC: &[u8; 4] = b"SSTY";
+
+const STYLE_VERSION: u8 = 1;
+
+/// Bytes before the defaults JSON
+const HEADER_LEN: usize = 8 + 6 * 4 + 4;
+
+/// Encoding of a voice style file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StyleFormat {
+    /// The released JSON format
+    Json,
+    /// Binary with f32 values, read back exactly
+    Binary,
+    /// Binary with f16 values, half the size; values keep about three
+    /// significant digits
+    BinaryF16,
+}
+
+impl StyleFormat {
+    /// Extension of files in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            StyleFormat::Json => "json",
+            StyleFormat::Binary | StyleFormat::BinaryF16 => "bin",
+        }
+    }
+}
+
+impl std::str::FromStr for StyleFormat {
+    type Err = SupertonicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(StyleFormat::Json),
+            "bin" | "binary" => Ok(StyleFormat::Binary),
+            "f16" | "bin-f16" | "binary-f16" => Ok(StyleFormat::BinaryF16),
+            other => Err(SupertonicError::Validation(format!(
+                "unknown voice style format '{}' (expected json, bin or f16)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Parse a voice style in any `StyleFormat`, told apart by its first bytes
+pub fn decode(bytes: &[u8]) -> Result<VoiceStyleData, SupertonicError> {
+    if bytes.starts_with(STYLE_MAGIC) {
+        decode_binary(bytes)
+    } else {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+/// Write `data` in `format`
+pub fn encode(data: &VoiceStyleData, format: StyleFormat) -> Result<Vec<u8>, SupertonicError> {
+    let half = match format {
+        StyleFormat::Json => return Ok(serde_json::to_vec(data)?),
+        StyleFormat::Binary => false,
+        StyleFormat::BinaryF16 => true,
+    };
+    let components = [&data.style_ttl, &data.style_dp];
+    let mut dims = Vec::with_capacity(6);
+    for component in components {
+        dims.extend(component_dims(component)?);
+    }
+    let defaults = match &data.defaults {
+        Some(defaults) => serde_json::to_vec(defaults)?,
+        None => Vec::new(),
+    };
+
+    let values: usize = components
+        .iter()
+        .map(|c| c.dims.iter().product::<usize>())
+        .sum();
+    let width = if half { 2 } else { 4 };
+    let mut out = Vec::with_capacity(HEADER_LEN + defaults.len() + values * width);
+    out.extend_from_slice(STYLE_MAGIC);
+    out.extend_from_slice(&[STYLE_VERSION, half as u8, 0, 0]);
+    for dim in dims {
+        out.extend_from_slice(&dim.to_le_bytes());
+    }
+    out.extend_from_slice(&(defaults.len() as u32).to_le_bytes());
+    out.extend_from_slice(&defaults);
+    for component in components {
+        for value in component.data.iter().flatten().flatten() {
+            if half {
+                out.extend_from_slice(&f16::from_f32(*value).to_le_bytes());
+            } else {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Rewrite the voice style at `in_path` (in any format) as `format` at
+/// `out_path`
+pub fn convert<P: AsRef<Path>, Q: AsRef<Path>>(
+    in_path: P,
+    out_path: Q,
+    format: StyleFormat,
+) -> Result<(), SupertonicError> {
+    let data = decode(&std::fs::read(in_path)?)?;
+    write_file_atomic(out_path, &encode(&data, format)?)
+}
+
+/// Dims of `component` as u32, checked against its data
+fn component_dims(component: &StyleComponent) -> Result<[u32; 3], SupertonicError> {
+    let [a, b, c] = component.dims[..] else {
+        return Err(invalid("style tensors must have three dims"));
+    };
+    if component.data.len() != a
+        || component.data.iter().any(|batch| batch.len() != b)
+        || component.data.iter().flatten().any(|row| row.len() != c)
+    {
+        return Err(invalid("style data does not match its dims"));
+    }
+    let dim = |d: usize| u32::try_from(d).map_err(|_| invalid("style dims are too large"));
+    Ok([dim(a)?, dim(b)?, dim(c)?])
+}
+
+fn decode_binary(bytes: &[u8]) -> Result<VoiceStyleData, SupertonicError> {
+    if bytes.len() < HEADER_LEN {
+        return Err(invalid("binary voice style is truncated"));
+    }
+    if bytes[4] != STYLE_VERSION {
+        return Err(invalid(&format!(
+            "binary voice style version {} is not supported",
+            bytes[4]
+        )));
+    }
+    let half = match bytes[5] {
+        0 => false,
+        1 => true,
+        other => return Err(invalid(&format!("unknown value type {}", other))),
+    };
+    let word = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap()) as usize;
+    let ttl_dims = [word(8), word(12), word(16)];
+    let dp_dims = [word(20), word(24), word(28)];
+    let defaults_len = word(32);
+
+    let width = if half { 2 } else { 4 };
+    // Checked, so corrupt dims are reported rather than overflowing
+    let count = |dims: &[usize; 3]| dims.iter().try_fold(1usize, |n, &d| n.checked_mul(d));
+    let expected_len = count(&ttl_dims)
+        .zip(count(&dp_dims))
+        .and_then(|(ttl, dp)| ttl.checked_add(dp))
+        .and_then(|values| values.checked_mul(width))
+        .and_then(|len| len.checked_add(HEADER_LEN + defaults_len));
+    if expected_len != Some(bytes.len()) {
+        return Err(invalid("binary voice style size does not match its dims"));
+    }
+
+    let defaults_end = HEADER_LEN + defaults_len;
+    let defaults: Option<SynthesisOptions> = match defaults_len {
+        0 => None,
+        _ => Some(serde_json::from_slice(&bytes[HEADER_LEN..defaults_end])?),
+    };
+    let mut values = bytes[defaults_end..].chunks_exact(width).map(|b| {
+        if half {
+            f16::from_le_bytes([b[0], b[1]]).to_f32()
+        } else {
+            f32::from_le_bytes([b[0], b[1], b[2], b[3]])
+        }
+    });
+    let mut component = |[a, b, c]: [usize; 3]| StyleComponent {
+        data: (0..a)
+            .map(|_| (0..b).map(|_| values.by_ref().take(c).collect()).collect())
+            .collect(),
+        dims: vec![a, b, c],
+        dtype: "float32".to_string(),
+    };
+    Ok(VoiceStyleData {
+        style_ttl: component(ttl_dims),
+        style_dp: component(dp_dims),
+        defaults,
+    })
+}
+
+fn invalid(message: &str) -> SupertonicError {
+    SupertonicError::Validation(format!("voice style: {}", message))
+}
@@ -1,12 +1,20 @@
 use ndarray::Array3;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use serde_json;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::error::SupertonicError;
+use crate::g2p::G2p;
+use crate::normalize::{NormalizeContext, TextNormalizer};
+use crate::warning::SynthesisWarning;
 
 // ============================================================================
 // Unicode Text Processor
@@ -14,6 +22,64 @@ use crate::error::SupertonicError;
 
 pub struct UnicodeProcessor {
     indexer: Vec<i64>,
+    normalizer: TextNormalizer,
+    context: NormalizeContext,
+    g2p: Option<Arc<dyn G2p>>,
+    unknown_chars: UnknownCharPolicy,
+    /// Problems met while processing, collected by the engine after each render
+    warnings: Mutex<Vec<SynthesisWarning>>,
+}
+
+/// What `UnicodeProcessor` does with characters the model has no token for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownCharPolicy {
+    /// Drop the character
+    #[default]
+    Skip,
+    /// Read the character as a space
+    ReplaceWithSpace,
+    /// Fail with the character and its position
+    Error,
+}
+
+impl std::str::FromStr for UnknownCharPolicy {
+    type Err = SupertonicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(UnknownCharPolicy::Skip),
+            "space" | "replace_with_space" => Ok(UnknownCharPolicy::ReplaceWithSpace),
+            "error" => Ok(UnknownCharPolicy::Error),
+            other => Err(SupertonicError::Validation(format!(
+                "unknown character policy '{}' (expected skip, space or error)",
+                other
+            ))),
+        }
+    }
+}
+
+/// A character of preprocessed text the model has no token for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct UnsupportedChar {
+    pub ch: char,
+    /// Index of the character (not byte) in the preprocessed text
+    pub position: usize,
+}
+
+/// Copies the tables and settings; warnings collected so far stay with the
+/// original
+impl Clone for UnicodeProcessor {
+    fn clone(&self) -> Self {
+        UnicodeProcessor {
+            indexer: self.indexer.clone(),
+            normalizer: self.normalizer.clone(),
+            context: self.context.clone(),
+            g2p: self.g2p.clone(),
+            unknown_chars: self.unknown_chars,
+            warnings: Mutex::new(Vec::new()),
+        }
+    }
 }
 
 impl UnicodeProcessor {
@@ -22,17 +88,188 @@ impl UnicodeProcessor {
         let reader = BufReader::new(file);
         let indexer: Vec<i64> =
             serde_json::from_reader(reader).map_err(SupertonicError::Serialization)?;
-        Ok(UnicodeProcessor { indexer })
+        Ok(UnicodeProcessor {
+            indexer,
+            normalizer: TextNormalizer::default(),
+            context: NormalizeContext::default(),
+            g2p: None,
+            unknown_chars: UnknownCharPolicy::default(),
+            warnings: Mutex::new(Vec::new()),
+        })
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, SupertonicError> {
         let indexer: Vec<i64> =
             serde_json::from_slice(bytes).map_err(SupertonicError::Serialization)?;
-        Ok(UnicodeProcessor { indexer })
+        Ok(UnicodeProcessor {
+            indexer,
+            normalizer: TextNormalizer::default(),
+            context: NormalizeContext::default(),
+            g2p: None,
+            unknown_chars: UnknownCharPolicy::default(),
+            warnings: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Normalization rules applied before text is indexed
+    pub fn normalizer(&self) -> &TextNormalizer {
+        &self.normalizer
+    }
+
+    /// Mutable access to the normalization rules, e.g. to extend the currency table
+    pub fn normalizer_mut(&mut self) -> &mut TextNormalizer {
+        &mut self.normalizer
+    }
+
+    /// Locale/time zone context applied to normalization and sentence splitting
+    pub fn context(&self) -> &NormalizeContext {
+        &self.context
     }
 
-    pub fn call(&self, text_list: &[String]) -> (Vec<Vec<i64>>, Array3<f32>) {
-        let processed_texts: Vec<String> = text_list.iter().map(|t| preprocess_text(t)).collect();
+    /// Replace the locale/time zone context, returning the previous one
+    pub fn set_context(&mut self, context: NormalizeContext) -> NormalizeContext {
+        std::mem::replace(&mut self.context, context)
+    }
+
+    /// Preprocess `text` with this processor's normalization rules
+    pub fn preprocess(&self, text: &str) -> String {
+        preprocess_text_with_context(text, &self.normalizer, &self.context)
+    }
+
+    /// `preprocess`, reporting what each stage changed
+    pub fn preprocess_traced(&self, text: &str) -> PreprocessReport {
+        preprocess_text_traced(text, &self.normalizer, &self.context)
+    }
+
+    /// Run `g2p` on preprocessed text before it is indexed; `None` disables the stage
+    pub fn set_g2p(&mut self, g2p: Option<Arc<dyn G2p>>) {
+        self.g2p = g2p;
+    }
+
+    /// Preprocess `text` and apply the G2P stage, if any: the text that is tokenized
+    pub fn prepare(&self, text: &str) -> String {
+        let (text, g2p_error) = self.prepare_unrecorded(text);
+        if let Some(e) = g2p_error {
+            tracing::warn!("G2P failed, using graphemes: {}", e);
+            self.warn(SynthesisWarning::G2pFallback {
+                message: e.to_string(),
+            });
+        }
+        text
+    }
+
+    /// `prepare`, handing back a G2P failure instead of recording it
+    fn prepare_unrecorded(&self, text: &str) -> (String, Option<SupertonicError>) {
+        let text = self.preprocess(text);
+        match &self.g2p {
+            Some(g2p) => match g2p.convert(&text) {
+                Ok(converted) => (converted, None),
+                Err(e) => (text, Some(e)),
+            },
+            None => (text, None),
+        }
+    }
+
+    /// Number of token ids `text` becomes after preprocessing and G2P.
+    /// Measuring records no warnings; they are reported when the text is
+    /// synthesized.
+    pub fn token_count(&self, text: &str) -> usize {
+        self.prepare_unrecorded(text).0.chars().count()
+    }
+
+    pub fn unknown_chars(&self) -> UnknownCharPolicy {
+        self.unknown_chars
+    }
+
+    pub fn set_unknown_chars(&mut self, policy: UnknownCharPolicy) {
+        self.unknown_chars = policy;
+    }
+
+    fn token_id(&self, ch: char) -> Option<i64> {
+        self.indexer.get(ch as usize).copied().filter(|&id| id >= 0)
+    }
+
+    /// Each character `text` is tokenized as, with its token id (-1 if the
+    /// model has none), before the unknown-character policy is applied
+    pub fn inspect(&self, text: &str) -> Vec<(char, i64)> {
+        self.prepare(text)
+            .chars()
+            .map(|ch| (ch, self.token_id(ch).unwrap_or(-1)))
+            .collect()
+    }
+
+    /// Characters of `text` that have no token, after preprocessing and G2P
+    pub fn unsupported_chars(&self, text: &str) -> Vec<UnsupportedChar> {
+        self.prepare(text)
+            .chars()
+            .enumerate()
+            .filter(|&(_, ch)| self.token_id(ch).is_none())
+            .map(|(position, ch)| UnsupportedChar { ch, position })
+            .collect()
+    }
+
+    fn warn(&self, warning: SynthesisWarning) {
+        let mut warnings = self.warnings.lock().unwrap();
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+
+    /// Warnings recorded since the last call, oldest first
+    pub(crate) fn take_warnings(&self) -> Vec<SynthesisWarning> {
+        std::mem::take(&mut *self.warnings.lock().unwrap())
+    }
+
+    /// Apply the unknown-character policy to prepared text
+    fn resolve_unknown(&self, text: String) -> Result<String, SupertonicError> {
+        if self.unknown_chars != UnknownCharPolicy::Error {
+            let chars: Vec<UnsupportedChar> = text
+                .chars()
+                .enumerate()
+                .filter(|&(_, ch)| self.token_id(ch).is_none())
+                .map(|(position, ch)| UnsupportedChar { ch, position })
+                .collect();
+            if !chars.is_empty() {
+                self.warn(SynthesisWarning::UnsupportedChars {
+                    text: text.clone(),
+                    chars,
+                    policy: self.unknown_chars,
+                });
+            }
+        }
+        match self.unknown_chars {
+            UnknownCharPolicy::Skip => Ok(text
+                .chars()
+                .filter(|&c| self.token_id(c).is_some())
+                .collect()),
+            UnknownCharPolicy::ReplaceWithSpace => Ok(text
+                .chars()
+                .map(|c| if self.token_id(c).is_some() { c } else { ' ' })
+                .collect()),
+            UnknownCharPolicy::Error => {
+                match text
+                    .chars()
+                    .enumerate()
+                    .find(|&(_, c)| self.token_id(c).is_none())
+                {
+                    Some((position, ch)) => Err(SupertonicError::TextProcessing(format!(
+                        "unsupported character {:?} (U+{:04X}) at position {} of \"{}\"",
+                        ch, ch as u32, position, text
+                    ))),
+                    None => Ok(text),
+                }
+            }
+        }
+    }
+
+    pub fn call(
+        &self,
+        text_list: &[String],
+    ) -> Result<(Vec<Vec<i64>>, Array3<f32>), SupertonicError> {
+        let processed_texts = text_list
+            .iter()
+            .map(|t| self.resolve_unknown(self.prepare(t)))
+            .collect::<Result<Vec<String>, _>>()?;
 
         let text_ids_lengths: Vec<usize> =
             processed_texts.iter().map(|t| t.chars().count()).collect();
@@ -42,29 +279,70 @@ impl UnicodeProcessor {
         let mut text_ids = Vec::new();
         for text in &processed_texts {
             let mut row = vec![0i64; max_len];
-            let unicode_vals = text_to_unicode_values(text);
-            for (j, &val) in unicode_vals.iter().enumerate() {
-                if val < self.indexer.len() {
-                    row[j] = self.indexer[val];
-                } else {
-                    row[j] = -1;
-                }
+            for (j, ch) in text.chars().enumerate() {
+                row[j] = self.token_id(ch).unwrap_or(-1);
             }
             text_ids.push(row);
         }
 
         let text_mask = get_text_mask(&text_ids_lengths);
 
-        (text_ids, text_mask)
+        Ok((text_ids, text_mask))
     }
 }
 
 pub fn preprocess_text(text: &str) -> String {
-    let mut text: String = text.nfkd().collect();
+    preprocess_text_with(text, &TextNormalizer::default())
+}
+
+/// `preprocess_text` with custom normalization rules
+pub fn preprocess_text_with(text: &str, normalizer: &TextNormalizer) -> String {
+    preprocess_text_with_context(text, normalizer, &NormalizeContext::default())
+}
+
+/// `preprocess_text` with custom normalization rules and locale/time zone context
+pub fn preprocess_text_with_context(
+    text: &str,
+    normalizer: &TextNormalizer,
+    context: &NormalizeContext,
+) -> String {
+    preprocess(text, normalizer, context, &mut Trace::none())
+}
+
+/// `preprocess_text_with_context`, also reporting what each stage changed
+pub fn preprocess_text_traced(
+    text: &str,
+    normalizer: &TextNormalizer,
+    context: &NormalizeContext,
+) -> PreprocessReport {
+    let mut steps = Vec::new();
+    let output = preprocess(text, normalizer, context, &mut Trace::new(&mut steps));
+    PreprocessReport {
+        input: text.to_string(),
+        output,
+        steps,
+    }
+}
+
+fn preprocess(
+    text: &str,
+    normalizer: &TextNormalizer,
+    context: &NormalizeContext,
+    trace: &mut Trace,
+) -> String {
+    let mut text = text.to_string();
+    trace.stage(PreprocessStage::UnicodeNormalization, &mut text, |t| {
+        t.nfkd().collect()
+    });
+
+    // Expand currency amounts and other written forms into words
+    normalizer.normalize_traced(&mut text, context, trace);
 
     // Remove emojis (wide Unicode range)
     let emoji_pattern = Regex::new(r"[\x{1F600}-\x{1F64F}\x{1F300}-\x{1F5FF}\x{1F680}-\x{1F6FF}\x{1F700}-\x{1F77F}\x{1F780}-\x{1F7FF}\x{1F800}-\x{1F8FF}\x{1F900}-\x{1F9FF}\x{1FA00}-\x{1FA6F}\x{1FA70}-\x{1FAFF}\x{2600}-\x{26FF}\x{2700}-\x{27BF}\x{1F1E6}-\x{1F1FF}]+").unwrap();
-    text = emoji_pattern.replace_all(&text, "").to_string();
+    trace.stage(PreprocessStage::EmojiRemoved, &mut text, |t| {
+        emoji_pattern.replace_all(t, "").to_string()
+    });
 
     // Replace various dashes and symbols
     let replacements = [
@@ -88,19 +366,29 @@ pub fn preprocess_text(text: &str) -> String {
         ("←", " "),         // left arrow
     ];
 
-    for (from, to) in &replacements {
-        text = text.replace(from, to);
-    }
+    trace.stage(PreprocessStage::Symbols, &mut text, |t| {
+        let mut t = t.to_string();
+        for (from, to) in &replacements {
+            t = t.replace(from, to);
+        }
+        t
+    });
 
     // Remove combining diacritics
     let diacritics_pattern = Regex::new(r"[\u{0302}\u{0303}\u{0304}\u{0305}\u{0306}\u{0307}\u{0308}\u{030A}\u{030B}\u{030C}\u{0327}\u{0328}\u{0329}\u{032A}\u{032B}\u{032C}\u{032D}\u{032E}\u{032F}]").unwrap();
-    text = diacritics_pattern.replace_all(&text, "").to_string();
+    trace.stage(PreprocessStage::Diacritics, &mut text, |t| {
+        diacritics_pattern.replace_all(t, "").to_string()
+    });
 
     // Remove special symbols
     let special_symbols = ["♥", "☆", "♡", "©", "\\"];
-    for symbol in &special_symbols {
-        text = text.replace(symbol, "");
-    }
+    trace.stage(PreprocessStage::Symbols, &mut text, |t| {
+        let mut t = t.to_string();
+        for symbol in &special_symbols {
+            t = t.replace(symbol, "");
+        }
+        t
+    });
 
     // Replace known expressions
     let expr_replacements = [
@@ -109,57 +397,63 @@ pub fn preprocess_text(text: &str) -> String {
         ("i.e.,", "that is, "),
     ];
 
-    for (from, to) in &expr_replacements {
-        text = text.replace(from, to);
-    }
-
-    // Fix spacing around punctuation
-    text = Regex::new(r" ,")
-        .unwrap()
-        .replace_all(&text, ",")
-        .to_string();
-    text = Regex::new(r" \.")
-        .unwrap()
-        .replace_all(&text, ".")
-        .to_string();
-    text = Regex::new(r" !")
-        .unwrap()
-        .replace_all(&text, "!")
-        .to_string();
-    text = Regex::new(r" \?")
-        .unwrap()
-        .replace_all(&text, "?")
-        .to_string();
-    text = Regex::new(r" ;")
-        .unwrap()
-        .replace_all(&text, ";")
-        .to_string();
-    text = Regex::new(r" :")
-        .unwrap()
-        .replace_all(&text, ":")
-        .to_string();
-    text = Regex::new(r" '")
-        .unwrap()
-        .replace_all(&text, "'")
-        .to_string();
-
-    // Remove duplicate quotes
-    while text.contains("\"\"") {
-        text = text.replace("\"\"", "\"");
-    }
-    while text.contains("''") {
-        text = text.replace("''", "'");
-    }
-    while text.contains("``") {
-        text = text.replace("``", "`");
-    }
+    trace.stage(PreprocessStage::Expressions, &mut text, |t| {
+        let mut t = t.to_string();
+        for (from, to) in &expr_replacements {
+            t = t.replace(from, to);
+        }
+        t
+    });
+
+    trace.stage(PreprocessStage::Punctuation, &mut text, |t| {
+        // Fix spacing around punctuation
+        let mut text = Regex::new(r" ,").unwrap().replace_all(t, ",").to_string();
+        text = Regex::new(r" \.")
+            .unwrap()
+            .replace_all(&text, ".")
+            .to_string();
+        text = Regex::new(r" !")
+            .unwrap()
+            .replace_all(&text, "!")
+            .to_string();
+        text = Regex::new(r" \?")
+            .unwrap()
+            .replace_all(&text, "?")
+            .to_string();
+        text = Regex::new(r" ;")
+            .unwrap()
+            .replace_all(&text, ";")
+            .to_string();
+        text = Regex::new(r" :")
+            .unwrap()
+            .replace_all(&text, ":")
+            .to_string();
+        text = Regex::new(r" '")
+            .unwrap()
+            .replace_all(&text, "'")
+            .to_string();
+
+        // Remove duplicate quotes
+        while text.contains("\"\"") {
+            text = text.replace("\"\"", "\"");
+        }
+        while text.contains("''") {
+            text = text.replace("''", "'");
+        }
+        while text.contains("``") {
+            text = text.replace("``", "`");
+        }
+        text
+    });
 
     // Remove extra spaces
-    text = Regex::new(r"\s+")
-        .unwrap()
-        .replace_all(&text, " ")
-        .to_string();
-    text = text.trim().to_string();
+    trace.stage(PreprocessStage::Whitespace, &mut text, |t| {
+        Regex::new(r"\s+")
+            .unwrap()
+            .replace_all(t, " ")
+            .trim()
+            .to_string()
+    });
 
     // If text doesn't end with punctuation, quotes, or closing brackets, add a period
     if !text.is_empty() {
@@ -167,13 +461,122 @@ pub fn preprocess_text(text: &str) -> String {
             Regex::new(r#"[.!?;:,'"\u{201C}\u{201D}\u{2018}\u{2019})\]}…。」』】〉》›»]$"#)
                 .unwrap();
         if !ends_with_punct.is_match(&text) {
-            text.push('.');
+            trace.stage(PreprocessStage::TrailingPeriod, &mut text, |t| {
+                format!("{}.", t)
+            });
         }
     }
 
     text
 }
 
+// ============================================================================
+// Preprocessing Reports
+// ============================================================================
+
+/// Stage of preprocessing that changed the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreprocessStage {
+    /// Compatibility decomposition (NFKD), e.g. "ﬁ" to "fi"
+    UnicodeNormalization,
+    RulesScript,
+    EmojiDescribed,
+    UrlsAndEmails,
+    Dates,
+    Times,
+    RelativeDates,
+    Acronyms,
+    /// Numbers rewritten from the locale's separators ("3,5" to "3.5")
+    LocaleNumbers,
+    Currency,
+    EmojiRemoved,
+    /// Dashes, quotes and symbols replaced or removed
+    Symbols,
+    Diacritics,
+    /// "@", "e.g." and "i.e." read as words
+    Expressions,
+    /// Spacing before punctuation and repeated quotes
+    Punctuation,
+    Whitespace,
+    TrailingPeriod,
+}
+
+/// One stage's change, trimmed to the part of the text that differs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PreprocessStep {
+    pub stage: PreprocessStage,
+    pub before: String,
+    pub after: String,
+}
+
+/// What preprocessing did to a text, for showing users what will be spoken
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PreprocessReport {
+    pub input: String,
+    pub output: String,
+    /// Stages that changed the text, in the order they ran
+    pub steps: Vec<PreprocessStep>,
+}
+
+/// Records preprocessing stages when a report is requested
+pub(crate) struct Trace<'a> {
+    steps: Option<&'a mut Vec<PreprocessStep>>,
+}
+
+impl<'a> Trace<'a> {
+    pub(crate) fn none() -> Self {
+        Trace { steps: None }
+    }
+
+    pub(crate) fn new(steps: &'a mut Vec<PreprocessStep>) -> Self {
+        Trace { steps: Some(steps) }
+    }
+
+    /// Replace `text` with `f(text)`, recording the change
+    pub(crate) fn stage(
+        &mut self,
+        stage: PreprocessStage,
+        text: &mut String,
+        f: impl FnOnce(&str) -> String,
+    ) {
+        let after = f(text);
+        if let Some(steps) = self.steps.as_deref_mut() {
+            if after != *text {
+                let (before, changed) = changed_span(text, &after);
+                steps.push(PreprocessStep {
+                    stage,
+                    before: before.to_string(),
+                    after: changed.to_string(),
+                });
+            }
+        }
+        *text = after;
+    }
+}
+
+/// The parts of `before` and `after` between their common prefix and suffix
+fn changed_span<'s>(before: &'s str, after: &'s str) -> (&'s str, &'s str) {
+    let prefix: usize = before
+        .chars()
+        .zip(after.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    let (before_rest, after_rest) = (&before[prefix..], &after[prefix..]);
+    let suffix: usize = before_rest
+        .chars()
+        .rev()
+        .zip(after_rest.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    (
+        &before_rest[..before_rest.len() - suffix],
+        &after_rest[..after_rest.len() - suffix],
+    )
+}
+
 pub fn text_to_unicode_values(text: &str) -> Vec<usize> {
     text.chars().map(|c| c as usize).collect()
 }
@@ -200,19 +603,130 @@ pub fn get_text_mask(text_ids_lengths: &[usize]) -> Array3<f32> {
 // Text Chunking
 // ============================================================================
 
-const MAX_CHUNK_LENGTH: usize = 300;
+pub(crate) const MAX_CHUNK_LENGTH: usize = 300;
 
 const ABBREVIATIONS: &[&str] = &[
     "Dr.", "Mr.", "Mrs.", "Ms.", "Prof.", "Sr.", "Jr.", "St.", "Ave.", "Rd.", "Blvd.", "Dept.",
     "Inc.", "Ltd.", "Co.", "Corp.", "etc.", "vs.", "i.e.", "e.g.", "Ph.D.",
 ];
 
+const ABBREVIATIONS_DE: &[&str] = &[
+    "Dr.", "Prof.", "Hr.", "Fr.", "Nr.", "Str.", "bzw.", "usw.", "vgl.", "ca.", "z.B.", "d.h.",
+];
+
+const ABBREVIATIONS_FR: &[&str] = &["M.", "Mme.", "Mlle.", "Dr.", "Pr.", "etc.", "cf.", "p.ex."];
+
+const ABBREVIATIONS_ES: &[&str] = &["Sr.", "Sra.", "Srta.", "Dr.", "Dra.", "Ud.", "Uds.", "etc."];
+
+/// Abbreviations that don't end a sentence, by the language of a BCP 47 locale
+fn abbreviations(locale: Option<&str>) -> &'static [&'static str] {
+    let language = locale
+        .and_then(|l| l.split(['-', '_']).next())
+        .map(|l| l.to_ascii_lowercase());
+    match language.as_deref() {
+        None | Some("en") => ABBREVIATIONS,
+        Some("de") => ABBREVIATIONS_DE,
+        Some("fr") => ABBREVIATIONS_FR,
+        Some("es") => ABBREVIATIONS_ES,
+        Some(_) => &[],
+    }
+}
+
+/// Kind of break that follows a chunk, which decides the pause inserted after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkBoundary {
+    /// End of a paragraph (or of the text)
+    Paragraph,
+    /// End of a sentence within a paragraph
+    Sentence,
+    /// Clause break inside a long sentence
+    Clause,
+    /// Between words of an over-long clause
+    Word,
+}
+
 pub fn chunk_text(text: &str, max_len: Option<usize>) -> Vec<String> {
+    chunk_text_with_locale(text, max_len, None)
+}
+
+/// `chunk_text` with sentence boundaries tuned for `locale` ("en-US", "de")
+pub fn chunk_text_with_locale(
+    text: &str,
+    max_len: Option<usize>,
+    locale: Option<&str>,
+) -> Vec<String> {
+    chunk_text_with_boundaries(text, max_len, locale)
+        .into_iter()
+        .map(|(chunk, _)| chunk)
+        .collect()
+}
+
+/// `chunk_text_with_locale`, also reporting the kind of break after each chunk
+pub fn chunk_text_with_boundaries(
+    text: &str,
+    max_len: Option<usize>,
+    locale: Option<&str>,
+) -> Vec<(String, ChunkBoundary)> {
     let max_len = max_len.unwrap_or(MAX_CHUNK_LENGTH);
+    chunk_text_measured(text, max_len, locale, &|s: &str| s.chars().count())
+}
+
+/// Chunk `text` so each chunk encodes to at most `max_tokens` token ids.
+///
+/// Lengths are measured with `processor`, so number, date and URL expansion
+/// and the G2P stage are accounted for; pieces are measured separately and
+/// summed, which errs on the long side. Each distinct piece is prepared once.
+pub fn chunk_text_by_tokens(
+    text: &str,
+    max_tokens: usize,
+    processor: &UnicodeProcessor,
+    locale: Option<&str>,
+) -> Vec<String> {
+    let counter = TokenCounter::new(processor);
+    chunk_text_measured(text, max_tokens, locale, &|s: &str| counter.count(s))
+        .into_iter()
+        .map(|(chunk, _)| chunk)
+        .collect()
+}
+
+/// Token counts for chunking, remembered per piece: an over-long clause is
+/// measured word by word, and a G2P stage such as `EspeakG2p` costs a
+/// process per measurement
+pub(crate) struct TokenCounter<'a> {
+    processor: &'a UnicodeProcessor,
+    counts: RefCell<HashMap<String, usize>>,
+}
+
+impl<'a> TokenCounter<'a> {
+    pub(crate) fn new(processor: &'a UnicodeProcessor) -> Self {
+        TokenCounter {
+            processor,
+            counts: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn count(&self, text: &str) -> usize {
+        if let Some(&count) = self.counts.borrow().get(text) {
+            return count;
+        }
+        let count = self.processor.token_count(text);
+        self.counts.borrow_mut().insert(text.to_string(), count);
+        count
+    }
+}
+
+pub(crate) fn chunk_text_measured(
+    text: &str,
+    max_len: usize,
+    locale: Option<&str>,
+    measure: &dyn Fn(&str) -> usize,
+) -> Vec<(String, ChunkBoundary)> {
+    let max_len = max_len.max(1);
     let text = text.trim();
 
     if text.is_empty() {
-        return vec![String::new()];
+        return vec![(String::new(), ChunkBoundary::Paragraph)];
     }
 
     // Split by paragraphs
@@ -220,19 +734,21 @@ pub fn chunk_text(text: &str, max_len: Option<usize>) -> Vec<String> {
     let paragraphs: Vec<&str> = para_re.split(text).collect();
     let mut chunks = Vec::new();
 
+    // Lengths come from `measure` (characters by default), so multibyte scripts get the same budget
     for para in paragraphs {
         let para = para.trim();
         if para.is_empty() {
             continue;
         }
 
-        if para.len() <= max_len {
-            chunks.push(para.to_string());
+        if measure(para) <= max_len {
+            chunks.push((para.to_string(), ChunkBoundary::Paragraph));
             continue;
         }
+        let para_start = chunks.len();
 
         // Split by sentences
-        let sentences = split_sentences(para);
+        let sentences = split_sentences(para, locale);
         let mut current = String::new();
         let mut current_len = 0;
 
@@ -242,131 +758,167 @@ pub fn chunk_text(text: &str, max_len: Option<usize>) -> Vec<String> {
                 continue;
             }
 
-            let sentence_len = sentence.len();
+            let sentence_len = measure(sentence);
             if sentence_len > max_len {
                 // If sentence is longer than max_len, split by comma or space
                 if !current.is_empty() {
-                    chunks.push(current.trim().to_string());
+                    chunks.push((current.trim().to_string(), ChunkBoundary::Sentence));
                     current.clear();
                     current_len = 0;
                 }
 
-                // Try splitting by comma
-                let parts: Vec<&str> = sentence.split(',').collect();
-                for part in parts {
-                    let part = part.trim();
+                // Try splitting by comma; CJK commas stay attached to their clause
+                let mut joiner = "";
+                let parts: Vec<&str> = sentence.split_inclusive(CLAUSE_BREAKS).collect();
+                for (part_index, raw) in parts.iter().enumerate() {
+                    let raw = raw.trim();
+                    let (part, next_joiner) = match raw.strip_suffix(',') {
+                        Some(part) => (part.trim_end(), ", "),
+                        None => (raw, word_separator(raw)),
+                    };
                     if part.is_empty() {
                         continue;
                     }
 
-                    let part_len = part.len();
+                    let part_len = measure(part);
                     if part_len > max_len {
                         // Split by space as last resort
-                        let words: Vec<&str> = part.split_whitespace().collect();
                         let mut word_chunk = String::new();
                         let mut word_chunk_len = 0;
 
-                        for word in words {
-                            let word_len = word.len();
-                            if word_chunk_len + word_len + 1 > max_len && !word_chunk.is_empty() {
-                                chunks.push(word_chunk.trim().to_string());
+                        for word in part
+                            .split_whitespace()
+                            .flat_map(|w| split_long_word(w, max_len))
+                        {
+                            let word_len = measure(&word);
+                            let separator = word_separator(&word_chunk);
+                            if word_chunk_len + word_len + separator.len() > max_len
+                                && !word_chunk.is_empty()
+                            {
+                                chunks.push((word_chunk.trim().to_string(), ChunkBoundary::Word));
                                 word_chunk.clear();
                                 word_chunk_len = 0;
                             }
 
                             if !word_chunk.is_empty() {
-                                word_chunk.push(' ');
-                                word_chunk_len += 1;
+                                word_chunk.push_str(separator);
+                                word_chunk_len += separator.len();
                             }
-                            word_chunk.push_str(word);
+                            word_chunk.push_str(&word);
                             word_chunk_len += word_len;
                         }
 
                         if !word_chunk.is_empty() {
-                            chunks.push(word_chunk.trim().to_string());
+                            let boundary = if part_index + 1 == parts.len() {
+                                ChunkBoundary::Sentence
+                            } else {
+                                ChunkBoundary::Clause
+                            };
+                            chunks.push((word_chunk.trim().to_string(), boundary));
                         }
                     } else {
-                        if current_len + part_len + 1 > max_len && !current.is_empty() {
-                            chunks.push(current.trim().to_string());
+                        let joiner_len = joiner.chars().count();
+                        if current_len + part_len + joiner_len > max_len && !current.is_empty() {
+                            chunks.push((current.trim().to_string(), ChunkBoundary::Clause));
                             current.clear();
                             current_len = 0;
                         }
 
                         if !current.is_empty() {
-                            current.push_str(", ");
-                            current_len += 2;
+                            current.push_str(joiner);
+                            current_len += joiner_len;
                         }
                         current.push_str(part);
                         current_len += part_len;
                     }
+                    joiner = next_joiner;
                 }
                 continue;
             }
 
-            if current_len + sentence_len + 1 > max_len && !current.is_empty() {
-                chunks.push(current.trim().to_string());
+            let separator = word_separator(&current);
+            if current_len + sentence_len + separator.len() > max_len && !current.is_empty() {
+                chunks.push((current.trim().to_string(), ChunkBoundary::Sentence));
                 current.clear();
                 current_len = 0;
             }
 
             if !current.is_empty() {
-                current.push(' ');
-                current_len += 1;
+                current.push_str(separator);
+                current_len += separator.len();
             }
             current.push_str(sentence);
             current_len += sentence_len;
         }
 
         if !current.is_empty() {
-            chunks.push(current.trim().to_string());
+            chunks.push((current.trim().to_string(), ChunkBoundary::Paragraph));
+        }
+        if let Some(last) = chunks[para_start..].last_mut() {
+            last.1 = ChunkBoundary::Paragraph;
         }
     }
 
     if chunks.is_empty() {
-        vec![String::new()]
+        vec![(String::new(), ChunkBoundary::Paragraph)]
     } else {
         chunks
     }
 }
 
-fn split_sentences(text: &str) -> Vec<String> {
-    // Rust's regex doesn't support lookbehind, so we use a simpler approach
-    // Split on sentence boundaries and then check if they're abbreviations
-    let re = Regex::new(r"([.!?])\s+").unwrap();
+/// Clause separators a long sentence may be split after
+const CLAUSE_BREAKS: &[char] = &[',', '、', '，'];
+
+/// Chinese and Japanese text, which is written without spaces between words
+fn is_unspaced_script(c: char) -> bool {
+    matches!(c,
+        '\u{3000}'..='\u{30FF}'   // CJK punctuation, Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}' // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+        | '\u{FF00}'..='\u{FFEF}' // Fullwidth forms
+    )
+}
 
-    // Find all matches
-    let matches: Vec<_> = re.find_iter(text).collect();
-    if matches.is_empty() {
-        return vec![text.to_string()];
+/// Separator placed after `text` when the next piece is appended
+fn word_separator(text: &str) -> &'static str {
+    match text.chars().next_back() {
+        Some(c) if is_unspaced_script(c) => "",
+        _ => " ",
     }
+}
 
-    let mut sentences = Vec::new();
-    let mut last_end = 0;
-
-    for m in matches {
-        // Get the text before the punctuation
-        let before_punc = &text[last_end..m.start()];
+/// Cut a word longer than `max_len` characters into `max_len`-character pieces
+fn split_long_word(word: &str, max_len: usize) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+    chars
+        .chunks(max_len)
+        .map(|piece| piece.iter().collect())
+        .collect()
+}
 
-        // Check if this ends with an abbreviation
-        let mut is_abbrev = false;
-        for abbrev in ABBREVIATIONS {
-            let combined = format!("{}{}", before_punc.trim(), &text[m.start()..m.start() + 1]);
-            if combined.ends_with(abbrev) {
-                is_abbrev = true;
-                break;
+/// Split text into sentences on Unicode (UAX #29) sentence boundaries.
+///
+/// Handles CJK terminators (。？！), closing quotes and brackets after a
+/// terminator (」』), runs like "?!" and "...", and decimal numbers. A break
+/// after one of the locale's abbreviations ("Dr.", "z.B.") is undone; `None`
+/// uses the English list.
+pub fn split_sentences(text: &str, locale: Option<&str>) -> Vec<String> {
+    let abbreviations = abbreviations(locale);
+    let mut sentences: Vec<String> = Vec::new();
+    let mut continues = false;
+
+    for segment in text.split_sentence_bounds() {
+        if continues {
+            if let Some(last) = sentences.last_mut() {
+                last.push_str(segment);
             }
+        } else {
+            sentences.push(segment.to_string());
         }
-
-        if !is_abbrev {
-            // This is a real sentence boundary
-            sentences.push(text[last_end..m.end()].to_string());
-            last_end = m.end();
-        }
-    }
-
-    // Add the remaining text
-    if last_end < text.len() {
-        sentences.push(text[last_end..].to_string());
+        let sentence = sentences.last().map(|s| s.trim_end()).unwrap_or_default();
+        continues = abbreviations
+            .iter()
+            .any(|abbrev| ends_with_word(sentence, abbrev));
     }
 
     if sentences.is_empty() {
@@ -375,3 +927,13 @@ fn split_sentences(text: &str) -> Vec<String> {
         sentences
     }
 }
+
+/// Whether `text` ends with `word` preceded by a non-alphanumeric character (or nothing)
+fn ends_with_word(text: &str, word: &str) -> bool {
+    text.strip_suffix(word).is_some_and(|before| {
+        before
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric())
+    })
+}
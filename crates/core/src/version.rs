@@ -0,0 +1,64 @@
+use serde::Serialize;
+
+// ============================================================================
+// Version Information
+// ============================================================================
+
+/// `tts.json` schema versions this build can load. The released configs carry
+/// no version field and are treated as version 1.
+pub const SUPPORTED_CONFIG_VERSIONS: &[u32] = &[1];
+
+/// Build and runtime version details, for bug reports and compatibility checks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionInfo {
+    /// Version of the `supertonic-tts` crate
+    pub version: &'static str,
+    /// ONNX Runtime API version the `ort` bindings target ("1.22")
+    pub ort_version: String,
+    /// Cargo features compiled into this build
+    pub features: Vec<&'static str>,
+    /// Supported `tts.json` schema versions
+    pub config_versions: Vec<u32>,
+}
+
+/// Version information for the current build
+pub fn version() -> VersionInfo {
+    let features = [
+        ("desktop", cfg!(feature = "desktop")),
+        ("android", cfg!(feature = "android")),
+        ("rocm", cfg!(feature = "rocm")),
+        ("scripting", cfg!(feature = "scripting")),
+        ("espeak", cfg!(feature = "espeak")),
+        ("epub", cfg!(feature = "epub")),
+        ("dsp", cfg!(feature = "dsp")),
+        ("playback", cfg!(feature = "playback")),
+        ("rodio", cfg!(feature = "rodio")),
+        ("test-hooks", cfg!(feature = "test-hooks")),
+    ];
+
+    VersionInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        ort_version: format!("1.{}", ort::MINOR_VERSION),
+        features: features
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| *name)
+            .collect(),
+        config_versions: SUPPORTED_CONFIG_VERSIONS.to_vec(),
+    }
+}
+
+impl std::fmt::Display for VersionInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let features = if self.features.is_empty() {
+            "none".to_string()
+        } else {
+            self.features.join(", ")
+        };
+        let configs: Vec<String> = self.config_versions.iter().map(|v| v.to_string()).collect();
+        writeln!(f, "supertonic-tts {}", self.version)?;
+        writeln!(f, "ONNX Runtime API: {}", self.ort_version)?;
+        writeln!(f, "Features: {}", features)?;
+        write!(f, "Model config versions: {}", configs.join(", "))
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::catalog::VoiceCatalog;
+use crate::error::SupertonicError;
+use crate::model::{load_voice_style, Style};
+
+// ============================================================================
+// Voice Manager
+// ============================================================================
+//
+// Apps and servers switch between a handful of voices by id. `VoiceManager`
+// loads each voice of a catalog on first use, keeps the most recently used
+// ones up to a capacity, and hands out shared `Arc<Style>`s, so several
+// threads can synthesize with the same voice without reloading or copying it.
+
+/// Voices of one directory, loaded on demand by id
+pub struct VoiceManager {
+    dir: PathBuf,
+    catalog: VoiceCatalog,
+    /// Voices kept loaded (`0` = no limit)
+    capacity: usize,
+    /// Least recently used first
+    loaded: Mutex<VecDeque<(String, Arc<Style>)>>,
+}
+
+impl VoiceManager {
+    /// Manage the voices of `dir`, as listed by `VoiceCatalog::discover`
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, SupertonicError> {
+        let catalog = VoiceCatalog::discover(&dir)?;
+        Ok(Self::new(dir, catalog))
+    }
+
+    /// Manage the voices of `catalog`, whose files are relative to `dir`
+    pub fn new<P: AsRef<Path>>(dir: P, catalog: VoiceCatalog) -> Self {
+        VoiceManager {
+            dir: dir.as_ref().to_path_buf(),
+            catalog,
+            capacity: 0,
+            loaded: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Keep at most `capacity` voices loaded, evicting the least recently
+    /// used (`0` = no limit)
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn catalog(&self) -> &VoiceCatalog {
+        &self.catalog
+    }
+
+    /// The style of `id`, loaded from its file the first time it is asked
+    /// for. Another thread asking for the same voice meanwhile may load it
+    /// too; one of the copies is kept.
+    pub fn get(&self, id: &str) -> Result<Arc<Style>, SupertonicError> {
+        if let Some(style) = self.touch(id) {
+            return Ok(style);
+        }
+        // Loaded without holding the lock, so other voices stay available
+        let path = self.catalog.style_path(&self.dir, id)?;
+        let style = load_voice_style(&[path.to_string_lossy().into_owned()], false)?;
+        Ok(self.insert(id, style))
+    }
+
+    /// Load every voice in `ids` now rather than on first use
+    pub fn preload(&self, ids: &[&str]) -> Result<(), SupertonicError> {
+        for id in ids {
+            self.get(id)?;
+        }
+        Ok(())
+    }
+
+    /// Add a style loaded elsewhere, e.g. from bytes or a bundle, replacing
+    /// any loaded under `id`; `get` returns it without consulting the catalog
+    pub fn insert(&self, id: &str, style: Style) -> Arc<Style> {
+        let style = Arc::new(style);
+        let mut loaded = self.lock();
+        loaded.retain(|(loaded_id, _)| loaded_id != id);
+        if self.capacity > 0 && loaded.len() >= self.capacity {
+            loaded.pop_front();
+        }
+        loaded.push_back((id.to_string(), style.clone()));
+        style
+    }
+
+    /// Drop `id` from memory, returning whether it was loaded; styles already
+    /// handed out stay valid
+    pub fn evict(&self, id: &str) -> bool {
+        let mut loaded = self.lock();
+        let before = loaded.len();
+        loaded.retain(|(loaded_id, _)| loaded_id != id);
+        loaded.len() != before
+    }
+
+    /// Drop every loaded voice
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    pub fn is_loaded(&self, id: &str) -> bool {
+        self.lock().iter().any(|(loaded_id, _)| loaded_id == id)
+    }
+
+    /// Ids of the loaded voices, least recently used first
+    pub fn loaded_ids(&self) -> Vec<String> {
+        self.lock().iter().map(|(id, _)| id.clone()).collect()
+    }
+
+    /// The loaded style of `id`, marked as most recently used
+    fn touch(&self, id: &str) -> Option<Arc<Style>> {
+        let mut loaded = self.lock();
+        let index = loaded.iter().position(|(loaded_id, _)| loaded_id == id)?;
+        let entry = loaded.remove(index)?;
+        let style = entry.1.clone();
+        loaded.push_back(entry);
+        Some(style)
+    }
+
+    /// The loaded voices, even if a thread panicked while holding them
+    fn lock(&self) -> MutexGuard<'_, VecDeque<(String, Arc<Style>)>> {
+        self.loaded.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
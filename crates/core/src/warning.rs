@@ -0,0 +1,79 @@
+use serde::Serialize;
+use std::fmt;
+
+use crate::text::{UnknownCharPolicy, UnsupportedChar};
+
+// ============================================================================
+// Synthesis Warnings
+// ============================================================================
+
+/// A non-fatal problem with the last render, see `TextToSpeech::last_warnings`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SynthesisWarning {
+    /// Characters without a model token were skipped or read as spaces
+    UnsupportedChars {
+        /// The preprocessed text they were found in
+        text: String,
+        chars: Vec<UnsupportedChar>,
+        policy: UnknownCharPolicy,
+    },
+    /// The G2P stage failed and the text was read as graphemes
+    G2pFallback { message: String },
+    /// A sentence too long for one chunk was split between words
+    SplitBetweenWords { chunk: usize },
+    /// Samples beyond full scale, which are clipped when written as integer PCM
+    Clipped { samples: usize, peak: f32 },
+}
+
+impl fmt::Display for SynthesisWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SynthesisWarning::UnsupportedChars {
+                text,
+                chars,
+                policy,
+            } => {
+                let list: String = chars.iter().map(|c| c.ch).collect();
+                write!(
+                    f,
+                    "{} unsupported character(s) {:?} {} in \"{}\"",
+                    chars.len(),
+                    list,
+                    if *policy == UnknownCharPolicy::ReplaceWithSpace {
+                        "read as spaces"
+                    } else {
+                        "skipped"
+                    },
+                    text
+                )
+            }
+            SynthesisWarning::G2pFallback { message } => {
+                write!(f, "G2P failed, read as graphemes: {}", message)
+            }
+            SynthesisWarning::SplitBetweenWords { chunk } => write!(
+                f,
+                "chunk {} ends mid-sentence; the sentence was too long and split between words",
+                chunk
+            ),
+            SynthesisWarning::Clipped { samples, peak } => write!(
+                f,
+                "{} sample(s) exceed full scale (peak {:.2}) and will clip in 16-bit output",
+                samples, peak
+            ),
+        }
+    }
+}
+
+/// `Clipped` warning for `samples`, if any exceed full scale
+pub(crate) fn clipping_warning(samples: &[f32]) -> Option<SynthesisWarning> {
+    let clipped = samples.iter().filter(|s| s.abs() > 1.0).count();
+    if clipped == 0 {
+        return None;
+    }
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    Some(SynthesisWarning::Clipped {
+        samples: clipped,
+        peak,
+    })
+}
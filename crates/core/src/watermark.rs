@@ -0,0 +1,141 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// ============================================================================
+// Watermarking
+// ============================================================================
+//
+// Spread-spectrum marking: a keyed pseudo-random +/-1 sequence, repeating every
+// `WATERMARK_PERIOD` samples, is added to the audio scaled by its envelope, so
+// the mark hides under the speech and vanishes in silence. The detector
+// whitens the audio with a first difference (speech energy sits low, the mark
+// is flat), folds it onto one period, and correlates against every rotation
+// of the sequence, which also finds marks in audio trimmed at the start.
+// Gain changes and added noise leave the mark detectable; resampling, pitch
+// or time changes and lossy codecs may not.
+
+/// Length of the repeating mark sequence in samples
+const WATERMARK_PERIOD: usize = 4096;
+
+/// Release time of the envelope the mark follows
+const ENVELOPE_RELEASE_SECS: f32 = 0.02;
+
+/// Level of the mark relative to the audio envelope, in dB
+pub const DEFAULT_WATERMARK_STRENGTH_DB: f32 = -36.0;
+
+/// Detection score above which `WatermarkDetection::is_present` holds.
+///
+/// Scores of unmarked audio are roughly standard normal per rotation; this
+/// threshold keeps false positives below one in a million files across all
+/// rotations.
+pub const WATERMARK_THRESHOLD: f32 = 6.0;
+
+/// An inaudible mark identifying audio as synthetic, keyed so only holders
+/// of the key can detect it
+#[derive(Debug, Clone)]
+pub struct Watermark {
+    sequence: Vec<f32>,
+    strength_db: f32,
+}
+
+/// Result of `Watermark::detect`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WatermarkDetection {
+    /// Normalized correlation with the mark; around 0 for unmarked audio
+    pub score: f32,
+    /// Position in the mark sequence the audio starts at (non-zero if trimmed)
+    pub offset: usize,
+}
+
+impl WatermarkDetection {
+    pub fn is_present(&self) -> bool {
+        self.score >= WATERMARK_THRESHOLD
+    }
+}
+
+impl Watermark {
+    pub fn new(key: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(key);
+        let sequence = (0..WATERMARK_PERIOD)
+            .map(|_| if rng.gen::<bool>() { 1.0 } else { -1.0 })
+            .collect();
+        Watermark {
+            sequence,
+            strength_db: DEFAULT_WATERMARK_STRENGTH_DB,
+        }
+    }
+
+    /// Mark at `strength_db` relative to the audio envelope instead of
+    /// `DEFAULT_WATERMARK_STRENGTH_DB`; stronger marks survive more processing
+    pub fn with_strength_db(mut self, strength_db: f32) -> Self {
+        self.strength_db = strength_db;
+        self
+    }
+
+    /// Add the mark to `samples` (mono, any sample rate)
+    pub fn embed(&self, samples: &mut [f32], sample_rate: i32) {
+        let strength = 10f32.powf(self.strength_db / 20.0);
+        let decay = (-1.0 / (ENVELOPE_RELEASE_SECS * sample_rate.max(1) as f32)).exp();
+        let mut envelope = 0.0f32;
+        for (i, sample) in samples.iter_mut().enumerate() {
+            envelope = sample.abs().max(envelope * decay);
+            *sample += strength * envelope * self.sequence[i % WATERMARK_PERIOD];
+        }
+    }
+
+    /// Look for the mark in `samples`
+    pub fn detect(&self, samples: &[f32]) -> WatermarkDetection {
+        let none = WatermarkDetection {
+            score: 0.0,
+            offset: 0,
+        };
+        if samples.len() < 2 {
+            return none;
+        }
+
+        // The difference of `samples[i + 1]` and `samples[i]` pairs with
+        // `sequence[i + 1] - sequence[i]`
+        let mut folded = vec![0.0f64; WATERMARK_PERIOD];
+        for (i, w) in samples.windows(2).enumerate() {
+            folded[(i + 1) % WATERMARK_PERIOD] += (w[1] - w[0]) as f64;
+        }
+        // Variance of the correlation against a random sequence: the
+        // differenced sequence has unit-lag correlation -1/2, so adjacent
+        // terms count too. Measured on the folded audio, so periodic sounds
+        // and marks made with other keys do not inflate the score.
+        let energy: f64 = folded.iter().map(|f| f * f).sum();
+        let lag: f64 = (0..WATERMARK_PERIOD)
+            .map(|j| folded[j] * folded[(j + 1) % WATERMARK_PERIOD])
+            .sum();
+        let variance = 2.0 * (energy - lag);
+        if variance <= 0.0 {
+            return none;
+        }
+        let chips: Vec<f64> = (0..WATERMARK_PERIOD)
+            .map(|j| {
+                let previous = (j + WATERMARK_PERIOD - 1) % WATERMARK_PERIOD;
+                (self.sequence[j] - self.sequence[previous]) as f64
+            })
+            .collect();
+
+        (0..WATERMARK_PERIOD)
+            .map(|offset| {
+                let correlation: f64 = folded
+                    .iter()
+                    .enumerate()
+                    .map(|(j, f)| f * chips[(j + offset) % WATERMARK_PERIOD])
+                    .sum();
+                WatermarkDetection {
+                    score: (correlation / variance.sqrt()) as f32,
+                    offset,
+                }
+            })
+            .fold(none, |best, candidate| {
+                if candidate.score > best.score {
+                    candidate
+                } else {
+                    best
+                }
+            })
+    }
+}
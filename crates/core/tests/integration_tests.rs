@@ -1,5 +1,8 @@
 use std::path::PathBuf;
-use supertonic_tts::{load_text_to_speech, load_voice_style};
+use supertonic_tts::{
+    load_text_to_speech, load_text_to_speech_with_config, load_text_to_speech_with_snapshot,
+    load_voice_style, SessionConfig, SynthesisCache, SynthesisOptions,
+};
 
 #[test]
 fn test_load_components() {
@@ -18,10 +21,90 @@ fn test_load_components() {
         }
     }
 
-    match load_voice_style(&vec![style_path.to_string()], false) {
+    match load_voice_style(&[style_path.to_string()], false) {
         Ok(_) => (),
         Err(e) => {
             panic!("Failed to load voice style: {:?}", e);
         }
     }
 }
+
+#[test]
+fn test_snapshot_keeps_session_config() {
+    let onnx_dir = "assets/onnx";
+    if !PathBuf::from(onnx_dir).join("tts.json").exists() {
+        eprintln!("Assets not found, skipping integration test.");
+        return;
+    }
+
+    // A GPU config where this build can run one
+    let config = SessionConfig::with_gpu(cfg!(feature = "rocm"));
+    let direct = load_text_to_speech_with_config(onnx_dir, &config).unwrap();
+    let snapshot_dir = std::env::temp_dir().join(format!("supertonic-snap-{}", std::process::id()));
+    let snapshot = load_text_to_speech_with_snapshot(onnx_dir, &snapshot_dir, &config).unwrap();
+    assert_eq!(snapshot.session_config(), &config);
+    assert_eq!(snapshot.latent_device(), direct.latent_device());
+    assert_eq!(snapshot.latent_device().is_some(), config.use_gpu);
+    let _ = std::fs::remove_dir_all(&snapshot_dir);
+}
+
+#[test]
+fn test_snapshot_falls_back_when_unwritable() {
+    let onnx_dir = "assets/onnx";
+    if !PathBuf::from(onnx_dir).join("tts.json").exists() {
+        eprintln!("Assets not found, skipping integration test.");
+        return;
+    }
+
+    // A snapshot dir beneath a regular file can never be created
+    let blocker = std::env::temp_dir().join(format!("supertonic-snap-file-{}", std::process::id()));
+    std::fs::write(&blocker, b"not a directory").unwrap();
+    let config = SessionConfig::default();
+    let tts = load_text_to_speech_with_snapshot(onnx_dir, blocker.join("snapshot"), &config)
+        .expect("an unwritable snapshot dir falls back to a plain load");
+    assert_eq!(tts.session_config(), &config);
+    std::fs::remove_file(&blocker).unwrap();
+}
+
+#[test]
+fn test_synthesis_cache_keys_and_invalidation() {
+    let onnx_dir = "assets/onnx";
+    let style_path = "assets/voice_styles/M1.json";
+    if !PathBuf::from(onnx_dir).join("tts.json").exists() || !PathBuf::from(style_path).exists() {
+        eprintln!("Assets not found, skipping integration test.");
+        return;
+    }
+
+    let mut tts = load_text_to_speech(onnx_dir, false).unwrap();
+    let style = load_voice_style(&[style_path.to_string()], false).unwrap();
+    let options = SynthesisOptions::default();
+    let hits = |tts: &supertonic_tts::TextToSpeech| tts.synthesis_cache().unwrap().hits();
+    tts.set_synthesis_cache(Some(SynthesisCache::new(64 << 20)));
+
+    // Seeded renders are cached per seed
+    tts.set_seed(7);
+    let (first, _) = tts
+        .synthesize("Sure, doing that now.", &style, &options)
+        .unwrap();
+    let (again, _) = tts
+        .synthesize("Sure, doing that now.", &style, &options)
+        .unwrap();
+    assert_eq!(hits(&tts), 1);
+    assert_eq!(first, again);
+    tts.set_seed(8);
+    tts.synthesize("Sure, doing that now.", &style, &options)
+        .unwrap();
+    assert_eq!(hits(&tts), 1);
+
+    // Settings that change the rendering drop what was cached under the old ones
+    tts.set_max_chunk_tokens(Some(40));
+    assert!(tts.synthesis_cache().unwrap().is_empty());
+
+    // A post-chunk hook has to see every chunk
+    tts.synthesize("Sure, doing that now.", &style, &options)
+        .unwrap();
+    tts.set_post_chunk_hook(Some(Box::new(|_, _| Ok(()))));
+    tts.synthesize("Sure, doing that now.", &style, &options)
+        .unwrap();
+    assert_eq!(hits(&tts), 1);
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "test-hooks")]
+
+use serde::Deserialize;
+use std::path::PathBuf;
+use supertonic_tts::parity::{compare, RefTensor, ReferenceTensors, StageOutputs, Tolerances};
+use supertonic_tts::{load_text_to_speech, load_voice_style};
+
+/// Inputs the reference tensors in `assets/parity` were exported with
+#[derive(Deserialize)]
+struct ParityInput {
+    text: String,
+    voice_style: String,
+    total_step: usize,
+    speed: f32,
+}
+
+#[test]
+fn test_reference_parity() {
+    let onnx_dir = "assets/onnx";
+    let parity_dir = PathBuf::from("assets/parity");
+
+    if !PathBuf::from(onnx_dir).exists() || !parity_dir.join("input.json").exists() {
+        eprintln!("Parity reference tensors not found, skipping parity test.");
+        return;
+    }
+
+    let input: ParityInput =
+        serde_json::from_slice(&std::fs::read(parity_dir.join("input.json")).unwrap()).unwrap();
+    let reference = ReferenceTensors::load(&parity_dir).unwrap();
+
+    let mut tts = load_text_to_speech(onnx_dir, false).unwrap();
+    let style = load_voice_style(&[input.voice_style], false).unwrap();
+
+    if let Some(noise) = &reference.noise {
+        tts.set_noise_override(Some(noise.to_array3().unwrap()));
+    }
+
+    tts.batch(&[input.text], &style, input.total_step, input.speed)
+        .unwrap();
+
+    let outputs = tts.last_stage_outputs().unwrap();
+    let report = compare(outputs, &reference, &Tolerances::default());
+    for stage in &report.stages {
+        eprintln!(
+            "{:>10}: max {:.3e} mean {:.3e} (tol {:.1e})",
+            stage.stage, stage.max_abs_diff, stage.mean_abs_diff, stage.tolerance
+        );
+    }
+    if let Some(failure) = report.first_failure() {
+        panic!("Parity check diverged at stage: {:?}", failure);
+    }
+}
+
+#[test]
+fn test_parity_compare() {
+    let outputs = StageOutputs {
+        text_ids: vec![vec![3, 1, 4]],
+        durations: vec![1.5],
+        latent: ndarray::Array3::from_shape_vec((1, 2, 2), vec![0.0, 0.5, 1.0, 1.5]).unwrap(),
+        ..StageOutputs::default()
+    };
+    let tensor = |dims: Vec<usize>, data: Vec<f32>| Some(RefTensor { dims, data });
+    let reference = ReferenceTensors {
+        text_ids: tensor(vec![1, 3], vec![3.0, 1.0, 4.0]),
+        durations: tensor(vec![1], vec![1.50005]),
+        latent: tensor(vec![1, 2, 2], vec![0.0, 0.5, 1.0, 1.52]),
+        ..ReferenceTensors::default()
+    };
+
+    let report = compare(&outputs, &reference, &Tolerances::default());
+    let stages: Vec<_> = report.stages.iter().map(|s| s.stage).collect();
+    assert_eq!(stages, ["text_ids", "durations", "latent"]);
+
+    // Within tolerance: exactly matching ids and durations off by less than 1e-4
+    assert!(report.stages[0].passed());
+    assert_eq!(report.stages[0].max_abs_diff, 0.0);
+    assert!(report.stages[1].passed());
+    assert!(report.stages[1].max_abs_diff < 1e-4);
+
+    // Outside tolerance: one latent value off by 0.02, the mean spread over all four
+    let latent = &report.stages[2];
+    assert!(!latent.passed());
+    assert!((latent.max_abs_diff - 0.02).abs() < 1e-6);
+    assert!((latent.mean_abs_diff - 0.005).abs() < 1e-6);
+    assert!(!report.passed());
+    assert_eq!(report.first_failure().map(|s| s.stage), Some("latent"));
+
+    // A shape mismatch fails however close the values are
+    let reshaped = ReferenceTensors {
+        latent: tensor(vec![1, 4, 1], vec![0.0, 0.5, 1.0, 1.5]),
+        ..ReferenceTensors::default()
+    };
+    let report = compare(&outputs, &reshaped, &Tolerances::default());
+    assert!(!report.passed());
+    assert_eq!(report.stages[0].max_abs_diff, f32::INFINITY);
+}
@@ -1,3 +1,6 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use supertonic_tts::model::sample_noisy_latent_with_rng;
 use supertonic_tts::{chunk_text, preprocess_text, sanitize_filename};
 
 #[test]
@@ -29,3 +32,1852 @@ fn test_sanitize_filename() {
     // It replaces non-alphanumeric with '_'
     assert_eq!(sanitized, "Hello_Worl");
 }
+
+#[test]
+fn test_seeded_noisy_latent_is_deterministic() {
+    let duration = [0.5, 0.25];
+    let (a, mask_a) =
+        sample_noisy_latent_with_rng(&duration, 100, 4, 2, 3, &mut StdRng::seed_from_u64(7));
+    let (b, mask_b) =
+        sample_noisy_latent_with_rng(&duration, 100, 4, 2, 3, &mut StdRng::seed_from_u64(7));
+    assert_eq!(a, b);
+    assert_eq!(mask_a, mask_b);
+    // Masked-out frames of the shorter item stay zero
+    assert_eq!(a.shape(), &[2, 6, 7]);
+    assert_eq!(a[[1, 0, 6]], 0.0);
+}
+
+#[test]
+fn test_noisy_latent_buffers_are_reused() {
+    use supertonic_tts::model::{sample_noisy_latent_into, LatentBuffers};
+
+    let mut buffers = LatentBuffers::default();
+    let mut rng = StdRng::seed_from_u64(7);
+    sample_noisy_latent_into(&[1.0], 100, 4, 2, 3, &mut rng, &mut buffers);
+    // A smaller batch afterwards matches a fresh sample of the same batch
+    let duration = [0.5, 0.25];
+    sample_noisy_latent_into(&duration, 100, 4, 2, 3, &mut rng, &mut buffers);
+    let mut expected_rng = StdRng::seed_from_u64(7);
+    sample_noisy_latent_with_rng(&[1.0], 100, 4, 2, 3, &mut expected_rng);
+    let (noise, mask) = sample_noisy_latent_with_rng(&duration, 100, 4, 2, 3, &mut expected_rng);
+    assert_eq!(buffers.noise, noise);
+    assert_eq!(buffers.mask, mask);
+}
+
+#[test]
+fn test_currency_normalization() {
+    use supertonic_tts::{preprocess_text_with, Currency, TextNormalizer};
+
+    assert_eq!(
+        preprocess_text("It costs $12.50"),
+        "It costs twelve dollars and fifty cents."
+    );
+    assert_eq!(preprocess_text("€5 each"), "five euros each.");
+    assert_eq!(
+        preprocess_text("£1,000 or $0.01"),
+        "one thousand pounds or one cent."
+    );
+
+    let mut normalizer = TextNormalizer::default();
+    normalizer.currencies.insert(Currency::new(
+        "₽",
+        "ruble",
+        "rubles",
+        Some(("kopek", "kopeks")),
+    ));
+    assert_eq!(
+        preprocess_text_with("₽21.5", &normalizer),
+        "twenty-one rubles and fifty kopeks."
+    );
+}
+
+#[test]
+fn test_voice_defaults_fill_unset_options() {
+    use supertonic_tts::{load_voice_style_from_bytes, SynthesisOptions};
+
+    let voice = br#"{
+        "style_ttl": { "data": [[[0.0]]], "dims": [1, 1, 1], "type": "float32" },
+        "style_dp": { "data": [[[0.0]]], "dims": [1, 1, 1], "type": "float32" },
+        "defaults": { "speed": 0.95, "total_step": 8 }
+    }"#;
+    let style = load_voice_style_from_bytes(&[voice.as_slice()], false).unwrap();
+
+    let request = SynthesisOptions {
+        total_step: Some(12),
+        ..Default::default()
+    };
+    let resolved = request.or(&style.defaults);
+    assert_eq!(resolved.total_step(), 12);
+    assert_eq!(resolved.speed(), 0.95);
+    assert_eq!(resolved.silence_duration(), 0.3);
+    assert_eq!(resolved.gain(), 1.0);
+    assert_eq!(resolved.output_sample_rate(44100).unwrap(), 44100);
+
+    let resampled = SynthesisOptions {
+        target_sample_rate: Some(16000),
+        ..Default::default()
+    };
+    assert_eq!(
+        resampled.or(&request).output_sample_rate(44100).unwrap(),
+        16000
+    );
+    let invalid = SynthesisOptions {
+        target_sample_rate: Some(0),
+        ..Default::default()
+    };
+    assert!(invalid.output_sample_rate(44100).is_err());
+}
+
+#[test]
+fn test_split_dialogue() {
+    use supertonic_tts::{split_dialogue, NarrationRole};
+
+    let segments = split_dialogue("\"Are you coming?\" she asked. “Soon,” he said.");
+    let roles: Vec<NarrationRole> = segments.iter().map(|s| s.role).collect();
+    assert_eq!(
+        roles,
+        vec![
+            NarrationRole::Dialogue,
+            NarrationRole::Narrator,
+            NarrationRole::Dialogue,
+            NarrationRole::Narrator,
+        ]
+    );
+    assert_eq!(segments[0].text, "\"Are you coming?\"");
+    assert_eq!(segments[1].text, "she asked.");
+    assert_eq!(segments[3].text, "he said.");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_hook_round_trip() {
+    use supertonic_tts::{command_hook, ChunkContext};
+
+    let mut hook = command_hook("cp {input} {output}");
+    let mut samples = vec![0.0f32, 0.5, -0.5, 0.25];
+    let ctx = ChunkContext {
+        index: 0,
+        text: "test",
+        sample_rate: 44100,
+        pause_before: 0.0,
+    };
+    hook(&mut samples, &ctx).unwrap();
+
+    assert_eq!(samples.len(), 4);
+    assert!((samples[1] - 0.5).abs() < 1e-3);
+    assert!((samples[2] + 0.5).abs() < 1e-3);
+}
+
+#[test]
+fn test_url_and_email_normalization() {
+    assert_eq!(
+        preprocess_text("Mail john.doe@example.com today"),
+        "Mail john dot doe at example dot com today."
+    );
+    assert_eq!(
+        preprocess_text("See https://docs.example.com/api/v2 for details."),
+        "See docs dot example dot com slash a p i slash v two for details."
+    );
+    assert_eq!(
+        preprocess_text("Visit www.rust-lang.org."),
+        "Visit w w w dot rust dash lang dot org."
+    );
+}
+
+#[test]
+fn test_acronym_spell_out() {
+    use supertonic_tts::{preprocess_text_with, TextNormalizer};
+
+    assert_eq!(
+        preprocess_text("NASA posted HTML docs for USA users"),
+        "Nasa posted H T M L docs for U S A users."
+    );
+    assert_eq!(preprocess_text("Two URLs"), "Two U R Ls.");
+    assert_eq!(preprocess_text("That is AMAZING"), "That is amazing.");
+
+    let mut normalizer = TextNormalizer::default();
+    normalizer.acronyms.insert_word("gif");
+    normalizer.acronyms.remove_word("NASA");
+    assert_eq!(
+        preprocess_text_with("NASA GIF", &normalizer),
+        "N A S A Gif."
+    );
+}
+
+#[cfg(feature = "scripting")]
+#[test]
+fn test_script_rules() {
+    use std::sync::Arc;
+    use supertonic_tts::{preprocess_text_with, ScriptRules, TextNormalizer};
+
+    let rules = ScriptRules::from_source(
+        r#"
+        fn normalize(text) {
+            if regex_match(text, "^Dr\\. [A-Z]") {
+                text = regex_replace(text, "^Dr\\. ([A-Z])", "Doctor $1");
+            }
+            regex_replace(text, "3 pts", number_to_words(3) + " points")
+        }
+        "#,
+    )
+    .unwrap();
+
+    let normalizer = TextNormalizer {
+        script: Some(Arc::new(rules)),
+        ..Default::default()
+    };
+    assert_eq!(
+        preprocess_text_with("Dr. Smith won 3 pts", &normalizer),
+        "Doctor Smith won three points."
+    );
+
+    assert!(ScriptRules::from_source("fn other(x) { x }").is_err());
+}
+
+#[test]
+fn test_locale_aware_times_and_dates() {
+    use supertonic_tts::{preprocess_text_with_context, NormalizeContext, TextNormalizer};
+
+    let normalizer = TextNormalizer {
+        relative_dates: true,
+        ..Default::default()
+    };
+    let mut us = NormalizeContext::new(Some("en-US"), Some("America/New_York")).unwrap();
+    us.today = chrono::NaiveDate::from_ymd_opt(2025, 10, 14);
+    let mut gb = NormalizeContext::new(Some("en-GB"), Some("Europe/London")).unwrap();
+    gb.today = us.today;
+
+    assert_eq!(
+        preprocess_text_with_context("Meet at 5 PM tomorrow.", &normalizer, &us),
+        "Meet at five p m tomorrow, Wednesday, October fifteenth."
+    );
+    assert_eq!(
+        preprocess_text_with_context("Doors open 17:30 on 3/4/2025", &normalizer, &us),
+        "Doors open seventeen thirty on March fourth, twenty twenty-five."
+    );
+    assert_eq!(
+        preprocess_text_with_context("Doors open 17:30 on 3/4/2025", &normalizer, &gb),
+        "Doors open seventeen thirty on April third, twenty twenty-five."
+    );
+    assert!(NormalizeContext::new(None, Some("Mars/Olympus")).is_err());
+}
+
+#[test]
+fn test_bare_times_get_no_meridiem() {
+    use supertonic_tts::{preprocess_text_with_context, NormalizeContext, TextNormalizer};
+
+    let normalizer = TextNormalizer::default();
+    let us = NormalizeContext::new(Some("en-US"), None).unwrap();
+    let read = |text: &str| preprocess_text_with_context(text, &normalizer, &us);
+
+    // Only a meridiem in the text is read out
+    assert_eq!(
+        read("The bus leaves at 7:30."),
+        "The bus leaves at seven thirty."
+    );
+    assert_eq!(
+        read("The bus leaves at 7:30 pm."),
+        "The bus leaves at seven thirty p m."
+    );
+    assert_eq!(read("Dinner at 7:00."), "Dinner at seven o'clock.");
+    // Verse references and ratios look the same as times
+    assert_eq!(
+        read("Read John 3:16 aloud."),
+        "Read John three sixteen aloud."
+    );
+    assert_eq!(
+        read("Mix it 1:10 with water."),
+        "Mix it one ten with water."
+    );
+}
+
+#[test]
+fn test_locale_numbers_and_currency() {
+    use supertonic_tts::{preprocess_text_with_context, NormalizeContext, TextNormalizer};
+
+    let normalizer = TextNormalizer::default();
+    let read = |text: &str, locale: &str| {
+        let ctx = NormalizeContext::new(Some(locale), None).unwrap();
+        preprocess_text_with_context(text, &normalizer, &ctx)
+    };
+
+    assert_eq!(
+        read("Es kostet 1.234,50 € bei 3,5 Prozent.", "de-DE"),
+        "Es kostet one thousand two hundred thirty-four euros and fifty cents bei 3.5 Prozent."
+    );
+    // Dotted dates and versions are not numbers in de-DE
+    assert_eq!(
+        read("Am 3.4.2025 kam v1.2", "de-DE"),
+        "Am 3.4.2025 kam v1.2."
+    );
+    assert_eq!(
+        read("Prix 2 500,00 €", "fr-FR"),
+        "Prix two thousand five hundred euros."
+    );
+    assert_eq!(read("It was ¥20", "zh-CN"), "It was twenty yuan.");
+    assert_eq!(
+        read("It was ¥20 and 3,5", "ja-JP"),
+        "It was twenty yen and 3,5."
+    );
+    assert_eq!(read("Only 50 kr", "sv-SE"), "Only fifty kronor.");
+}
+
+#[test]
+fn test_strict_markup_issues() {
+    use supertonic_tts::{markup_issues, StrictIssueKind};
+
+    assert!(markup_issues("Fine (really) [ok] if 3 < 4.").is_empty());
+
+    let issues = markup_issues("Hello <break time=\"1s\"/> there.\nOops) and (more");
+    let found: Vec<(StrictIssueKind, usize, usize)> = issues
+        .iter()
+        .map(|i| {
+            let p = i.position.unwrap();
+            (i.kind, p.line, p.column)
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (StrictIssueKind::Markup, 1, 7),
+            (StrictIssueKind::UnbalancedBracket(')'), 2, 5),
+            (StrictIssueKind::UnbalancedBracket('('), 2, 11),
+        ]
+    );
+    assert_eq!(
+        issues[2].to_string(),
+        "line 2, column 11: '(' is never closed"
+    );
+}
+
+#[test]
+fn test_synthesis_warning_serialization() {
+    use supertonic_tts::SynthesisWarning;
+
+    let warning = SynthesisWarning::SplitBetweenWords { chunk: 3 };
+    assert_eq!(
+        serde_json::to_string(&warning).unwrap(),
+        r#"{"kind":"split_between_words","chunk":3}"#
+    );
+    assert!(warning.to_string().starts_with("chunk 3 ends mid-sentence"));
+}
+
+#[test]
+fn test_inspect_token_ids() {
+    use supertonic_tts::UnicodeProcessor;
+
+    let mut indexer = vec![-1i64; 128];
+    indexer['a' as usize] = 1;
+    indexer['e' as usize] = 5;
+    indexer['.' as usize] = 2;
+    let processor = UnicodeProcessor::from_bytes(&serde_json::to_vec(&indexer).unwrap()).unwrap();
+
+    // "é" decomposes into "e" and a combining accent the indexer has no token for
+    assert_eq!(processor.prepare("aé"), "ae\u{301}.");
+    assert_eq!(
+        processor.inspect("aé"),
+        vec![('a', 1), ('e', 5), ('\u{301}', -1), ('.', 2)]
+    );
+}
+
+#[test]
+fn test_preprocess_report() {
+    use supertonic_tts::{
+        preprocess_text_traced, NormalizeContext, PreprocessStage, TextNormalizer,
+    };
+
+    let normalizer = TextNormalizer::default();
+    let report = preprocess_text_traced("Costs €5 😀", &normalizer, &NormalizeContext::default());
+    assert_eq!(report.output, "Costs five euros.");
+    let steps: Vec<(PreprocessStage, &str, &str)> = report
+        .steps
+        .iter()
+        .map(|s| (s.stage, s.before.as_str(), s.after.as_str()))
+        .collect();
+    assert_eq!(
+        steps,
+        vec![
+            (PreprocessStage::Currency, "€5", "five euros"),
+            (PreprocessStage::EmojiRemoved, "😀", ""),
+            (PreprocessStage::Whitespace, " ", ""),
+            (PreprocessStage::TrailingPeriod, "", "."),
+        ]
+    );
+}
+
+#[test]
+fn test_wav_write_is_atomic() {
+    use supertonic_tts::{read_wav_bytes, read_wav_file, write_wav_file};
+
+    let dir = std::env::temp_dir().join(format!("supertonic-atomic-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("out.wav");
+
+    write_wav_file(&path, &[0.0, 0.5, -0.5], 24000).unwrap();
+    let (samples, rate) = read_wav_file(&path).unwrap();
+    assert_eq!(rate, 24000);
+    assert_eq!(samples.len(), 3);
+
+    // Only the final file remains; the temporary file was renamed into place
+    let names: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(names, vec![std::ffi::OsString::from("out.wav")]);
+
+    // Decoding the same bytes in memory gives the same samples
+    let bytes = std::fs::read(&path).unwrap();
+    let (from_bytes, bytes_rate) = read_wav_bytes(&bytes).unwrap();
+    assert_eq!((from_bytes, bytes_rate), (samples, rate));
+    assert!(read_wav_bytes(b"not a wav").is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_wav_stream_writer() {
+    use supertonic_tts::{read_wav_file, WavStreamWriter};
+
+    let dir = std::env::temp_dir().join(format!("supertonic-stream-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("stream.wav");
+
+    let mut writer = WavStreamWriter::create(&path, 100).unwrap();
+    writer.write_samples(&[0.0, 0.5]).unwrap();
+    writer.write_samples(&[-0.5, 2.0]).unwrap();
+    assert!((writer.duration() - 0.04).abs() < 1e-6);
+    assert!(!path.exists());
+    writer.finish().unwrap();
+
+    let (samples, rate) = read_wav_file(&path).unwrap();
+    assert_eq!(rate, 100);
+    assert_eq!(samples.len(), 4);
+    assert!((samples[3] - 1.0).abs() < 1e-3);
+
+    // An abandoned writer leaves nothing behind
+    let abandoned = dir.join("abandoned.wav");
+    let mut writer = WavStreamWriter::create(&abandoned, 100).unwrap();
+    writer.write_samples(&[0.1]).unwrap();
+    drop(writer);
+    let names: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(names, vec![std::ffi::OsString::from("stream.wav")]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_audio_sinks() {
+    use supertonic_tts::{read_wav_file, AudioSink, CallbackSink, TeeSink, WavStreamWriter};
+
+    let dir = std::env::temp_dir().join(format!("supertonic-sinks-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("sink.wav");
+
+    let mut blocks = 0;
+    let mut sink = TeeSink(
+        Vec::new(),
+        TeeSink(
+            WavStreamWriter::create(&path, 100).unwrap(),
+            CallbackSink(|_: &[f32]| {
+                blocks += 1;
+                Ok(())
+            }),
+        ),
+    );
+    let dyn_sink: &mut dyn AudioSink = &mut sink;
+    dyn_sink.write(&[0.5, 0.25]).unwrap();
+    dyn_sink.write(&[0.0]).unwrap();
+    dyn_sink.finalize().unwrap();
+
+    let TeeSink(memory, TeeSink(mut wav, _)) = sink;
+    assert_eq!(memory, vec![0.5, 0.25, 0.0]);
+    assert_eq!(read_wav_file(&path).unwrap().0.len(), 3);
+    // A finalized WAV sink rejects further writes
+    assert!(wav.write(&[0.1]).is_err());
+    drop(wav);
+    assert_eq!(blocks, 2);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_ring_buffer_sink() {
+    use supertonic_tts::{AudioSink, RingBufferSink};
+
+    let (mut sink, mut reader) = RingBufferSink::new(4);
+    assert_eq!(sink.try_write(&[1.0, 2.0, 3.0, 4.0, 5.0]), 4);
+
+    // Reads wrap around the buffer; running dry mid-stream is an under-run
+    let mut out = [0.0; 3];
+    assert_eq!(reader.read(&mut out), 3);
+    assert_eq!(out, [1.0, 2.0, 3.0]);
+    sink.write(&[5.0, 6.0]).unwrap();
+    assert_eq!(reader.read(&mut out), 3);
+    assert_eq!(out, [4.0, 5.0, 6.0]);
+    assert_eq!(reader.read(&mut out), 0);
+    assert_eq!((reader.underruns(), reader.underrun_samples()), (1, 3));
+
+    // A producer on another thread blocks until the reader makes room
+    let producer = std::thread::spawn(move || {
+        let samples: Vec<f32> = (0..64).map(|i| i as f32).collect();
+        sink.write(&samples).unwrap();
+        sink.finalize().unwrap();
+    });
+    let mut received = Vec::new();
+    while !reader.is_finished() {
+        let mut block = [0.0; 2];
+        let count = reader.read(&mut block);
+        received.extend_from_slice(&block[..count]);
+    }
+    producer.join().unwrap();
+    assert_eq!(received, (0..64).map(|i| i as f32).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_wav_formats() {
+    use supertonic_tts::{read_wav_file, write_wav_file_with_format, WavFormat};
+
+    let dir = std::env::temp_dir().join(format!("supertonic-formats-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let samples = [0.0, 0.25, -0.75, 1.5];
+
+    for (format, bits, tolerance) in [
+        (WavFormat::Pcm16, 16, 1e-4),
+        (WavFormat::Pcm24, 24, 1e-6),
+        (WavFormat::Float32, 32, 0.0),
+    ] {
+        let path = dir.join(format!("{:?}.wav", format));
+        write_wav_file_with_format(&path, &samples, 24000, format).unwrap();
+        assert_eq!(
+            hound::WavReader::open(&path)
+                .unwrap()
+                .spec()
+                .bits_per_sample,
+            bits
+        );
+
+        let (read, _) = read_wav_file(&path).unwrap();
+        // Integer formats clamp to full scale; float keeps the overshoot
+        let expected_peak = if format == WavFormat::Float32 {
+            1.5
+        } else {
+            1.0
+        };
+        for (got, want) in read.iter().zip([0.0, 0.25, -0.75, expected_peak]) {
+            assert!(
+                (got - want).abs() <= tolerance,
+                "{:?}: {} vs {}",
+                format,
+                got,
+                want
+            );
+        }
+    }
+    assert_eq!("pcm24".parse::<WavFormat>().unwrap(), WavFormat::Pcm24);
+
+    // In-memory encoding matches the file writer byte for byte
+    let bytes = supertonic_tts::wav_bytes(&samples, 24000).unwrap();
+    assert_eq!(bytes, std::fs::read(dir.join("Pcm16.wav")).unwrap());
+    assert_eq!(
+        supertonic_tts::to_pcm16(&samples),
+        vec![0, 8191, -24575, 32767]
+    );
+    // Whole blocks and the remainder convert alike, clamping out-of-range samples
+    let long: Vec<f32> = (0..37).map(|i| i as f32 / 12.0 - 1.5).collect();
+    let pcm = supertonic_tts::to_pcm16(&long);
+    assert_eq!((pcm[0], pcm[18], pcm[36]), (-32767, 0, 32767));
+    assert_eq!(pcm[12], (-0.5f32 * 32767.0) as i16);
+
+    let opus = supertonic_tts::OpusOptions::default();
+    assert!(opus.validate().is_ok());
+    let bad_frame = supertonic_tts::OpusOptions {
+        frame_ms: 30.0,
+        ..opus
+    };
+    assert!(bad_frame.validate().is_err());
+    let bad_bitrate = supertonic_tts::OpusOptions {
+        bitrate_kbps: Some(512),
+        ..opus
+    };
+    assert!(bad_bitrate.validate().is_err());
+    assert!("pcm8".parse::<WavFormat>().is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_time_stretch_keeps_pitch() {
+    use supertonic_tts::{time_stretch, SpeedMode, SynthesisOptions};
+
+    let rate = 24000;
+    let tone: Vec<f32> = (0..rate)
+        .map(|n| (2.0 * std::f32::consts::PI * 200.0 * n as f32 / rate as f32).sin())
+        .collect();
+    let crossings = |s: &[f32]| {
+        s.windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count()
+    };
+
+    for factor in [0.8, 1.5] {
+        let stretched = time_stretch(&tone, rate, factor);
+        let expected = (rate as f32 / factor).round() as usize;
+        assert_eq!(stretched.len(), expected);
+        // Still 200 Hz: about 400 zero crossings per second of output
+        let per_sec = crossings(&stretched) as f32 * rate as f32 / stretched.len() as f32;
+        assert!((per_sec - 400.0).abs() < 12.0, "{}: {}", factor, per_sec);
+    }
+
+    let options = SynthesisOptions {
+        speed: Some(1.5),
+        speed_mode: Some(SpeedMode::PostProcess),
+        ..Default::default()
+    };
+    assert_eq!(options.model_speed(), 1.0);
+    assert_eq!(options.stretch_factor(), Some(1.5));
+    assert_eq!(
+        "post-process".parse::<SpeedMode>().unwrap(),
+        SpeedMode::PostProcess
+    );
+}
+
+#[cfg(feature = "dsp")]
+#[test]
+fn test_pitch_shift() {
+    use supertonic_tts::pitch_shift;
+
+    let rate = 24000;
+    let tone: Vec<f32> = (0..rate)
+        .map(|n| (2.0 * std::f32::consts::PI * 200.0 * n as f32 / rate as f32).sin())
+        .collect();
+    let crossings = |s: &[f32]| {
+        s.windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count()
+    };
+
+    // An octave up doubles the frequency and keeps the length
+    let shifted = pitch_shift(&tone, rate, 12.0);
+    assert_eq!(shifted.len(), tone.len());
+    let middle = &shifted[rate as usize / 10..rate as usize * 9 / 10];
+    let hz = crossings(middle) as f32 / 2.0 / (middle.len() as f32 / rate as f32);
+    assert!((hz - 400.0).abs() < 15.0, "{}", hz);
+}
+
+#[test]
+fn test_dc_removal_and_high_pass() {
+    use supertonic_tts::{high_pass, remove_dc};
+
+    let rate = 24000;
+    let rms = |s: &[f32]| (s.iter().map(|x| x * x).sum::<f32>() / s.len() as f32).sqrt();
+    let tone = |hz: f32| -> Vec<f32> {
+        (0..rate)
+            .map(|n| (2.0 * std::f32::consts::PI * hz * n as f32 / rate as f32).sin())
+            .collect()
+    };
+
+    // A constant offset decays away
+    let mut offset = vec![0.5; rate as usize];
+    remove_dc(&mut offset, rate);
+    assert!(offset[rate as usize - 1].abs() < 1e-3);
+
+    // 20 Hz rumble is cut hard, a 200 Hz voice fundamental passes
+    let mut rumble = tone(20.0);
+    high_pass(&mut rumble, rate, 50.0);
+    assert!(rms(&rumble[rate as usize / 2..]) < 0.2);
+    let mut voice = tone(200.0);
+    high_pass(&mut voice, rate, 50.0);
+    assert!(rms(&voice[rate as usize / 2..]) > 0.65);
+}
+
+#[test]
+fn test_fades() {
+    use supertonic_tts::{fade, fade_ms};
+
+    let mut samples = vec![1.0; 8];
+    fade(&mut samples, 4, 2);
+    assert_eq!(samples, vec![0.0, 0.25, 0.5, 0.75, 1.0, 1.0, 0.5, 0.0]);
+
+    // Fades longer than the buffer are cut to it
+    let mut short = vec![1.0; 2];
+    fade_ms(&mut short, 1000, 10.0, 0.0);
+    assert_eq!(short, vec![0.0, 0.5]);
+}
+
+#[test]
+fn test_stereo_pan_and_wav() {
+    use supertonic_tts::{pan_stereo, read_wav_file, write_wav_file_interleaved, WavFormat};
+
+    let left = pan_stereo(&[1.0, 0.5], -1.0);
+    assert!((left[0] - 1.0).abs() < 1e-6 && left[1].abs() < 1e-6);
+    let center = pan_stereo(&[1.0], 0.0);
+    assert!((center[0] - center[1]).abs() < 1e-6);
+    assert!((center[0] * center[0] + center[1] * center[1] - 1.0).abs() < 1e-5);
+
+    // Two-channel files read back downmixed to mono
+    let dir = std::env::temp_dir().join(format!("supertonic-stereo-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("stereo.wav");
+    let stereo = [0.5, 0.0, 0.0, 0.5];
+    write_wav_file_interleaved(&path, &stereo, 2, 8000, WavFormat::Float32).unwrap();
+    let (mono, rate) = read_wav_file(&path).unwrap();
+    assert_eq!((mono, rate), (vec![0.25, 0.25], 8000));
+    assert!(write_wav_file_interleaved(&path, &stereo[..3], 2, 8000, WavFormat::Pcm16).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_wav_metadata_round_trip() {
+    use supertonic_tts::{
+        read_wav_file, read_wav_metadata, write_wav_file_tagged, AudioMetadata, SynthesisOptions,
+        WavFormat,
+    };
+
+    let options = SynthesisOptions {
+        speed: Some(1.2),
+        ..Default::default()
+    };
+    let metadata = AudioMetadata::for_synthesis("Hello there.\nSecond line.", Some("M1"), &options);
+    assert_eq!(metadata.title.as_deref(), Some("Hello there."));
+    let comment = metadata.comment.clone().unwrap();
+    assert!(comment.starts_with("Hello there.\nSecond line.") && comment.contains("\"speed\":1.2"));
+    assert!(!comment.contains("null"));
+    let metadata = AudioMetadata {
+        album: Some("The Book".to_string()),
+        track: Some(3),
+        ..metadata
+    };
+
+    let dir = std::env::temp_dir().join(format!("supertonic-tags-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("tagged.wav");
+    // An odd number of 24-bit samples leaves the data chunk needing a pad byte
+    write_wav_file_tagged(
+        &path,
+        &[0.5, 0.0, -0.5],
+        1,
+        8000,
+        WavFormat::Pcm24,
+        &metadata,
+    )
+    .unwrap();
+    assert_eq!(read_wav_metadata(&path).unwrap(), metadata);
+    let bytes = std::fs::read(&path).unwrap();
+    let riff_size = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+    assert_eq!(riff_size, bytes.len() - 8);
+    let (samples, rate) = read_wav_file(&path).unwrap();
+    assert_eq!((samples.len(), rate), (3, 8000));
+
+    write_wav_file_tagged(
+        &path,
+        &[0.0],
+        1,
+        8000,
+        WavFormat::Pcm16,
+        &AudioMetadata::default(),
+    )
+    .unwrap();
+    assert!(read_wav_metadata(&path).unwrap().is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_timing_report() {
+    use std::time::Duration;
+    use supertonic_tts::{StageTimings, TimingReport};
+
+    let timings = StageTimings {
+        vocoder: Duration::from_millis(250),
+        ..Default::default()
+    };
+    let report = TimingReport::new(timings, Duration::from_secs(1), 4.0);
+    assert_eq!(report.vocoder_ms, 250.0);
+    assert_eq!(report.total_ms, 1000.0);
+    assert_eq!(report.rtf, 0.25);
+    // No audio, no real-time factor
+    assert_eq!(
+        TimingReport::new(timings, Duration::from_secs(1), 0.0).rtf,
+        0.0
+    );
+}
+
+#[test]
+fn test_length_buckets() {
+    use supertonic_tts::length_buckets;
+
+    assert_eq!(
+        length_buckets(&[40, 200, 45, 220, 100]),
+        vec![vec![0, 2], vec![4], vec![1, 3]]
+    );
+    // Small differences are not worth an extra pass
+    assert_eq!(length_buckets(&[3, 12, 8]), vec![vec![0, 2, 1]]);
+    assert!(length_buckets(&[]).is_empty());
+}
+
+#[test]
+fn test_vocoder_windows() {
+    use supertonic_tts::{crossfade_into, vocoder_windows, VOCODER_WINDOW_OVERLAP};
+
+    assert_eq!(VOCODER_WINDOW_OVERLAP, 4);
+    assert_eq!(vocoder_windows(20, 8), vec![0..8, 4..12, 8..16, 12..20]);
+    // The last window is cut at the end of the latent
+    assert_eq!(vocoder_windows(18, 10), vec![0..10, 6..16, 12..18]);
+    assert_eq!(vocoder_windows(5, 8), vec![0..5]);
+
+    // Each window fades in over the samples it shares with the one before
+    let mut out = vec![1.0; 6];
+    crossfade_into(&mut out, &[0.0; 4], 4);
+    assert_eq!(out, [1.0, 0.75, 0.5, 0.25, 1.0, 1.0]);
+    let mut out = vec![1.0; 4];
+    crossfade_into(&mut out, &[0.0; 6], 2);
+    assert_eq!(out, [1.0, 0.5, 0.0, 0.0]);
+    // The first window is copied as it is
+    let mut out = vec![0.0; 3];
+    crossfade_into(&mut out, &[0.5; 3], 0);
+    assert_eq!(out, [0.5; 3]);
+}
+
+#[test]
+fn test_dedup_slots() {
+    use supertonic_tts::{dedup_slots, fan_out};
+
+    let texts = ["hi", "bye", "hi", "hi", "ok", "bye"];
+    let (slots, unique) = dedup_slots(texts.len(), |i, j| texts[i] == texts[j]);
+    assert_eq!(unique, vec![0, 1, 4]);
+    assert_eq!(slots, vec![0, 1, 0, 0, 2, 1]);
+
+    // Results of the distinct texts are copied back to every position
+    let results: Vec<String> = unique.iter().map(|&i| texts[i].to_uppercase()).collect();
+    assert_eq!(
+        fan_out(&results, &slots),
+        ["HI", "BYE", "HI", "HI", "OK", "BYE"]
+    );
+
+    // Nothing is shared when no two items are interchangeable
+    let (slots, unique) = dedup_slots(3, |i, j| i == j);
+    assert_eq!((slots, unique), (vec![0, 1, 2], vec![0, 1, 2]));
+    assert_eq!(dedup_slots(0, |_, _| true), (vec![], vec![]));
+}
+
+#[test]
+fn test_compress_silences() {
+    use supertonic_tts::compress_silences;
+
+    // 0.5 s lead-in, a 1 s gap and a 0.2 s gap between bursts of tone at 1 kHz
+    let tone = |n: usize| (0..n).map(|i| if i % 2 == 0 { 0.5 } else { -0.5 });
+    let mut samples = vec![0.0f32; 500];
+    samples.extend(tone(100));
+    samples.extend(vec![0.0; 1000]);
+    samples.extend(tone(100));
+    samples.extend(vec![0.0; 200]);
+    samples.extend(tone(100));
+
+    let compressed = compress_silences(&samples, 1000, 0.3);
+    // Only the long internal gap shrinks, to 0.3 s
+    assert_eq!(compressed.len(), 500 + 100 + 300 + 100 + 200 + 100);
+    assert_eq!(&compressed[..500], &samples[..500]);
+    assert!(compressed[600..900].iter().all(|&s| s == 0.0));
+    assert_eq!(compress_silences(&samples, 1000, 2.0), samples);
+}
+
+#[test]
+fn test_waveform_and_mel_preview() {
+    use supertonic_tts::{mel_spectrogram, preview, MelOptions};
+
+    let samples = [0.0, 0.5, -1.0, 0.0, 0.25, -0.25];
+    let buckets = preview(&samples, 2);
+    assert_eq!(buckets.len(), 2);
+    assert_eq!((buckets[0].peak, buckets[1].peak), (1.0, 0.25));
+    assert!((buckets[1].rms - (0.125f32 / 3.0).sqrt()).abs() < 1e-6);
+    assert_eq!(preview(&samples, 100).len(), samples.len());
+
+    // A 1 kHz tone is loudest in the band around 1 kHz
+    let rate = 16000;
+    let tone: Vec<f32> = (0..rate)
+        .map(|i| (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / rate as f32).sin())
+        .collect();
+    let mel = mel_spectrogram(&tone, rate, &MelOptions::default());
+    assert_eq!(mel.frames.len(), (rate as usize).div_ceil(256));
+    assert_eq!(mel.max_hz, 8000.0);
+    let frame = &mel.frames[10];
+    let loudest = (0..frame.len())
+        .max_by(|&a, &b| frame[a].total_cmp(&frame[b]))
+        .unwrap();
+    let mel_of = |hz: f32| 2595.0 * (1.0 + hz / 700.0).log10();
+    let center = mel_of(8000.0) * (loudest + 1) as f32 / 81.0;
+    assert!((center - mel_of(1000.0)).abs() < mel_of(8000.0) / 81.0);
+    assert!(frame[loudest] > -6.0 && frame[loudest] < 3.0);
+}
+
+#[test]
+fn test_audio_stats() {
+    use supertonic_tts::AudioStats;
+
+    // 0.25 s of silence, 0.5 s of a full-scale 500 Hz square wave with two overs, 0.25 s of silence
+    let mut samples = vec![0.0f32; 2000];
+    samples.extend((0..4000).map(|i| if i / 8 % 2 == 0 { 1.0 } else { -1.0 }));
+    samples[3000] = 1.5;
+    samples[3001] = -1.5;
+    samples.extend(vec![0.0; 2000]);
+
+    let stats = AudioStats::measure(&samples, 8000);
+    assert_eq!(stats.duration_secs, 1.0);
+    assert_eq!((stats.peak, stats.clipped_samples), (1.5, 2));
+    assert!((stats.leading_silence_secs - 0.25).abs() < 1e-6);
+    assert!((stats.trailing_silence_secs - 0.25).abs() < 1e-6);
+    assert!(stats.loudness_lufs.is_some() && !stats.is_silent());
+    assert!(AudioStats::measure(&[0.0; 100], 8000).is_silent());
+}
+
+#[test]
+fn test_mixer_places_clips() {
+    use supertonic_tts::Mixer;
+
+    let mut mixer = Mixer::new(10);
+    mixer.add(vec![0.5, 0.5], 0.0, 0.0);
+    mixer.add(vec![1.0], 0.1, -6.0206);
+    mixer.append(vec![0.25], 0.2, 0.0);
+    assert_eq!(mixer.len(), 5);
+    assert!((mixer.duration() - 0.5).abs() < 1e-6);
+
+    let mix = mixer.render();
+    // The second clip overlaps the first at half gain; the gap stays silent
+    assert!((mix[1] - 1.0).abs() < 1e-4);
+    assert_eq!(&mix[2..4], &[0.0, 0.0]);
+    assert_eq!(mix[4], 0.25);
+}
+
+#[test]
+fn test_background_ducks_under_speech() {
+    use supertonic_tts::{mix_background, BackgroundOptions};
+
+    let rate = 1000;
+    let options = BackgroundOptions {
+        gain_db: 0.0,
+        ..BackgroundOptions::default()
+    };
+    // One second of silence, then one second of speech; the bed loops over both
+    let mut speech = vec![0.0; 1000];
+    speech.extend(vec![0.5; 1000]);
+    let mixed = mix_background(&speech, 1, &[0.1; 100], rate, &options);
+    assert_eq!(mixed.len(), 2000);
+    assert!((mixed[999] - 0.1).abs() < 1e-6);
+    let ducked = 0.1 * 10f32.powf(options.duck_db / 20.0);
+    assert!((mixed[1999] - 0.5 - ducked).abs() < 1e-4);
+
+    // Stereo speech gets the bed on both channels
+    let stereo = mix_background(&[0.0; 4], 2, &[0.1], rate, &options);
+    assert_eq!(stereo, vec![0.1; 4]);
+}
+
+#[test]
+fn test_watermark_round_trip() {
+    use supertonic_tts::Watermark;
+
+    // Three seconds of a voiced, syllable-modulated signal
+    let rate = 24000;
+    let voice: Vec<f32> = (0..3 * rate)
+        .map(|i| {
+            let t = i as f32 / rate as f32;
+            let syllables = (t * 4.0 * std::f32::consts::TAU).sin().max(0.0);
+            let tone = (t * 140.0 * std::f32::consts::TAU).sin()
+                + 0.5 * (t * 420.0 * std::f32::consts::TAU).sin();
+            0.3 * syllables * tone
+        })
+        .collect();
+
+    let watermark = Watermark::new(42);
+    let unmarked = watermark.detect(&voice);
+    assert!(!unmarked.is_present(), "score {}", unmarked.score);
+
+    let mut marked = voice.clone();
+    watermark.embed(&mut marked, rate);
+    let detection = watermark.detect(&marked);
+    assert!(detection.is_present(), "score {}", detection.score);
+    let other = Watermark::new(7).detect(&marked);
+    assert!(!other.is_present(), "score {}", other.score);
+
+    // Survives trimming the start and a gain change
+    let trimmed: Vec<f32> = marked[1000..].iter().map(|s| s * 0.5).collect();
+    let detection = watermark.detect(&trimmed);
+    assert!(detection.is_present(), "score {}", detection.score);
+    assert_eq!(detection.offset, 1000);
+}
+
+#[test]
+fn test_peak_normalization_and_soft_limit() {
+    use supertonic_tts::{normalize_peak, soft_limit};
+
+    let mut samples = vec![0.1, -0.25, 0.2];
+    let gain_db = normalize_peak(&mut samples, -6.0).unwrap();
+    assert!((gain_db - 6.0).abs() < 0.05);
+    assert!((samples[1].abs() - 10f32.powf(-6.0 / 20.0)).abs() < 1e-5);
+    assert_eq!(normalize_peak(&mut [0.0, 0.0], -1.0), None);
+
+    let ceiling = 10f32.powf(-1.0 / 20.0);
+    let mut samples = vec![0.1, 0.6, 1.5, -3.0, 0.85];
+    soft_limit(&mut samples, -1.0);
+    // Quiet samples are untouched, loud ones never exceed the ceiling and keep their order
+    assert_eq!(&samples[..2], &[0.1, 0.6]);
+    assert!(samples.iter().all(|s| s.abs() <= ceiling));
+    assert!(samples[2] > samples[4] && samples[3] < -samples[4]);
+}
+
+#[test]
+fn test_style_broadcast() {
+    use supertonic_tts::load_voice_style_from_bytes;
+
+    let voice = br#"{
+        "style_ttl": { "data": [[[0.5, 1.0]]], "dims": [1, 1, 2], "type": "float32" },
+        "style_dp": { "data": [[[2.0]]], "dims": [1, 1, 1], "type": "float32" }
+    }"#;
+    let single = load_voice_style_from_bytes(&[voice.as_slice()], false).unwrap();
+    let batch = single.broadcast(3).unwrap();
+    assert_eq!(batch.batch_size(), 3);
+    assert_eq!(batch.ttl.shape(), &[3, 1, 2]);
+    assert_eq!(batch.ttl[[2, 0, 1]], 1.0);
+
+    let pair = load_voice_style_from_bytes(&[voice.as_slice(), voice.as_slice()], false).unwrap();
+    assert!(pair.broadcast(3).is_err());
+}
+
+#[test]
+fn test_emoji_policy() {
+    use supertonic_tts::{preprocess_text_with, EmojiPolicy, TextNormalizer};
+
+    assert_eq!(preprocess_text("Great job 👍"), "Great job.");
+
+    let normalizer = TextNormalizer {
+        emoji: EmojiPolicy::Describe,
+        ..Default::default()
+    };
+    assert_eq!(
+        preprocess_text_with("Great job 👍🏽 ❤️.", &normalizer),
+        "Great job thumbs up red heart."
+    );
+}
+
+#[test]
+fn test_integrated_loudness_and_matching() {
+    use supertonic_tts::{integrated_loudness, match_gains};
+
+    let sr = 44100;
+    let tone = |amp: f32| -> Vec<f32> {
+        (0..sr * 2)
+            .map(|i| amp * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / sr as f32).sin())
+            .collect()
+    };
+    // A full-scale 1 kHz sine reads -3.01 LUFS
+    let full = integrated_loudness(&tone(1.0), sr).unwrap();
+    assert!((full + 3.01).abs() < 0.1, "got {full}");
+    let half = integrated_loudness(&tone(0.5), sr).unwrap();
+    assert!((full - half - 6.02).abs() < 0.1);
+    assert_eq!(integrated_loudness(&vec![0.0; 1000], sr), None);
+
+    let gains = match_gains(&[Some(-20.0), Some(-26.0), None]);
+    assert_eq!(gains, vec![-3.0, 3.0, 0.0]);
+}
+
+#[test]
+fn test_split_sentences() {
+    use supertonic_tts::split_sentences;
+
+    let trimmed = |text: &str, locale: Option<&str>| -> Vec<String> {
+        split_sentences(text, locale)
+            .iter()
+            .map(|s| s.trim().to_string())
+            .collect()
+    };
+
+    assert_eq!(
+        trimmed("Dr. Smith paid $3.50 today... Then he left?! Yes.", None),
+        vec!["Dr. Smith paid $3.50 today...", "Then he left?!", "Yes."]
+    );
+    assert_eq!(
+        trimmed("「こんにちは。」元気ですか？！はい。", Some("ja-JP")),
+        vec!["「こんにちは。」", "元気ですか？！", "はい。"]
+    );
+    assert_eq!(
+        trimmed("Das kostet ca. 5 Euro. Nr. 7 ist frei.", Some("de-DE")),
+        vec!["Das kostet ca. 5 Euro.", "Nr. 7 ist frei."]
+    );
+}
+
+#[test]
+fn test_chunk_text_counts_characters() {
+    // 24 and 19 characters, but three bytes each
+    let text =
+        "今日はとても良い天気ですね、散歩に行きましょう。明日は雨が降るそうです、傘を忘れずに。";
+    assert_eq!(
+        chunk_text(text, Some(24)),
+        vec![
+            "今日はとても良い天気ですね、散歩に行きましょう。",
+            "明日は雨が降るそうです、傘を忘れずに。"
+        ]
+    );
+
+    // Long sentences break after 、 without inserting spaces
+    let chunks = chunk_text(text, Some(20));
+    assert_eq!(chunks[0], "今日はとても良い天気ですね、");
+    assert!(chunks.iter().all(|c| c.chars().count() <= 20));
+    assert_eq!(chunks.concat(), text);
+}
+
+#[test]
+fn test_chunk_boundaries_and_pauses() {
+    use supertonic_tts::{chunk_text_with_boundaries, ChunkBoundary, SynthesisOptions};
+
+    let text = "First sentence here. Second one, which runs on for a while.\n\nNew paragraph.";
+    let chunks = chunk_text_with_boundaries(text, Some(30), None);
+    let boundaries: Vec<ChunkBoundary> = chunks.iter().map(|(_, b)| *b).collect();
+    assert_eq!(
+        boundaries,
+        vec![
+            ChunkBoundary::Sentence,
+            ChunkBoundary::Clause,
+            ChunkBoundary::Paragraph,
+            ChunkBoundary::Paragraph
+        ]
+    );
+    assert_eq!(chunks[1].0, "Second one");
+
+    let options = SynthesisOptions {
+        silence_duration: Some(0.3),
+        paragraph_silence: Some(0.8),
+        ..Default::default()
+    };
+    let pauses = options.pauses();
+    assert_eq!(pauses.after(ChunkBoundary::Paragraph), 0.8);
+    assert_eq!(pauses.after(ChunkBoundary::Sentence), 0.3);
+    assert_eq!(pauses.after(ChunkBoundary::Word), 0.3);
+}
+
+#[test]
+fn test_performance_profile_round_trip() {
+    use supertonic_tts::{current_device, PerformanceProfile};
+
+    let profile = PerformanceProfile {
+        device: current_device(),
+        chars_per_audio_sec: 15.0,
+        fixed_cost: 0.02,
+        step_cost: 0.01,
+        vocoder_cost: 0.03,
+    };
+    assert!((profile.rtf(5) - 0.1).abs() < 1e-9);
+    assert!((profile.capacity(5) - 10.0).abs() < 1e-6);
+    assert!((profile.audio_secs(30, 2.0) - 1.0).abs() < 1e-9);
+
+    let path = std::env::temp_dir().join("supertonic_test_profile.json");
+    profile.save(&path).unwrap();
+    assert_eq!(
+        PerformanceProfile::load_for_current_device(&path),
+        Some(profile.clone())
+    );
+
+    let other = PerformanceProfile {
+        device: "elsewhere".to_string(),
+        ..profile
+    };
+    other.save(&path).unwrap();
+    assert_eq!(PerformanceProfile::load_for_current_device(&path), None);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_chunk_text_by_tokens() {
+    use supertonic_tts::{chunk_text_by_tokens, UnicodeProcessor};
+
+    let processor = UnicodeProcessor::from_bytes(b"[]").unwrap();
+    let text = "It costs $2500000 today. See you.";
+    assert_eq!(chunk_text(text, Some(60)), vec![text]);
+    // "$2500000" expands to "two million five hundred thousand dollars"
+    assert_eq!(
+        chunk_text_by_tokens(text, 60, &processor, None),
+        vec!["It costs $2500000 today.", "See you."]
+    );
+    assert!(processor.token_count("It costs $2500000 today.") > 40);
+}
+
+#[test]
+fn test_chunk_text_by_tokens_measures_each_piece_once() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use supertonic_tts::error::SupertonicError;
+    use supertonic_tts::{chunk_text_by_tokens, G2p, UnicodeProcessor};
+
+    struct CountingG2p(AtomicUsize);
+    impl G2p for CountingG2p {
+        fn convert(&self, text: &str) -> Result<String, SupertonicError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(text.to_string())
+        }
+    }
+
+    let g2p = Arc::new(CountingG2p(AtomicUsize::new(0)));
+    let mut processor = UnicodeProcessor::from_bytes(b"[]").unwrap();
+    processor.set_g2p(Some(g2p.clone()));
+    // One clause too long for a chunk, so it is measured word by word
+    let text = vec!["again"; 50].join(" ");
+    let chunks = chunk_text_by_tokens(&text, 40, &processor, None);
+    assert!(chunks.len() > 1);
+    // The paragraph, the sentence, the clause and the one distinct word
+    assert!(g2p.0.load(Ordering::SeqCst) <= 4);
+}
+
+#[test]
+fn test_unknown_char_policy() {
+    use supertonic_tts::{UnicodeProcessor, UnknownCharPolicy};
+
+    // Only ASCII has token ids
+    let indexer: Vec<i64> = (0..128).collect();
+    let mut processor =
+        UnicodeProcessor::from_bytes(serde_json::to_string(&indexer).unwrap().as_bytes()).unwrap();
+    let text = vec!["Hello жworld.".to_string()];
+
+    let unsupported = processor.unsupported_chars(&text[0]);
+    assert_eq!(unsupported.len(), 1);
+    assert_eq!(unsupported[0].ch, 'ж');
+
+    let (ids, _) = processor.call(&text).unwrap();
+    assert!(ids[0].iter().all(|&id| id >= 0));
+
+    processor.set_unknown_chars(UnknownCharPolicy::ReplaceWithSpace);
+    let (spaced, _) = processor.call(&text).unwrap();
+    assert_eq!(spaced[0].len(), ids[0].len() + 1);
+    assert!(spaced[0].iter().all(|&id| id >= 0));
+
+    processor.set_unknown_chars(UnknownCharPolicy::Error);
+    let err = processor.call(&text).unwrap_err().to_string();
+    assert!(err.contains("position"), "{}", err);
+}
+
+#[test]
+fn test_sentence_bridge() {
+    use supertonic_tts::{SentenceBridge, SpeechSegment};
+
+    let mut bridge = SentenceBridge::new();
+    assert!(bridge
+        .push(SpeechSegment::new("Good morning, how", Some("en")))
+        .is_empty());
+    let partial = SpeechSegment {
+        partial: true,
+        ..SpeechSegment::new("are yo", Some("en"))
+    };
+    assert!(bridge.push(partial).is_empty());
+
+    let ready = bridge.push(SpeechSegment::new("are you? I am", Some("en")));
+    let texts: Vec<&str> = ready.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["Good morning, how are you?"]);
+
+    // A language switch releases the held-back text
+    let ready = bridge.push(SpeechSegment::new("Guten Tag.", Some("de")));
+    let texts: Vec<&str> = ready.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["I am", "Guten Tag."]);
+    assert_eq!(ready[1].language.as_deref(), Some("de"));
+    assert!(bridge.flush().is_none());
+}
+
+#[cfg(feature = "epub")]
+#[test]
+fn test_xhtml_to_text() {
+    use supertonic_tts::xhtml_to_text;
+
+    let xhtml = r#"<?xml version="1.0"?>
+<html><head><title>Ignored</title><style>p { margin: 0 }</style></head>
+<body>
+  <h1>Chapter&nbsp;One</h1>
+  <p>It was a <em>dark</em> and
+     stormy night &amp; the rain fell&#8230;</p>
+
+  <p>Tom &lt;said&gt; nothing.<br/>Then he left.</p>
+</body></html>"#;
+    assert_eq!(
+        xhtml_to_text(xhtml),
+        "Chapter One\n\nIt was a dark and stormy night & the rain fell…\n\nTom <said> nothing.\n\nThen he left."
+    );
+}
+
+#[test]
+fn test_resample_and_normalize_loudness() {
+    use supertonic_tts::{integrated_loudness, normalize_loudness, resample};
+
+    let tone = |rate: i32, secs: f32| -> Vec<f32> {
+        (0..(rate as f32 * secs) as usize)
+            .map(|i| 0.25 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / rate as f32).sin())
+            .collect()
+    };
+    let input = tone(44_100, 1.0);
+    let output = resample(&input, 44_100, 48_000);
+    assert_eq!(output.len(), 48_000);
+    // Away from the edges the result matches a tone rendered at 48 kHz
+    let expected = tone(48_000, 1.0);
+    let error = output[1000..47_000]
+        .iter()
+        .zip(&expected[1000..47_000])
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0f32, f32::max);
+    assert!(error < 1e-3, "max error {}", error);
+    assert_eq!(resample(&input, 44_100, 44_100), input);
+
+    let mut samples = output;
+    assert!(normalize_loudness(&mut samples, 48_000, -16.0).is_some());
+    let loudness = integrated_loudness(&samples, 48_000).unwrap();
+    assert!((loudness + 16.0).abs() < 0.01, "{}", loudness);
+    assert!(normalize_loudness(&mut vec![0.0; 4800], 48_000, -16.0).is_none());
+}
+
+#[test]
+fn test_asset_checksums() {
+    use supertonic_tts::error::SupertonicError;
+    use supertonic_tts::{load_voice_style, sha256_hex, AssetManifest};
+
+    assert_eq!(
+        sha256_hex(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+
+    let dir = std::env::temp_dir().join(format!("supertonic-checksums-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("voice.json"), b"{}").unwrap();
+    let manifest = AssetManifest::generate(&dir).unwrap();
+    manifest.save(&dir).unwrap();
+    assert_eq!(AssetManifest::find(&dir).unwrap(), Some(manifest.clone()));
+    manifest.verify_file(&dir, "voice.json").unwrap();
+    // Files the manifest does not list pass
+    manifest.verify_bytes("other.json", b"anything").unwrap();
+
+    // A corrupted style fails before it is parsed, naming the file
+    std::fs::write(dir.join("voice.json"), b"{ ").unwrap();
+    let path = dir.join("voice.json").to_string_lossy().into_owned();
+    match load_voice_style(&[path], false) {
+        Err(SupertonicError::IntegrityError { file, .. }) => assert_eq!(file, "voice.json"),
+        other => panic!("expected an integrity error, got {:?}", other.err()),
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_binary_voice_styles() {
+    use supertonic_tts::load_voice_style_from_bytes;
+    use supertonic_tts::model::{StyleComponent, VoiceStyleData};
+    use supertonic_tts::style::{decode, encode};
+    use supertonic_tts::{StyleFormat, SynthesisOptions};
+
+    let component = |dims: [usize; 3], offset: f32| StyleComponent {
+        data: (0..dims[0])
+            .map(|_| {
+                (0..dims[1])
+                    .map(|j| {
+                        (0..dims[2])
+                            .map(|k| offset + (j * dims[2] + k) as f32 * 0.01)
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect(),
+        dims: dims.to_vec(),
+        dtype: "float32".to_string(),
+    };
+    let data = VoiceStyleData {
+        style_ttl: component([1, 3, 4], -0.5),
+        style_dp: component([1, 2, 2], 0.25),
+        defaults: Some(SynthesisOptions {
+            speed: Some(1.1),
+            ..Default::default()
+        }),
+    };
+    let json = encode(&data, StyleFormat::Json).unwrap();
+    let json_style = load_voice_style_from_bytes(&[json.as_slice()], false).unwrap();
+
+    let binary = encode(&data, StyleFormat::Binary).unwrap();
+    let decoded = decode(&binary).unwrap();
+    assert_eq!(decoded.style_ttl.data, data.style_ttl.data);
+    assert_eq!(decoded.style_dp.dims, data.style_dp.dims);
+    assert_eq!(decoded.defaults.unwrap().speed, Some(1.1));
+    let style = load_voice_style_from_bytes(&[binary.as_slice()], false).unwrap();
+    assert_eq!(style.ttl, json_style.ttl);
+    assert_eq!(style.dp, json_style.dp);
+
+    let half = encode(&data, StyleFormat::BinaryF16).unwrap();
+    assert!(half.len() < binary.len());
+    let style = load_voice_style_from_bytes(&[half.as_slice()], false).unwrap();
+    for (a, b) in style.ttl.iter().zip(json_style.ttl.iter()) {
+        assert!((a - b).abs() < 1e-3);
+    }
+
+    // Truncated files are rejected rather than read short
+    assert!(decode(&binary[..binary.len() - 1]).is_err());
+}
+
+#[test]
+fn test_voice_catalog() {
+    use supertonic_tts::{VoiceCatalog, VOICE_CATALOG};
+
+    let dir = std::env::temp_dir().join(format!("supertonic-catalog-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for name in ["M1.json", "F1.bin", "checksums.json", "notes.txt"] {
+        std::fs::write(dir.join(name), b"{}").unwrap();
+    }
+
+    // Without a catalog, voices are named after their style files
+    let inferred = VoiceCatalog::discover(&dir).unwrap();
+    let ids: Vec<_> = inferred.voices.iter().map(|v| v.id.as_str()).collect();
+    assert_eq!(ids, ["F1", "M1"]);
+    assert_eq!(inferred.style_path(&dir, "F1").unwrap(), dir.join("F1.bin"));
+
+    std::fs::write(
+        dir.join(VOICE_CATALOG),
+        r#"{ "voices": [
+            { "id": "M1", "name": "Alex", "language": "en-US", "tags": ["calm"] },
+            { "id": "K1", "language": "ko", "file": "https://example.com/K1.bin" }
+        ] }"#,
+    )
+    .unwrap();
+    let catalog = VoiceCatalog::discover(&dir).unwrap();
+    assert_eq!(catalog.get("M1").unwrap().name, "Alex");
+    assert_eq!(catalog.get("K1").unwrap().name, "K1");
+    assert_eq!(catalog.style_path(&dir, "M1").unwrap(), dir.join("M1.json"));
+    assert!(catalog.style_path(&dir, "K1").is_err());
+    assert!(catalog.style_path(&dir, "F1").is_err());
+    let english: Vec<_> = catalog.by_language("en").map(|v| v.id.as_str()).collect();
+    assert_eq!(english, ["M1"]);
+
+    assert!(VoiceCatalog::from_bytes(br#"{ "voices": [{ "id": "A" }, { "id": "A" }] }"#).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_voice_manager() {
+    use std::sync::Arc;
+    use supertonic_tts::model::{StyleComponent, VoiceStyleData};
+    use supertonic_tts::style::encode;
+    use supertonic_tts::{StyleFormat, VoiceManager};
+
+    let dir = std::env::temp_dir().join(format!("supertonic-voices-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let component = || StyleComponent {
+        data: vec![vec![vec![0.5; 2]; 2]],
+        dims: vec![1, 2, 2],
+        dtype: "float32".to_string(),
+    };
+    let data = VoiceStyleData {
+        style_ttl: component(),
+        style_dp: component(),
+        defaults: None,
+    };
+    for id in ["A", "B", "C"] {
+        let bytes = encode(&data, StyleFormat::Binary).unwrap();
+        std::fs::write(dir.join(format!("{}.bin", id)), bytes).unwrap();
+    }
+
+    let voices = Arc::new(VoiceManager::open(&dir).unwrap().with_capacity(2));
+    assert_eq!(voices.catalog().voices.len(), 3);
+    let a = voices.get("A").unwrap();
+    assert!(Arc::ptr_eq(&a, &voices.get("A").unwrap()));
+
+    // Shared across threads; the least recently used voice is evicted
+    let handle = {
+        let voices = Arc::clone(&voices);
+        std::thread::spawn(move || voices.get("B").map(|_| ()))
+    };
+    handle.join().unwrap().unwrap();
+    voices.get("A").unwrap();
+    voices.get("C").unwrap();
+    assert_eq!(voices.loaded_ids(), ["A", "C"]);
+    assert!(voices.evict("A"));
+    assert!(!voices.is_loaded("A"));
+    assert!(voices.get("missing").is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_config_validation() {
+    use supertonic_tts::config::load_cfgs_from_bytes;
+
+    let config = |sample_rate: i32, latent_dim: i32| {
+        format!(
+            r#"{{ "ae": {{ "sample_rate": {}, "base_chunk_size": 512 }},
+                 "ttl": {{ "chunk_compress_factor": 6, "latent_dim": {} }} }}"#,
+            sample_rate, latent_dim
+        )
+    };
+    assert!(load_cfgs_from_bytes(config(44100, 24).as_bytes()).is_ok());
+    let err = load_cfgs_from_bytes(config(0, 24).as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("ae.sample_rate"));
+    let err = load_cfgs_from_bytes(config(44100, 0).as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("ttl.latent_dim"));
+}
+
+#[test]
+fn test_tensor_info_display() {
+    use supertonic_tts::TensorInfo;
+
+    let info = TensorInfo {
+        name: "text_ids".to_string(),
+        dtype: "i64".to_string(),
+        shape: vec![-1, -1, 4],
+        dim_names: vec!["batch".to_string(), String::new(), String::new()],
+    };
+    assert_eq!(info.to_string(), "text_ids: i64 [batch, ?, 4]");
+    assert_eq!(info.dynamic_dims().collect::<Vec<_>>(), [0, 1]);
+}
+
+#[test]
+fn test_load_from_readers_reads_config_first() {
+    use supertonic_tts::error::SupertonicError;
+    use supertonic_tts::{load_text_to_speech_from_readers, ModelReaders};
+
+    let empty = || Box::new(std::io::empty()) as Box<dyn std::io::Read>;
+    let readers = ModelReaders {
+        config: Box::new(
+            &br#"{ "ae": { "sample_rate": 0, "base_chunk_size": 512 },
+            "ttl": { "chunk_compress_factor": 6, "latent_dim": 24 } }"#[..],
+        ),
+        duration_predictor: empty(),
+        text_encoder: empty(),
+        vector_estimator: empty(),
+        vocoder: empty(),
+        unicode_indexer: empty(),
+    };
+    match load_text_to_speech_from_readers(readers, false) {
+        Err(SupertonicError::Config(message)) => assert!(message.contains("sample_rate")),
+        other => panic!("expected a config error, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn test_data_dir_override() {
+    use supertonic_tts::paths;
+
+    let dir = std::env::temp_dir().join(format!("supertonic-data-{}", std::process::id()));
+    assert_eq!(
+        paths::data_dir_with(Some(dir.clone().into_os_string())),
+        Some(dir.clone())
+    );
+    assert_ne!(paths::data_dir_with(None), Some(dir.clone()));
+
+    // Installed models are used once they are there, unless ./assets has its own
+    let assets = dir.join("assets");
+    let local = std::path::Path::new("assets/onnx/tts.json").is_file();
+    if !local {
+        assert_eq!(
+            paths::assets_dir_with(Some(assets.clone())),
+            std::path::PathBuf::from("assets")
+        );
+    }
+    let onnx = assets.join("onnx");
+    std::fs::create_dir_all(&onnx).unwrap();
+    std::fs::write(onnx.join("tts.json"), b"{}").unwrap();
+    if !local {
+        assert_eq!(paths::assets_dir_with(Some(assets.clone())), assets);
+    }
+    assert_eq!(
+        paths::assets_dir_with(None),
+        std::path::PathBuf::from("assets")
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_empty_engine_registry() {
+    use supertonic_tts::error::SupertonicError;
+    use supertonic_tts::EngineRegistry;
+
+    let registry = EngineRegistry::new();
+    assert!(registry.is_empty());
+    assert_eq!(registry.default_id(), None);
+    assert!(registry.remove("en").is_none());
+    assert!(matches!(
+        registry.resolve(Some("ko")),
+        Err(SupertonicError::Validation(message)) if message.contains("'ko'")
+    ));
+    assert!(registry.resolve(None).is_err());
+    assert!(registry.set_default("en").is_err());
+}
+
+#[test]
+fn test_lru_cache_eviction() {
+    use supertonic_tts::LruCache;
+
+    let mut cache = LruCache::new(10);
+    cache.insert("a", 1, 4);
+    cache.insert("b", 2, 4);
+    assert_eq!(cache.get(&"a"), Some(1));
+    assert_eq!(cache.get(&"c"), None);
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+    // "b" was used longest ago, so it makes room
+    cache.insert("c", 3, 4);
+    assert!(!cache.contains(&"b"));
+    assert_eq!(cache.size(), 8);
+
+    // Replacing an entry frees its old size first
+    cache.insert("a", 4, 6);
+    assert_eq!((cache.len(), cache.size()), (2, 10));
+    assert_eq!(cache.get(&"a"), Some(4));
+
+    // Too large for the whole cache: not kept, nothing evicted
+    cache.insert("d", 5, 11);
+    assert!(!cache.contains(&"d"));
+    assert_eq!(cache.len(), 2);
+
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(cache.size(), 0);
+
+    // Entries read since they were added outlive those that were not
+    let mut cache = LruCache::new(4);
+    for key in 0..4 {
+        cache.insert(key, key, 1);
+    }
+    cache.get(&0);
+    cache.get(&2);
+    cache.insert(4, 4, 1);
+    cache.insert(5, 5, 1);
+    let mut kept: Vec<i32> = (0..6).filter(|key| cache.contains(key)).collect();
+    kept.sort();
+    assert_eq!(kept, [0, 2, 4, 5]);
+}
+
+#[test]
+fn test_pcm16_matches_scalar_conversion() {
+    use supertonic_tts::{pcm16_into, to_pcm16};
+
+    let scalar = |s: f32| (s.clamp(-1.0, 1.0) * 32767.0) as i16;
+    let sample = |i: usize| match i % 7 {
+        0 => 1.5,
+        1 => -2.0,
+        2 => f32::INFINITY,
+        3 => f32::NEG_INFINITY,
+        _ => (i as f32 * 0.37).sin(),
+    };
+    // Below, equal to and past the 16-sample blocks, with and without a remainder
+    for len in [0, 1, 15, 16, 17, 32, 45] {
+        let samples: Vec<f32> = (0..len).map(sample).collect();
+        let expected: Vec<i16> = samples.iter().map(|&s| scalar(s)).collect();
+        assert_eq!(to_pcm16(&samples), expected, "length {}", len);
+        let mut out = vec![0; len];
+        pcm16_into(&samples, &mut out);
+        assert_eq!(out, expected, "length {}", len);
+    }
+    assert_eq!(
+        to_pcm16(&[1.0, -1.0, 2.0, -2.0]),
+        [32767, -32767, 32767, -32767]
+    );
+}
+
+#[test]
+fn test_wav_bytes_round_trip() {
+    use supertonic_tts::{read_wav_bytes, to_pcm16, wav_bytes};
+
+    let samples: Vec<f32> = (0..100).map(|i| (i as f32 * 0.1).sin() * 0.8).collect();
+    let bytes = wav_bytes(&samples, 24_000).unwrap();
+    let (decoded, sample_rate) = read_wav_bytes(&bytes).unwrap();
+    assert_eq!(sample_rate, 24_000);
+    let expected: Vec<f32> = to_pcm16(&samples)
+        .iter()
+        .map(|&s| s as f32 / 32768.0)
+        .collect();
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn test_encoder_cache_hits() {
+    use supertonic_tts::cache::{EncoderCache, EncoderKey, EncoderOutputs};
+    use supertonic_tts::load_voice_style_from_bytes;
+
+    let voice = |value: f32| {
+        let component =
+            format!(r#"{{ "data": [[[{value}, 0.5]]], "dims": [1, 1, 2], "type": "float32" }}"#);
+        let json = format!(r#"{{ "style_ttl": {component}, "style_dp": {component} }}"#);
+        load_voice_style_from_bytes(&[json.as_bytes()], false).unwrap()
+    };
+    let (calm, bright) = (voice(0.1), voice(0.9));
+    let outputs = |duration: f32| EncoderOutputs {
+        duration: vec![duration],
+        text_emb: ndarray::Array3::zeros((1, 2, 3)),
+    };
+    let hello = vec![vec![1, 2, 3]];
+
+    let mut cache = EncoderCache::new(2);
+    assert!(cache.get(&EncoderKey::new(&hello, &calm)).is_none());
+    cache.insert(EncoderKey::new(&hello, &calm), outputs(1.5));
+
+    // The same tokens and voice skip the encoders, whatever else changes
+    let hit = cache.get(&EncoderKey::new(&hello, &calm)).unwrap();
+    assert_eq!(hit.duration, [1.5]);
+    assert_eq!(hit.text_emb.shape(), [1, 2, 3]);
+    // Another voice or other tokens are encoded again
+    assert!(cache.get(&EncoderKey::new(&hello, &bright)).is_none());
+    assert!(cache.get(&EncoderKey::new(&[vec![1, 2]], &calm)).is_none());
+    assert_eq!((cache.hits(), cache.misses()), (1, 3));
+
+    // Each batch counts as one entry
+    cache.insert(EncoderKey::new(&hello, &bright), outputs(2.0));
+    cache.insert(EncoderKey::new(&[vec![4]], &calm), outputs(0.5));
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(&EncoderKey::new(&hello, &calm)).is_none());
+}
+
+#[test]
+fn test_reload_config_check() {
+    use supertonic_tts::cache::{EncoderCache, EncoderKey, EncoderOutputs};
+    use supertonic_tts::config::load_cfgs_from_bytes;
+    use supertonic_tts::error::SupertonicError;
+    use supertonic_tts::{check_reload_config, load_voice_style_from_bytes};
+
+    let config = |sample_rate: i32, latent_dim: i32| {
+        let json = format!(
+            r#"{{ "ae": {{ "sample_rate": {}, "base_chunk_size": 512 }},
+                 "ttl": {{ "chunk_compress_factor": 6, "latent_dim": {} }} }}"#,
+            sample_rate, latent_dim
+        );
+        load_cfgs_from_bytes(json.as_bytes()).unwrap()
+    };
+    let loaded = config(44100, 24);
+    assert!(check_reload_config(&loaded, &config(44100, 24)).is_ok());
+    match check_reload_config(&loaded, &config(24000, 32)) {
+        Err(SupertonicError::Validation(message)) => {
+            assert!(message.contains("ae.sample_rate is 24000, was 44100"));
+            assert!(message.contains("ttl.latent_dim is 32, was 24"));
+            assert!(!message.contains("base_chunk_size"));
+        }
+        other => panic!("expected a validation error, got {:?}", other.err()),
+    }
+
+    // Outputs of the old models are dropped, the caches themselves stay
+    let component = r#"{ "data": [[[0.1, 0.5]]], "dims": [1, 1, 2], "type": "float32" }"#;
+    let json = format!(r#"{{ "style_ttl": {component}, "style_dp": {component} }}"#);
+    let style = load_voice_style_from_bytes(&[json.as_bytes()], false).unwrap();
+    let key = EncoderKey::new(&[vec![1, 2]], &style);
+    let mut encoder_cache = EncoderCache::new(4);
+    encoder_cache.insert(
+        key.clone(),
+        EncoderOutputs {
+            duration: vec![1.0],
+            text_emb: ndarray::Array3::zeros((1, 1, 1)),
+        },
+    );
+    encoder_cache.clear();
+    assert!(encoder_cache.is_empty());
+    assert!(encoder_cache.get(&key).is_none());
+    assert_eq!(encoder_cache.capacity(), 4);
+}
+
+#[cfg(feature = "bundle")]
+#[test]
+fn test_tar_zst_bundle_round_trip() {
+    use supertonic_tts::ModelBundle;
+
+    let mut tar = tar::Builder::new(Vec::new());
+    for (path, contents) in [
+        ("supertonic/onnx/tts.json", &b"{}"[..]),
+        ("supertonic/voice_styles/M1.json", &b"{ \"voice\": 1 }"[..]),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, path, contents).unwrap();
+    }
+    let tar = tar.into_inner().unwrap();
+    let compressed = ruzstd::encoding::compress_to_vec(
+        tar.as_slice(),
+        ruzstd::encoding::CompressionLevel::Fastest,
+    );
+
+    let check = |bundle: ModelBundle| {
+        assert_eq!(bundle.file("tts.json"), Some(&b"{}"[..]));
+        assert_eq!(bundle.voice_styles(), [("M1", &b"{ \"voice\": 1 }"[..])]);
+    };
+    check(ModelBundle::from_bytes(&compressed).unwrap());
+    let path = std::env::temp_dir().join(format!("supertonic-{}.tar.zst", std::process::id()));
+    std::fs::write(&path, &compressed).unwrap();
+    check(ModelBundle::open(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    // A truncated stream fails instead of yielding a partial bundle
+    assert!(ModelBundle::from_bytes(&compressed[..compressed.len() / 2]).is_err());
+}
+
+#[test]
+fn test_snapshot_freshness() {
+    use supertonic_tts::snapshot::{snapshot_is_fresh, SnapshotManifest};
+    use supertonic_tts::{GraphOptLevel, SessionConfig};
+
+    let models = [
+        "duration_predictor",
+        "text_encoder",
+        "vector_estimator",
+        "vocoder",
+    ];
+    let dir = std::env::temp_dir().join(format!("supertonic-snapshot-{}", std::process::id()));
+    let onnx_dir = dir.join("onnx");
+    let snapshot_dir = dir.join("snapshot");
+    std::fs::create_dir_all(&onnx_dir).unwrap();
+    std::fs::create_dir_all(&snapshot_dir).unwrap();
+    for name in models {
+        std::fs::write(onnx_dir.join(format!("{}.onnx", name)), b"model").unwrap();
+    }
+
+    let config = SessionConfig::default();
+    let manifest = SnapshotManifest::current(&onnx_dir, &config).unwrap();
+    assert_eq!(manifest.execution_providers, vec!["cpu".to_string()]);
+    assert_eq!(manifest.graph_opt_level, GraphOptLevel::All);
+
+    // Not fresh until the manifest and every optimized graph are present
+    assert!(!snapshot_is_fresh(&snapshot_dir, &manifest));
+    manifest.save(&snapshot_dir).unwrap();
+    assert!(!snapshot_is_fresh(&snapshot_dir, &manifest));
+    for name in models {
+        std::fs::write(
+            snapshot_dir.join(format!("{}.optimized.onnx", name)),
+            b"graph",
+        )
+        .unwrap();
+    }
+    assert!(snapshot_is_fresh(&snapshot_dir, &manifest));
+
+    // Any change to the device, optimization level or session options is stale
+    let stale_configs = [
+        SessionConfig::with_gpu(true),
+        SessionConfig {
+            graph_opt_level: Some(GraphOptLevel::Basic),
+            ..SessionConfig::default()
+        },
+        SessionConfig {
+            intra_threads: Some(2),
+            ..SessionConfig::default()
+        },
+        SessionConfig {
+            memory_arena: false,
+            ..SessionConfig::default()
+        },
+    ];
+    for stale in &stale_configs {
+        let expected = SnapshotManifest::current(&onnx_dir, stale).unwrap();
+        assert!(!snapshot_is_fresh(&snapshot_dir, &expected), "{:?}", stale);
+    }
+
+    // So is a changed source model
+    std::fs::write(onnx_dir.join("vocoder.onnx"), b"retrained model").unwrap();
+    let expected = SnapshotManifest::current(&onnx_dir, &config).unwrap();
+    assert!(!snapshot_is_fresh(&snapshot_dir, &expected));
+
+    // And a missing optimized graph or an unreadable manifest
+    assert!(snapshot_is_fresh(&snapshot_dir, &manifest));
+    std::fs::remove_file(snapshot_dir.join("vocoder.optimized.onnx")).unwrap();
+    assert!(!snapshot_is_fresh(&snapshot_dir, &manifest));
+    std::fs::write(snapshot_dir.join("vocoder.optimized.onnx"), b"graph").unwrap();
+    std::fs::write(snapshot_dir.join("snapshot.json"), b"{").unwrap();
+    assert!(!snapshot_is_fresh(&snapshot_dir, &manifest));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
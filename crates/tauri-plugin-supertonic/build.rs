@@ -2,11 +2,32 @@ const COMMANDS: &[&str] = &[
     "initialize",
     "set_voice",
     "load_engine",
+    "reload_engine",
+    "load_model",
+    "unload_model",
+    "list_models",
+    "load_bundle",
     "load_voice",
     "speak",
+    "speak_clipboard",
     "speak_batch",
     "get_engine_info",
+    "calibrate",
+    "estimate_latency",
+    "preview",
+    "preview_chunks",
+    "preprocess_report",
+    "get_capabilities",
+    "get_version",
+    "register_hotkeys",
+    "unregister_hotkeys",
     "save_wav",
+    "audio_preview",
+    "play_audio",
+    "pause_playback",
+    "resume_playback",
+    "stop_playback",
+    "read_aloud",
 ];
 
 fn main() {
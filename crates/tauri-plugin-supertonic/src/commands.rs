@@ -1,9 +1,20 @@
 use crate::error::{Error, Result};
+#[cfg(desktop)]
+use crate::hotkey::{self, HotkeyBindings};
 use crate::SupertonicState;
 use std::fs;
+use std::io::{self, Read};
 use std::path::PathBuf;
+use std::sync::Arc;
+#[cfg(feature = "playback")]
+use supertonic_tts::Player;
 use supertonic_tts::{
-    load_text_to_speech_from_memory, load_voice_style_from_bytes, write_wav_file, ModelBytes,
+    capabilities, load_text_to_speech_from_memory, load_text_to_speech_from_readers,
+    load_text_to_speech_with_snapshot, load_voice_style_from_bytes, mel_spectrogram, version,
+    write_wav_file, AudioStats, Capabilities, MelOptions, MelSpectrogram, ModelBytes, ModelReaders,
+    PerformanceProfile, PreprocessReport, PreviewRange, SessionConfig, SharedEngine,
+    SynthesisOptions, SynthesisWarning, TextToSpeech, VersionInfo, VoiceCatalog, VoiceEntry,
+    VoiceManager, WaveformBucket, DEFAULT_MODEL_ID, DEFAULT_PREVIEW_SECS,
 };
 use tauri::{AppHandle, Manager, Runtime, State};
 
@@ -62,11 +73,71 @@ fn read_resource<R: Runtime>(app: &AppHandle<R>, resource_path: &str) -> Result<
     fs::read(&path).map_err(Error::Io)
 }
 
-/// List of available voices
-#[derive(serde::Serialize)]
-pub struct VoiceInfo {
-    pub id: String,
-    pub name: String,
+/// Load the engine directly from bundled resources (no snapshot)
+fn load_bundled_engine<R: Runtime>(app: &AppHandle<R>) -> Result<TextToSpeech> {
+    let config_bytes = read_resource(app, "onnx/tts.json")?;
+    let dp_bytes = read_resource(app, "onnx/duration_predictor.onnx")?;
+    let text_enc_bytes = read_resource(app, "onnx/text_encoder.onnx")?;
+    let vector_est_bytes = read_resource(app, "onnx/vector_estimator.onnx")?;
+    let vocoder_bytes = read_resource(app, "onnx/vocoder.onnx")?;
+    let unicode_indexer_bytes = read_resource(app, "onnx/unicode_indexer.json")?;
+
+    let models = ModelBytes {
+        config: &config_bytes,
+        duration_predictor: &dp_bytes,
+        text_encoder: &text_enc_bytes,
+        vector_estimator: &vector_est_bytes,
+        vocoder: &vocoder_bytes,
+        unicode_indexer: &unicode_indexer_bytes,
+    };
+
+    load_text_to_speech_from_memory(models, false).map_err(Error::Supertonic)
+}
+
+/// Fallbacks for options neither the frontend nor the selected voice sets
+fn plugin_default_options() -> SynthesisOptions {
+    SynthesisOptions {
+        total_step: Some(10),
+        speed: Some(1.0),
+        silence_duration: Some(0.2),
+        ..Default::default()
+    }
+}
+
+/// Where `calibrate` saves the performance profile
+fn profile_path<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_cache_dir()
+        .ok()
+        .map(|dir| dir.join("performance-profile.json"))
+}
+
+/// Plugin defaults plus the saved performance profile, if this machine has one
+fn configure_engine<R: Runtime>(app: &AppHandle<R>, engine: &mut TextToSpeech) {
+    engine.set_default_options(plugin_default_options());
+    engine.set_profile(profile_path(app).and_then(PerformanceProfile::load_for_current_device));
+}
+
+/// The engine loaded as `model_id`, or the default engine without one
+fn model_engine(state: &SupertonicState, model_id: Option<&str>) -> Result<SharedEngine> {
+    match model_id {
+        Some(id) => state.engines.get(id).ok_or_else(|| {
+            Error::State(format!(
+                "No model loaded as '{}'. Call 'load_model' first.",
+                id
+            ))
+        }),
+        None => state.engines.default_engine().ok_or(Error::State(
+            "Engine not initialized. Call 'initialize' first.".to_string(),
+        )),
+    }
+}
+
+/// Load `engine` as the default engine, replacing the one loaded before
+fn set_default_engine(state: &SupertonicState, engine: TextToSpeech) {
+    state.engines.insert(DEFAULT_MODEL_ID, engine);
+    // Still the default when other models were loaded first
+    let _ = state.engines.set_default(DEFAULT_MODEL_ID);
 }
 
 /// Initialize response with available voices
@@ -74,7 +145,26 @@ pub struct VoiceInfo {
 pub struct InitResponse {
     pub success: bool,
     pub sample_rate: i32,
-    pub available_voices: Vec<VoiceInfo>,
+    /// The bundled `voices.json`, or one entry per style file without one
+    pub available_voices: Vec<VoiceEntry>,
+}
+
+/// Manager of the bundled voice styles, opened on first use
+fn voice_manager<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &SupertonicState,
+) -> Result<Arc<VoiceManager>> {
+    let mut voices = state.voices.lock().unwrap();
+    if let Some(voices) = &*voices {
+        return Ok(Arc::clone(voices));
+    }
+    let voices_dir = get_assets_dir(app)?.join("voice_styles");
+    let manager = if voices_dir.is_dir() {
+        VoiceManager::open(&voices_dir).map_err(Error::Supertonic)?
+    } else {
+        VoiceManager::new(&voices_dir, VoiceCatalog::default())
+    };
+    Ok(Arc::clone(voices.insert(Arc::new(manager))))
 }
 
 /// Initialize the TTS engine with bundled resources
@@ -83,51 +173,35 @@ pub async fn initialize<R: Runtime>(
     app: AppHandle<R>,
     state: State<'_, SupertonicState>,
 ) -> Result<InitResponse> {
-    // Load ONNX models from bundled resources
-    let config_bytes = read_resource(&app, "onnx/tts.json")?;
-    let dp_bytes = read_resource(&app, "onnx/duration_predictor.onnx")?;
-    let text_enc_bytes = read_resource(&app, "onnx/text_encoder.onnx")?;
-    let vector_est_bytes = read_resource(&app, "onnx/vector_estimator.onnx")?;
-    let vocoder_bytes = read_resource(&app, "onnx/vocoder.onnx")?;
-    let unicode_indexer_bytes = read_resource(&app, "onnx/unicode_indexer.json")?;
-
-    let models = ModelBytes {
-        config: &config_bytes,
-        duration_predictor: &dp_bytes,
-        text_encoder: &text_enc_bytes,
-        vector_estimator: &vector_est_bytes,
-        vocoder: &vocoder_bytes,
-        unicode_indexer: &unicode_indexer_bytes,
-    };
-
-    let engine = load_text_to_speech_from_memory(models, false).map_err(Error::Supertonic)?;
-    let sample_rate = engine.sample_rate;
-
-    *state.engine.lock().unwrap() = Some(engine);
-
-    // Discover available voice styles
-    let assets_dir = get_assets_dir(&app)?;
-    let voices_dir = assets_dir.join("voice_styles");
-
-    let mut available_voices = Vec::new();
-    if let Ok(entries) = fs::read_dir(&voices_dir) {
-        for entry in entries.flatten() {
-            if let Some(name) = entry.file_name().to_str() {
-                if name.ends_with(".json") {
-                    let id = name.trim_end_matches(".json").to_string();
-                    available_voices.push(VoiceInfo {
-                        id: id.clone(),
-                        name: id,
-                    });
+    // Prefer restoring an optimized snapshot from the app cache for fast cold starts;
+    // the snapshot is rebuilt transparently whenever the bundled models change, and
+    // a snapshot that cannot be used never stops the bundled models from loading.
+    let mut engine = match app.path().app_cache_dir() {
+        Ok(cache_dir) => {
+            let onnx_dir = get_assets_dir(&app)?.join("onnx");
+            match load_text_to_speech_with_snapshot(
+                &onnx_dir,
+                cache_dir.join("engine-snapshot"),
+                &SessionConfig::default(),
+            ) {
+                Ok(engine) => engine,
+                Err(e) => {
+                    tracing::warn!("Engine snapshot unavailable, loading bundled models: {}", e);
+                    load_bundled_engine(&app)?
                 }
             }
         }
-    }
+        Err(_) => load_bundled_engine(&app)?,
+    };
+    configure_engine(&app, &mut engine);
+    let sample_rate = engine.sample_rate;
+
+    set_default_engine(&state, engine);
 
     Ok(InitResponse {
         success: true,
         sample_rate,
-        available_voices,
+        available_voices: voice_manager(&app, &state)?.catalog().voices.clone(),
     })
 }
 
@@ -138,11 +212,9 @@ pub async fn set_voice<R: Runtime>(
     state: State<'_, SupertonicState>,
     voice_id: String,
 ) -> Result<()> {
-    let voice_path = format!("voice_styles/{}.json", voice_id);
-    let voice_bytes = read_resource(&app, &voice_path)?;
-
-    let byte_slices = vec![voice_bytes.as_slice()];
-    let style = load_voice_style_from_bytes(&byte_slices, false).map_err(Error::Supertonic)?;
+    let style = voice_manager(&app, &state)?
+        .get(&voice_id)
+        .map_err(Error::Supertonic)?;
 
     *state.style.lock().unwrap() = Some(style);
 
@@ -152,7 +224,7 @@ pub async fn set_voice<R: Runtime>(
 /// Legacy: Load engine from custom path (for development/testing)
 #[tauri::command]
 pub async fn load_engine<R: Runtime>(
-    _app: AppHandle<R>,
+    app: AppHandle<R>,
     state: State<'_, SupertonicState>,
     onnx_dir: String,
 ) -> Result<()> {
@@ -175,8 +247,129 @@ pub async fn load_engine<R: Runtime>(
         unicode_indexer: &unicode_indexer_bytes,
     };
 
-    let engine = load_text_to_speech_from_memory(models, false).map_err(Error::Supertonic)?;
-    *state.engine.lock().unwrap() = Some(engine);
+    let mut engine = load_text_to_speech_from_memory(models, false).map_err(Error::Supertonic)?;
+    configure_engine(&app, &mut engine);
+    set_default_engine(&state, engine);
+
+    Ok(())
+}
+
+/// Swap the models of the default engine (or the one loaded as `model_id`)
+/// for those in `onnx_dir` (or the bundled ones) in place, keeping its
+/// settings and the selected voice; requests already waiting for the engine
+/// run on the new models
+#[tauri::command]
+pub async fn reload_engine<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    onnx_dir: Option<String>,
+    model_id: Option<String>,
+) -> Result<()> {
+    let read = |name: &str| match &onnx_dir {
+        Some(dir) => fs::read(PathBuf::from(dir).join(name)).map_err(Error::Io),
+        None => read_resource(&app, &format!("onnx/{}", name)),
+    };
+    let config_bytes = read("tts.json")?;
+    let dp_bytes = read("duration_predictor.onnx")?;
+    let text_enc_bytes = read("text_encoder.onnx")?;
+    let vector_est_bytes = read("vector_estimator.onnx")?;
+    let vocoder_bytes = read("vocoder.onnx")?;
+    let unicode_indexer_bytes = read("unicode_indexer.json")?;
+
+    let models = ModelBytes {
+        config: &config_bytes,
+        duration_predictor: &dp_bytes,
+        text_encoder: &text_enc_bytes,
+        vector_estimator: &vector_est_bytes,
+        vocoder: &vocoder_bytes,
+        unicode_indexer: &unicode_indexer_bytes,
+    };
+
+    let engine = model_engine(&state, model_id.as_deref())?;
+    let mut engine = engine.lock().unwrap();
+    engine.reload(models).map_err(Error::Supertonic)
+}
+
+/// Load the models in `onnx_dir` (or the bundled ones) as `model_id`, next
+/// to the engines already loaded, for commands given that `model_id`;
+/// loading an id again replaces its engine
+#[tauri::command]
+pub async fn load_model<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    model_id: String,
+    onnx_dir: Option<String>,
+) -> Result<()> {
+    let open = |name: &str| -> Result<Box<dyn Read>> {
+        match &onnx_dir {
+            Some(dir) => Ok(Box::new(
+                fs::File::open(PathBuf::from(dir).join(name)).map_err(Error::Io)?,
+            )),
+            None => Ok(Box::new(io::Cursor::new(read_resource(
+                &app,
+                &format!("onnx/{}", name),
+            )?))),
+        }
+    };
+    let readers = ModelReaders {
+        config: open("tts.json")?,
+        duration_predictor: open("duration_predictor.onnx")?,
+        text_encoder: open("text_encoder.onnx")?,
+        vector_estimator: open("vector_estimator.onnx")?,
+        vocoder: open("vocoder.onnx")?,
+        unicode_indexer: open("unicode_indexer.json")?,
+    };
+
+    let mut engine = load_text_to_speech_from_readers(readers, false).map_err(Error::Supertonic)?;
+    configure_engine(&app, &mut engine);
+    state.engines.insert(&model_id, engine);
+
+    Ok(())
+}
+
+/// Drop the engine loaded as `model_id`, returning whether there was one;
+/// requests already running on it finish first
+#[tauri::command]
+pub async fn unload_model<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    model_id: String,
+) -> Result<bool> {
+    Ok(state.engines.remove(&model_id).is_some())
+}
+
+/// Loaded model sets
+#[derive(serde::Serialize)]
+pub struct ModelList {
+    /// Ids of the loaded engines, in order
+    pub models: Vec<String>,
+    /// Id commands without a `model_id` use
+    pub default_model: Option<String>,
+}
+
+#[tauri::command]
+pub async fn list_models<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+) -> Result<ModelList> {
+    Ok(ModelList {
+        models: state.engines.ids(),
+        default_model: state.engines.default_id(),
+    })
+}
+
+/// Load the engine from a single-archive model bundle (`.zip` or `.tar`)
+#[cfg(feature = "bundle")]
+#[tauri::command]
+pub async fn load_bundle<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    bundle_path: String,
+) -> Result<()> {
+    let mut engine = supertonic_tts::load_text_to_speech_from_bundle(&bundle_path, false)
+        .map_err(Error::Supertonic)?;
+    configure_engine(&app, &mut engine);
+    set_default_engine(&state, engine);
 
     Ok(())
 }
@@ -196,53 +389,158 @@ pub async fn load_voice<R: Runtime>(
     let byte_slices: Vec<&[u8]> = bytes_buffers.iter().map(|b| b.as_slice()).collect();
     let style = load_voice_style_from_bytes(&byte_slices, false).map_err(Error::Supertonic)?;
 
-    *state.style.lock().unwrap() = Some(style);
+    *state.style.lock().unwrap() = Some(Arc::new(style));
 
     Ok(())
 }
 
 /// Response from speak command
-#[derive(serde::Serialize)]
+#[derive(Clone, serde::Serialize)]
 pub struct SpeakResponse {
     pub audio: Vec<f32>,
     pub duration: f32,
     pub sample_rate: i32,
+    /// Non-fatal problems, e.g. skipped characters
+    pub warnings: Vec<SynthesisWarning>,
+    /// Peak, RMS, loudness and silence figures of the audio
+    pub stats: Option<AudioStats>,
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn speak<R: Runtime>(
     _app: AppHandle<R>,
     state: State<'_, SupertonicState>,
     text: String,
     speed: Option<f32>,
     silence_duration: Option<f32>,
+    paragraph_silence: Option<f32>,
+    clause_silence: Option<f32>,
     total_step: Option<usize>,
+    gain_db: Option<f32>,
+    locale: Option<String>,
+    timezone: Option<String>,
+    target_sample_rate: Option<i32>,
+    limiter_db: Option<f32>,
+    model_id: Option<String>,
 ) -> Result<SpeakResponse> {
-    let mut engine_guard = state.engine.lock().unwrap();
-    let engine = engine_guard.as_mut().ok_or(Error::State(
-        "Engine not initialized. Call 'initialize' first.".to_string(),
-    ))?;
+    let options = SynthesisOptions {
+        total_step,
+        speed,
+        silence_duration,
+        paragraph_silence,
+        clause_silence,
+        gain_db,
+        locale,
+        timezone,
+        target_sample_rate,
+        limiter_db,
+        ..Default::default()
+    };
+    speak_text(&state, model_id.as_deref(), &text, &options)
+}
+
+/// What `speak_clipboard` resolves with: the seconds read aloud when the
+/// plugin plays the clipboard itself, otherwise the audio for the frontend
+#[cfg(all(desktop, feature = "playback"))]
+pub type ClipboardSpeech = f32;
+#[cfg(all(desktop, not(feature = "playback")))]
+pub type ClipboardSpeech = SpeakResponse;
+
+/// Speak the text currently on the system clipboard. With the `playback`
+/// feature it is read aloud on the default output device like `read_aloud`;
+/// without it the audio is returned for the frontend to play.
+#[cfg(desktop)]
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn speak_clipboard<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    speed: Option<f32>,
+    silence_duration: Option<f32>,
+    paragraph_silence: Option<f32>,
+    clause_silence: Option<f32>,
+    total_step: Option<usize>,
+    gain_db: Option<f32>,
+    locale: Option<String>,
+    timezone: Option<String>,
+    target_sample_rate: Option<i32>,
+    limiter_db: Option<f32>,
+) -> Result<ClipboardSpeech> {
+    let text = read_clipboard(&app)?;
+    let options = SynthesisOptions {
+        total_step,
+        speed,
+        silence_duration,
+        paragraph_silence,
+        clause_silence,
+        gain_db,
+        locale,
+        timezone,
+        target_sample_rate,
+        limiter_db,
+        ..Default::default()
+    };
+    #[cfg(feature = "playback")]
+    return read_text_aloud(&state, None, &text, &options);
+    #[cfg(not(feature = "playback"))]
+    speak_text(&state, None, &text, &options)
+}
+
+/// Clipboard text, cleaned up for reading aloud
+#[cfg(desktop)]
+pub(crate) fn read_clipboard<R: Runtime>(app: &AppHandle<R>) -> Result<String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let text = app
+        .clipboard()
+        .read_text()
+        .map_err(|e| Error::Clipboard(e.to_string()))?;
+    let text = clean_clipboard_text(&text);
+    if text.is_empty() {
+        return Err(Error::Clipboard("Clipboard has no text".to_string()));
+    }
+    Ok(text)
+}
+
+/// Join hard-wrapped lines of copied text, keeping blank-line paragraph breaks
+#[cfg(desktop)]
+fn clean_clipboard_text(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+pub(crate) fn speak_text(
+    state: &SupertonicState,
+    model_id: Option<&str>,
+    text: &str,
+    options: &SynthesisOptions,
+) -> Result<SpeakResponse> {
+    let engine = model_engine(state, model_id)?;
+    let mut engine = engine.lock().unwrap();
 
     let style_guard = state.style.lock().unwrap();
     let style = style_guard.as_ref().ok_or(Error::State(
         "No voice selected. Call 'set_voice' first.".to_string(),
     ))?;
 
-    let sample_rate = engine.sample_rate;
+    let sample_rate = engine
+        .output_sample_rate(options)
+        .map_err(Error::Supertonic)?;
     let (audio, duration) = engine
-        .call(
-            &text,
-            style,
-            total_step.unwrap_or(10),
-            speed.unwrap_or(1.0),
-            silence_duration.unwrap_or(0.2),
-        )
+        .synthesize(text, style, options)
         .map_err(Error::Supertonic)?;
 
     Ok(SpeakResponse {
         audio,
         duration,
         sample_rate,
+        warnings: engine.last_warnings().to_vec(),
+        stats: engine.last_audio_stats().first().copied(),
     })
 }
 
@@ -252,6 +550,9 @@ pub struct BatchSpeakResponse {
     pub audio_list: Vec<Vec<f32>>,
     pub durations: Vec<f32>,
     pub sample_rate: i32,
+    pub warnings: Vec<SynthesisWarning>,
+    /// Figures per text, as in `SpeakResponse::stats`
+    pub stats: Vec<AudioStats>,
 }
 
 #[tauri::command]
@@ -261,31 +562,36 @@ pub async fn speak_batch<R: Runtime>(
     texts: Vec<String>,
     speed: Option<f32>,
     total_step: Option<usize>,
+    gain_db: Option<f32>,
+    model_id: Option<String>,
 ) -> Result<BatchSpeakResponse> {
-    let mut engine_guard = state.engine.lock().unwrap();
-    let engine = engine_guard
-        .as_mut()
-        .ok_or(Error::State("Engine not initialized".to_string()))?;
+    let engine = model_engine(&state, model_id.as_deref())?;
+    let mut engine = engine.lock().unwrap();
 
     let style_guard = state.style.lock().unwrap();
     let style = style_guard
         .as_ref()
         .ok_or(Error::State("No voice selected".to_string()))?;
 
-    let sample_rate = engine.sample_rate;
+    let options = SynthesisOptions {
+        total_step,
+        speed,
+        gain_db,
+        ..Default::default()
+    };
+    let sample_rate = engine
+        .output_sample_rate(&options)
+        .map_err(Error::Supertonic)?;
     let (audio_list, durations) = engine
-        .batch(
-            &texts,
-            style,
-            total_step.unwrap_or(10),
-            speed.unwrap_or(1.0),
-        )
+        .synthesize_batch(&texts, style, &options)
         .map_err(Error::Supertonic)?;
 
     Ok(BatchSpeakResponse {
         audio_list,
         durations,
         sample_rate,
+        warnings: engine.last_warnings().to_vec(),
+        stats: engine.last_audio_stats().to_vec(),
     })
 }
 
@@ -295,6 +601,7 @@ pub struct EngineInfo {
     pub initialized: bool,
     pub voice_loaded: bool,
     pub sample_rate: Option<i32>,
+    pub capabilities: Capabilities,
 }
 
 #[tauri::command]
@@ -302,30 +609,440 @@ pub async fn get_engine_info<R: Runtime>(
     _app: AppHandle<R>,
     state: State<'_, SupertonicState>,
 ) -> Result<EngineInfo> {
-    let engine_guard = state.engine.lock().unwrap();
+    let engine = state.engines.default_engine();
     let style_guard = state.style.lock().unwrap();
 
     Ok(EngineInfo {
-        initialized: engine_guard.is_some(),
+        initialized: engine.is_some(),
         voice_loaded: style_guard.is_some(),
-        sample_rate: engine_guard.as_ref().map(|e| e.sample_rate),
+        sample_rate: engine.map(|e| e.lock().unwrap().sample_rate),
+        capabilities: capabilities(),
+    })
+}
+
+/// Measure this machine's synthesis costs with the current voice.
+///
+/// Takes a few seconds. The profile is saved to the app cache and reused by
+/// later sessions on the same machine.
+#[tauri::command]
+pub async fn calibrate<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+) -> Result<PerformanceProfile> {
+    let engine = model_engine(&state, None)?;
+    let mut engine = engine.lock().unwrap();
+
+    let style_guard = state.style.lock().unwrap();
+    let style = style_guard.as_ref().ok_or(Error::State(
+        "No voice selected. Call 'set_voice' first.".to_string(),
+    ))?;
+
+    let profile = engine.calibrate(style).map_err(Error::Supertonic)?;
+    if let Some(path) = profile_path(&app) {
+        profile.save(path).map_err(Error::Supertonic)?;
+    }
+    Ok(profile)
+}
+
+/// Response from estimate_latency command
+#[derive(serde::Serialize)]
+pub struct LatencyEstimateResponse {
+    /// Seconds of audio the text renders to
+    pub audio_secs: f64,
+    /// Expected synthesis time in seconds
+    pub synthesis_secs: f64,
+}
+
+/// Predict audio length and synthesis time for `text` without synthesizing it.
+///
+/// Runs a quick calibration first if `calibrate` has never been run on this machine.
+#[tauri::command]
+pub async fn estimate_latency<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    text: String,
+    speed: Option<f32>,
+    total_step: Option<usize>,
+) -> Result<LatencyEstimateResponse> {
+    let engine = model_engine(&state, None)?;
+    let mut engine = engine.lock().unwrap();
+
+    let style_guard = state.style.lock().unwrap();
+    let style = style_guard.as_ref().ok_or(Error::State(
+        "No voice selected. Call 'set_voice' first.".to_string(),
+    ))?;
+
+    let options = SynthesisOptions {
+        total_step,
+        speed,
+        ..Default::default()
+    };
+    let estimate = engine
+        .estimate_latency(&text, style, &options)
+        .map_err(Error::Supertonic)?;
+
+    Ok(LatencyEstimateResponse {
+        audio_secs: estimate.audio.as_secs_f64(),
+        synthesis_secs: estimate.synthesis.as_secs_f64(),
+    })
+}
+
+/// Response from preview command
+#[derive(serde::Serialize)]
+pub struct PreviewResponse {
+    pub audio: Vec<f32>,
+    pub duration: f32,
+    pub sample_rate: i32,
+    /// Index of the first chunk rendered
+    pub first_chunk: usize,
+    /// Number of chunks rendered
+    pub rendered_chunks: usize,
+    /// Number of chunks in the whole text
+    pub chunk_count: usize,
+    /// Whether the audio was cut off at `max_secs`
+    pub truncated: bool,
+    pub warnings: Vec<SynthesisWarning>,
+}
+
+/// Quickly render the start of `text` (up to `max_secs`, default 8 s) or, with
+/// `chunk`, one chunk of it, at a low step count for auditioning while editing
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn preview<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    text: String,
+    max_secs: Option<f32>,
+    chunk: Option<usize>,
+    speed: Option<f32>,
+    total_step: Option<usize>,
+    gain_db: Option<f32>,
+    locale: Option<String>,
+    model_id: Option<String>,
+) -> Result<PreviewResponse> {
+    let engine = model_engine(&state, model_id.as_deref())?;
+    let mut engine = engine.lock().unwrap();
+
+    let style_guard = state.style.lock().unwrap();
+    let style = style_guard.as_ref().ok_or(Error::State(
+        "No voice selected. Call 'set_voice' first.".to_string(),
+    ))?;
+
+    let options = SynthesisOptions {
+        total_step,
+        speed,
+        gain_db,
+        locale,
+        ..Default::default()
+    };
+    let range = match chunk {
+        Some(index) => PreviewRange::Chunk(index),
+        None => PreviewRange::Start {
+            max_secs: max_secs.unwrap_or(DEFAULT_PREVIEW_SECS),
+        },
+    };
+    let sample_rate = engine
+        .output_sample_rate(&options)
+        .map_err(Error::Supertonic)?;
+    let preview = engine
+        .preview(&text, style, &options, range)
+        .map_err(Error::Supertonic)?;
+
+    Ok(PreviewResponse {
+        audio: preview.samples,
+        duration: preview.duration,
+        sample_rate,
+        first_chunk: preview.chunks.start,
+        rendered_chunks: preview.chunks.len(),
+        chunk_count: preview.chunk_count,
+        truncated: preview.truncated,
+        warnings: engine.last_warnings().to_vec(),
     })
 }
 
+/// The chunks `text` is synthesized in, as indexed by the `chunk` argument of `preview`
+#[tauri::command]
+pub async fn preview_chunks<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    text: String,
+    locale: Option<String>,
+) -> Result<Vec<String>> {
+    let engine = model_engine(&state, None)?;
+    let mut engine = engine.lock().unwrap();
+
+    let options = SynthesisOptions {
+        locale,
+        ..Default::default()
+    };
+    engine
+        .preview_chunks(&text, &options)
+        .map_err(Error::Supertonic)
+}
+
+/// What preprocessing does to `text`, stage by stage, to show users what will be spoken
+#[tauri::command]
+pub async fn preprocess_report<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    text: String,
+    locale: Option<String>,
+    timezone: Option<String>,
+) -> Result<PreprocessReport> {
+    let engine = model_engine(&state, None)?;
+    let mut engine = engine.lock().unwrap();
+
+    let options = SynthesisOptions {
+        locale,
+        timezone,
+        ..Default::default()
+    };
+    engine
+        .preprocess_report(&text, &options)
+        .map_err(Error::Supertonic)
+}
+
+/// Optional features supported by this build of the plugin
+#[tauri::command]
+pub async fn get_capabilities<R: Runtime>(_app: AppHandle<R>) -> Result<Capabilities> {
+    Ok(capabilities())
+}
+
+/// Crate, ONNX Runtime and model config versions of this build
+#[tauri::command]
+pub async fn get_version<R: Runtime>(_app: AppHandle<R>) -> Result<VersionInfo> {
+    Ok(version())
+}
+
+/// Register global shortcuts that speak the clipboard and stop playback.
+///
+/// Unset shortcuts use the defaults (`CmdOrControl+Shift+S` / `CmdOrControl+Shift+X`);
+/// previously registered plugin shortcuts are replaced.
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn register_hotkeys<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    speak: Option<String>,
+    stop: Option<String>,
+) -> Result<HotkeyBindings> {
+    let bindings = HotkeyBindings {
+        speak: speak.unwrap_or_else(|| hotkey::DEFAULT_SPEAK_SHORTCUT.to_string()),
+        stop: stop.unwrap_or_else(|| hotkey::DEFAULT_STOP_SHORTCUT.to_string()),
+    };
+    let mut registered = state.hotkeys.lock().unwrap();
+    if let Some(previous) = registered.take() {
+        hotkey::unregister(&app, &previous)?;
+    }
+    hotkey::register(&app, &bindings)?;
+    *registered = Some(bindings.clone());
+    Ok(bindings)
+}
+
+/// Remove the shortcuts added by `register_hotkeys`
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn unregister_hotkeys<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+) -> Result<()> {
+    if let Some(previous) = state.hotkeys.lock().unwrap().take() {
+        hotkey::unregister(&app, &previous)?;
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn save_wav<R: Runtime>(
     _app: AppHandle<R>,
     state: State<'_, SupertonicState>,
     audio: Vec<f32>,
     output_path: String,
+    sample_rate: Option<i32>,
 ) -> Result<()> {
-    let engine_guard = state.engine.lock().unwrap();
-    let engine = engine_guard
-        .as_ref()
-        .ok_or(Error::State("Engine not initialized".to_string()))?;
+    let engine = model_engine(&state, None)?;
+    let engine = engine.lock().unwrap();
 
-    write_wav_file(&output_path, &audio, engine.sample_rate)
+    // Audio rendered with `target_sample_rate` is saved at that rate
+    let sample_rate = sample_rate.unwrap_or(engine.sample_rate);
+    write_wav_file(&output_path, &audio, sample_rate)
         .map_err(|e| Error::State(format!("Failed to write WAV: {}", e)))?;
 
     Ok(())
 }
+
+/// Response for audio_preview
+#[derive(serde::Serialize)]
+pub struct AudioPreviewResponse {
+    /// Peak and RMS level per bucket, for drawing the waveform
+    pub waveform: Vec<WaveformBucket>,
+    pub mel: Option<MelSpectrogram>,
+}
+
+/// Waveform overview of audio (e.g. a `speak` response) and, when
+/// `mel_bands` is given, its mel spectrogram
+#[tauri::command]
+pub async fn audio_preview<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    audio: Vec<f32>,
+    buckets: usize,
+    sample_rate: Option<i32>,
+    mel_bands: Option<usize>,
+) -> Result<AudioPreviewResponse> {
+    let mel = match mel_bands {
+        Some(bands) => {
+            let sample_rate = match sample_rate {
+                Some(rate) => rate,
+                None => model_engine(&state, None)?.lock().unwrap().sample_rate,
+            };
+            let options = MelOptions {
+                bands,
+                ..Default::default()
+            };
+            Some(mel_spectrogram(&audio, sample_rate, &options))
+        }
+        None => None,
+    };
+    Ok(AudioPreviewResponse {
+        waveform: supertonic_tts::audio::preview(&audio, buckets),
+        mel,
+    })
+}
+
+/// Queue audio (e.g. a `speak` response) on the default output device,
+/// opening it on first use
+#[cfg(feature = "playback")]
+#[tauri::command]
+pub async fn play_audio<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    audio: Vec<f32>,
+    sample_rate: Option<i32>,
+) -> Result<()> {
+    let sample_rate = match sample_rate {
+        Some(rate) => rate,
+        None => model_engine(&state, None)?.lock().unwrap().sample_rate,
+    };
+
+    default_player(&state)?.play(&audio, sample_rate);
+    Ok(())
+}
+
+/// The player on the default output device, opened on first use; callers
+/// hold their own handle so pause and stop are not blocked while it plays
+#[cfg(feature = "playback")]
+fn default_player(state: &SupertonicState) -> Result<Arc<Player>> {
+    let mut player = state.player.lock().unwrap();
+    let player = match &mut *player {
+        Some(player) => player,
+        None => player.insert(Arc::new(Player::open_default()?)),
+    };
+    Ok(Arc::clone(player))
+}
+
+/// Read text aloud on the default output device, playing each chunk as soon
+/// as it is synthesized while the next one renders; resolves once everything
+/// has played, with the duration read. `stop_playback` ends the reading
+/// early with an error.
+#[cfg(feature = "playback")]
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn read_aloud<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+    text: String,
+    speed: Option<f32>,
+    silence_duration: Option<f32>,
+    paragraph_silence: Option<f32>,
+    clause_silence: Option<f32>,
+    total_step: Option<usize>,
+    gain_db: Option<f32>,
+    locale: Option<String>,
+    timezone: Option<String>,
+    limiter_db: Option<f32>,
+    low_latency: Option<bool>,
+    model_id: Option<String>,
+) -> Result<f32> {
+    let options = SynthesisOptions {
+        total_step,
+        speed,
+        silence_duration,
+        paragraph_silence,
+        clause_silence,
+        gain_db,
+        locale,
+        timezone,
+        limiter_db,
+        low_latency,
+        ..Default::default()
+    };
+    read_text_aloud(&state, model_id.as_deref(), &text, &options)
+}
+
+/// Play `text` on the default player as it is synthesized, returning the
+/// duration read
+#[cfg(feature = "playback")]
+pub(crate) fn read_text_aloud(
+    state: &SupertonicState,
+    model_id: Option<&str>,
+    text: &str,
+    options: &SynthesisOptions,
+) -> Result<f32> {
+    let player = default_player(state)?;
+
+    let engine = model_engine(state, model_id)?;
+    let mut engine = engine.lock().unwrap();
+
+    let style_guard = state.style.lock().unwrap();
+    let style = style_guard.as_ref().ok_or(Error::State(
+        "No voice selected. Call 'set_voice' first.".to_string(),
+    ))?;
+
+    player
+        .read_aloud(&mut engine, text, style, options)
+        .map_err(Error::Supertonic)
+}
+
+#[cfg(feature = "playback")]
+#[tauri::command]
+pub async fn pause_playback<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+) -> Result<()> {
+    if let Some(player) = state.player.lock().unwrap().as_ref() {
+        player.pause();
+    }
+    Ok(())
+}
+
+#[cfg(feature = "playback")]
+#[tauri::command]
+pub async fn resume_playback<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+) -> Result<()> {
+    if let Some(player) = state.player.lock().unwrap().as_ref() {
+        player.resume();
+    }
+    Ok(())
+}
+
+/// Silence playback and drop everything queued
+#[cfg(feature = "playback")]
+#[tauri::command]
+pub async fn stop_playback<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SupertonicState>,
+) -> Result<()> {
+    stop_player(&state);
+    Ok(())
+}
+
+/// Stop the default player, if one was ever opened
+#[cfg(feature = "playback")]
+pub(crate) fn stop_player(state: &SupertonicState) {
+    if let Some(player) = state.player.lock().unwrap().as_ref() {
+        player.stop();
+    }
+}
@@ -11,6 +11,10 @@ pub enum Error {
     Supertonic(#[from] SupertonicError),
     #[error("Plugin State Error: {0}")]
     State(String),
+    #[error("Clipboard Error: {0}")]
+    Clipboard(String),
+    #[error("Hotkey Error: {0}")]
+    Hotkey(String),
 }
 
 impl Serialize for Error {
@@ -0,0 +1,86 @@
+use supertonic_tts::SynthesisOptions;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+#[cfg(feature = "playback")]
+use crate::commands::stop_player;
+use crate::commands::{read_clipboard, speak_text};
+use crate::error::{Error, Result};
+use crate::SupertonicState;
+
+// ============================================================================
+// Global Hotkeys
+// ============================================================================
+//
+// System-wide shortcuts for read-aloud apps: one speaks the clipboard, the
+// other stops playback, both the plugin's own (with the `playback` feature)
+// and, through an event, the frontend's. Audio is delivered through events
+// because there is no invoking frontend call to return it to. Requires the app
+// to register `tauri-plugin-global-shortcut` and `tauri-plugin-clipboard-manager`.
+
+pub const DEFAULT_SPEAK_SHORTCUT: &str = "CmdOrControl+Shift+S";
+pub const DEFAULT_STOP_SHORTCUT: &str = "CmdOrControl+Shift+X";
+
+/// Emitted when the speak shortcut starts synthesizing (no payload)
+pub const EVENT_HOTKEY_STARTED: &str = "supertonic://hotkey-speak-started";
+/// Emitted with a `SpeakResponse` once the clipboard text is synthesized
+pub const EVENT_HOTKEY_FINISHED: &str = "supertonic://hotkey-speak-finished";
+/// Emitted with the error message when reading or synthesizing fails
+pub const EVENT_HOTKEY_ERROR: &str = "supertonic://hotkey-speak-error";
+/// Emitted when the stop shortcut is pressed, after the plugin's player has
+/// stopped; the frontend stops its own playback
+pub const EVENT_HOTKEY_STOP: &str = "supertonic://hotkey-stop";
+
+/// Accelerators bound by `register_hotkeys`, e.g. "CmdOrControl+Shift+S"
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HotkeyBindings {
+    pub speak: String,
+    pub stop: String,
+}
+
+pub(crate) fn register<R: Runtime>(app: &AppHandle<R>, bindings: &HotkeyBindings) -> Result<()> {
+    let shortcuts = app.global_shortcut();
+    shortcuts
+        .on_shortcut(bindings.speak.as_str(), |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                speak_clipboard_in_background(app.clone());
+            }
+        })
+        .map_err(|e| Error::Hotkey(e.to_string()))?;
+    shortcuts
+        .on_shortcut(bindings.stop.as_str(), |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                #[cfg(feature = "playback")]
+                stop_player(&app.state::<SupertonicState>());
+                let _ = app.emit(EVENT_HOTKEY_STOP, ());
+            }
+        })
+        .map_err(|e| {
+            let _ = shortcuts.unregister(bindings.speak.as_str());
+            Error::Hotkey(e.to_string())
+        })
+}
+
+pub(crate) fn unregister<R: Runtime>(app: &AppHandle<R>, bindings: &HotkeyBindings) -> Result<()> {
+    app.global_shortcut()
+        .unregister_multiple([bindings.speak.as_str(), bindings.stop.as_str()])
+        .map_err(|e| Error::Hotkey(e.to_string()))
+}
+
+/// Synthesize the clipboard off the shortcut thread, reporting through events
+fn speak_clipboard_in_background<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let _ = app.emit(EVENT_HOTKEY_STARTED, ());
+        let state = app.state::<SupertonicState>();
+        let result = read_clipboard(&app)
+            .and_then(|text| speak_text(&state, None, &text, &SynthesisOptions::default()));
+        match result {
+            Ok(response) => {
+                let _ = app.emit(EVENT_HOTKEY_FINISHED, response);
+            }
+            Err(e) => {
+                let _ = app.emit(EVENT_HOTKEY_ERROR, e.to_string());
+            }
+        }
+    });
+}
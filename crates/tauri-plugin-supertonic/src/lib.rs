@@ -1,8 +1,8 @@
-use std::sync::Mutex;
-use supertonic_tts::{Style, TextToSpeech};
+use std::sync::{Arc, Mutex};
+use supertonic_tts::{EngineRegistry, Style, VoiceManager};
 use tauri::{
     plugin::{Builder, TauriPlugin},
-    AppHandle, Manager, Runtime,
+    Manager, Runtime,
 };
 
 pub use models::*;
@@ -14,13 +14,33 @@ mod mobile;
 
 mod commands;
 mod error;
+#[cfg(desktop)]
+mod hotkey;
 mod models;
 
 pub use error::{Error, Result};
+#[cfg(desktop)]
+pub use hotkey::{
+    HotkeyBindings, EVENT_HOTKEY_ERROR, EVENT_HOTKEY_FINISHED, EVENT_HOTKEY_STARTED,
+    EVENT_HOTKEY_STOP,
+};
+
+#[cfg(desktop)]
+pub use desktop::Supertonic;
+#[cfg(mobile)]
+pub use mobile::Supertonic;
 
 struct SupertonicState {
-    engine: Mutex<Option<TextToSpeech>>,
-    style: Mutex<Option<Style>>,
+    /// Loaded model sets; `initialize` and the legacy loaders load
+    /// `DEFAULT_MODEL_ID`, `load_model` any other
+    engines: EngineRegistry,
+    style: Mutex<Option<Arc<Style>>>,
+    /// Bundled voices, opened by the first command that needs them
+    voices: Mutex<Option<Arc<VoiceManager>>>,
+    #[cfg(desktop)]
+    hotkeys: Mutex<Option<HotkeyBindings>>,
+    #[cfg(feature = "playback")]
+    player: Mutex<Option<Arc<supertonic_tts::Player>>>,
 }
 
 /// Extensions to [`tauri::App`], [`tauri::AppHandle`] and [`tauri::Window`] to access the supertonic plugin.
@@ -34,18 +54,6 @@ impl<R: Runtime, T: Manager<R>> crate::SupertonicExt<R> for T {
     }
 }
 
-/// Access to the supertonic APIs.
-pub struct Supertonic<R: Runtime> {
-    #[allow(dead_code)]
-    app: AppHandle<R>,
-}
-
-impl<R: Runtime> Supertonic<R> {
-    pub fn ping(&self, payload: String) -> crate::Result<String> {
-        Ok(format!("Pong: {}", payload))
-    }
-}
-
 /// Initializes the plugin.
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
     Builder::new("supertonic")
@@ -53,11 +61,41 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
             commands::initialize,
             commands::set_voice,
             commands::load_engine,
+            commands::reload_engine,
+            commands::load_model,
+            commands::unload_model,
+            commands::list_models,
+            #[cfg(feature = "bundle")]
+            commands::load_bundle,
             commands::load_voice,
             commands::speak,
+            #[cfg(desktop)]
+            commands::speak_clipboard,
             commands::speak_batch,
             commands::get_engine_info,
-            commands::save_wav
+            commands::calibrate,
+            commands::estimate_latency,
+            commands::preview,
+            commands::preview_chunks,
+            commands::preprocess_report,
+            commands::get_capabilities,
+            commands::get_version,
+            #[cfg(desktop)]
+            commands::register_hotkeys,
+            #[cfg(desktop)]
+            commands::unregister_hotkeys,
+            commands::save_wav,
+            commands::audio_preview,
+            #[cfg(feature = "playback")]
+            commands::play_audio,
+            #[cfg(feature = "playback")]
+            commands::pause_playback,
+            #[cfg(feature = "playback")]
+            commands::resume_playback,
+            #[cfg(feature = "playback")]
+            commands::stop_playback,
+            #[cfg(feature = "playback")]
+            commands::read_aloud
         ])
         .setup(|app, api| {
             #[cfg(mobile)]
@@ -67,8 +105,13 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
             app.manage(supertonic);
 
             app.manage(SupertonicState {
-                engine: Mutex::new(None),
+                engines: EngineRegistry::new(),
                 style: Mutex::new(None),
+                voices: Mutex::new(None),
+                #[cfg(desktop)]
+                hotkeys: Mutex::new(None),
+                #[cfg(feature = "playback")]
+                player: Mutex::new(None),
             });
 
             Ok(())